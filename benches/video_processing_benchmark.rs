@@ -42,6 +42,7 @@ fn benchmark_crop_processing(c: &mut Criterion) {
                         black_box(1920.0),
                         black_box(1080.0),
                         black_box(objects),
+                        &land2port::crop::CropOptions::default(),
                     );
                     black_box(result)
                 })
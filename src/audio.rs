@@ -1,6 +1,266 @@
+use crate::crop::CropResult;
 use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use std::process::Command;
 
+/// Name of the file that records processing progress inside an output directory
+pub const PROGRESS_FILE_NAME: &str = "progress.json";
+
+/// Video codec used for every re-encoded output video
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    H265,
+    Av1,
+}
+
+impl VideoCodec {
+    /// Name of the ffmpeg encoder for this codec
+    pub fn ffmpeg_encoder(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::H265 => "libx265",
+            VideoCodec::Av1 => "libsvtav1",
+        }
+    }
+
+    /// Largest CRF value the encoder accepts
+    fn max_crf(&self) -> u32 {
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => 51,
+            VideoCodec::Av1 => 63,
+        }
+    }
+
+    /// Name of the ffmpeg bitstream filter that rewrites the color tags in the stream headers
+    ///
+    /// AV1 has none here, its color tags are only set on the container.
+    fn metadata_bsf(&self) -> Option<&'static str> {
+        match self {
+            VideoCodec::H264 => Some("h264_metadata"),
+            VideoCodec::H265 => Some("hevc_metadata"),
+            VideoCodec::Av1 => None,
+        }
+    }
+}
+
+impl std::str::FromStr for VideoCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "h264" => Ok(VideoCodec::H264),
+            "h265" | "hevc" => Ok(VideoCodec::H265),
+            "av1" => Ok(VideoCodec::Av1),
+            _ => Err(format!("unsupported output codec '{}', expected h264, h265 or av1", s)),
+        }
+    }
+}
+
+/// Formats the codec as `--output-codec` expects it
+impl std::fmt::Display for VideoCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoCodec::H264 => write!(f, "h264"),
+            VideoCodec::H265 => write!(f, "h265"),
+            VideoCodec::Av1 => write!(f, "av1"),
+        }
+    }
+}
+
+/// Codec and quality of re-encoded output videos, from `--output-codec` and `--output-quality`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VideoEncoding {
+    pub codec: VideoCodec,
+    /// Constant rate factor, or `None` for the encoder's default
+    pub crf: Option<u32>,
+}
+
+impl VideoEncoding {
+    /// Checks whether this is ffmpeg's default H.264 encoding, which needs no extra arguments
+    pub fn is_default(&self) -> bool {
+        self.codec == VideoCodec::H264 && self.crf.is_none()
+    }
+
+    /// Checks that the CRF is in range for the codec and that ffmpeg has the encoder
+    pub fn validate(&self) -> Result<()> {
+        if let Some(crf) = self.crf {
+            if crf > self.codec.max_crf() {
+                anyhow::bail!(
+                    "--output-quality {} is out of range for {}, expected 0 to {}",
+                    crf,
+                    self.codec,
+                    self.codec.max_crf()
+                );
+            }
+        }
+        if self.is_default() {
+            return Ok(());
+        }
+
+        let output = Command::new("ffmpeg")
+            .args(["-hide_banner", "-encoders"])
+            .output()
+            .context("Failed to execute ffmpeg command to list encoders")?;
+        let encoders = String::from_utf8_lossy(&output.stdout);
+        let encoder = self.codec.ffmpeg_encoder();
+        if !encoders
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(encoder))
+        {
+            anyhow::bail!(
+                "ffmpeg has no {} encoder, which --output-codec {} needs",
+                encoder,
+                self.codec
+            );
+        }
+        Ok(())
+    }
+
+    /// Builds the ffmpeg `-c:v` and `-crf` output options, empty for the default encoding
+    fn ffmpeg_args(&self) -> Vec<String> {
+        if self.is_default() {
+            return Vec::new();
+        }
+        let mut args = vec!["-c:v".to_string(), self.codec.ffmpeg_encoder().to_string()];
+        if let Some(crf) = self.crf {
+            args.extend(["-crf".to_string(), crf.to_string()]);
+        }
+        if self.codec == VideoCodec::H265 {
+            // Tag HEVC as hvc1 so Apple players recognize it
+            args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+        }
+        args
+    }
+}
+
+/// Processing state persisted so an interrupted run can be resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressState {
+    /// Number of source frames up to and including the last one written to the output video
+    pub last_frame: u64,
+    /// Crop applied to the most recently written frame
+    pub last_crop: Option<CropResult>,
+}
+
+/// Bitrate used when compressing audio to AAC for transcription
+pub const DEFAULT_AAC_BITRATE_KBPS: u32 = 128;
+
+/// Audio codec used when compressing the extracted audio
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioCodec {
+    Mp3,
+    Aac,
+}
+
+impl AudioCodec {
+    /// File extension for audio compressed with this codec
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Aac => "m4a",
+        }
+    }
+}
+
+impl std::str::FromStr for AudioCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mp3" => Ok(AudioCodec::Mp3),
+            "aac" => Ok(AudioCodec::Aac),
+            _ => Err(format!("unsupported audio codec '{}', expected mp3 or aac", s)),
+        }
+    }
+}
+
+/// Formats the codec as `--audio-codec` expects it
+impl std::fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioCodec::Mp3 => write!(f, "mp3"),
+            AudioCodec::Aac => write!(f, "aac"),
+        }
+    }
+}
+
+/// Default distance in pixels between captions and the top or bottom edge of the video
+pub const DEFAULT_CAPTION_MARGIN: u32 = 20;
+
+/// Where captions are placed vertically
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptionVerticalPosition {
+    /// Along the bottom edge, `margin` pixels above it
+    Bottom { margin: u32 },
+    /// Along the top edge, `margin` pixels below it
+    Top { margin: u32 },
+    /// In the vertical middle of the video
+    VerticalCenter,
+}
+
+impl CaptionVerticalPosition {
+    /// Offset added to the bottom row ASS alignment (1, 2 or 3) for this position
+    ///
+    /// `force_style` takes the numpad layout of ASS v4+ styles, so each row is 3 codes higher.
+    fn alignment_offset(self) -> u32 {
+        match self {
+            CaptionVerticalPosition::Bottom { .. } => 0,
+            CaptionVerticalPosition::VerticalCenter => 3,
+            CaptionVerticalPosition::Top { .. } => 6,
+        }
+    }
+
+    /// Vertical margin in pixels, which ASS ignores for centered captions
+    fn margin(self) -> u32 {
+        match self {
+            CaptionVerticalPosition::Bottom { margin } | CaptionVerticalPosition::Top { margin } => margin,
+            CaptionVerticalPosition::VerticalCenter => 0,
+        }
+    }
+}
+
+impl Default for CaptionVerticalPosition {
+    fn default() -> Self {
+        CaptionVerticalPosition::Bottom {
+            margin: DEFAULT_CAPTION_MARGIN,
+        }
+    }
+}
+
+impl std::str::FromStr for CaptionVerticalPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bottom" => Ok(CaptionVerticalPosition::Bottom {
+                margin: DEFAULT_CAPTION_MARGIN,
+            }),
+            "top" => Ok(CaptionVerticalPosition::Top {
+                margin: DEFAULT_CAPTION_MARGIN,
+            }),
+            "center" => Ok(CaptionVerticalPosition::VerticalCenter),
+            _ => Err(format!("unsupported caption position '{}', expected top, bottom or center", s)),
+        }
+    }
+}
+
+/// Formats the position as `--caption-position` expects it (without the margin)
+impl std::fmt::Display for CaptionVerticalPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptionVerticalPosition::Bottom { .. } => write!(f, "bottom"),
+            CaptionVerticalPosition::Top { .. } => write!(f, "top"),
+            CaptionVerticalPosition::VerticalCenter => write!(f, "center"),
+        }
+    }
+}
+
 /// Configuration options for caption styling and positioning
 #[derive(Debug, Clone)]
 pub struct CaptionStyle {
@@ -12,8 +272,8 @@ pub struct CaptionStyle {
     pub font_name: String,
     /// Horizontal alignment: "left", "center", or "right"
     pub h_align: String,
-    /// Margin from bottom in pixels
-    pub margin_bottom: u32,
+    /// Vertical placement, including the margin from the top or bottom edge
+    pub vertical_position: CaptionVerticalPosition,
     /// Background color in hex format (e.g., "000000" for black)
     pub bg_color: Option<String>,
     /// Background opacity (0.0 to 1.0)
@@ -35,7 +295,7 @@ impl Default for CaptionStyle {
             font_color: "FFFFFF".to_string(),
             font_name: "Arial".to_string(),
             h_align: "center".to_string(),
-            margin_bottom: 20,  // 20 pixels from bottom
+            vertical_position: CaptionVerticalPosition::default(),  // 20 pixels from bottom
             bg_color: None,
             bg_opacity: None,
             outline_color: Some("000000".to_string()),
@@ -47,14 +307,21 @@ impl Default for CaptionStyle {
 }
 
 /// Extracts audio from a video file using ffmpeg
-pub fn extract_audio(video_path: &str, output_path: &str) -> Result<()> {
-    let status = Command::new("ffmpeg")
-        .args([
+///
+/// Only the audio within `subclip` is extracted, so it lines up with the processed video.
+pub fn extract_audio(video_path: &str, output_path: &str, subclip: &Subclip) -> Result<()> {
+    let mut args = subclip.ffmpeg_input_args();
+    args.extend(
+        [
             "-i", video_path,
             "-vn",  // Disable video
             "-acodec", "copy",  // Copy audio stream without re-encoding
             output_path,
-        ])
+        ]
+        .map(String::from),
+    );
+    let status = Command::new("ffmpeg")
+        .args(args)
         .status()
         .context("Failed to execute ffmpeg command")?;
 
@@ -65,6 +332,234 @@ pub fn extract_audio(video_path: &str, output_path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Builds the ffmpeg arguments that copy a single audio track out of a video
+fn audio_track_args(video_path: &str, track_index: usize, output_path: &str, subclip: &Subclip) -> Vec<String> {
+    let mut args = subclip.ffmpeg_input_args();
+    args.extend([
+        "-i".to_string(),
+        video_path.to_string(),
+        "-vn".to_string(), // Disable video
+        "-map".to_string(),
+        format!("0:a:{}", track_index),
+        "-acodec".to_string(),
+        "copy".to_string(), // Copy audio stream without re-encoding
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Extracts one audio track from a video file using ffmpeg
+///
+/// # Arguments
+/// * `video_path` - The source video
+/// * `track_index` - The zero-based index among the audio tracks (see `count_audio_tracks`)
+/// * `output_path` - The extracted audio file
+/// * `subclip` - The time range to extract
+pub fn extract_audio_track(
+    video_path: &str,
+    track_index: usize,
+    output_path: &str,
+    subclip: &Subclip,
+) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(audio_track_args(video_path, track_index, output_path, subclip))
+        .status()
+        .context("Failed to execute ffmpeg command to extract audio track")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Counts the audio tracks of a video using ffprobe
+pub fn count_audio_tracks(video_path: &str) -> Result<usize> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-select_streams", "a",
+            "-show_entries", "stream=index",
+            "-of", "csv=p=0",
+            video_path,
+        ])
+        .output()
+        .context("Failed to execute ffprobe command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
+    }
+
+    // ffprobe prints one stream index per line
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count())
+}
+
+/// Lists the codec of each subtitle stream of a video using ffprobe, in stream order
+fn subtitle_stream_codecs(video_path: &str) -> Result<Vec<String>> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-select_streams", "s",
+            "-show_entries", "stream=codec_name",
+            "-of", "csv=p=0",
+            video_path,
+        ])
+        .output()
+        .context("Failed to execute ffprobe command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
+    }
+
+    // ffprobe prints one codec name per line
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Counts the subtitle streams of a video using ffprobe
+pub fn count_subtitle_streams(video_path: &str) -> Result<usize> {
+    Ok(subtitle_stream_codecs(video_path)?.len())
+}
+
+/// Checks whether a subtitle stream holds closed captions (EIA-608/SCC), which ffmpeg cannot
+/// write to SRT directly
+pub fn is_closed_caption_stream(video_path: &str, stream_index: usize) -> Result<bool> {
+    let codecs = subtitle_stream_codecs(video_path)?;
+    Ok(codecs
+        .get(stream_index)
+        .is_some_and(|codec| codec == "eia_608" || codec == "scc"))
+}
+
+/// Builds the ffmpeg arguments that extract a single subtitle stream out of a video
+fn subtitle_stream_args(video_path: &str, stream_index: usize, output_path: &str) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-map".to_string(),
+        format!("0:s:{}", stream_index),
+        output_path.to_string(), // The subtitle format follows the extension
+    ]
+}
+
+/// Extracts one embedded subtitle stream from a video file using ffmpeg
+///
+/// # Arguments
+/// * `video_path` - The source video
+/// * `stream_index` - The zero-based index among the subtitle streams (see `count_subtitle_streams`)
+/// * `output_srt` - The extracted subtitle file, usually `.srt` (`.scc` for closed captions)
+pub fn extract_subtitle_stream(video_path: &str, stream_index: usize, output_srt: &str) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(subtitle_stream_args(video_path, stream_index, output_srt))
+        .status()
+        .context("Failed to execute ffmpeg command to extract subtitle stream")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg arguments that convert a closed caption file to SRT
+fn cc_to_srt_args(cc_path: &str, srt_path: &str) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        cc_path.to_string(),
+        "-c:s".to_string(),
+        "srt".to_string(),
+        srt_path.to_string(),
+    ]
+}
+
+/// Converts an SCC closed caption file to SRT using ffmpeg
+pub fn convert_cc_to_srt(cc_path: &str, srt_path: &str) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(cc_to_srt_args(cc_path, srt_path))
+        .status()
+        .context("Failed to execute ffmpeg command to convert closed captions")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Sample rate in Hz of the audio fed to a local Whisper model
+#[cfg(feature = "local-whisper")]
+pub const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Builds the ffmpeg arguments that decode audio to raw 16 kHz mono 32-bit float samples on stdout
+#[cfg(feature = "local-whisper")]
+fn whisper_samples_args(audio_path: &str) -> Vec<String> {
+    vec![
+        "-v".to_string(),
+        "error".to_string(),
+        "-i".to_string(),
+        audio_path.to_string(),
+        "-vn".to_string(), // Disable video
+        "-ac".to_string(),
+        "1".to_string(), // Mix down to mono
+        "-ar".to_string(),
+        WHISPER_SAMPLE_RATE.to_string(),
+        "-f".to_string(),
+        "f32le".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Decodes an audio or video file into the 16 kHz mono samples a local Whisper model expects
+#[cfg(feature = "local-whisper")]
+pub fn decode_whisper_samples(audio_path: &str) -> Result<Vec<f32>> {
+    let output = Command::new("ffmpeg")
+        .args(whisper_samples_args(audio_path))
+        .output()
+        .context("Failed to execute ffmpeg command to decode audio")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", output.status);
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect())
+}
+
+/// Builds the ffmpeg arguments that copy the video stream and drop all audio tracks
+fn video_without_audio_args(video_path: &str, output_path: &str) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-an".to_string(), // Disable audio
+        "-c:v".to_string(),
+        "copy".to_string(), // Copy video stream without re-encoding
+        output_path.to_string(),
+    ]
+}
+
+/// Extracts the video stream of a file without any audio tracks using ffmpeg
+pub fn extract_video_without_audio(video_path: &str, output_path: &str) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(video_without_audio_args(video_path, output_path))
+        .status()
+        .context("Failed to execute ffmpeg command to strip audio")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
 /// Checks if ffmpeg is installed and available in the system
 pub fn check_ffmpeg_installed() -> Result<()> {
     let status = Command::new("ffmpeg")
@@ -79,15 +574,249 @@ pub fn check_ffmpeg_installed() -> Result<()> {
     Ok(())
 }
 
+/// Builds the ffmpeg arguments that decode a whole video without writing any output
+fn integrity_check_args(path: &str) -> Vec<String> {
+    vec![
+        "-v".to_string(),
+        "error".to_string(), // Only report decoding errors
+        "-i".to_string(),
+        path.to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Turns the result of an ffmpeg integrity check into an error naming the first reported problem
+fn check_integrity_output(path: &str, success: bool, stderr: &str) -> Result<()> {
+    // With `-v error` ffmpeg only writes to stderr when something is wrong
+    let first_error = stderr
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty());
+    match (success, first_error) {
+        (true, None) => Ok(()),
+        (_, Some(line)) => anyhow::bail!("Video {} is corrupted or truncated: {}", path, line),
+        (false, None) => anyhow::bail!("Video {} could not be decoded by ffmpeg", path),
+    }
+}
+
+/// Decodes a whole video with ffmpeg to check it is not truncated or corrupted
+pub fn verify_video_integrity(path: &str) -> Result<()> {
+    let output = Command::new("ffmpeg")
+        .args(integrity_check_args(path))
+        .output()
+        .context("Failed to execute ffmpeg command to verify video")?;
+
+    check_integrity_output(path, output.status.success(), &String::from_utf8_lossy(&output.stderr))
+}
+
+/// Checks if ffprobe is installed and available in the system
+pub fn check_ffprobe_installed() -> Result<()> {
+    let status = Command::new("ffprobe")
+        .arg("-version")
+        .status()
+        .context("Failed to execute ffprobe command. Is ffprobe installed?")?;
+
+    if !status.success() {
+        anyhow::bail!("ffprobe is not properly installed");
+    }
+
+    Ok(())
+}
+
+/// Lists the model execution providers usable on this machine
+///
+/// CPU is always available. CUDA and TensorRT are reported when an NVIDIA driver is found
+/// (`nvidia-smi` runs) and CoreML on macOS.
+pub fn detect_available_execution_providers() -> Vec<String> {
+    let mut providers = vec!["cpu".to_string()];
+    let has_nvidia_driver = Command::new("nvidia-smi")
+        .arg("-L")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if has_nvidia_driver {
+        providers.push("cuda".to_string());
+        providers.push("tensorrt".to_string());
+    }
+    if cfg!(target_os = "macos") {
+        providers.push("coreml".to_string());
+    }
+    providers
+}
+
+/// Checks that the external tools needed by the enabled features are installed
+///
+/// # Arguments
+/// * `requires_ffmpeg` - Whether ffmpeg must be available
+/// * `requires_ffprobe` - Whether ffprobe must be available
+pub fn check_dependencies(requires_ffmpeg: bool, requires_ffprobe: bool) -> Result<()> {
+    if requires_ffmpeg {
+        check_ffmpeg_installed()?;
+    }
+    if requires_ffprobe {
+        check_ffprobe_installed()?;
+    }
+    Ok(())
+}
+
+/// Removes all HTML-like formatting tags (e.g. `<b>`, `<font color="...">`) from SRT content
+pub fn strip_html_from_srt(srt_content: &str) -> String {
+    let tag = Regex::new(r"<[^>]+>").expect("valid tag regex");
+    tag.replace_all(srt_content, "").to_string()
+}
+
+/// Converts HTML-like formatting tags in SRT content to ASS override tags
+///
+/// Maps `<b>`/`</b>` to `{\b1}`/`{\b0}`, `<i>`/`</i>` to `{\i1}`/`{\i0}` and
+/// `<font color="#RRGGBB">` to `{\c&HBBGGRR&}` (ASS colors are in BGR order).
+/// A closing `</font>` resets the color. Other tags are left untouched.
+pub fn convert_html_to_ass_tags(srt_content: &str) -> String {
+    let font_color =
+        Regex::new(r#"(?i)<font\s+color\s*=\s*["']?#([0-9a-f]{2})([0-9a-f]{2})([0-9a-f]{2})["']?\s*>"#)
+            .expect("valid font color regex");
+    let simple_tag = Regex::new(r"(?i)<(/?)([bi])>").expect("valid simple tag regex");
+    let font_close = Regex::new(r"(?i)</font>").expect("valid font close regex");
+
+    let converted = font_color.replace_all(srt_content, |caps: &regex::Captures| {
+        format!(
+            "{{\\c&H{}{}{}&}}",
+            caps[3].to_uppercase(),
+            caps[2].to_uppercase(),
+            caps[1].to_uppercase()
+        )
+    });
+    let converted = simple_tag.replace_all(&converted, |caps: &regex::Captures| {
+        let state = if caps[1].is_empty() { "1" } else { "0" };
+        format!("{{\\{}{}}}", caps[2].to_lowercase(), state)
+    });
+    font_close.replace_all(&converted, "{\\c}").to_string()
+}
+
+/// Rewrites the HTML-like formatting tags of an SRT file in place
+///
+/// # Arguments
+/// * `srt_path` - The SRT file to rewrite
+/// * `convert_html` - Convert supported tags to ASS override tags
+/// * `strip_html` - Remove all (remaining) tags
+pub fn rewrite_srt_html(srt_path: &str, convert_html: bool, strip_html: bool) -> Result<()> {
+    let mut content = fs::read_to_string(srt_path)
+        .with_context(|| format!("Failed to read SRT file: {}", srt_path))?;
+    if convert_html {
+        content = convert_html_to_ass_tags(&content);
+    }
+    if strip_html {
+        content = strip_html_from_srt(&content);
+    }
+    fs::write(srt_path, content).with_context(|| format!("Failed to write SRT file: {}", srt_path))?;
+    Ok(())
+}
+
+/// Counts the cues in SRT content
+///
+/// Cues are blank-line separated blocks; blocks without a `-->` timing line (such as stray
+/// headers) are not counted.
+pub fn count_srt_cues(content: &str) -> usize {
+    content
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty() && block.contains("-->"))
+        .count()
+}
+
+/// Builds the ffmpeg arguments that copy all streams of a video unchanged
+fn copy_video_args(video_path: &str, output_path: &str) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        output_path.to_string(),
+    ]
+}
+
+/// Builds the ffmpeg arguments that burn in captions with `filter_str` and re-encode the video
+fn burn_captions_args(
+    video_path: &str,
+    filter_str: &str,
+    output_path: &str,
+    encoding: &VideoEncoding,
+) -> Vec<String> {
+    let mut args = vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-vf".to_string(),
+        filter_str.to_string(),
+    ];
+    args.extend(encoding.ffmpeg_args());
+    args.extend([
+        "-c:a".to_string(),
+        "copy".to_string(), // Copy audio stream without re-encoding
+        output_path.to_string(),
+    ]);
+    args
+}
+
 /// Burns SRT captions into a video file using ffmpeg with customizable styling
+///
+/// When the SRT file has no cues (e.g. a silent or music-only transcription) the video is
+/// copied unchanged instead.
+///
+/// # Arguments
+/// * `video_path` - The video to burn captions into
+/// * `srt_path` - The captions
+/// * `output_path` - The captioned video
+/// * `style` - The caption style, or `None` for the default style
+/// * `encoding` - The codec and quality of the captioned video
 pub fn burn_captions(
     video_path: &str,
     srt_path: &str,
     output_path: &str,
     style: Option<CaptionStyle>,
+    encoding: &VideoEncoding,
 ) -> Result<()> {
-    let style = style.unwrap_or_default();
-    
+    let srt_content = fs::read_to_string(srt_path)
+        .with_context(|| format!("Failed to read SRT file: {}", srt_path))?;
+    if count_srt_cues(&srt_content) == 0 {
+        println!("Transcript has no captions, copying video without burning captions");
+        let status = Command::new("ffmpeg")
+            .args(copy_video_args(video_path, output_path))
+            .status()
+            .context("Failed to execute ffmpeg command to copy video")?;
+
+        if !status.success() {
+            anyhow::bail!("ffmpeg command failed with status: {}", status);
+        }
+        return Ok(());
+    }
+
+    let filter_str = caption_filter(srt_path, style.unwrap_or_default());
+    println!("filter_str: {}", filter_str);
+
+    let status = Command::new("ffmpeg")
+        .args(burn_captions_args(video_path, &filter_str, output_path, encoding))
+        .status()
+        .context("Failed to execute ffmpeg command to burn captions")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg subtitles filter that burns in an SRT file with the given style
+///
+/// The alignment uses the ASS numpad codes: 1/2/3 (left/center/right) along the bottom, 4/5/6
+/// in the middle and 7/8/9 along the top.
+fn caption_filter(srt_path: &str, style: CaptionStyle) -> String {
+    let h_alignment = match style.h_align.as_str() {
+        "left" => 1,
+        "center" => 2,
+        "right" => 3,
+        _ => 1,
+    };
+
     // Build the subtitle filter string with styling options
     let mut filter_str = format!(
         "subtitles={}:force_style='FontName={},FontSize={},PrimaryColour=&H{},Alignment={},MarginV={}",
@@ -95,13 +824,8 @@ pub fn burn_captions(
         style.font_name,
         style.font_size,
         style.font_color,
-        match style.h_align.as_str() {
-            "left" => "1",
-            "center" => "2",
-            "right" => "3",
-            _ => "1",
-        },
-        style.margin_bottom
+        h_alignment + style.vertical_position.alignment_offset(),
+        style.vertical_position.margin()
     );
 
     // Determine BorderStyle based on what's specified (check before moving values)
@@ -163,47 +887,438 @@ pub fn burn_captions(
     
     filter_str.push_str(&format!(",BorderStyle={}", border_style));
     filter_str.push('\'');
+    filter_str
+}
 
-    println!("filter_str: {}", filter_str);
-
-    let status = Command::new("ffmpeg")
+/// Gets the codec name of the first audio stream using ffprobe
+pub fn probe_audio_codec(path: &str) -> Result<String> {
+    let output = Command::new("ffprobe")
         .args([
-            "-i", video_path,
-            "-vf", &filter_str,
-            "-c:a", "copy",  // Copy audio stream without re-encoding
-            output_path,
+            "-v", "quiet",
+            "-select_streams", "a:0",
+            "-show_entries", "stream=codec_name",
+            "-of", "csv=p=0",
+            path,
         ])
-        .status()
-        .context("Failed to execute ffmpeg command to burn captions")?;
+        .output()
+        .context("Failed to execute ffprobe command")?;
 
-    if !status.success() {
-        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
     }
 
-    Ok(())
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
 }
 
-/// Combines a video file with an audio file into a new video file
-pub fn combine_video_audio(
-    video_path: &str,
-    audio_path: &str,
-    output_path: &str,
-) -> Result<()> {
-    let status = Command::new("ffmpeg")
-        .args([
-            "-i", video_path,  // Input video
-            "-i", audio_path,  // Input audio
-            "-c:v", "copy",    // Copy video stream without re-encoding
-            "-c:a", "copy",    // Copy audio stream without re-encoding
-            "-map", "0:v:0",   // Use video from first input
-            "-map", "1:a:0",   // Use audio from second input
-            "-shortest",       // End when shortest input ends
-            output_path,
-        ])
-        .status()
-        .context("Failed to execute ffmpeg command to combine video and audio")?;
+/// Smallest frame width and height accepted for processing
+pub const MIN_SUPPORTED_RESOLUTION: (u32, u32) = (256, 144);
 
-    if !status.success() {
+/// Largest frame width and height accepted for processing (8K UHD)
+pub const MAX_SUPPORTED_RESOLUTION: (u32, u32) = (7680, 4320);
+
+/// Video stream and container information read with ffprobe
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    pub duration_sec: f32,
+    pub video_codec: String,
+    pub audio_codec: Option<String>,
+    /// Frame count reported by the container, when available
+    pub frame_count: Option<u64>,
+}
+
+impl VideoMetadata {
+    /// Gets the total number of frames, estimated from duration and frame rate if the container
+    /// does not report it
+    pub fn total_frames(&self) -> u64 {
+        self.frame_count
+            .unwrap_or_else(|| (self.duration_sec * self.fps).round().max(0.0) as u64)
+    }
+}
+
+/// A time range of the source video selected with `--start-time` and `--end-time`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Subclip {
+    /// Start of the range in seconds
+    pub start_sec: f32,
+    /// End of the range in seconds, or `None` for the end of the video
+    pub end_sec: Option<f32>,
+}
+
+impl Subclip {
+    /// Creates a subclip from the optional `--start-time` and `--end-time` values
+    pub fn new(start_sec: Option<f32>, end_sec: Option<f32>) -> Self {
+        Self {
+            start_sec: start_sec.unwrap_or(0.0),
+            end_sec,
+        }
+    }
+
+    /// Checks whether the subclip covers the whole video
+    pub fn is_full(&self) -> bool {
+        self.start_sec <= 0.0 && self.end_sec.is_none()
+    }
+
+    /// Checks that the range is not empty and lies within a video of `duration_sec` seconds
+    pub fn validate(&self, duration_sec: f32) -> Result<()> {
+        if self.start_sec < 0.0 {
+            anyhow::bail!("--start-time must not be negative, got {}", self.start_sec);
+        }
+        if let Some(end_sec) = self.end_sec {
+            if self.start_sec >= end_sec {
+                anyhow::bail!(
+                    "--start-time ({}s) must be before --end-time ({}s)",
+                    self.start_sec,
+                    end_sec
+                );
+            }
+            if end_sec > duration_sec {
+                anyhow::bail!(
+                    "--end-time ({}s) exceeds the video duration ({:.2}s)",
+                    end_sec,
+                    duration_sec
+                );
+            }
+        }
+        if self.start_sec >= duration_sec {
+            anyhow::bail!(
+                "--start-time ({}s) exceeds the video duration ({:.2}s)",
+                self.start_sec,
+                duration_sec
+            );
+        }
+        Ok(())
+    }
+
+    /// Moves the start of the range to `resume_sec` when that is later, for a resumed run
+    pub fn resumed_at(&self, resume_sec: f32) -> Self {
+        Self {
+            start_sec: self.start_sec.max(resume_sec),
+            end_sec: self.end_sec,
+        }
+    }
+
+    /// Returns the range relative to a video that starts `offset_sec` seconds into this one
+    pub fn shifted(&self, offset_sec: f32) -> Self {
+        Self {
+            start_sec: (self.start_sec - offset_sec).max(0.0),
+            end_sec: self.end_sec.map(|end_sec| end_sec - offset_sec),
+        }
+    }
+
+    /// Checks whether the zero-based frame `frame` comes before the start of the range
+    pub fn is_before_start(&self, frame: u64, frame_rate: f32) -> bool {
+        (frame as f64 / frame_rate as f64) < self.start_sec as f64
+    }
+
+    /// Checks whether the zero-based frame `frame` comes at or after the end of the range
+    pub fn is_past_end(&self, frame: u64, frame_rate: f32) -> bool {
+        self.end_sec
+            .is_some_and(|end_sec| frame as f64 / frame_rate as f64 >= end_sec as f64)
+    }
+
+    /// Builds the ffmpeg input options (`-ss` and `-t`) that select the range
+    fn ffmpeg_input_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.start_sec > 0.0 {
+            args.extend(["-ss".to_string(), format!("{:.3}", self.start_sec)]);
+        }
+        if let Some(end_sec) = self.end_sec {
+            args.extend(["-t".to_string(), format!("{:.3}", end_sec - self.start_sec)]);
+        }
+        args
+    }
+}
+
+#[derive(Deserialize)]
+struct FfprobeOutput {
+    #[serde(default)]
+    streams: Vec<FfprobeStream>,
+    format: Option<FfprobeFormat>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    avg_frame_rate: Option<String>,
+    r_frame_rate: Option<String>,
+    nb_frames: Option<String>,
+    duration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeFormat {
+    duration: Option<String>,
+}
+
+/// Parses an ffprobe frame rate such as `30000/1001` or `25`
+fn parse_frame_rate(rate: &str) -> Option<f32> {
+    let fps = match rate.split_once('/') {
+        Some((num, den)) => {
+            let den: f32 = den.parse().ok()?;
+            if den == 0.0 {
+                return None;
+            }
+            num.parse::<f32>().ok()? / den
+        }
+        None => rate.parse().ok()?,
+    };
+    (fps > 0.0).then_some(fps)
+}
+
+/// Parses the JSON printed by `ffprobe -print_format json -show_streams -show_format`
+fn parse_ffprobe_json(json: &str) -> Result<VideoMetadata> {
+    let output: FfprobeOutput =
+        serde_json::from_str(json).context("Failed to parse ffprobe output")?;
+
+    let video = output
+        .streams
+        .iter()
+        .find(|stream| stream.codec_type.as_deref() == Some("video"))
+        .context("No video stream found")?;
+    let audio_codec = output
+        .streams
+        .iter()
+        .find(|stream| stream.codec_type.as_deref() == Some("audio"))
+        .and_then(|stream| stream.codec_name.clone());
+
+    let fps = video
+        .avg_frame_rate
+        .as_deref()
+        .and_then(parse_frame_rate)
+        .or_else(|| video.r_frame_rate.as_deref().and_then(parse_frame_rate))
+        .context("Video stream has no frame rate")?;
+    let duration_sec = output
+        .format
+        .as_ref()
+        .and_then(|format| format.duration.as_deref())
+        .or(video.duration.as_deref())
+        .and_then(|duration| duration.parse().ok())
+        .unwrap_or(0.0);
+
+    Ok(VideoMetadata {
+        width: video.width.context("Video stream has no width")?,
+        height: video.height.context("Video stream has no height")?,
+        fps,
+        duration_sec,
+        video_codec: video.codec_name.clone().unwrap_or_default(),
+        audio_codec,
+        frame_count: video.nb_frames.as_deref().and_then(|n| n.parse().ok()),
+    })
+}
+
+/// Reads codec, resolution, frame rate and duration of a video using ffprobe
+pub fn probe_video_metadata(path: &str) -> Result<VideoMetadata> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-print_format", "json",
+            "-show_streams",
+            "-show_format",
+            path,
+        ])
+        .output()
+        .context("Failed to execute ffprobe command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
+    }
+
+    parse_ffprobe_json(&String::from_utf8(output.stdout)?)
+}
+
+/// Parses the rotate tag printed by ffprobe into 0, 90, 180 or 270 degrees
+fn parse_rotation(output: &str) -> Result<u32> {
+    let tag = output.trim();
+    if tag.is_empty() {
+        return Ok(0);
+    }
+    let degrees: i32 = tag
+        .parse()
+        .with_context(|| format!("Invalid rotation tag: {}", tag))?;
+    let rotation = degrees.rem_euclid(360) as u32;
+    if rotation % 90 != 0 {
+        anyhow::bail!("Unsupported rotation: {} degrees", degrees);
+    }
+    Ok(rotation)
+}
+
+/// Reads the rotation metadata of a video's first stream using ffprobe
+pub fn probe_video_rotation(video_path: &str) -> Result<u32> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-select_streams", "v:0",
+            "-show_entries", "stream_tags=rotate",
+            "-of", "csv=p=0",
+            video_path,
+        ])
+        .output()
+        .context("Failed to execute ffprobe command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
+    }
+
+    parse_rotation(&String::from_utf8(output.stdout)?)
+}
+
+/// Builds the ffmpeg arguments that re-encode a video rotated clockwise by `rotation` degrees
+fn normalize_rotation_args(video: &str, output: &str, rotation: u32) -> Vec<String> {
+    vec![
+        // The display matrix is counter-clockwise while rotate tags are clockwise
+        "-display_rotation".to_string(),
+        ((360 - rotation % 360) % 360).to_string(),
+        "-i".to_string(),
+        video.to_string(),
+        // ffmpeg rotates the frames while re-encoding, so the output must not be rotated again
+        "-metadata:s:v:0".to_string(),
+        "rotate=0".to_string(),
+        "-c:a".to_string(),
+        "copy".to_string(),
+        output.to_string(),
+    ]
+}
+
+/// Bakes a rotation into a video's frames using ffmpeg, leaving the output without a rotate tag
+pub fn normalize_rotation(video: &str, output: &str, rotation: u32) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(normalize_rotation_args(video, output, rotation))
+        .status()
+        .context("Failed to execute ffmpeg command to normalize rotation")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// A chapter marker embedded in a video container
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub start_sec: f64,
+    pub end_sec: f64,
+    pub title: String,
+}
+
+#[derive(Deserialize)]
+struct FfprobeChapters {
+    #[serde(default)]
+    chapters: Vec<FfprobeChapter>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeChapter {
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+/// Parses the JSON printed by `ffprobe -print_format json -show_chapters`
+fn parse_ffprobe_chapters(json: &str) -> Result<Vec<Chapter>> {
+    let output: FfprobeChapters =
+        serde_json::from_str(json).context("Failed to parse ffprobe chapter output")?;
+
+    output
+        .chapters
+        .into_iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            Ok(Chapter {
+                start_sec: chapter.start_time.parse().context("Invalid chapter start time")?,
+                end_sec: chapter.end_time.parse().context("Invalid chapter end time")?,
+                title: chapter
+                    .tags
+                    .get("title")
+                    .cloned()
+                    .unwrap_or_else(|| format!("Chapter {}", i + 1)),
+            })
+        })
+        .collect()
+}
+
+/// Reads the chapter markers of a video using ffprobe
+pub fn extract_chapters(path: &str) -> Result<Vec<Chapter>> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-print_format", "json",
+            "-show_chapters",
+            path,
+        ])
+        .output()
+        .context("Failed to execute ffprobe command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
+    }
+
+    parse_ffprobe_chapters(&String::from_utf8(output.stdout)?)
+}
+
+/// Checks that a video's resolution is within the range that can be processed
+pub fn check_resolution_supported(meta: &VideoMetadata) -> Result<()> {
+    let (min_width, min_height) = MIN_SUPPORTED_RESOLUTION;
+    let (max_width, max_height) = MAX_SUPPORTED_RESOLUTION;
+
+    if meta.width < min_width || meta.height < min_height {
+        anyhow::bail!(
+            "Unsupported resolution {}x{}: minimum is {}x{}",
+            meta.width,
+            meta.height,
+            min_width,
+            min_height
+        );
+    }
+    if meta.width > max_width || meta.height > max_height {
+        anyhow::bail!(
+            "Unsupported resolution {}x{}: maximum is {}x{}",
+            meta.width,
+            meta.height,
+            max_width,
+            max_height
+        );
+    }
+
+    Ok(())
+}
+
+/// Combines a video file with an audio file into a new video file
+///
+/// AAC audio (and audio whose codec cannot be probed) is copied without re-encoding;
+/// other codecs are re-encoded to AAC so they fit the MP4 container.
+pub fn combine_video_audio(
+    video_path: &str,
+    audio_path: &str,
+    output_path: &str,
+) -> Result<()> {
+    let audio_codec = match probe_audio_codec(audio_path) {
+        Ok(codec) if !codec.is_empty() && codec != "aac" => "aac",
+        _ => "copy",
+    };
+
+    let status = Command::new("ffmpeg")
+        .args([
+            "-i", video_path,  // Input video
+            "-i", audio_path,  // Input audio
+            "-c:v", "copy",    // Copy video stream without re-encoding
+            "-c:a", audio_codec, // Copy AAC audio, otherwise encode to AAC
+            "-map", "0:v:0",   // Use video from first input
+            "-map", "1:a:0",   // Use audio from second input
+            "-shortest",       // End when shortest input ends
+            output_path,
+        ])
+        .status()
+        .context("Failed to execute ffmpeg command to combine video and audio")?;
+
+    if !status.success() {
         anyhow::bail!("ffmpeg command failed with status: {}", status);
     }
 
@@ -228,4 +1343,1322 @@ pub fn compress_to_mp3(input_path: &str, output_path: &str) -> Result<()> {
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Builds the ffmpeg arguments for AAC compression
+fn aac_compression_args(input_path: &str, output_path: &str, bitrate_kbps: u32) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        input_path.to_string(),
+        "-vn".to_string(), // Disable video
+        "-acodec".to_string(),
+        "aac".to_string(), // Use AAC codec (native to MP4 containers)
+        "-b:a".to_string(),
+        format!("{}k", bitrate_kbps),
+        output_path.to_string(),
+    ]
+}
+
+/// Compresses an audio file to AAC using ffmpeg
+///
+/// # Arguments
+/// * `input_path` - The audio (or video) file to compress
+/// * `output_path` - The AAC output file, typically `.m4a`
+/// * `bitrate_kbps` - Target audio bitrate in kilobits per second
+pub fn compress_to_aac(input_path: &str, output_path: &str, bitrate_kbps: u32) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(aac_compression_args(input_path, output_path, bitrate_kbps))
+        .status()
+        .context("Failed to execute ffmpeg command to compress audio")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Default integrated loudness target in LUFS, per EBU R128
+pub const DEFAULT_TARGET_LUFS: f64 = -23.0;
+
+/// True peak ceiling in dBTP used by the loudness normalization
+const LOUDNORM_TRUE_PEAK: f64 = -1.5;
+
+/// Loudness range target in LU used by the loudness normalization
+const LOUDNORM_LRA: f64 = 11.0;
+
+/// Input loudness measured by the first pass of ffmpeg's `loudnorm` filter
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct LoudnormMeasurement {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+/// Builds the `loudnorm` filter options shared by both passes
+fn loudnorm_filter(target_lufs: f64) -> String {
+    format!("loudnorm=I={}:TP={}:LRA={}", target_lufs, LOUDNORM_TRUE_PEAK, LOUDNORM_LRA)
+}
+
+/// Builds the ffmpeg arguments for the first, measuring loudnorm pass, which writes no output
+fn loudnorm_measure_args(input_path: &str, target_lufs: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        input_path.to_string(),
+        "-vn".to_string(),
+        "-af".to_string(),
+        format!("{}:print_format=json", loudnorm_filter(target_lufs)),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Builds the ffmpeg arguments for the second loudnorm pass, which applies the measured correction
+fn loudnorm_apply_args(
+    input_path: &str,
+    output_path: &str,
+    target_lufs: f64,
+    measurement: &LoudnormMeasurement,
+) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        input_path.to_string(),
+        "-vn".to_string(),
+        "-af".to_string(),
+        format!(
+            "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+            loudnorm_filter(target_lufs),
+            measurement.input_i,
+            measurement.input_tp,
+            measurement.input_lra,
+            measurement.input_thresh,
+            measurement.target_offset
+        ),
+        // loudnorm resamples to 192 kHz internally, so bring the output back to a normal rate
+        "-ar".to_string(),
+        "48000".to_string(),
+        output_path.to_string(),
+    ]
+}
+
+/// Parses the JSON block that the measuring loudnorm pass prints at the end of ffmpeg's stderr
+fn parse_loudnorm_measurement(stderr: &str) -> Result<LoudnormMeasurement> {
+    let start = stderr
+        .rfind('{')
+        .context("ffmpeg loudnorm pass printed no loudness measurement")?;
+    let end = stderr[start..]
+        .find('}')
+        .map(|end| start + end + 1)
+        .context("ffmpeg loudnorm pass printed an incomplete loudness measurement")?;
+    let measurement: LoudnormMeasurement = serde_json::from_str(&stderr[start..end])
+        .context("Failed to parse ffmpeg loudnorm measurement")?;
+
+    // Silent audio measures as -inf, which the second pass cannot correct from
+    for value in [&measurement.input_i, &measurement.input_tp, &measurement.input_thresh] {
+        if !value.parse::<f64>().is_ok_and(f64::is_finite) {
+            anyhow::bail!(
+                "Cannot normalize audio: measured loudness is {} (is the audio silent?)",
+                value
+            );
+        }
+    }
+    Ok(measurement)
+}
+
+/// Normalizes the loudness of an audio file with ffmpeg's two-pass `loudnorm` filter
+///
+/// The first pass measures the input loudness and the second applies a linear correction
+/// from that measurement, which is more accurate than single-pass dynamic normalization.
+///
+/// # Arguments
+/// * `input` - The audio (or video) file to normalize
+/// * `output` - The normalized audio file, e.g. `.wav`
+/// * `target_lufs` - Integrated loudness target in LUFS, between -70 and -5
+pub fn normalize_audio(input: &str, output: &str, target_lufs: f64) -> Result<()> {
+    if !(-70.0..=-5.0).contains(&target_lufs) {
+        anyhow::bail!("Target loudness must be between -70 and -5 LUFS, got {}", target_lufs);
+    }
+
+    let measure = Command::new("ffmpeg")
+        .args(loudnorm_measure_args(input, target_lufs))
+        .output()
+        .context("Failed to execute ffmpeg command to measure audio loudness")?;
+
+    if !measure.status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", measure.status);
+    }
+    let measurement = parse_loudnorm_measurement(&String::from_utf8_lossy(&measure.stderr))?;
+
+    let status = Command::new("ffmpeg")
+        .args(loudnorm_apply_args(input, output, target_lufs, &measurement))
+        .status()
+        .context("Failed to execute ffmpeg command to normalize audio")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg arguments that embed an ICC profile and Rec. 709 color metadata
+///
+/// The frames pass through `iccdetect`, so the video is re-encoded with `encoding`.
+fn icc_profile_args(
+    video_path: &str,
+    icc_path: &str,
+    output_path: &str,
+    encoding: &VideoEncoding,
+) -> Vec<String> {
+    let profile_name = Path::new(icc_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| icc_path.to_string());
+
+    let mut args = vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-vf".to_string(),
+        "iccdetect".to_string(),
+    ];
+    args.extend(encoding.ffmpeg_args());
+    if let Some(bsf) = encoding.codec.metadata_bsf() {
+        args.push("-bsf:v".to_string());
+        args.push(format!(
+            "{}=colour_primaries=1:transfer_characteristics=1:matrix_coefficients=1",
+            bsf
+        ));
+    }
+    args.extend([
+        "-color_primaries".to_string(),
+        "bt709".to_string(),
+        "-color_trc".to_string(),
+        "bt709".to_string(),
+        "-colorspace".to_string(),
+        "bt709".to_string(),
+        "-metadata:s:v:0".to_string(),
+        format!("icc_profile={}", profile_name),
+        "-c:a".to_string(),
+        "copy".to_string(),
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Builds the ffmpeg arguments that reset the color metadata of a video encoded with `codec` to
+/// unspecified and remove the ICC profile tag
+fn strip_icc_profile_args(video_path: &str, output_path: &str, codec: VideoCodec) -> Vec<String> {
+    let mut args = vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-c".to_string(),
+        "copy".to_string(), // Only the metadata changes, no re-encoding
+    ];
+    if let Some(bsf) = codec.metadata_bsf() {
+        args.push("-bsf:v".to_string());
+        args.push(format!(
+            "{}=colour_primaries=2:transfer_characteristics=2:matrix_coefficients=2",
+            bsf
+        ));
+    }
+    args.extend([
+        "-metadata:s:v:0".to_string(),
+        "icc_profile=".to_string(),
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Embeds an ICC profile into a video and tags it as Rec. 709 for broadcast delivery
+///
+/// # Arguments
+/// * `video_path` - The video to tag
+/// * `icc_path` - The ICC profile the delivery is graded against
+/// * `output_path` - The tagged output video
+/// * `encoding` - The codec and quality to re-encode the video with
+pub fn embed_icc_profile(
+    video_path: &str,
+    icc_path: &str,
+    output_path: &str,
+    encoding: &VideoEncoding,
+) -> Result<()> {
+    if !Path::new(icc_path).is_file() {
+        anyhow::bail!("ICC profile not found: {}", icc_path);
+    }
+
+    let status = Command::new("ffmpeg")
+        .args(icc_profile_args(video_path, icc_path, output_path, encoding))
+        .status()
+        .context("Failed to execute ffmpeg command to embed color profile")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Removes the ICC profile and color tags written by `embed_icc_profile`, without re-encoding
+///
+/// # Arguments
+/// * `video_path` - The video to strip
+/// * `output_path` - The stripped output video
+/// * `codec` - The codec `video_path` is encoded with, which decides how the stream is retagged
+pub fn strip_icc_profile(video_path: &str, output_path: &str, codec: VideoCodec) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(strip_icc_profile_args(video_path, output_path, codec))
+        .status()
+        .context("Failed to execute ffmpeg command to strip color profile")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg arguments that resample a video to a new frame rate
+fn frame_rate_args(
+    input_path: &str,
+    output_path: &str,
+    target_fps: f32,
+    encoding: &VideoEncoding,
+) -> Vec<String> {
+    let mut args = vec![
+        "-i".to_string(),
+        input_path.to_string(),
+        "-filter:v".to_string(),
+        format!("fps={}", target_fps),
+    ];
+    args.extend(encoding.ffmpeg_args());
+    args.extend(["-c:a".to_string(), "copy".to_string(), output_path.to_string()]);
+    args
+}
+
+/// Converts a video to a new frame rate using ffmpeg, dropping or duplicating frames as needed
+///
+/// # Arguments
+/// * `input_path` - The video to convert
+/// * `output_path` - The converted video
+/// * `target_fps` - The frame rate of the output, e.g. 23.976
+/// * `encoding` - The codec and quality of the converted video
+pub fn convert_frame_rate(
+    input_path: &str,
+    output_path: &str,
+    target_fps: f32,
+    encoding: &VideoEncoding,
+) -> Result<()> {
+    if target_fps <= 0.0 {
+        anyhow::bail!("Target frame rate must be positive, got {}", target_fps);
+    }
+
+    let status = Command::new("ffmpeg")
+        .args(frame_rate_args(input_path, output_path, target_fps, encoding))
+        .status()
+        .context("Failed to execute ffmpeg command to convert frame rate")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg arguments that re-encode a video, copying its audio
+fn encode_video_args(input_path: &str, output_path: &str, encoding: &VideoEncoding) -> Vec<String> {
+    let mut args = vec!["-i".to_string(), input_path.to_string()];
+    args.extend(encoding.ffmpeg_args());
+    args.extend(["-c:a".to_string(), "copy".to_string(), output_path.to_string()]);
+    args
+}
+
+/// Re-encodes a video with another codec or quality using ffmpeg
+pub fn encode_video(input_path: &str, output_path: &str, encoding: &VideoEncoding) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(encode_video_args(input_path, output_path, encoding))
+        .status()
+        .context("Failed to execute ffmpeg command to encode video")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Returns the time to seek to so decoding starts exactly at the zero-based frame `frame`
+///
+/// This is half a frame early, so rounding the timestamp never drops the frame itself.
+pub fn frame_seek_time(frame: u64, frame_rate: f32) -> f64 {
+    ((frame as f64 - 0.5) / frame_rate as f64).max(0.0)
+}
+
+/// Builds the ffmpeg arguments that write the video stream from `start_sec` on, losslessly
+fn seek_video_args(video_path: &str, start_sec: f64, output_path: &str) -> Vec<String> {
+    vec![
+        // Seeking on the input jumps to the keyframe before `start_sec` instead of decoding up to it
+        "-ss".to_string(),
+        format!("{:.3}", start_sec),
+        "-i".to_string(),
+        video_path.to_string(),
+        "-map".to_string(),
+        "0:v:0".to_string(),
+        // Lossless, so the frames decode to the same pixels as in the source
+        "-c:v".to_string(),
+        "libx264".to_string(),
+        "-qp".to_string(),
+        "0".to_string(),
+        "-preset".to_string(),
+        "ultrafast".to_string(),
+        output_path.to_string(),
+    ]
+}
+
+/// Writes the video stream of `video_path` from `start_sec` on to `output_path`
+///
+/// Used to resume an interrupted run without decoding the frames it already wrote. Only the
+/// video is kept; audio and captions are read from the original source.
+pub fn seek_video(video_path: &str, start_sec: f64, output_path: &str) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(seek_video_args(video_path, start_sec, output_path))
+        .status()
+        .context("Failed to execute ffmpeg command to seek in the video")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Reads the average frame rate of a video's first stream using ffprobe
+pub fn get_video_fps(path: &str) -> Result<f32> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-select_streams", "v:0",
+            "-show_entries", "stream=avg_frame_rate",
+            "-of", "csv=p=0",
+            path,
+        ])
+        .output()
+        .context("Failed to execute ffprobe command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ffprobe command failed with status: {}", output.status);
+    }
+
+    let rate = String::from_utf8(output.stdout)?;
+    parse_frame_rate(rate.trim()).with_context(|| format!("Invalid frame rate: {}", rate.trim()))
+}
+
+/// Builds the ffmpeg arguments that apply a crop filter and keep the original audio
+fn crop_filter_args(video_path: &str, filter: &str, output_path: &str) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        video_path.to_string(),
+        "-vf".to_string(),
+        filter.to_string(),
+        "-c:a".to_string(),
+        "copy".to_string(), // Audio is unaffected by cropping
+        output_path.to_string(),
+    ]
+}
+
+/// Applies an ffmpeg crop filter (see `CropResult::to_ffmpeg_filter`) to a video
+///
+/// # Arguments
+/// * `video_path` - The source video
+/// * `filter` - The ffmpeg video filter graph to apply
+/// * `output_path` - The cropped output video
+pub fn apply_crop_filter(video_path: &str, filter: &str, output_path: &str) -> Result<()> {
+    let status = Command::new("ffmpeg")
+        .args(crop_filter_args(video_path, filter, output_path))
+        .status()
+        .context("Failed to execute ffmpeg command to apply crop filter")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg arguments that pad an audio file with silence at the start and end
+fn silence_padding_args(
+    input_path: &str,
+    output_path: &str,
+    start_pad_sec: f32,
+    end_pad_sec: f32,
+) -> Vec<String> {
+    let silence_input = |duration: f32| {
+        vec![
+            "-f".to_string(),
+            "lavfi".to_string(),
+            "-t".to_string(),
+            format!("{:.3}", duration),
+            "-i".to_string(),
+            "aevalsrc=0".to_string(),
+        ]
+    };
+
+    let mut args = Vec::new();
+    let mut segments = 0;
+    if start_pad_sec > 0.0 {
+        args.extend(silence_input(start_pad_sec));
+        segments += 1;
+    }
+    args.extend(["-i".to_string(), input_path.to_string()]);
+    segments += 1;
+    if end_pad_sec > 0.0 {
+        args.extend(silence_input(end_pad_sec));
+        segments += 1;
+    }
+
+    let streams: String = (0..segments).map(|i| format!("[{}:a]", i)).collect();
+    args.extend([
+        "-filter_complex".to_string(),
+        format!("{}concat=n={}:v=0:a=1", streams, segments),
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Pads an audio file with silence so transcript timestamps line up with the original video
+///
+/// # Arguments
+/// * `input_path` - The audio file to pad
+/// * `output_path` - The padded output audio file
+/// * `start_pad_sec` - Seconds of silence to add before the audio
+/// * `end_pad_sec` - Seconds of silence to add after the audio
+pub fn add_silence_padding(
+    input_path: &str,
+    output_path: &str,
+    start_pad_sec: f32,
+    end_pad_sec: f32,
+) -> Result<()> {
+    if start_pad_sec < 0.0 || end_pad_sec < 0.0 {
+        anyhow::bail!(
+            "Padding must not be negative (start: {}, end: {})",
+            start_pad_sec,
+            end_pad_sec
+        );
+    }
+
+    let status = Command::new("ffmpeg")
+        .args(silence_padding_args(input_path, output_path, start_pad_sec, end_pad_sec))
+        .status()
+        .context("Failed to execute ffmpeg command to pad audio")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg arguments that pad a video with black frames and its audio with silence
+fn video_padding_args(
+    input_path: &str,
+    output_path: &str,
+    start_pad_sec: f32,
+    end_pad_sec: f32,
+) -> Vec<String> {
+    vec![
+        "-i".to_string(),
+        input_path.to_string(),
+        "-vf".to_string(),
+        format!(
+            "tpad=start_duration={:.3}:stop_duration={:.3}:start_mode=add:stop_mode=add:color=black",
+            start_pad_sec, end_pad_sec
+        ),
+        "-af".to_string(),
+        format!(
+            "adelay={}:all=1,apad=pad_dur={:.3}",
+            (start_pad_sec * 1000.0).round() as u64,
+            end_pad_sec
+        ),
+        output_path.to_string(),
+    ]
+}
+
+/// Pads a video with black frames and silence at the start and end
+///
+/// Use this instead of `add_silence_padding` when the video itself must stay in sync with
+/// the padded audio, e.g. when the padded file is transcribed and later burned with captions.
+///
+/// # Arguments
+/// * `input_path` - The video to pad
+/// * `output_path` - The padded output video
+/// * `start_pad_sec` - Seconds of black and silence to add before the video
+/// * `end_pad_sec` - Seconds of black and silence to add after the video
+pub fn add_video_padding(
+    input_path: &str,
+    output_path: &str,
+    start_pad_sec: f32,
+    end_pad_sec: f32,
+) -> Result<()> {
+    if start_pad_sec < 0.0 || end_pad_sec < 0.0 {
+        anyhow::bail!(
+            "Padding must not be negative (start: {}, end: {})",
+            start_pad_sec,
+            end_pad_sec
+        );
+    }
+
+    let status = Command::new("ffmpeg")
+        .args(video_padding_args(input_path, output_path, start_pad_sec, end_pad_sec))
+        .status()
+        .context("Failed to execute ffmpeg command to pad video")?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds the ffmpeg concat demuxer list for a set of input videos
+///
+/// Single quotes in paths are escaped the way the concat demuxer expects (`'\''`).
+fn concat_list_content(input_paths: &[&str]) -> String {
+    input_paths
+        .iter()
+        .map(|path| format!("file '{}'\n", path.replace('\'', r"'\''")))
+        .collect()
+}
+
+/// Builds the ffmpeg arguments that join the videos in a concat list without re-encoding
+fn concat_args(list_path: &str, output_path: &str) -> Vec<String> {
+    vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(), // Allow absolute paths in the list
+        "-i".to_string(),
+        list_path.to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        output_path.to_string(),
+    ]
+}
+
+/// Concatenates videos with identical codecs into a single file using the ffmpeg concat demuxer
+///
+/// A single input is copied as is.
+///
+/// # Arguments
+/// * `input_paths` - The videos to join, in playback order
+/// * `output_path` - The joined output video
+pub fn concat_videos(input_paths: &[&str], output_path: &str) -> Result<()> {
+    match input_paths {
+        [] => anyhow::bail!("No videos to concatenate"),
+        [input_path] => {
+            fs::copy(input_path, output_path)
+                .with_context(|| format!("Failed to copy {} to {}", input_path, output_path))?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    // The concat demuxer resolves relative paths against the list file, so list absolute paths
+    let absolute_paths = input_paths
+        .iter()
+        .map(|path| {
+            std::path::absolute(path)
+                .map(|path| path.to_string_lossy().to_string())
+                .with_context(|| format!("Failed to resolve path: {}", path))
+        })
+        .collect::<Result<Vec<String>>>()?;
+    let absolute_paths: Vec<&str> = absolute_paths.iter().map(String::as_str).collect();
+
+    let list_path = format!("{}.concat.txt", output_path);
+    fs::write(&list_path, concat_list_content(&absolute_paths))
+        .with_context(|| format!("Failed to write concat list: {}", list_path))?;
+
+    let status = Command::new("ffmpeg")
+        .args(concat_args(&list_path, output_path))
+        .status()
+        .context("Failed to execute ffmpeg command to concatenate videos");
+    let _ = fs::remove_file(&list_path);
+    let status = status?;
+
+    if !status.success() {
+        anyhow::bail!("ffmpeg command failed with status: {}", status);
+    }
+
+    Ok(())
+}
+
+/// Writes `progress.json` into the output directory
+///
+/// The file is written to a temporary path first and then renamed so an
+/// interruption mid-write never leaves a truncated progress file behind.
+///
+/// # Arguments
+/// * `output_dir` - Directory the progress file is written to
+/// * `last_frame` - Number of source frames up to and including the last one written to the output
+/// * `last_crop` - Crop applied to the most recently written frame
+pub fn create_progress_file(
+    output_dir: &str,
+    last_frame: u64,
+    last_crop: Option<&CropResult>,
+) -> Result<()> {
+    let state = ProgressState {
+        last_frame,
+        last_crop: last_crop.cloned(),
+    };
+    let json = serde_json::to_string_pretty(&state).context("Failed to serialize progress state")?;
+
+    let progress_path = Path::new(output_dir).join(PROGRESS_FILE_NAME);
+    let tmp_path = progress_path.with_extension("json.tmp");
+    fs::write(&tmp_path, json)
+        .with_context(|| format!("Failed to write progress file: {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, &progress_path)
+        .with_context(|| format!("Failed to write progress file: {}", progress_path.display()))?;
+
+    Ok(())
+}
+
+/// Reads `progress.json` from the output directory of a previous run
+pub fn read_progress_file(output_dir: &str) -> Result<ProgressState> {
+    let progress_path = Path::new(output_dir).join(PROGRESS_FILE_NAME);
+    let json = fs::read_to_string(&progress_path)
+        .with_context(|| format!("Failed to read progress file: {}", progress_path.display()))?;
+    let state = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse progress file: {}", progress_path.display()))?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crop::CropArea;
+
+    fn temp_output_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("land2port_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_check_dependencies() {
+        // Nothing required always succeeds
+        assert!(check_dependencies(false, false).is_ok());
+
+        // ffprobe may not be installed where tests run, but a missing tool must be an error,
+        // not a panic, and must name the tool
+        match check_dependencies(false, true) {
+            Ok(()) => {}
+            Err(e) => assert!(format!("{:#}", e).contains("ffprobe")),
+        }
+    }
+
+    #[test]
+    fn test_video_without_audio_args() {
+        let args = video_without_audio_args("in.mp4", "video_only.mp4");
+        assert_eq!(args, vec!["-i", "in.mp4", "-an", "-c:v", "copy", "video_only.mp4"]);
+    }
+
+    #[test]
+    fn test_audio_track_args() {
+        let args = audio_track_args("in.mp4", 2, "out.m4a", &Subclip::default());
+        assert_eq!(
+            args,
+            vec!["-i", "in.mp4", "-vn", "-map", "0:a:2", "-acodec", "copy", "out.m4a"]
+        );
+
+        let subclip = Subclip::new(Some(90.0), Some(120.5));
+        let args = audio_track_args("in.mp4", 0, "out.m4a", &subclip);
+        assert_eq!(&args[..5], ["-ss", "90.000", "-t", "30.500", "-i"]);
+    }
+
+    #[test]
+    fn test_subclip_frames_and_validation() {
+        let subclip = Subclip::new(Some(1.0), Some(2.0));
+        assert!(!subclip.is_full());
+        assert!(subclip.is_before_start(29, 30.0));
+        assert!(!subclip.is_before_start(30, 30.0));
+        assert!(!subclip.is_past_end(59, 30.0));
+        assert!(subclip.is_past_end(60, 30.0));
+        assert!(Subclip::new(None, None).is_full());
+        assert!(!Subclip::new(None, None).is_past_end(u64::MAX / 2, 30.0));
+        assert_eq!(Subclip::new(Some(5.0), None).ffmpeg_input_args(), ["-ss", "5.000"]);
+
+        assert!(subclip.validate(10.0).is_ok());
+        assert!(Subclip::new(Some(3.0), Some(2.0)).validate(10.0).is_err());
+        assert!(Subclip::new(Some(2.0), Some(2.0)).validate(10.0).is_err());
+        assert!(Subclip::new(Some(1.0), Some(12.0)).validate(10.0).is_err());
+        assert!(Subclip::new(Some(10.0), None).validate(10.0).is_err());
+        assert!(Subclip::new(Some(-1.0), None).validate(10.0).is_err());
+    }
+
+    #[test]
+    fn test_subclip_frame_boundaries() {
+        // A frame exactly at start_sec is kept and a frame exactly at end_sec is dropped
+        let subclip = Subclip::new(Some(1.0), Some(2.0));
+        assert!(!subclip.is_before_start(30, 30.0));
+        assert!(subclip.is_past_end(60, 30.0));
+
+        // Frame 2997 at 29.97 fps is 100 seconds in
+        let subclip = Subclip::new(Some(100.0), Some(200.0));
+        assert!(subclip.is_before_start(2996, 29.97));
+        assert!(!subclip.is_before_start(2997, 29.97));
+        assert!(!subclip.is_past_end(5993, 29.97));
+        assert!(subclip.is_past_end(5994, 29.97));
+
+        // A range starting at zero never skips the first frame
+        assert!(!Subclip::new(None, Some(1.0)).is_before_start(0, 29.97));
+    }
+
+    #[test]
+    fn test_subclip_resume() {
+        // Resuming inside the range starts the audio at the resume point
+        let subclip = Subclip::new(Some(1.0), Some(20.0));
+        assert_eq!(subclip.resumed_at(5.0), Subclip::new(Some(5.0), Some(20.0)));
+        assert_eq!(subclip.resumed_at(0.5), subclip);
+
+        // Relative to the video from 5s on, the range ends 5s earlier
+        assert_eq!(subclip.shifted(5.0), Subclip::new(None, Some(15.0)));
+        assert_eq!(Subclip::new(Some(8.0), None).shifted(5.0), Subclip::new(Some(3.0), None));
+    }
+
+    #[test]
+    fn test_seek_video_args() {
+        let args = seek_video_args("in.mp4", 3.3167, "out.mp4");
+        assert_eq!(&args[..4], ["-ss", "3.317", "-i", "in.mp4"]);
+        assert!(args.windows(2).any(|w| w[0] == "-qp" && w[1] == "0"));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+
+        // Frame 100 at 30 fps starts at 3.333s, seeking half a frame earlier keeps it
+        let seek = frame_seek_time(100, 30.0);
+        assert!(seek < 100.0 / 30.0 && seek > 99.0 / 30.0);
+        assert_eq!(frame_seek_time(0, 30.0), 0.0);
+    }
+
+    #[test]
+    fn test_subtitle_stream_args() {
+        let args = subtitle_stream_args("in.mkv", 1, "out.srt");
+        assert_eq!(args, vec!["-i", "in.mkv", "-map", "0:s:1", "out.srt"]);
+
+        let args = cc_to_srt_args("captions.scc", "out.srt");
+        assert_eq!(args, vec!["-i", "captions.scc", "-c:s", "srt", "out.srt"]);
+    }
+
+    #[test]
+    fn test_caption_filter_alignment() {
+        let cases = [
+            (CaptionVerticalPosition::Bottom { margin: 30 }, "Alignment=2,MarginV=30"),
+            (CaptionVerticalPosition::VerticalCenter, "Alignment=5,MarginV=0"),
+            (CaptionVerticalPosition::Top { margin: 40 }, "Alignment=8,MarginV=40"),
+        ];
+        for (vertical_position, expected) in cases {
+            let style = CaptionStyle {
+                vertical_position,
+                ..Default::default()
+            };
+            let filter = caption_filter("transcript.srt", style);
+            assert!(filter.starts_with("subtitles=transcript.srt:force_style='"), "{}", filter);
+            assert!(filter.contains(expected), "{} missing from {}", expected, filter);
+        }
+
+        // Every column and row maps to its numpad code
+        let rows = [
+            (CaptionVerticalPosition::Bottom { margin: 20 }, [1, 2, 3]),
+            (CaptionVerticalPosition::VerticalCenter, [4, 5, 6]),
+            (CaptionVerticalPosition::Top { margin: 20 }, [7, 8, 9]),
+        ];
+        for (vertical_position, codes) in rows {
+            for (h_align, code) in ["left", "center", "right"].into_iter().zip(codes) {
+                let style = CaptionStyle {
+                    h_align: h_align.to_string(),
+                    vertical_position,
+                    ..Default::default()
+                };
+                let expected = format!("Alignment={},", code);
+                let filter = caption_filter("transcript.srt", style);
+                assert!(filter.contains(&expected), "{} missing from {}", expected, filter);
+            }
+        }
+    }
+
+    #[test]
+    fn test_caption_position_from_str() {
+        assert_eq!(
+            "TOP".parse::<CaptionVerticalPosition>(),
+            Ok(CaptionVerticalPosition::Top { margin: DEFAULT_CAPTION_MARGIN })
+        );
+        assert_eq!("center".parse::<CaptionVerticalPosition>(), Ok(CaptionVerticalPosition::VerticalCenter));
+        assert!("left".parse::<CaptionVerticalPosition>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "local-whisper")]
+    fn test_whisper_samples_args() {
+        let args = whisper_samples_args("audio.m4a");
+        assert_eq!(
+            args,
+            vec!["-v", "error", "-i", "audio.m4a", "-vn", "-ac", "1", "-ar", "16000", "-f", "f32le", "-"]
+        );
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(parse_rotation("").unwrap(), 0);
+        assert_eq!(parse_rotation("0\n").unwrap(), 0);
+        assert_eq!(parse_rotation("90\n").unwrap(), 90);
+        assert_eq!(parse_rotation("180\n").unwrap(), 180);
+        assert_eq!(parse_rotation("270\n").unwrap(), 270);
+        assert_eq!(parse_rotation("-90\n").unwrap(), 270);
+        assert!(parse_rotation("45").is_err());
+        assert!(parse_rotation("abc").is_err());
+    }
+
+    #[test]
+    fn test_normalize_rotation_args() {
+        let args = normalize_rotation_args("in.mp4", "out.mp4", 90);
+        assert_eq!(args[..4], ["-display_rotation", "270", "-i", "in.mp4"]);
+        assert!(args.windows(2).any(|w| w[0] == "-c:a" && w[1] == "copy"));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+
+        let args = normalize_rotation_args("in.mp4", "out.mp4", 0);
+        assert_eq!(args[1], "0");
+    }
+
+    #[test]
+    fn test_frame_rate_args() {
+        let default = VideoEncoding::default();
+        let args = frame_rate_args("in.mp4", "out.mp4", 23.976, &default);
+        assert_eq!(
+            args,
+            vec!["-i", "in.mp4", "-filter:v", "fps=23.976", "-c:a", "copy", "out.mp4"]
+        );
+        assert_eq!(frame_rate_args("in.mp4", "out.mp4", 24.0, &default)[3], "fps=24");
+    }
+
+    #[test]
+    fn test_video_encoding_args() {
+        assert!(VideoEncoding::default().ffmpeg_args().is_empty());
+
+        let hevc = VideoEncoding {
+            codec: "hevc".parse().unwrap(),
+            crf: Some(28),
+        };
+        assert_eq!(hevc.ffmpeg_args(), vec!["-c:v", "libx265", "-crf", "28", "-tag:v", "hvc1"]);
+        assert_eq!(
+            encode_video_args("in.mp4", "out.mp4", &hevc),
+            vec!["-i", "in.mp4", "-c:v", "libx265", "-crf", "28", "-tag:v", "hvc1", "-c:a", "copy", "out.mp4"]
+        );
+
+        let av1 = VideoEncoding {
+            codec: VideoCodec::Av1,
+            crf: None,
+        };
+        let args = burn_captions_args("in.mp4", "subtitles=t.srt", "out.mp4", &av1);
+        assert_eq!(
+            args,
+            vec!["-i", "in.mp4", "-vf", "subtitles=t.srt", "-c:v", "libsvtav1", "-c:a", "copy", "out.mp4"]
+        );
+
+        let h264 = VideoEncoding {
+            codec: VideoCodec::H264,
+            crf: Some(18),
+        };
+        assert_eq!(h264.ffmpeg_args(), vec!["-c:v", "libx264", "-crf", "18"]);
+
+        assert!("vp9".parse::<VideoCodec>().is_err());
+        assert_eq!(VideoCodec::H265.to_string(), "h265");
+        let out_of_range = VideoEncoding {
+            codec: VideoCodec::H264,
+            crf: Some(52),
+        };
+        assert!(out_of_range.validate().is_err());
+    }
+
+    #[test]
+    fn test_verify_video_integrity_output() {
+        assert_eq!(
+            integrity_check_args("in.mp4"),
+            vec!["-v", "error", "-i", "in.mp4", "-f", "null", "-"]
+        );
+        assert!(check_integrity_output("in.mp4", true, "").is_ok());
+
+        // The first error ffmpeg reports is included in the message
+        let stderr = "[h264 @ 0x1] Invalid NAL unit size\n[h264 @ 0x1] error while decoding MB 3 7\n";
+        let error = check_integrity_output("in.mp4", true, stderr).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Video in.mp4 is corrupted or truncated: [h264 @ 0x1] Invalid NAL unit size"
+        );
+        assert!(check_integrity_output("in.mp4", false, "").is_err());
+    }
+
+    #[test]
+    fn test_aac_compression_args() {
+        let args = aac_compression_args("in.mp4", "out.m4a", 192);
+        assert_eq!(
+            args,
+            vec!["-i", "in.mp4", "-vn", "-acodec", "aac", "-b:a", "192k", "out.m4a"]
+        );
+    }
+
+    #[test]
+    fn test_loudnorm_two_pass_args() {
+        let args = loudnorm_measure_args("in.mp4", -23.0);
+        assert!(args.windows(2).any(|w| w[0] == "-af" && w[1] == "loudnorm=I=-23:TP=-1.5:LRA=11:print_format=json"));
+        assert_eq!(&args[args.len() - 3..], ["-f", "null", "-"]);
+
+        let stderr = r#"[Parsed_loudnorm_0 @ 0x5581] 
+{
+	"input_i" : "-31.62",
+	"input_tp" : "-9.87",
+	"input_lra" : "6.30",
+	"input_thresh" : "-42.05",
+	"output_i" : "-23.07",
+	"output_tp" : "-1.50",
+	"output_lra" : "5.10",
+	"output_thresh" : "-33.45",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.07"
+}
+"#;
+        let measurement = parse_loudnorm_measurement(stderr).unwrap();
+        assert_eq!(measurement.input_i, "-31.62");
+        assert_eq!(measurement.target_offset, "0.07");
+
+        let args = loudnorm_apply_args("in.mp4", "out.wav", -16.0, &measurement);
+        assert!(args.windows(2).any(|w| w[0] == "-af"
+            && w[1]
+                == "loudnorm=I=-16:TP=-1.5:LRA=11:measured_I=-31.62:measured_TP=-9.87:measured_LRA=6.30:measured_thresh=-42.05:offset=0.07:linear=true"));
+        assert_eq!(args.last().map(String::as_str), Some("out.wav"));
+    }
+
+    #[test]
+    fn test_parse_loudnorm_measurement_errors() {
+        assert!(parse_loudnorm_measurement("Output #0, null, to 'pipe:':").is_err());
+        let silent = r#"{"input_i" : "-inf", "input_tp" : "-inf", "input_lra" : "0.00", "input_thresh" : "-70.00", "target_offset" : "inf"}"#;
+        let error = parse_loudnorm_measurement(silent).unwrap_err();
+        assert!(error.to_string().contains("silent"));
+        assert!(normalize_audio("in.mp4", "out.wav", 0.0).is_err());
+    }
+
+    #[test]
+    fn test_icc_profile_args() {
+        let encoding = VideoEncoding::default();
+        let args = icc_profile_args("in.mp4", "/profiles/Rec709.icc", "out.mp4", &encoding);
+        assert_eq!(args.first().map(String::as_str), Some("-i"));
+        assert_eq!(args[1], "in.mp4");
+        assert!(args.windows(2).any(|w| w[0] == "-vf" && w[1] == "iccdetect"));
+        assert!(args.windows(2).any(|w| w[0] == "-color_primaries" && w[1] == "bt709"));
+        assert!(args.windows(2).any(|w| w[0] == "-color_trc" && w[1] == "bt709"));
+        assert!(args.windows(2).any(|w| w[0] == "-colorspace" && w[1] == "bt709"));
+        assert!(args.windows(2).any(|w| w[0] == "-c:a" && w[1] == "copy"));
+        assert!(args.contains(&"icc_profile=Rec709.icc".to_string()));
+        assert!(args.iter().any(|arg| arg.starts_with("h264_metadata=colour_primaries=1")));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+
+        // The video is re-encoded with the output encoding and tagged with the matching filter
+        let hevc = VideoEncoding {
+            codec: VideoCodec::H265,
+            crf: Some(28),
+        };
+        let args = icc_profile_args("in.mp4", "Rec709.icc", "out.mp4", &hevc);
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "libx265"));
+        assert!(args.iter().any(|arg| arg.starts_with("hevc_metadata=")));
+        assert!(!args.iter().any(|arg| arg.starts_with("h264_metadata")));
+        let av1 = VideoEncoding {
+            codec: VideoCodec::Av1,
+            crf: None,
+        };
+        let args = icc_profile_args("in.mp4", "Rec709.icc", "out.mp4", &av1);
+        assert!(!args.contains(&"-bsf:v".to_string()));
+        assert!(args.windows(2).any(|w| w[0] == "-colorspace" && w[1] == "bt709"));
+    }
+
+    #[test]
+    fn test_strip_icc_profile_args() {
+        let args = strip_icc_profile_args("in.mp4", "out.mp4", VideoCodec::H264);
+        assert_eq!(args[1], "in.mp4");
+        assert!(args.windows(2).any(|w| w[0] == "-c" && w[1] == "copy"));
+        assert!(args.contains(&"icc_profile=".to_string()));
+        assert!(args.iter().any(|arg| arg.starts_with("h264_metadata=colour_primaries=2")));
+        assert!(!args.iter().any(|arg| arg == "bt709"));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+
+        let args = strip_icc_profile_args("in.mp4", "out.mp4", VideoCodec::H265);
+        assert!(args.iter().any(|arg| arg.starts_with("hevc_metadata=")));
+        let args = strip_icc_profile_args("in.mp4", "out.mp4", VideoCodec::Av1);
+        assert!(!args.contains(&"-bsf:v".to_string()));
+    }
+
+    #[test]
+    fn test_embed_icc_profile_missing_file() {
+        let result = embed_icc_profile(
+            "in.mp4",
+            "/nonexistent/profile.icc",
+            "out.mp4",
+            &VideoEncoding::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    const FFPROBE_JSON: &str = r#"{
+        "streams": [
+            {
+                "index": 0,
+                "codec_name": "h264",
+                "codec_type": "video",
+                "width": 1920,
+                "height": 1080,
+                "r_frame_rate": "30000/1001",
+                "avg_frame_rate": "30000/1001",
+                "duration": "10.010000",
+                "nb_frames": "300"
+            },
+            {
+                "index": 1,
+                "codec_name": "aac",
+                "codec_type": "audio",
+                "sample_rate": "48000",
+                "channels": 2
+            }
+        ],
+        "format": {
+            "filename": "input.mp4",
+            "format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+            "duration": "10.010000"
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_ffprobe_json() {
+        let meta = parse_ffprobe_json(FFPROBE_JSON).unwrap();
+        assert_eq!((meta.width, meta.height), (1920, 1080));
+        assert!((meta.fps - 29.97).abs() < 0.01);
+        assert!((meta.duration_sec - 10.01).abs() < 1e-4);
+        assert_eq!(meta.video_codec, "h264");
+        assert_eq!(meta.audio_codec.as_deref(), Some("aac"));
+        assert_eq!(meta.total_frames(), 300);
+    }
+
+    #[test]
+    fn test_parse_ffprobe_json_without_audio_or_frame_count() {
+        let json = r#"{
+            "streams": [
+                {"codec_name": "vp9", "codec_type": "video", "width": 1280, "height": 720,
+                 "r_frame_rate": "25/1", "avg_frame_rate": "0/0"}
+            ],
+            "format": {"duration": "4.000000"}
+        }"#;
+        let meta = parse_ffprobe_json(json).unwrap();
+        assert_eq!(meta.fps, 25.0);
+        assert_eq!(meta.audio_codec, None);
+        assert_eq!(meta.total_frames(), 100);
+
+        assert!(parse_ffprobe_json(r#"{"streams": []}"#).is_err());
+        assert!(parse_ffprobe_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_ffprobe_chapters() {
+        let json = r#"{
+            "chapters": [
+                {"id": 0, "time_base": "1/1000", "start": 0, "start_time": "0.000000",
+                 "end": 60000, "end_time": "60.000000", "tags": {"title": "Intro"}},
+                {"id": 1, "time_base": "1/1000", "start": 60000, "start_time": "60.000000",
+                 "end": 90000, "end_time": "90.000000"}
+            ]
+        }"#;
+        let chapters = parse_ffprobe_chapters(json).unwrap();
+        assert_eq!(
+            chapters,
+            vec![
+                Chapter { start_sec: 0.0, end_sec: 60.0, title: "Intro".to_string() },
+                Chapter { start_sec: 60.0, end_sec: 90.0, title: "Chapter 2".to_string() },
+            ]
+        );
+
+        assert!(parse_ffprobe_chapters("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_resolution_supported() {
+        let mut meta = parse_ffprobe_json(FFPROBE_JSON).unwrap();
+        assert!(check_resolution_supported(&meta).is_ok());
+
+        meta.width = 160;
+        meta.height = 90;
+        assert!(check_resolution_supported(&meta).is_err());
+
+        meta.width = 15360;
+        meta.height = 8640;
+        assert!(check_resolution_supported(&meta).is_err());
+    }
+
+    #[test]
+    fn test_count_srt_cues() {
+        // Silence or music transcribes to an empty file
+        assert_eq!(count_srt_cues(""), 0);
+        assert_eq!(count_srt_cues("\n\n"), 0);
+        assert_eq!(count_srt_cues("WEBVTT\n\n"), 0);
+
+        let srt = "1\n00:00:00,000 --> 00:00:01,500\nHello\n\n2\n00:00:02,000 --> 00:00:03,000\nWorld\n";
+        assert_eq!(count_srt_cues(srt), 2);
+        assert_eq!(count_srt_cues(&srt.replace('\n', "\r\n")), 2);
+    }
+
+    #[test]
+    fn test_burn_captions_without_cues_copies_video() {
+        assert_eq!(
+            copy_video_args("in.mp4", "out.mp4"),
+            vec!["-i", "in.mp4", "-c", "copy", "out.mp4"]
+        );
+
+        // The SRT file is read before ffmpeg runs, so a missing file fails on either path
+        assert!(burn_captions(
+            "in.mp4",
+            "/nonexistent/transcript.srt",
+            "out.mp4",
+            None,
+            &VideoEncoding::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_strip_html_from_srt() {
+        let srt = "1\n00:00:00,000 --> 00:00:02,000\n<b>Hello</b> <font color=\"#FF8000\">world</font>\n";
+        assert_eq!(
+            strip_html_from_srt(srt),
+            "1\n00:00:00,000 --> 00:00:02,000\nHello world\n"
+        );
+        // Timing arrows are not tags
+        assert!(strip_html_from_srt(srt).contains("-->"));
+    }
+
+    #[test]
+    fn test_convert_html_to_ass_tags() {
+        let cue = "<b>Bold</b>, <i>italic</i> and <font color=\"#FF8000\">orange <B>loud</B></font> <u>kept</u>";
+        assert_eq!(
+            convert_html_to_ass_tags(cue),
+            "{\\b1}Bold{\\b0}, {\\i1}italic{\\i0} and {\\c&H0080FF&}orange {\\b1}loud{\\b0}{\\c} <u>kept</u>"
+        );
+
+        // Converting and then stripping leaves only ASS tags behind
+        assert_eq!(
+            strip_html_from_srt(&convert_html_to_ass_tags(cue)),
+            "{\\b1}Bold{\\b0}, {\\i1}italic{\\i0} and {\\c&H0080FF&}orange {\\b1}loud{\\b0}{\\c} kept"
+        );
+    }
+
+    #[test]
+    fn test_crop_filter_args() {
+        let filter = CropResult::Single(CropArea::new(100.0, 0.0, 608.0, 1080.0)).to_ffmpeg_filter();
+        let args = crop_filter_args("in.mp4", &filter, "out.mp4");
+        assert_eq!(
+            args,
+            vec!["-i", "in.mp4", "-vf", "crop=608:1080:100:0", "-c:a", "copy", "out.mp4"]
+        );
+    }
+
+    #[test]
+    fn test_concat_list_content() {
+        let content = concat_list_content(&[
+            "/videos/part one.mp4",
+            "/videos/part two.mp4",
+            "/videos/it's three.mp4",
+        ]);
+        assert_eq!(
+            content,
+            "file '/videos/part one.mp4'\nfile '/videos/part two.mp4'\nfile '/videos/it'\\''s three.mp4'\n"
+        );
+        assert_eq!(
+            concat_args("list.txt", "out.mp4"),
+            vec!["-f", "concat", "-safe", "0", "-i", "list.txt", "-c", "copy", "out.mp4"]
+        );
+        assert!(concat_videos(&[], "out.mp4").is_err());
+    }
+
+    #[test]
+    fn test_silence_padding_args() {
+        let args = silence_padding_args("in.mp4", "out.mp4", 1.5, 2.0);
+        assert_eq!(
+            args,
+            vec![
+                "-f", "lavfi", "-t", "1.500", "-i", "aevalsrc=0",
+                "-i", "in.mp4",
+                "-f", "lavfi", "-t", "2.000", "-i", "aevalsrc=0",
+                "-filter_complex", "[0:a][1:a][2:a]concat=n=3:v=0:a=1",
+                "out.mp4",
+            ]
+        );
+
+        // Only the requested pads are added
+        let args = silence_padding_args("in.mp4", "out.mp4", 0.0, 2.0);
+        assert_eq!(args[1], "in.mp4");
+        assert!(args.contains(&"[0:a][1:a]concat=n=2:v=0:a=1".to_string()));
+
+        assert!(add_silence_padding("in.mp4", "out.mp4", -1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_video_padding_args() {
+        let args = video_padding_args("in.mp4", "out.mp4", 1.5, 2.0);
+        assert_eq!(args[1], "in.mp4");
+        assert!(args.windows(2).any(|w| w[0] == "-vf"
+            && w[1].starts_with("tpad=start_duration=1.500:stop_duration=2.000")));
+        assert!(args.windows(2).any(|w| w[0] == "-af" && w[1] == "adelay=1500:all=1,apad=pad_dur=2.000"));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+    }
+
+    #[test]
+    fn test_audio_codec_from_str() {
+        assert_eq!("mp3".parse::<AudioCodec>(), Ok(AudioCodec::Mp3));
+        assert_eq!("AAC".parse::<AudioCodec>(), Ok(AudioCodec::Aac));
+        assert!("flac".parse::<AudioCodec>().is_err());
+        assert_eq!(AudioCodec::Aac.extension(), "m4a");
+    }
+
+    #[test]
+    fn test_progress_file_round_trip() {
+        let output_dir = temp_output_dir("progress_round_trip");
+        let crop = CropResult::Stacked(
+            CropArea::new(0.0, 60.0, 960.0, 853.3),
+            CropArea::new(960.0, 120.0, 960.0, 853.3),
+        );
+
+        create_progress_file(&output_dir, 100, Some(&crop)).unwrap();
+        let state = read_progress_file(&output_dir).unwrap();
+
+        assert_eq!(state.last_frame, 100);
+        match state.last_crop {
+            Some(CropResult::Stacked(crop1, crop2)) => {
+                assert_eq!(crop1, CropArea::new(0.0, 60.0, 960.0, 853.3));
+                assert_eq!(crop2, CropArea::new(960.0, 120.0, 960.0, 853.3));
+            }
+            _ => panic!("Expected stacked crop to be restored"),
+        }
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_progress_file_overwrite_and_missing() {
+        let output_dir = temp_output_dir("progress_overwrite");
+
+        // Reading before anything was written should fail
+        assert!(read_progress_file(&output_dir).is_err());
+
+        create_progress_file(&output_dir, 100, None).unwrap();
+        create_progress_file(&output_dir, 200, None).unwrap();
+        let state = read_progress_file(&output_dir).unwrap();
+        assert_eq!(state.last_frame, 200);
+        assert!(state.last_crop.is_none());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+}
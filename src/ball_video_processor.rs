@@ -1,12 +1,23 @@
 use crate::cli::Args;
+use crate::config;
 use crate::crop;
-use crate::image::CutDetector;
+use crate::image::{CutDetector, ScoreboardBallLayout};
 use crate::video_processor_utils;
 use crate::video_processor::VideoProcessor;
-use crate::video_processor_utils::predict_current_hbb;
-use anyhow::Result;
+use crate::video_processor_utils::{CropRenderer, predict_current_hbb};
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
 use usls::{Viewer, Hbb};
 
+/// Predictions stop once confidence in the predicted ball position falls below this value
+const MIN_PREDICTION_CONFIDENCE: f32 = 0.1;
+
+/// Number of recent ball sizes kept to recognize detections that are not the ball
+const SIZE_HISTORY_LEN: usize = 5;
+
+/// Ball sizes needed before a detection can be flagged as an artifact
+const MIN_SIZE_HISTORY_LEN: usize = 3;
+
 /// Video processor that handles cropping with ball-specific logic
 pub struct BallVideoProcessor {
     previous_crop: Option<crop::CropResult>,
@@ -15,6 +26,13 @@ pub struct BallVideoProcessor {
     hbb_two_frames_ago: Option<Hbb>,
     hbb_last_frame: Option<Hbb>,
     cut_detector: CutDetector,
+    prediction_confidence: f32,
+    missed_frames: usize,
+    max_prediction_frames: usize,
+    zone_of_interest: Option<crop::CropArea>,
+    scoreboard_zone: Option<crop::CropArea>,
+    size_history: VecDeque<(f32, f32)>,
+    max_size_change: f32,
 }
 
 impl BallVideoProcessor {
@@ -26,9 +44,100 @@ impl BallVideoProcessor {
             hbb_three_frames_ago: None,
             hbb_two_frames_ago: None,
             hbb_last_frame: None,
-            cut_detector: CutDetector::new(args.cut_similarity, args.cut_start),
+            cut_detector: CutDetector::new(args.cut_similarity, args.cut_start, args.cut_detection_method)
+                .with_warmup_frames(config::cut_warmup_frames(args)),
+            prediction_confidence: 1.0,
+            missed_frames: 0,
+            max_prediction_frames: args.ball_max_prediction_frames,
+            zone_of_interest: None,
+            scoreboard_zone: None,
+            size_history: VecDeque::with_capacity(SIZE_HISTORY_LEN + 1),
+            max_size_change: args.ball_max_size_change,
         }
     }
+
+    /// Only uses balls whose center lies inside `zone`, e.g. to ignore LED boards in the stands
+    pub fn with_zone_of_interest(mut self, zone: crop::CropArea) -> Self {
+        self.zone_of_interest = Some(zone);
+        self
+    }
+
+    /// Shows `zone` above the ball crop whenever a ball is detected, e.g. a corner score graphic
+    ///
+    /// The zone is grown to the 9:4 shape of the top quarter of the output.
+    pub fn with_scoreboard_zone(
+        mut self,
+        zone: crop::CropArea,
+        frame_width: f32,
+        frame_height: f32,
+    ) -> Self {
+        self.scoreboard_zone = Some(zone.expand_to_aspect(
+            ScoreboardBallLayout::SCOREBOARD_ASPECT,
+            frame_width,
+            frame_height,
+        ));
+        self
+    }
+
+    /// Stacks the scoreboard zone above a single ball crop
+    fn with_scoreboard(&self, crop_result: &crop::CropResult) -> crop::CropResult {
+        match (&self.scoreboard_zone, crop_result) {
+            (Some(scoreboard), crop::CropResult::Single(ball_crop)) => {
+                crop::CropResult::Stacked(scoreboard.clone(), ball_crop.clone())
+            }
+            _ => crop_result.clone(),
+        }
+    }
+
+    /// Resets prediction confidence after a ball is detected or the shot changes
+    fn reset_prediction_confidence(&mut self) {
+        self.missed_frames = 0;
+        self.prediction_confidence = 1.0;
+    }
+
+    /// Remembers the size of the followed ball, keeping the last `SIZE_HISTORY_LEN` sizes
+    fn record_ball_size(&mut self, ball: &Hbb) {
+        self.size_history.push_back((ball.width(), ball.height()));
+        if self.size_history.len() > SIZE_HISTORY_LEN {
+            self.size_history.pop_front();
+        }
+    }
+}
+
+/// Returns the detection with the highest confidence
+fn highest_confidence_ball<'a>(objects: &[&'a Hbb]) -> Option<&'a Hbb> {
+    objects.iter().copied().max_by(|a, b| {
+        let conf_a = a.confidence().unwrap_or(0.0);
+        let conf_b = b.confidence().unwrap_or(0.0);
+        conf_a.partial_cmp(&conf_b).unwrap_or(std::cmp::Ordering::Equal)
+    })
+}
+
+/// Checks whether a detection's size jumps too far from the recent ball sizes to be the ball
+///
+/// Out-of-focus highlights (bokeh circles) on dark backgrounds can look like a ball to the model
+/// but rarely match its size. Nothing is flagged until `MIN_SIZE_HISTORY_LEN` sizes are known.
+///
+/// # Arguments
+/// * `new_size` - The width and height of the detection
+/// * `history` - The widths and heights of the recently followed balls
+/// * `max_change` - The largest allowed relative change from the median width or height (0 disables)
+pub fn is_likely_artifact(
+    new_size: (f32, f32),
+    history: &VecDeque<(f32, f32)>,
+    max_change: f32,
+) -> bool {
+    if max_change <= 0.0 || history.len() < MIN_SIZE_HISTORY_LEN {
+        return false;
+    }
+    let median = |mut values: Vec<f32>| {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        values[values.len() / 2]
+    };
+    let median_width = median(history.iter().map(|size| size.0).collect());
+    let median_height = median(history.iter().map(|size| size.1).collect());
+    let change = |value: f32, median: f32| (value - median).abs() / median.max(1.0);
+    change(new_size.0, median_width) > max_change || change(new_size.1, median_height) > max_change
 }
 
 impl VideoProcessor for BallVideoProcessor {
@@ -40,10 +149,9 @@ impl VideoProcessor for BallVideoProcessor {
         objects: &[&usls::Hbb],
         args: &Args,
         viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
         _smooth_duration_frames: usize,
     ) -> Result<()> {
-        let current_ball_count = objects.len();
-        
         // Determine if there was a cut
         let is_cut = if let Some(ref most_recent) = self.most_recent_image {
             self.cut_detector.is_cut(most_recent, img)?
@@ -54,6 +162,39 @@ impl VideoProcessor for BallVideoProcessor {
         // Update most_recent_image for next frame (need to clone for storage)
         self.most_recent_image = Some(img.clone());
 
+        // Ball sizes from the previous shot say nothing about the new one
+        if is_cut {
+            self.size_history.clear();
+        }
+
+        // Drop detections whose size does not match the recent balls, e.g. bokeh circles
+        let balls: Vec<&Hbb> = objects
+            .iter()
+            .copied()
+            .filter(|ball| {
+                !is_likely_artifact((ball.width(), ball.height()), &self.size_history, self.max_size_change)
+            })
+            .collect();
+        let has_artifacts = balls.len() < objects.len();
+        if has_artifacts {
+            video_processor_utils::debug_println(format_args!(
+                "Ignoring {} detection(s) with an unlikely ball size",
+                objects.len() - balls.len()
+            ));
+        }
+        let objects = &balls[..];
+        let current_ball_count = objects.len();
+
+        if is_cut || current_ball_count > 0 {
+            self.reset_prediction_confidence();
+        } else {
+            self.missed_frames += 1;
+            self.prediction_confidence = video_processor_utils::prediction_confidence(
+                self.missed_frames,
+                self.max_prediction_frames,
+            );
+        }
+
         // Apply the ball-specific algorithm
         let (crop_result, needs_storage) = if is_cut {
             // If there was a cut, use latest_crop
@@ -67,14 +208,7 @@ impl VideoProcessor for BallVideoProcessor {
             if current_ball_count > 0 {
                 if current_ball_count > 1 {
                     // Multiple balls detected - find the highest confidence ball
-                    let highest_confidence_ball = objects
-                        .iter()
-                        .max_by(|a, b| {
-                            let conf_a = a.confidence().unwrap_or(0.0);
-                            let conf_b = b.confidence().unwrap_or(0.0);
-                            conf_a.partial_cmp(&conf_b).unwrap_or(std::cmp::Ordering::Equal)
-                        })
-                        .unwrap();
+                    let highest_confidence_ball = highest_confidence_ball(objects).unwrap();
 
                     video_processor_utils::debug_println(format_args!(
                         "No cut, multiple balls detected ({}), using highest confidence ball (confidence: {:.3})",
@@ -89,7 +223,13 @@ impl VideoProcessor for BallVideoProcessor {
                         img.width() as f32,
                         img.height() as f32,
                         &[highest_confidence_ball],
-                    )?;
+                        &crop::CropOptions {
+                            padding_percent: args.head_padding,
+                            use_weighted_center: args.use_weighted_center,
+                            ..crop::CropOptions::default()
+                        },
+                    )?
+                    .context("no crop calculated for the highest confidence ball")?;
 
                     self.hbb_three_frames_ago = self.hbb_two_frames_ago.take();
                     self.hbb_two_frames_ago = self.hbb_last_frame.take();
@@ -100,6 +240,26 @@ impl VideoProcessor for BallVideoProcessor {
                         highest_confidence_ball.height(),
                     ));
 
+                    (single_ball_crop, true)
+                } else if has_artifacts {
+                    // Single ball left after dropping artifacts, latest_crop still includes them
+                    video_processor_utils::debug_println(format_args!("No cut, single ball left after dropping artifacts, recalculating ball crop"));
+                    let single_ball_crop = crop::calculate_crop_area(
+                        false, // Don't use stack crop for single ball
+                        false, // Not graphic mode for ball processing
+                        img.width() as f32,
+                        img.height() as f32,
+                        objects,
+                        &crop::CropOptions {
+                            padding_percent: args.head_padding,
+                            use_weighted_center: args.use_weighted_center,
+                            ..crop::CropOptions::default()
+                        },
+                    )?
+                    .context("no crop calculated for the remaining ball")?;
+                    self.hbb_three_frames_ago = self.hbb_two_frames_ago.take();
+                    self.hbb_two_frames_ago = self.hbb_last_frame.take();
+                    self.hbb_last_frame = Some(objects[0].clone());
                     (single_ball_crop, true)
                 } else {
                     // Single ball detected, use latest_crop
@@ -111,15 +271,32 @@ impl VideoProcessor for BallVideoProcessor {
                 }
             } else {
                 // If no balls detected, try to predict position or use previous crop
-                if let (Some(three_frames_ago), Some(two_frames_ago), Some(last_frame)) = (&self.hbb_three_frames_ago, &self.hbb_two_frames_ago, &self.hbb_last_frame) {
-                    let current_hbb = predict_current_hbb(three_frames_ago, two_frames_ago, last_frame, img.width() as f32, img.height() as f32);
+                let prediction_stopped = self.prediction_confidence < MIN_PREDICTION_CONFIDENCE;
+                if let (Some(prev_crop), true) = (&self.previous_crop, prediction_stopped) {
+                    video_processor_utils::debug_println(format_args!(
+                        "No cut, no balls detected for {} frames, prediction confidence too low, using previous ball crop",
+                        self.missed_frames
+                    ));
+                    // The ball may come back at a different size, e.g. after a zoom
+                    self.size_history.clear();
+                    (prev_crop.clone(), false)
+                } else if let (Some(three_frames_ago), Some(two_frames_ago), Some(last_frame)) = (&self.hbb_three_frames_ago, &self.hbb_two_frames_ago, &self.hbb_last_frame) {
+                    let predicted_hbb = predict_current_hbb(three_frames_ago, two_frames_ago, last_frame, img.width() as f32, img.height() as f32);
+                    // Trust the prediction less the longer the ball has been missing
+                    let current_hbb = video_processor_utils::blend_hbb_toward(last_frame, &predicted_hbb, self.prediction_confidence);
                     let current_crop = crop::calculate_crop_area(
                         false, // Don't use stack crop for single ball
                         false, // Not graphic mode for ball processing
                         img.width() as f32,
                         img.height() as f32,
                         &[&current_hbb],
-                    )?;
+                        &crop::CropOptions {
+                            padding_percent: args.head_padding,
+                            use_weighted_center: args.use_weighted_center,
+                            ..crop::CropOptions::default()
+                        },
+                    )?
+                    .context("no crop calculated for the predicted ball position")?;
                     self.hbb_three_frames_ago = self.hbb_two_frames_ago.take();
                     self.hbb_two_frames_ago = self.hbb_last_frame.take();
                     self.hbb_last_frame = Some(current_hbb);
@@ -140,16 +317,43 @@ impl VideoProcessor for BallVideoProcessor {
             }
         };
 
+        if let Some(ball) = highest_confidence_ball(objects) {
+            self.record_ball_size(ball);
+        }
+
         // Update previous crop only if needed (avoid double clone)
         if needs_storage {
             self.previous_crop = Some(crop_result.clone());
         }
 
+        // Show the scoreboard above the ball while a ball is in play
+        let output_crop = if current_ball_count > 0 {
+            self.with_scoreboard(&crop_result)
+        } else {
+            crop_result
+        };
+
         // Process and display the chosen crop
-        video_processor_utils::process_and_display_crop(img, &crop_result, viewer, args.headless)?;
+        video_processor_utils::process_and_display_crop(img, &output_crop, viewer, args.headless, args.pillarbox, renderer)?;
         Ok(())
     }
 
+    /// Returns the zone balls must be centered in to be used
+    fn zone_of_interest(&self) -> Option<&crop::CropArea> {
+        self.zone_of_interest.as_ref()
+    }
+
+    /// Returns the crop most recently applied to an output frame
+    fn last_applied_crop(&self) -> Option<&crop::CropResult> {
+        self.previous_crop.as_ref()
+    }
+
+    /// Restores the previous crop from a resumed run and seeds the ball position history from it
+    fn restore_previous_crop(&mut self, crop: crop::CropResult) {
+        self.hbb_last_frame = video_processor_utils::crop_result_to_dominant_hbb(&crop);
+        self.previous_crop = Some(crop);
+    }
+
     /// Override debug info to include ball-specific information
     fn print_debug_info(&self, objects: &[&usls::Hbb], latest_crop: &crop::CropResult, is_graphic: bool) {
         video_processor_utils::print_default_debug_info(objects, latest_crop, is_graphic);
@@ -157,5 +361,61 @@ impl VideoProcessor for BallVideoProcessor {
         video_processor_utils::debug_println(format_args!("hbb_three_frames_ago: {:?}", self.hbb_three_frames_ago));
         video_processor_utils::debug_println(format_args!("hbb_two_frames_ago: {:?}", self.hbb_two_frames_ago));
         video_processor_utils::debug_println(format_args!("hbb_last_frame: {:?}", self.hbb_last_frame));
+        video_processor_utils::debug_println(format_args!("prediction_confidence: {:.2} (missed frames: {})", self.prediction_confidence, self.missed_frames));
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(sizes: &[(f32, f32)]) -> VecDeque<(f32, f32)> {
+        sizes.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_size_stable_sequence_is_not_an_artifact() {
+        let sizes = history(&[(20.0, 20.0), (22.0, 21.0), (21.0, 22.0), (23.0, 23.0), (22.0, 22.0)]);
+        assert!(!is_likely_artifact((24.0, 23.0), &sizes, 0.5));
+        assert!(!is_likely_artifact((16.0, 16.0), &sizes, 0.5));
+    }
+
+    #[test]
+    fn test_sudden_size_jump_is_an_artifact() {
+        let sizes = history(&[(20.0, 20.0), (22.0, 21.0), (21.0, 22.0), (23.0, 23.0), (22.0, 22.0)]);
+        // A large bokeh circle
+        assert!(is_likely_artifact((80.0, 78.0), &sizes, 0.5));
+        // A tiny highlight
+        assert!(is_likely_artifact((6.0, 6.0), &sizes, 0.5));
+        // Only one dimension jumping is enough
+        assert!(is_likely_artifact((22.0, 60.0), &sizes, 0.5));
+        // A single outlier in the history does not move the median
+        let sizes = history(&[(20.0, 20.0), (90.0, 90.0), (21.0, 21.0), (22.0, 22.0)]);
+        assert!(is_likely_artifact((90.0, 90.0), &sizes, 0.5));
+    }
+
+    #[test]
+    fn test_artifact_check_needs_history() {
+        let sizes = history(&[(20.0, 20.0), (21.0, 21.0)]);
+        assert!(!is_likely_artifact((80.0, 80.0), &sizes, 0.5));
+
+        // A zero threshold disables the check
+        let sizes = history(&[(20.0, 20.0), (21.0, 21.0), (22.0, 22.0)]);
+        assert!(!is_likely_artifact((80.0, 80.0), &sizes, 0.0));
+    }
+
+    #[test]
+    fn test_restore_previous_crop_seeds_ball_history() {
+        use argh::FromArgs;
+
+        let args = Args::from_args(&["land2port"], &["--object", "ball"]).unwrap();
+        let mut processor = BallVideoProcessor::new(&args);
+
+        let crop = crop::CropResult::Single(crop::CropArea::new(656.25, 0.0, 607.5, 1080.0));
+        processor.restore_previous_crop(crop.clone());
+
+        let hbb = processor.hbb_last_frame.as_ref().unwrap();
+        assert_eq!((hbb.xmin(), hbb.ymin(), hbb.width(), hbb.height()), (656.25, 0.0, 607.5, 1080.0));
+        assert_eq!(processor.last_applied_crop(), Some(&crop));
+    }
+}
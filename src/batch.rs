@@ -0,0 +1,318 @@
+use crate::audio;
+use crate::cli::Args;
+use crate::progress::{self, SharedProgressTracker, VideoProgressTracker, lock_progress};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Extensions of the video files picked up by `--batch-dir`
+const BATCH_VIDEO_EXTENSIONS: [&str; 2] = ["mp4", "mov"];
+
+/// Name of the summary written to the batch output directory
+pub const BATCH_SUMMARY_FILE_NAME: &str = "batch_summary.json";
+
+/// Frame rate of the batch progress bar when no file of the batch can be probed
+const DEFAULT_BATCH_FRAME_RATE: f64 = 30.0;
+
+/// Checks whether a path has one of the `BATCH_VIDEO_EXTENSIONS`, ignoring case
+fn is_batch_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            BATCH_VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+/// Finds the `.mp4` and `.mov` files directly inside `dir`, sorted by path
+pub fn find_batch_videos(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read batch directory: {}", dir.display()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && is_batch_video(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Finds the files matching a glob pattern such as `./videos/*.mp4`, sorted by path
+pub fn glob_batch_videos(pattern: &str) -> Result<Vec<PathBuf>> {
+    let paths = glob::glob(pattern).with_context(|| format!("Invalid batch glob: {}", pattern))?;
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path?;
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// A file the batch processed successfully
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchSuccess {
+    pub source: String,
+    pub output_dir: String,
+    /// The final video, absent when none was written (e.g. with `--dry-run`)
+    pub final_video: Option<String>,
+}
+
+/// A file the batch skipped after its processing failed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchFailure {
+    pub source: String,
+    pub error: String,
+}
+
+/// Outcome of a batch, written to `batch_summary.json`
+#[derive(Debug, Default, Serialize)]
+pub struct BatchSummary {
+    pub succeeded: Vec<BatchSuccess>,
+    pub failed: Vec<BatchFailure>,
+}
+
+impl BatchSummary {
+    /// Returns the final videos of the files that succeeded, in batch order
+    pub fn final_videos(&self) -> Vec<&str> {
+        self.succeeded
+            .iter()
+            .filter_map(|success| success.final_video.as_deref())
+            .collect()
+    }
+}
+
+/// Runs the single-video pipeline over several input files
+///
+/// Each file is processed with a copy of the arguments pointing `--source` at it, into its own
+/// subdirectory of the batch output directory. A failed file is reported and skipped.
+pub struct BatchProcessor {
+    files: Vec<PathBuf>,
+    args: Args,
+}
+
+impl BatchProcessor {
+    /// Creates a batch over `files` using `args` for every file
+    pub fn new(files: Vec<PathBuf>, args: Args) -> Self {
+        Self { files, args }
+    }
+
+    /// Returns the subdirectory name for the `index`-th file, numbered to keep the batch order
+    /// and to tell apart files with the same name
+    fn output_dir_name(file: &Path, index: usize) -> String {
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "video".to_string());
+        format!("{:03}_{}", index + 1, stem)
+    }
+
+    /// Returns the arguments for the `index`-th file
+    ///
+    /// With `--output-filepath` the final video is copied into that directory, named like the
+    /// file's output subdirectory (e.g. `002_interview.mp4`) so `a.mp4` and `a.mov` do not
+    /// overwrite each other. `--concat-output` applies to the whole batch, so it is cleared.
+    fn file_args(&self, file: &Path, index: usize) -> Args {
+        let mut args = self.args.clone();
+        args.source = file.to_string_lossy().to_string();
+        args.concat_output = None;
+        if !self.args.output_filepath.is_empty() {
+            let file_name = format!("{}.mp4", Self::output_dir_name(file, index));
+            args.output_filepath = Path::new(&self.args.output_filepath)
+                .join(file_name)
+                .to_string_lossy()
+                .to_string();
+        }
+        args
+    }
+
+    /// Creates the progress tracker for the batch, sized from the files that can be probed
+    fn batch_progress(&self) -> SharedProgressTracker {
+        let metadata: Vec<audio::VideoMetadata> = self
+            .files
+            .iter()
+            .filter_map(|file| audio::probe_video_metadata(&file.to_string_lossy()).ok())
+            .collect();
+        let total_frames = metadata.iter().map(|metadata| metadata.total_frames()).sum();
+        let frame_rate = metadata
+            .first()
+            .map_or(DEFAULT_BATCH_FRAME_RATE, |metadata| metadata.fps as f64);
+        let tracker = Arc::new(Mutex::new(VideoProgressTracker::new_batch(
+            self.files.len(),
+            total_frames,
+            frame_rate,
+        )));
+        progress::register_signal_handler(Arc::clone(&tracker), self.args.progress_json_pipe.clone());
+        tracker
+    }
+
+    /// Processes every file with `process` and writes `batch_summary.json` into `batch_dir`
+    ///
+    /// # Arguments
+    /// * `batch_dir` - The directory to create the per-file output directories in
+    /// * `process` - Processes one file, given its arguments, output directory and the batch
+    ///   progress tracker, and returns its final video if one was written
+    pub async fn run<F, Fut>(&self, batch_dir: &Path, mut process: F) -> Result<BatchSummary>
+    where
+        F: FnMut(Args, String, SharedProgressTracker) -> Fut,
+        Fut: Future<Output = Result<Option<String>>>,
+    {
+        if !self.args.output_filepath.is_empty() {
+            fs::create_dir_all(&self.args.output_filepath).with_context(|| {
+                format!("Failed to create output directory: {}", self.args.output_filepath)
+            })?;
+        }
+
+        let progress = self.batch_progress();
+        let mut summary = BatchSummary::default();
+        for (index, file) in self.files.iter().enumerate() {
+            let file_name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.display().to_string());
+            println!("File {}/{}: {}", index + 1, self.files.len(), file_name);
+            lock_progress(&progress).begin_video(index, &file_name);
+
+            let output_dir = batch_dir.join(Self::output_dir_name(file, index));
+            let output_dir = output_dir.to_string_lossy().to_string();
+            let result = match fs::create_dir_all(&output_dir) {
+                Ok(()) => process(self.file_args(file, index), output_dir.clone(), Arc::clone(&progress)).await,
+                Err(e) => Err(e).with_context(|| format!("Failed to create output directory: {}", output_dir)),
+            };
+
+            let source = file.display().to_string();
+            match result {
+                Ok(final_video) => summary.succeeded.push(BatchSuccess {
+                    source,
+                    output_dir,
+                    final_video,
+                }),
+                Err(e) => {
+                    println!("✗ Failed to process {}: {:#}", file_name, e);
+                    summary.failed.push(BatchFailure {
+                        source,
+                        error: format!("{:#}", e),
+                    });
+                }
+            }
+            lock_progress(&progress).end_video();
+        }
+        lock_progress(&progress).finish();
+
+        let summary_path = batch_dir.join(BATCH_SUMMARY_FILE_NAME);
+        let json = serde_json::to_string_pretty(&summary).context("Failed to serialize batch summary")?;
+        fs::write(&summary_path, json)
+            .with_context(|| format!("Failed to write batch summary: {}", summary_path.display()))?;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argh::FromArgs;
+
+    fn temp_batch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("land2port_batch_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_find_batch_videos() {
+        let dir = temp_batch_dir("find");
+        for name in ["b.mp4", "a.MOV", "notes.txt", "c.mkv"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        fs::create_dir_all(dir.join("nested.mp4")).unwrap();
+
+        let files = find_batch_videos(&dir).unwrap();
+        assert_eq!(files, vec![dir.join("a.MOV"), dir.join("b.mp4")]);
+
+        let pattern = format!("{}/*.mp4", dir.display());
+        assert_eq!(glob_batch_videos(&pattern).unwrap(), vec![dir.join("b.mp4")]);
+
+        assert!(find_batch_videos(&dir.join("missing")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_files_are_skipped() {
+        let dir = temp_batch_dir("run");
+        let files = vec![PathBuf::from("one.mp4"), PathBuf::from("two.mp4"), PathBuf::from("three.mov")];
+        let args = Args::from_args(&["land2port"], &[]).unwrap();
+        let batch = BatchProcessor::new(files, args);
+
+        let mut sources = Vec::new();
+        let mut completed_videos = Vec::new();
+        let summary = batch
+            .run(&dir, |args, output_dir, progress| {
+                sources.push(args.source.clone());
+                completed_videos.push(lock_progress(&progress).completed_videos());
+                let is_broken = args.source == "two.mp4";
+                async move {
+                    assert!(Path::new(&output_dir).is_dir());
+                    if is_broken {
+                        anyhow::bail!("corrupt input");
+                    }
+                    Ok(Some(format!("{}/final_output.mp4", output_dir)))
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(sources, vec!["one.mp4", "two.mp4", "three.mov"]);
+        // Failed files still advance the batch counter
+        assert_eq!(completed_videos, vec![0, 1, 2]);
+        assert_eq!(summary.succeeded.len(), 2);
+        assert_eq!(summary.succeeded[1].output_dir, dir.join("003_three").to_string_lossy());
+        assert_eq!(
+            summary.final_videos(),
+            vec![
+                format!("{}/final_output.mp4", dir.join("001_one").display()),
+                format!("{}/final_output.mp4", dir.join("003_three").display()),
+            ]
+        );
+        assert_eq!(
+            summary.failed,
+            vec![BatchFailure {
+                source: "two.mp4".to_string(),
+                error: "corrupt input".to_string(),
+            }]
+        );
+
+        let json = fs::read_to_string(dir.join(BATCH_SUMMARY_FILE_NAME)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["succeeded"].as_array().unwrap().len(), 2);
+        assert_eq!(json["failed"][0]["source"], "two.mp4");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_args_override_source_and_output() {
+        let args = Args::from_args(
+            &["land2port"],
+            &["--output-filepath", "out", "--headless", "--concat-output", "all.mp4"],
+        )
+        .unwrap();
+        let batch = BatchProcessor::new(Vec::new(), args);
+
+        let file_args = batch.file_args(Path::new("videos/interview.mov"), 1);
+        assert_eq!(file_args.source, "videos/interview.mov");
+        assert_eq!(file_args.output_filepath, Path::new("out").join("002_interview.mp4").to_string_lossy());
+        assert!(file_args.headless);
+        // The batch outputs are concatenated once after the batch, not per file
+        assert!(file_args.concat_output.is_none());
+
+        // Files differing only in their extension get different output files
+        let mp4_args = batch.file_args(Path::new("videos/interview.mp4"), 0);
+        assert_ne!(mp4_args.output_filepath, file_args.output_filepath);
+    }
+}
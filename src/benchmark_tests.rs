@@ -58,7 +58,8 @@ mod benchmark_tests {
         let start = Instant::now();
         for _ in 0..iterations {
             let _result = crate::crop::calculate_crop_area(
-                false, false, 1920.0, 1080.0, &objects_slice
+                false, false, 1920.0, 1080.0, &objects_slice,
+                &crate::crop::CropOptions::default(),
             );
         }
         let duration = start.elapsed();
@@ -100,4 +101,46 @@ mod benchmark_tests {
         // Reference should be faster
         assert!(reference_duration < clone_duration);
     }
+
+    #[test]
+    fn benchmark_resize_filters() {
+        use crate::image::{create_cropped_image, PillarboxMode, RenderOptions, ResizeFilter};
+
+        let frame = usls::Image::from(::image::RgbImage::from_fn(1920, 1080, |x, y| {
+            ::image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        }));
+        let crop_result = CropResult::Single(CropArea::new(656.0, 0.0, 608.0, 1080.0));
+        let iterations = 3;
+
+        println!("Resize filter benchmark ({} iterations, 1920x1080 -> 1080x1920):", iterations);
+        let mut durations = Vec::new();
+        for filter in [
+            ResizeFilter::Lanczos3,
+            ResizeFilter::CatmullRom,
+            ResizeFilter::Triangle,
+            ResizeFilter::Nearest,
+        ] {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                let _cropped = create_cropped_image(
+                    &frame,
+                    &crop_result,
+                    1080,
+                    PillarboxMode::Black,
+                    RenderOptions {
+                        filter,
+                        ..RenderOptions::default()
+                    },
+                    None,
+                )
+                .unwrap();
+            }
+            let duration = start.elapsed();
+            println!("  {:?}: {:?} per frame", filter, duration / iterations);
+            durations.push(duration);
+        }
+
+        // Nearest neighbor should be faster than Lanczos3
+        assert!(durations[3] < durations[0]);
+    }
 }
@@ -1,20 +1,84 @@
+use crate::audio::{AudioCodec, CaptionVerticalPosition, DEFAULT_TARGET_LUFS, VideoCodec};
+use crate::config::DtypePolicy;
+use crate::crop::NoDetectionStrategy;
+use crate::image::{
+    CutDetectionMethod, DEFAULT_IMAGE_CACHE_SIZE, DEFAULT_SHARPEN_RADIUS, PillarboxMode, ResizeFilter,
+    WatermarkPosition,
+};
+use crate::transcript::CaptionFormat;
 use argh::FromArgs;
 
 /// YOLO Example
-#[derive(FromArgs, Debug)]
+#[derive(FromArgs, Debug, Clone)]
 pub struct Args {
+    /// config: TOML file with option values, overridden by options given on the command line
+    #[argh(option)]
+    pub config: Option<String>,
+
+    /// output config: write the effective options of this run to a TOML file for reuse with --config
+    #[argh(option)]
+    pub output_config: Option<String>,
+
     /// object type: face, head, ball, sports ball, frisbee, person, car, truck, or boat
     #[argh(option, default = "String::from(\"face\")")]
     pub object: String,
 
+    /// object names: match detections with any of these labels instead of --object, e.g. --object-names head --object-names face
+    #[argh(option)]
+    pub object_names: Vec<String>,
+
+    /// intersection over union above which two detections matched by --object-names are treated as duplicates (default: 0.5)
+    #[argh(option, default = "0.5")]
+    pub nms_threshold: f32,
+
+    /// drop detections whose center is closer than this many pixels to a more confident detection (default: 0.0 = disabled)
+    #[argh(option, default = "0.0")]
+    pub dedup_distance: f32,
+
+    /// model registry: JSON file mapping custom object aliases to model paths (default: ~/.config/land2port/models.json)
+    #[argh(option)]
+    pub model_registry: Option<String>,
+
     /// source: image, image folder, video stream
     #[argh(option, default = "String::from(\"./video/video1.mp4\")")]
     pub source: String,
 
+    /// batch dir: process every .mp4 and .mov file in this directory instead of --source
+    #[argh(option)]
+    pub batch_dir: Option<String>,
+
+    /// batch glob: process every file matching this pattern, e.g. "./videos/*.mp4", instead of --source
+    #[argh(option)]
+    pub batch_glob: Option<String>,
+
+    /// source rotation in degrees (0, 90, 180 or 270) baked into the source before processing; read from the video's rotate tag when not given
+    #[argh(option)]
+    pub source_rotation: Option<u32>,
+
+    /// decode the whole source with ffmpeg before processing to reject truncated or corrupted files
+    #[argh(switch)]
+    pub verify_input: bool,
+
     /// model dtype
     #[argh(option, default = "String::from(\"auto\")")]
     pub dtype: String,
 
+    /// how --dtype auto picks the dtype: conservative, aggressive, or library (default: conservative)
+    #[argh(option, default = "DtypePolicy::Conservative")]
+    pub dtype_policy: DtypePolicy,
+
+    /// quantization policy: JSON file with the model dtype and layer types to keep in FP32, e.g. {"default": "fp16", "force_fp32_layers": ["BatchNormalization"]}; overrides --dtype
+    #[argh(option, long = "quantization-policy")]
+    pub quantization_policy_file: Option<String>,
+
+    /// number of warm-up inferences to run before processing (default: 2)
+    #[argh(option, default = "2")]
+    pub model_dry_run_frames: usize,
+
+    /// skip model warm-up, e.g. when the model is already warm
+    #[argh(switch)]
+    pub skip_dry_run: bool,
+
     /// version
     #[argh(option, default = "11.0")]
     pub ver: f32,
@@ -23,6 +87,14 @@ pub struct Args {
     #[argh(option, default = "String::from(\"cpu:0\")")]
     pub device: String,
 
+    /// ONNX Runtime execution provider to use instead of --device, e.g. rocm:0, directml or openvino
+    #[argh(option, long = "custom-ep")]
+    pub custom_execution_provider: Option<String>,
+
+    /// list the execution providers available on this machine and exit
+    #[argh(switch)]
+    pub list_eps: bool,
+
     /// scale: n, s, m, l
     #[argh(option, default = "String::from(\"m\")")]
     pub scale: String,
@@ -35,14 +107,186 @@ pub struct Args {
     #[argh(option, default = "1.5")]
     pub smooth_duration: f32,
 
+    /// blend this many frames from the old crop to the new one when history smoothing changes crop (default: 0 = immediate)
+    #[argh(option, default = "0")]
+    pub interpolated_transition_frames: usize,
+
+    /// ease the crop over this many frames when history smoothing flushes its queue (default: 0 = immediate)
+    #[argh(option, default = "0")]
+    pub interpolation_frames: usize,
+
+    /// follow a crop that keeps moving the same way for 3 frames along its predicted path instead of waiting for the smooth duration (history smoothing only)
+    #[argh(switch)]
+    pub use_crop_prediction: bool,
+
+    /// keep the current crop until a change in object count has lasted this many frames (history smoothing only, default: 0 = immediate)
+    #[argh(option, default = "0")]
+    pub object_count_debounce_frames: usize,
+
+    /// shorten the smooth duration as the crop moves faster (history smoothing only)
+    #[argh(switch)]
+    pub adaptive_smoothing: bool,
+
+    /// motion speed in pixels per frame at which adaptive smoothing drops to a single frame (default: 20.0)
+    #[argh(option, default = "20.0")]
+    pub max_expected_speed: f32,
+
     /// object probability threshold
     #[argh(option, default = "0.7")]
     pub object_prob_threshold: f32,
 
+    /// warn when the crop covers less than this fraction of the frame (0.0-1.0)
+    #[argh(option)]
+    pub min_crop_coverage: Option<f32>,
+
+    /// warn for every frame whose crop extends outside the broadcast safe area
+    #[argh(switch)]
+    pub safe_zone_check: bool,
+
+    /// safe area inset from each edge as a percentage of the frame size, used by --safe-zone-check (default: 5.0)
+    #[argh(option, default = "5.0")]
+    pub safe_zone_margin: f32,
+
+    /// camera motion compensation: when detection drops out, pan the last crop with the estimated camera motion
+    #[argh(switch)]
+    pub camera_motion_compensation: bool,
+
+    /// debug side by side: directory to save previews of the original and cropped frame side by side
+    #[argh(option)]
+    pub debug_side_by_side: Option<String>,
+
+    /// save a side-by-side preview every N frames (default: 30)
+    #[argh(option, default = "30")]
+    pub debug_side_by_side_interval: u64,
+
+    /// debug frame output: directory to save 9:16 debug frames with detections, crop areas and frame info drawn in
+    #[argh(option)]
+    pub debug_frame_output: Option<String>,
+
+    /// save a debug frame every N frames (default: 30)
+    #[argh(option, default = "30")]
+    pub debug_frame_interval: u64,
+
+    /// dry run: run detection and smoothing and log every crop decision, without writing the output video or running the caption pipeline
+    #[argh(switch)]
+    pub dry_run: bool,
+
+    /// debug overlay: write the full frames with the crop outlined in green, a differing previous crop in yellow, and the object count and confidences, instead of the cropped output
+    #[argh(switch)]
+    pub debug_overlay: bool,
+
+    /// export crops: write the crop applied to every frame to this CSV file, for analysing crop and smoothing decisions
+    #[argh(option)]
+    pub export_crops: Option<String>,
+
+    /// save crop sequence: write the calculated crop of every frame to this JSON file, merging runs of near-identical crops into keyframes
+    #[argh(option)]
+    pub save_crop_sequence: Option<String>,
+
+    /// load crop sequence: use the crops of a file written with --save-crop-sequence instead of the calculated crops
+    #[argh(option)]
+    pub load_crop_sequence: Option<String>,
+
+    /// debug: at the end of the video, save the last N frames still buffered for smoothing (default: 0)
+    #[argh(option, default = "0")]
+    pub replay_last_n_frames: usize,
+
+    /// crop to use for replayed frames, as x,y,w,h pixels or x%,y%,w%,h% (default: the chosen crop)
+    #[argh(option)]
+    pub replay_crop: Option<String>,
+
+    /// directory to save replayed frames to (default: replay)
+    #[argh(option, default = "String::from(\"replay\")")]
+    pub replay_dir: String,
+
+    /// crop change webhook: URL to POST a JSON notification to whenever the smoothed crop changes (history smoothing only)
+    #[argh(option)]
+    pub crop_change_webhook: Option<String>,
+
+    /// log transitions: write every smoothed crop change with its reason to transitions.json in the output directory (history smoothing only)
+    #[argh(switch)]
+    pub log_transitions: bool,
+
+    /// normalize audio: normalize the loudness of the audio sent for transcription with a two-pass EBU R128 loudnorm, which helps with quiet recordings
+    #[argh(switch)]
+    pub normalize_audio: bool,
+
+    /// target loudness in LUFS for --normalize-audio, between -70 and -5 (default: -23.0)
+    #[argh(option, default = "DEFAULT_TARGET_LUFS")]
+    pub target_lufs: f64,
+
+    /// start time: only process the video from this many seconds in (default: start of the video)
+    #[argh(option)]
+    pub start_time: Option<f32>,
+
+    /// end time: stop processing the video at this many seconds (default: end of the video)
+    #[argh(option)]
+    pub end_time: Option<f32>,
+
+    /// seconds of silence to add before the audio sent for transcription, to line captions up with the original video (default: 0)
+    #[argh(option, default = "0.0")]
+    pub audio_pad_start: f32,
+
+    /// seconds of silence to add after the audio sent for transcription (default: 0)
+    #[argh(option, default = "0.0")]
+    pub audio_pad_end: f32,
+
+    /// minimum free disk space in GB required in the output directory before processing starts; 0 disables the check (default: 2.0)
+    #[argh(option, default = "2.0")]
+    pub min_free_gb: f32,
+
+    /// only process the first frame and save it as first_frame.jpg in the output directory, skipping audio and captions
+    #[argh(switch)]
+    pub first_frame_only: bool,
+
+    /// margin added around each detected head before the crop is calculated, as a percentage of the head's diagonal (default: 5.0)
+    #[argh(option, default = "5.0")]
+    pub head_padding: f32,
+
+    /// smallest width/height ratio of a face or head detection to be used (default: 0.3)
+    #[argh(option, default = "0.3")]
+    pub head_min_aspect: f32,
+
+    /// largest width/height ratio of a face or head detection to be used (default: 3.0)
+    #[argh(option, default = "3.0")]
+    pub head_max_aspect: f32,
+
+    /// shift every calculated crop horizontally by this many pixels, negative values move it left (default: 0.0)
+    #[argh(option, default = "0.0")]
+    pub crop_offset_x: f32,
+
+    /// shift every calculated crop vertically by this many pixels, negative values move it up (default: 0.0)
+    #[argh(option, default = "0.0")]
+    pub crop_offset_y: f32,
+
+    /// scale every calculated crop around its center, values below 1.0 zoom in (default: 1.0)
+    #[argh(option, default = "1.0")]
+    pub crop_scale: f32,
+
+    /// only use balls centered in this zone, as x,y,w,h pixels or x%,y%,w%,h% of the frame
+    #[argh(option)]
+    pub ball_zone: Option<String>,
+
+    /// with --object ball, show this zone (e.g. the score graphic) in the top quarter above the ball, as x,y,w,h pixels or x%,y%,w%,h% of the frame
+    #[argh(option)]
+    pub ball_scoreboard_zone: Option<String>,
+
+    /// consecutive frames without a ball after which position prediction stops (default: 10)
+    #[argh(option, default = "10")]
+    pub ball_max_prediction_frames: usize,
+
+    /// largest relative change in ball size from the recent median before a detection is ignored as an artifact; 0 disables (default: 0.5)
+    #[argh(option, default = "0.5")]
+    pub ball_max_size_change: f32,
+
     /// object area threshold (minimum area as percentage of frame, ignored for ball objects)
     #[argh(option, default = "0.02")]
     pub object_area_threshold: f32,
 
+    /// class area threshold: minimum area of one detection class as name:value, overriding --object-area-threshold, e.g. --class-area-threshold heads:0.005 --class-area-threshold person:0.03
+    #[argh(option)]
+    pub class_area_threshold: Vec<String>,
+
     /// cut similarity threshold (default: 0.3)
     #[argh(option, default = "0.3")]
     pub cut_similarity: f64,
@@ -51,6 +295,62 @@ pub struct Args {
     #[argh(option, default = "0.8")]
     pub cut_start: f64,
 
+    /// cut detection method: rgb (pixel-level), histogram (faster color histograms) or phash (perceptual hash, robust to compression) (default: rgb)
+    #[argh(option, default = "CutDetectionMethod::RgbHybrid")]
+    pub cut_detection_method: CutDetectionMethod,
+
+    /// number of frames at the start during which cut detection is suppressed; never less than --model-dry-run-frames (default: 5)
+    #[argh(option, default = "5")]
+    pub cut_warmup_frames: usize,
+
+    /// how to fill the space around single crops: black, blur[:radius], mirror or solid:RRGGBB (default: black)
+    #[argh(option, default = "PillarboxMode::Black")]
+    pub pillarbox: PillarboxMode,
+
+    /// number of resized crops to cache so stable crops are not rescaled every frame; 0 disables (default: 8)
+    #[argh(option, default = "DEFAULT_IMAGE_CACHE_SIZE")]
+    pub image_cache_size: usize,
+
+    /// print the mean and standard deviation of the time spent per frame in each processing stage and a crop history overview
+    #[argh(switch)]
+    pub perf_stats: bool,
+
+    /// keep every Nth output frame for a thumbnail grid saved next to the output; 0 disables (default: 0)
+    #[argh(option, default = "0")]
+    pub thumbnail_interval: u64,
+
+    /// number of columns in the thumbnail grid (default: 4)
+    #[argh(option, default = "4")]
+    pub thumbnail_grid_cols: usize,
+
+    /// number of rows in the thumbnail grid (default: 4)
+    #[argh(option, default = "4")]
+    pub thumbnail_grid_rows: usize,
+
+    /// filter used to scale crops: lanczos3, catmullrom, triangle or nearest (default: lanczos3)
+    #[argh(option, default = "ResizeFilter::Lanczos3")]
+    pub resize_filter: ResizeFilter,
+
+    /// sharpen amount: strength of the unsharp mask applied to crops after scaling (default: 0.0 = off)
+    #[argh(option, default = "0.0")]
+    pub sharpen_amount: f32,
+
+    /// sharpen radius: sigma of the Gaussian blur used by --sharpen-amount (default: 1.0)
+    #[argh(option, default = "DEFAULT_SHARPEN_RADIUS")]
+    pub sharpen_radius: f32,
+
+    /// watermark path: image (e.g. a transparent PNG) blended onto every output frame
+    #[argh(option)]
+    pub watermark_path: Option<String>,
+
+    /// watermark position: top-left, top-right, bottom-left, bottom-right, center or custom:X,Y (default: bottom-right)
+    #[argh(option, default = "WatermarkPosition::BottomRight")]
+    pub watermark_position: WatermarkPosition,
+
+    /// watermark opacity: multiplies the watermark's alpha, from 0.0 to 1.0 (default: 1.0)
+    #[argh(option, default = "1.0")]
+    pub watermark_opacity: f32,
+
     /// use headless mode
     #[argh(switch)]
     pub headless: bool,
@@ -59,10 +359,54 @@ pub struct Args {
     #[argh(switch)]
     pub use_stack_crop: bool,
 
+    /// stack two heads that are above each other, e.g. in aerial shots, as top and bottom crops
+    #[argh(switch)]
+    pub allow_vertical_stack: bool,
+
+    /// zoom in frames: number of frames to zoom into a single head after a no-heads crop (0 disables)
+    #[argh(option, default = "0")]
+    pub zoom_in_frames: usize,
+
+    /// use a confidence-weighted bounding box so low-confidence heads expand multi-head crops less
+    #[argh(switch)]
+    pub weighted_bbox: bool,
+
+    /// center multi-head crops on the confidence-weighted center of the heads instead of their bounding box
+    #[argh(switch)]
+    pub use_weighted_center: bool,
+
+    /// what to output for frames without detections: center, previous or skip (default: center)
+    #[argh(option, default = "NoDetectionStrategy::CenterCrop")]
+    pub no_detection_strategy: NoDetectionStrategy,
+
     /// use simple smoothing instead of history smoothing
     #[argh(switch)]
     pub use_simple_smoothing: bool,
 
+    /// use Kalman smoothing instead of history smoothing: filter single crops without buffering frames
+    #[argh(switch)]
+    pub use_kalman_smoothing: bool,
+
+    /// kalman process noise: higher values follow movement faster (default: 1.0)
+    #[argh(option, long = "kalman-q", default = "1.0")]
+    pub kalman_process_noise: f32,
+
+    /// kalman measurement noise: higher values smooth more (default: 10.0)
+    #[argh(option, long = "kalman-r", default = "10.0")]
+    pub kalman_measurement_noise: f32,
+
+    /// with simple smoothing, delay output by this many frames so crop changes must persist through them (default: 0 = disabled)
+    #[argh(option, default = "0")]
+    pub simple_lookahead_frames: usize,
+
+    /// simple median window: with simple smoothing, use the median of the last N single crops
+    #[argh(option)]
+    pub simple_median_window: Option<usize>,
+
+    /// with simple smoothing, blend towards new crops by the ratio of head confidences instead of snapping when the new heads are less confident
+    #[argh(switch)]
+    pub confidence_blend: bool,
+
     /// keep graphic
     #[argh(switch)]
     pub keep_graphic: bool,
@@ -71,11 +415,103 @@ pub struct Args {
     #[argh(option, default = "0.3")]
     pub graphic_threshold: f32,
 
+    /// with --keep-graphic, classify frames without detections with CLIP only every N frames and reuse the result in between (default: 1)
+    #[argh(option, long = "clip-classify-every-n-frames", default = "1")]
+    pub clip_classify_interval: usize,
+
     /// add captions: extract audio, transcribe, burn captions, and recombine
     #[argh(switch)]
     pub add_captions: bool,
 
+    /// transcript language: ISO-639-1 code of the spoken language (e.g. en, fr, ja) so Whisper does not have to detect it, or auto to detect it
+    #[argh(option)]
+    pub transcript_language: Option<String>,
+
+    /// transcript temperature: Whisper sampling temperature from 0 to 1, higher values give more varied transcripts (default: the backend's default)
+    #[argh(option)]
+    pub transcript_temperature: Option<f32>,
+
+    /// transcription backend: openai (Whisper API, needs OPENAI_API_KEY) or local (needs --whisper-model-path) (default: openai)
+    #[argh(option, default = "String::from(\"openai\")")]
+    pub transcription_backend: String,
+
+    /// whisper model path: GGML Whisper model file for --transcription-backend local, e.g. ggml-base.en.bin
+    #[argh(option)]
+    pub whisper_model_path: Option<String>,
+
+    /// split caption cues longer than this many milliseconds at sentence boundaries (default: 0 = disabled)
+    #[argh(option, default = "0")]
+    pub split_long_cues: u32,
+
+    /// with --add-captions, caption chapter titles from the source's chapter markers instead of transcribing
+    #[argh(switch)]
+    pub captions_from_chapters: bool,
+
+    /// with --add-captions, use a subtitle stream embedded in the source instead of transcribing
+    #[argh(switch)]
+    pub use_embedded_subtitles: bool,
+
+    /// zero-based index of the embedded subtitle stream to use (default: 0)
+    #[argh(option, default = "0")]
+    pub subtitle_stream_index: usize,
+
+    /// remove HTML-like formatting tags (e.g. <b>, <font>) from the transcript before burning captions
+    #[argh(switch)]
+    pub srt_strip_html: bool,
+
+    /// convert HTML-like formatting tags (<b>, <i>, <font color>) in the transcript to ASS styling
+    #[argh(switch)]
+    pub srt_convert_html: bool,
+
+    /// caption format: also save the captions as srt or vtt (WebVTT, for web players) next to the output video (default: srt)
+    #[argh(option, default = "CaptionFormat::Srt")]
+    pub caption_format: CaptionFormat,
+
+    /// caption position: burn captions in at the top, bottom or center of the video (default: bottom)
+    #[argh(option, default = "CaptionVerticalPosition::default()")]
+    pub caption_position: CaptionVerticalPosition,
+
+    /// index of the audio track used for captions and the final output, e.g. 1 for clean audio when track 0 has commentary (default: 0)
+    #[argh(option, default = "0")]
+    pub audio_track_index: usize,
+
+    /// audio codec used to compress audio for transcription: mp3 or aac (default: mp3)
+    #[argh(option, default = "AudioCodec::Mp3")]
+    pub audio_codec: AudioCodec,
+
     /// output filepath: if set, move the final video to this location
     #[argh(option, default = "String::from(\"\")")]
     pub output_filepath: String,
+
+    /// output codec of re-encoded videos: h264, h265 or av1 (default: h264)
+    #[argh(option, default = "VideoCodec::H264")]
+    pub output_codec: VideoCodec,
+
+    /// output quality: CRF of re-encoded videos, lower is better, 0-51 for h264 and h265 and 0-63 for av1 (default: the encoder's default)
+    #[argh(option)]
+    pub output_quality: Option<u32>,
+
+    /// frame rate of the output video, e.g. 23.976 or 24 (default: 0.0 = same as the source)
+    #[argh(option, default = "0.0")]
+    pub output_fps: f32,
+
+    /// icc profile: path to an ICC profile to embed into the output video, which is tagged as Rec. 709 for broadcast delivery
+    #[argh(option, long = "icc-profile")]
+    pub icc_profile_path: Option<String>,
+
+    /// remove the ICC profile and color tags from the output video
+    #[argh(switch)]
+    pub strip_icc_profile: bool,
+
+    /// progress json pipe: file or named pipe to write a JSON progress summary to on SIGUSR1 instead of stderr (Unix only)
+    #[argh(option)]
+    pub progress_json_pipe: Option<String>,
+
+    /// resume from: output directory of an interrupted run to continue from its progress.json
+    #[argh(option)]
+    pub resume_from: Option<String>,
+
+    /// concat output: join the video segments (the interrupted and resumed run, or the final videos of a batch in order) and write the result to this file
+    #[argh(option)]
+    pub concat_output: Option<String>,
 }
@@ -1,7 +1,196 @@
-use anyhow::Result;
-use usls::{Config, Task, NAMES_COCO_80};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use usls::{Config, DType, Task, NAMES_COCO_80};
+use crate::audio;
 use crate::cli::Args;
 
+/// Object types with built-in model handling in `get_model_path` and `build_config`
+const BUILT_IN_OBJECTS: [&str; 10] = [
+    "face",
+    "head",
+    "ball",
+    "person",
+    "car",
+    "motorcycle",
+    "truck",
+    "boat",
+    "frisbee",
+    "sports ball",
+];
+
+/// Widest frame for which `auto_select_dtype` picks FP16 on accelerated devices
+const MAX_FP16_FRAME_WIDTH: u32 = 1920;
+
+/// Frame width assumed when the source cannot be probed
+const DEFAULT_FRAME_WIDTH: u32 = 1920;
+
+/// How the model dtype is chosen when `--dtype auto` is given
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DtypePolicy {
+    /// FP16 on CUDA/CoreML up to 1080p input, FP32 otherwise
+    Conservative,
+    /// FP16 on CUDA/CoreML at any resolution, FP32 on CPU
+    Aggressive,
+    /// Let the usls library pick the dtype
+    Library,
+}
+
+impl std::str::FromStr for DtypePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "conservative" => Ok(DtypePolicy::Conservative),
+            "aggressive" => Ok(DtypePolicy::Aggressive),
+            "library" => Ok(DtypePolicy::Library),
+            _ => Err(format!(
+                "unsupported dtype policy '{}', expected conservative, aggressive or library",
+                s
+            )),
+        }
+    }
+}
+
+/// Formats the policy as `--dtype-policy` expects it
+impl std::fmt::Display for DtypePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DtypePolicy::Conservative => write!(f, "conservative"),
+            DtypePolicy::Aggressive => write!(f, "aggressive"),
+            DtypePolicy::Library => write!(f, "library"),
+        }
+    }
+}
+
+impl DtypePolicy {
+    /// Picks the model dtype for a device and input frame width
+    ///
+    /// # Returns
+    /// The dtype to use, or `None` to leave the choice to the usls library
+    pub fn auto_select(&self, device: &str, frame_width: u32) -> Option<DType> {
+        match self {
+            DtypePolicy::Conservative => Some(auto_select_dtype(device, frame_width)),
+            DtypePolicy::Aggressive if is_accelerated_device(device) => Some(DType::Fp16),
+            DtypePolicy::Aggressive => Some(DType::Fp32),
+            DtypePolicy::Library => None,
+        }
+    }
+}
+
+/// Checks whether a device string refers to a GPU or Neural Engine backend with fast FP16
+fn is_accelerated_device(device: &str) -> bool {
+    let device = device.to_lowercase();
+    device.starts_with("cuda") || device.starts_with("coreml")
+}
+
+/// Picks FP16 for CUDA and CoreML devices up to 1080p width and FP32 otherwise
+///
+/// # Arguments
+/// * `device` - The device string passed to `--device`, e.g. `cuda:0`
+/// * `frame_width` - The width of the input video frames
+pub fn auto_select_dtype(device: &str, frame_width: u32) -> DType {
+    if is_accelerated_device(device) && frame_width <= MAX_FP16_FRAME_WIDTH {
+        DType::Fp16
+    } else {
+        DType::Fp32
+    }
+}
+
+/// Resolves the model dtype from `--dtype`, applying the dtype policy for `auto`
+fn resolve_model_dtype(args: &Args) -> Result<DType> {
+    if args.dtype != "auto" {
+        return args.dtype.parse();
+    }
+
+    if args.dtype_policy == DtypePolicy::Library {
+        return Ok(DType::Auto);
+    }
+    let frame_width = audio::probe_video_metadata(&args.source)
+        .map(|metadata| metadata.width)
+        .unwrap_or(DEFAULT_FRAME_WIDTH);
+    Ok(args
+        .dtype_policy
+        .auto_select(&model_device(args), frame_width)
+        .unwrap_or(DType::Auto))
+}
+
+/// The device string the model runs on: the `--custom-ep` execution provider if given,
+/// otherwise `--device`
+fn model_device(args: &Args) -> String {
+    match &args.custom_execution_provider {
+        Some(provider) => provider.trim().to_lowercase(),
+        None => args.device.clone(),
+    }
+}
+
+/// Number of warm-up inferences to run before processing, honoring `--skip-dry-run`
+fn model_dry_run_frames(args: &Args) -> usize {
+    if args.skip_dry_run {
+        0
+    } else {
+        args.model_dry_run_frames
+    }
+}
+
+/// Number of cut detector comparisons to suppress while the model warms up
+///
+/// Never shorter than the model warm-up, so both stay consistent when `--model-dry-run-frames`
+/// is raised.
+pub fn cut_warmup_frames(args: &Args) -> usize {
+    args.cut_warmup_frames.max(model_dry_run_frames(args))
+}
+
+/// Maps user-defined model aliases to ONNX model file paths
+///
+/// The registry is a JSON object such as `{"my_model": "/path/to/model.onnx"}`.
+#[derive(Debug, Clone, Default)]
+pub struct ModelRegistry {
+    models: HashMap<String, String>,
+}
+
+impl ModelRegistry {
+    /// Loads a registry from a JSON file
+    pub fn load(path: &Path) -> Result<ModelRegistry> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read model registry: {}", path.display()))?;
+        let models = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse model registry: {}", path.display()))?;
+        Ok(ModelRegistry { models })
+    }
+
+    /// Looks up the model file path for an alias
+    pub fn lookup(&self, alias: &str) -> Option<&str> {
+        self.models.get(alias).map(String::as_str)
+    }
+}
+
+/// Returns the default model registry location, `~/.config/land2port/models.json`
+fn default_model_registry_path() -> Option<PathBuf> {
+    env::var("HOME")
+        .ok()
+        .map(|home| Path::new(&home).join(".config/land2port/models.json"))
+}
+
+/// Resolves a custom object alias through the model registry
+///
+/// An explicitly passed registry must exist; the default registry is only used when present.
+fn lookup_registry_model(object: &str, registry_path: Option<&str>) -> Result<Option<String>> {
+    let path = match registry_path {
+        Some(path) => PathBuf::from(path),
+        None => match default_model_registry_path() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        },
+    };
+
+    let registry = ModelRegistry::load(&path)?;
+    Ok(registry.lookup(object).map(str::to_string))
+}
+
 /// Determines the model file path based on object type, version, and scale
 fn get_model_path(object: &str, ver: f32, scale: &str) -> String {
     match object {
@@ -29,18 +218,92 @@ fn get_model_path(object: &str, ver: f32, scale: &str) -> String {
     }
 }
 
+/// Model versions with built-in face, head and ball models
+const SUPPORTED_VERSIONS: [f32; 4] = [6.0, 8.0, 10.0, 11.0];
+
+/// Objects with built-in model files and the scales each one recognizes
+///
+/// Head models ignore the version and scale, so every combination maps to the same file.
+const SUPPORTED_MODEL_SCALES: [(&str, &[&str]); 3] = [
+    ("face", &["n", "s", "m", "l"]),
+    ("head", &["n", "s", "m", "l"]),
+    ("ball", &["n", "m"]),
+];
+
+/// A recognized `(object, ver, scale)` combination with a built-in model file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportedModel {
+    pub object: &'static str,
+    pub ver: f32,
+    pub scale: &'static str,
+    pub path: String,
+}
+
+/// Iterates over every `(object, ver, scale)` combination with a built-in model file
+pub fn iter_supported_models() -> impl Iterator<Item = SupportedModel> {
+    SUPPORTED_MODEL_SCALES
+        .into_iter()
+        .flat_map(|(object, scales)| {
+            SUPPORTED_VERSIONS.into_iter().flat_map(move |ver| {
+                scales.iter().map(move |scale| SupportedModel {
+                    object,
+                    ver,
+                    scale,
+                    path: get_model_path(object, ver, scale),
+                })
+            })
+        })
+}
+
+/// Checks that an object with built-in model files is requested with a recognized version and scale
+///
+/// COCO objects and model registry aliases are not restricted.
+fn validate_supported_model(object: &str, ver: f32, scale: &str) -> Result<()> {
+    if !SUPPORTED_MODEL_SCALES.iter().any(|(name, _)| *name == object) {
+        return Ok(());
+    }
+    if iter_supported_models().any(|model| model.object == object && model.ver == ver && model.scale == scale) {
+        return Ok(());
+    }
+
+    let combinations: Vec<String> = iter_supported_models()
+        .filter(|model| model.object == object)
+        .map(|model| format!("v{}{}", model.ver, model.scale))
+        .collect();
+    anyhow::bail!(
+        "Unsupported model for {}: version {} scale {}, supported: {}",
+        object,
+        ver,
+        scale,
+        combinations.join(", ")
+    )
+}
+
 /// Builds a YOLO model configuration from command line arguments
 pub fn build_config(args: &Args) -> Result<Config> {
-    let model_path = get_model_path(&args.object, args.ver, &args.scale);
+    validate_supported_model(&args.object, args.ver, &args.scale)?;
+    let mut model_path = get_model_path(&args.object, args.ver, &args.scale);
+    if !BUILT_IN_OBJECTS.contains(&args.object.as_str()) {
+        if let Some(registry_model) =
+            lookup_registry_model(&args.object, args.model_registry.as_deref())?
+        {
+            model_path = registry_model;
+        }
+    }
     
     let mut config = Config::yolo()
         .with_task(Task::ObjectDetection)
         .with_model_file(&model_path)
         .with_version(args.ver.try_into()?)
         .with_scale(args.scale.parse()?)
-        .with_model_dtype(args.dtype.parse()?)
-        .with_model_device(args.device.parse()?)
-        .with_model_num_dry_run(2);
+        .with_model_dtype(resolve_model_dtype(args)?)
+        .with_model_device(model_device(args).parse().with_context(|| {
+            format!(
+                "Unsupported device '{}', see --list-eps for the available execution providers",
+                model_device(args)
+            )
+        })?)
+        .with_model_num_dry_run(model_dry_run_frames(args));
 
     if model_path.is_empty() {
         config = config.with_class_names(&NAMES_COCO_80);
@@ -59,6 +322,68 @@ pub fn build_config(args: &Args) -> Result<Config> {
     Ok(config)
 }
 
+/// Model precision settings loaded from a `--quantization-policy` JSON file
+///
+/// The file looks like `{"default": "fp16", "force_fp32_layers": ["BatchNormalization"]}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizationPolicy {
+    /// The dtype used for the model
+    pub default: DType,
+    /// Layer types that must stay FP32 for accuracy
+    pub force_fp32_layers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct QuantizationPolicyFile {
+    default: String,
+    #[serde(default)]
+    force_fp32_layers: Vec<String>,
+}
+
+impl QuantizationPolicy {
+    /// Loads a quantization policy from a JSON file
+    pub fn load(path: &Path) -> Result<QuantizationPolicy> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read quantization policy: {}", path.display()))?;
+        let file: QuantizationPolicyFile = serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse quantization policy: {}", path.display()))?;
+        let default = file
+            .default
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid quantization policy dtype '{}': {}", file.default, e))?;
+        Ok(QuantizationPolicy {
+            default,
+            force_fp32_layers: file.force_fp32_layers,
+        })
+    }
+}
+
+/// Builds a YOLO model configuration whose dtype comes from a quantization policy
+///
+/// usls applies one dtype to the whole model, so layers in `force_fp32_layers` stay FP32 only
+/// if the quantized model file was exported with them excluded; they are reported as a reminder.
+pub fn build_config_with_quantization(args: &Args, policy: &QuantizationPolicy) -> Result<Config> {
+    if !policy.force_fp32_layers.is_empty() && policy.default != DType::Fp32 {
+        println!(
+            "Quantization policy: using {:?}; the model file must keep these layers in FP32: {}",
+            policy.default,
+            policy.force_fp32_layers.join(", ")
+        );
+    }
+    Ok(build_config(args)?.with_model_dtype(policy.default))
+}
+
+/// Builds the YOLO model configuration, applying `--quantization-policy` when given
+pub fn build_model_config(args: &Args) -> Result<Config> {
+    match &args.quantization_policy_file {
+        Some(path) => {
+            let policy = QuantizationPolicy::load(Path::new(path))?;
+            build_config_with_quantization(args, &policy)
+        }
+        None => build_config(args),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +412,196 @@ mod tests {
         assert_eq!(get_model_path("car", 8.0, "m"), "");
         assert_eq!(get_model_path("sports ball", 8.0, "m"), "");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_iter_supported_models() {
+        // 16 face models, 16 head combinations sharing one file and 8 ball models
+        assert_eq!(iter_supported_models().count(), 40);
+        assert_eq!(iter_supported_models().filter(|m| m.object == "face").count(), 16);
+        assert_eq!(iter_supported_models().filter(|m| m.object == "ball").count(), 8);
+
+        let face = iter_supported_models()
+            .find(|m| m.object == "face" && m.ver == 10.0 && m.scale == "s")
+            .unwrap();
+        assert_eq!(face.path, "./model/yolov10s-face.onnx");
+        assert!(
+            iter_supported_models()
+                .filter(|m| m.object == "head")
+                .all(|m| m.path == "./model/v8-head-fp16.onnx")
+        );
+    }
+
+    #[test]
+    fn test_validate_supported_model() {
+        assert!(validate_supported_model("face", 11.0, "m").is_ok());
+        assert!(validate_supported_model("head", 11.0, "m").is_ok());
+        assert!(validate_supported_model("ball", 8.0, "n").is_ok());
+        assert!(validate_supported_model("face", 9.0, "m").is_err());
+        assert!(validate_supported_model("face", 8.0, "x").is_err());
+        assert!(validate_supported_model("ball", 8.0, "s").is_err());
+
+        // COCO objects and registry aliases are not restricted
+        assert!(validate_supported_model("person", 9.0, "x").is_ok());
+        assert!(validate_supported_model("my_model", 9.0, "x").is_ok());
+    }
+
+    fn write_registry(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("land2port_{}_{}.json", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_auto_select_dtype() {
+        assert_eq!(auto_select_dtype("cuda:0", 1920), DType::Fp16);
+        assert_eq!(auto_select_dtype("coreml", 1280), DType::Fp16);
+        assert_eq!(auto_select_dtype("cuda:0", 3840), DType::Fp32);
+        assert_eq!(auto_select_dtype("cpu:0", 1280), DType::Fp32);
+        assert_eq!(auto_select_dtype("cpu:0", 3840), DType::Fp32);
+    }
+
+    #[test]
+    fn test_dtype_policy_auto_select() {
+        let conservative = DtypePolicy::Conservative;
+        assert_eq!(conservative.auto_select("cuda:0", 1920), Some(DType::Fp16));
+        assert_eq!(conservative.auto_select("cuda:0", 3840), Some(DType::Fp32));
+
+        let aggressive = DtypePolicy::Aggressive;
+        assert_eq!(aggressive.auto_select("coreml", 3840), Some(DType::Fp16));
+        assert_eq!(aggressive.auto_select("cpu:0", 1280), Some(DType::Fp32));
+
+        assert_eq!(DtypePolicy::Library.auto_select("cuda:0", 1920), None);
+    }
+
+    #[test]
+    fn test_model_dry_run_frames() {
+        use argh::FromArgs;
+
+        let args = Args::from_args(&["land2port"], &[]).unwrap();
+        assert_eq!(model_dry_run_frames(&args), 2);
+
+        let args = Args::from_args(&["land2port"], &["--model-dry-run-frames", "5"]).unwrap();
+        assert_eq!(model_dry_run_frames(&args), 5);
+
+        let args = Args::from_args(
+            &["land2port"],
+            &["--model-dry-run-frames", "5", "--skip-dry-run"],
+        )
+        .unwrap();
+        assert_eq!(model_dry_run_frames(&args), 0);
+        assert_eq!(cut_warmup_frames(&args), 5);
+
+        let args = Args::from_args(
+            &["land2port"],
+            &["--model-dry-run-frames", "8", "--cut-warmup-frames", "3"],
+        )
+        .unwrap();
+        assert_eq!(cut_warmup_frames(&args), 8);
+    }
+
+    #[test]
+    fn test_custom_execution_provider_passthrough() {
+        use argh::FromArgs;
+
+        let args = Args::from_args(&["land2port"], &["--device", "cuda:0"]).unwrap();
+        assert_eq!(model_device(&args), "cuda:0");
+
+        // The custom provider takes precedence and is passed through as given
+        let args =
+            Args::from_args(&["land2port"], &["--device", "cuda:0", "--custom-ep", " ROCm:1 "])
+                .unwrap();
+        assert_eq!(model_device(&args), "rocm:1");
+        let args = Args::from_args(&["land2port"], &["--custom-ep", "openvino"]).unwrap();
+        assert_eq!(model_device(&args), "openvino");
+    }
+
+    #[test]
+    fn test_dtype_policy_from_str() {
+        assert_eq!("conservative".parse::<DtypePolicy>(), Ok(DtypePolicy::Conservative));
+        assert_eq!("Aggressive".parse::<DtypePolicy>(), Ok(DtypePolicy::Aggressive));
+        assert_eq!("library".parse::<DtypePolicy>(), Ok(DtypePolicy::Library));
+        assert!("fast".parse::<DtypePolicy>().is_err());
+    }
+
+    #[test]
+    fn test_model_registry_lookup() {
+        let path = write_registry(
+            "registry_lookup",
+            r#"{"my_model": "/models/my_model.onnx", "other": "./model/other.onnx"}"#,
+        );
+        let registry = ModelRegistry::load(&path).unwrap();
+
+        assert_eq!(registry.lookup("my_model"), Some("/models/my_model.onnx"));
+        assert_eq!(registry.lookup("other"), Some("./model/other.onnx"));
+        assert_eq!(registry.lookup("missing"), None);
+
+        // Explicit registry paths are used for custom objects
+        let model = lookup_registry_model("my_model", path.to_str()).unwrap();
+        assert_eq!(model.as_deref(), Some("/models/my_model.onnx"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_quantization_policy_load() {
+        let path = write_registry(
+            "quantization_policy",
+            r#"{"default": "fp16", "force_fp32_layers": ["BatchNormalization", "Softmax"]}"#,
+        );
+        let policy = QuantizationPolicy::load(&path).unwrap();
+        assert_eq!(policy.default, DType::Fp16);
+        assert_eq!(policy.force_fp32_layers, vec!["BatchNormalization", "Softmax"]);
+        fs::remove_file(&path).unwrap();
+
+        // The layer list is optional
+        let path = write_registry("quantization_policy_default", r#"{"default": "int8"}"#);
+        let policy = QuantizationPolicy::load(&path).unwrap();
+        assert_eq!(policy.default, DType::Int8);
+        assert!(policy.force_fp32_layers.is_empty());
+        fs::remove_file(&path).unwrap();
+
+        let path = write_registry("quantization_policy_bad_dtype", r#"{"default": "fp7"}"#);
+        assert!(QuantizationPolicy::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+        assert!(QuantizationPolicy::load(Path::new("/nonexistent/policy.json")).is_err());
+    }
+
+    #[test]
+    fn test_build_config_with_quantization() {
+        let args = <Args as argh::FromArgs>::from_args(
+            &["land2port"],
+            &["--object", "person", "--dtype", "fp32"],
+        )
+        .unwrap();
+        let policy = QuantizationPolicy {
+            default: DType::Fp16,
+            force_fp32_layers: vec!["BatchNormalization".to_string()],
+        };
+        assert!(build_config_with_quantization(&args, &policy).is_ok());
+
+        // Without a policy file the plain configuration is built
+        assert!(build_model_config(&args).is_ok());
+
+        let args = <Args as argh::FromArgs>::from_args(
+            &["land2port"],
+            &["--object", "person", "--quantization-policy", "/nonexistent/policy.json"],
+        )
+        .unwrap();
+        assert!(build_model_config(&args).is_err());
+    }
+
+    #[test]
+    fn test_model_registry_errors() {
+        let path = write_registry("registry_malformed", r#"{"my_model": "/models/my_model.onnx""#);
+        assert!(ModelRegistry::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+
+        // A registry that is not a string map is rejected
+        let path = write_registry("registry_wrong_type", r#"{"my_model": 3}"#);
+        assert!(ModelRegistry::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+
+        // An explicitly requested registry must exist
+        assert!(lookup_registry_model("my_model", Some("/nonexistent/models.json")).is_err());
+    }
+}
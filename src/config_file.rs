@@ -0,0 +1,370 @@
+use crate::cli::Args;
+use anyhow::{Context, Result};
+use argh::FromArgs;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A value in a config file that can be turned back into command line arguments
+trait ConfigValue {
+    /// Appends `flag` and this value to `cli_args` the way argh expects them
+    fn push_cli_args(&self, flag: &str, cli_args: &mut Vec<String>);
+}
+
+impl ConfigValue for bool {
+    /// Switches are only passed when enabled, as they cannot be turned off on the command line
+    fn push_cli_args(&self, flag: &str, cli_args: &mut Vec<String>) {
+        if *self {
+            cli_args.push(flag.to_string());
+        }
+    }
+}
+
+impl ConfigValue for Vec<String> {
+    /// Repeats the flag for every value
+    fn push_cli_args(&self, flag: &str, cli_args: &mut Vec<String>) {
+        for value in self {
+            cli_args.push(flag.to_string());
+            cli_args.push(value.clone());
+        }
+    }
+}
+
+macro_rules! impl_config_value {
+    ($($ty:ty),*) => {
+        $(
+            impl ConfigValue for $ty {
+                fn push_cli_args(&self, flag: &str, cli_args: &mut Vec<String>) {
+                    cli_args.push(flag.to_string());
+                    cli_args.push(self.to_string());
+                }
+            }
+        )*
+    };
+}
+
+impl_config_value!(String, f32, f64, u32, u64, usize);
+
+/// Reads an `Args` field as a config file value
+///
+/// `value` fields are copied, `option` fields keep their `None` and `display` fields (options
+/// with their own parser) are written the way they are given on the command line.
+macro_rules! config_value {
+    (value, $arg:expr) => {
+        Some($arg.clone())
+    };
+    (option, $arg:expr) => {
+        $arg.clone()
+    };
+    (display, $arg:expr) => {
+        Some($arg.to_string())
+    };
+}
+
+/// Declares `ConfigFile` with one optional entry per `Args` field and the flag it sets
+macro_rules! config_file {
+    ($($field:ident: $ty:ty [$kind:ident] => $flag:literal,)*) => {
+        /// Options loaded from a `--config` TOML file
+        ///
+        /// Keys are the `Args` field names, e.g. `smooth_duration = 2.0`, and values are written
+        /// as on the command line, e.g. `pillarbox = "blur:20"`. Missing keys keep their
+        /// defaults and unknown keys are rejected.
+        #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        pub struct ConfigFile {
+            $(pub $field: Option<$ty>,)*
+        }
+
+        impl ConfigFile {
+            /// Captures the effective options of a run, e.g. to save them as a preset
+            pub fn from_args(args: &Args) -> Self {
+                Self {
+                    $($field: config_value!($kind, args.$field),)*
+                }
+            }
+
+            /// Converts the file's options into command line arguments, leaving out every
+            /// option that also appears in `cli_args` so the command line takes precedence
+            pub fn to_cli_args(&self, cli_args: &[String]) -> Vec<String> {
+                let mut config_args = Vec::new();
+                $(
+                    if let Some(value) = &self.$field {
+                        if !cli_args.iter().any(|arg| arg == $flag) {
+                            value.push_cli_args($flag, &mut config_args);
+                        }
+                    }
+                )*
+                config_args
+            }
+        }
+    };
+}
+
+config_file! {
+    object: String [value] => "--object",
+    object_names: Vec<String> [value] => "--object-names",
+    nms_threshold: f32 [value] => "--nms-threshold",
+    dedup_distance: f32 [value] => "--dedup-distance",
+    model_registry: String [option] => "--model-registry",
+    batch_dir: String [option] => "--batch-dir",
+    batch_glob: String [option] => "--batch-glob",
+    source: String [value] => "--source",
+    source_rotation: u32 [option] => "--source-rotation",
+    verify_input: bool [value] => "--verify-input",
+    dtype: String [value] => "--dtype",
+    dtype_policy: String [display] => "--dtype-policy",
+    quantization_policy_file: String [option] => "--quantization-policy",
+    model_dry_run_frames: usize [value] => "--model-dry-run-frames",
+    skip_dry_run: bool [value] => "--skip-dry-run",
+    ver: f32 [value] => "--ver",
+    device: String [value] => "--device",
+    custom_execution_provider: String [option] => "--custom-ep",
+    list_eps: bool [value] => "--list-eps",
+    scale: String [value] => "--scale",
+    smooth_percentage: f32 [value] => "--smooth-percentage",
+    smooth_duration: f32 [value] => "--smooth-duration",
+    interpolated_transition_frames: usize [value] => "--interpolated-transition-frames",
+    interpolation_frames: usize [value] => "--interpolation-frames",
+    use_crop_prediction: bool [value] => "--use-crop-prediction",
+    object_count_debounce_frames: usize [value] => "--object-count-debounce-frames",
+    adaptive_smoothing: bool [value] => "--adaptive-smoothing",
+    max_expected_speed: f32 [value] => "--max-expected-speed",
+    object_prob_threshold: f32 [value] => "--object-prob-threshold",
+    min_crop_coverage: f32 [option] => "--min-crop-coverage",
+    safe_zone_check: bool [value] => "--safe-zone-check",
+    safe_zone_margin: f32 [value] => "--safe-zone-margin",
+    camera_motion_compensation: bool [value] => "--camera-motion-compensation",
+    debug_side_by_side: String [option] => "--debug-side-by-side",
+    debug_side_by_side_interval: u64 [value] => "--debug-side-by-side-interval",
+    debug_frame_output: String [option] => "--debug-frame-output",
+    debug_frame_interval: u64 [value] => "--debug-frame-interval",
+    dry_run: bool [value] => "--dry-run",
+    debug_overlay: bool [value] => "--debug-overlay",
+    export_crops: String [option] => "--export-crops",
+    save_crop_sequence: String [option] => "--save-crop-sequence",
+    load_crop_sequence: String [option] => "--load-crop-sequence",
+    replay_last_n_frames: usize [value] => "--replay-last-n-frames",
+    replay_crop: String [option] => "--replay-crop",
+    replay_dir: String [value] => "--replay-dir",
+    crop_change_webhook: String [option] => "--crop-change-webhook",
+    log_transitions: bool [value] => "--log-transitions",
+    start_time: f32 [option] => "--start-time",
+    end_time: f32 [option] => "--end-time",
+    normalize_audio: bool [value] => "--normalize-audio",
+    target_lufs: f64 [value] => "--target-lufs",
+    audio_pad_start: f32 [value] => "--audio-pad-start",
+    audio_pad_end: f32 [value] => "--audio-pad-end",
+    min_free_gb: f32 [value] => "--min-free-gb",
+    first_frame_only: bool [value] => "--first-frame-only",
+    head_padding: f32 [value] => "--head-padding",
+    head_min_aspect: f32 [value] => "--head-min-aspect",
+    head_max_aspect: f32 [value] => "--head-max-aspect",
+    crop_offset_x: f32 [value] => "--crop-offset-x",
+    crop_offset_y: f32 [value] => "--crop-offset-y",
+    crop_scale: f32 [value] => "--crop-scale",
+    ball_zone: String [option] => "--ball-zone",
+    ball_scoreboard_zone: String [option] => "--ball-scoreboard-zone",
+    ball_max_prediction_frames: usize [value] => "--ball-max-prediction-frames",
+    ball_max_size_change: f32 [value] => "--ball-max-size-change",
+    object_area_threshold: f32 [value] => "--object-area-threshold",
+    class_area_threshold: Vec<String> [value] => "--class-area-threshold",
+    cut_similarity: f64 [value] => "--cut-similarity",
+    cut_start: f64 [value] => "--cut-start",
+    cut_detection_method: String [display] => "--cut-detection-method",
+    cut_warmup_frames: usize [value] => "--cut-warmup-frames",
+    pillarbox: String [display] => "--pillarbox",
+    image_cache_size: usize [value] => "--image-cache-size",
+    perf_stats: bool [value] => "--perf-stats",
+    thumbnail_interval: u64 [value] => "--thumbnail-interval",
+    thumbnail_grid_cols: usize [value] => "--thumbnail-grid-cols",
+    thumbnail_grid_rows: usize [value] => "--thumbnail-grid-rows",
+    resize_filter: String [display] => "--resize-filter",
+    sharpen_amount: f32 [value] => "--sharpen-amount",
+    sharpen_radius: f32 [value] => "--sharpen-radius",
+    watermark_path: String [option] => "--watermark-path",
+    watermark_position: String [display] => "--watermark-position",
+    watermark_opacity: f32 [value] => "--watermark-opacity",
+    headless: bool [value] => "--headless",
+    use_stack_crop: bool [value] => "--use-stack-crop",
+    allow_vertical_stack: bool [value] => "--allow-vertical-stack",
+    zoom_in_frames: usize [value] => "--zoom-in-frames",
+    weighted_bbox: bool [value] => "--weighted-bbox",
+    use_weighted_center: bool [value] => "--use-weighted-center",
+    no_detection_strategy: String [display] => "--no-detection-strategy",
+    use_simple_smoothing: bool [value] => "--use-simple-smoothing",
+    use_kalman_smoothing: bool [value] => "--use-kalman-smoothing",
+    kalman_process_noise: f32 [value] => "--kalman-q",
+    kalman_measurement_noise: f32 [value] => "--kalman-r",
+    simple_lookahead_frames: usize [value] => "--simple-lookahead-frames",
+    simple_median_window: usize [option] => "--simple-median-window",
+    confidence_blend: bool [value] => "--confidence-blend",
+    keep_graphic: bool [value] => "--keep-graphic",
+    graphic_threshold: f32 [value] => "--graphic-threshold",
+    clip_classify_interval: usize [value] => "--clip-classify-every-n-frames",
+    add_captions: bool [value] => "--add-captions",
+    transcript_language: String [option] => "--transcript-language",
+    transcript_temperature: f32 [option] => "--transcript-temperature",
+    transcription_backend: String [value] => "--transcription-backend",
+    whisper_model_path: String [option] => "--whisper-model-path",
+    split_long_cues: u32 [value] => "--split-long-cues",
+    captions_from_chapters: bool [value] => "--captions-from-chapters",
+    use_embedded_subtitles: bool [value] => "--use-embedded-subtitles",
+    subtitle_stream_index: usize [value] => "--subtitle-stream-index",
+    srt_strip_html: bool [value] => "--srt-strip-html",
+    srt_convert_html: bool [value] => "--srt-convert-html",
+    caption_format: String [display] => "--caption-format",
+    caption_position: String [display] => "--caption-position",
+    audio_track_index: usize [value] => "--audio-track-index",
+    audio_codec: String [display] => "--audio-codec",
+    output_filepath: String [value] => "--output-filepath",
+    output_codec: String [display] => "--output-codec",
+    output_quality: u32 [option] => "--output-quality",
+    output_fps: f32 [value] => "--output-fps",
+    icc_profile_path: String [option] => "--icc-profile",
+    strip_icc_profile: bool [value] => "--strip-icc-profile",
+    progress_json_pipe: String [option] => "--progress-json-pipe",
+    resume_from: String [option] => "--resume-from",
+    concat_output: String [option] => "--concat-output",
+}
+
+impl ConfigFile {
+    /// Loads a config file
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+    }
+
+    /// Writes the options to a TOML file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string(self).context("Failed to serialize config file")?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write config file: {}", path.display()))
+    }
+}
+
+/// Parses the command line arguments after the options from a `--config` file
+///
+/// Options come from the built-in defaults, then the config file, then the command line, each
+/// overriding the one before.
+///
+/// # Arguments
+/// * `command` - The program name
+/// * `cli_args` - The command line arguments after the program name
+pub fn parse_args(command: &str, cli_args: &[String]) -> Result<Args, argh::EarlyExit> {
+    let config_path = cli_args
+        .iter()
+        .position(|arg| arg == "--config")
+        .and_then(|index| cli_args.get(index + 1));
+    let mut merged_args = match config_path {
+        Some(path) => ConfigFile::load(Path::new(path))
+            .map_err(|e| argh::EarlyExit {
+                output: format!("{:#}", e),
+                status: Err(()),
+            })?
+            .to_cli_args(cli_args),
+        None => Vec::new(),
+    };
+    merged_args.extend(cli_args.iter().cloned());
+
+    let merged_args: Vec<&str> = merged_args.iter().map(String::as_str).collect();
+    Args::from_args(&[command], &merged_args)
+}
+
+/// Parses the process arguments with `parse_args`, exiting like `argh::from_env` on
+/// `--help` or invalid arguments
+pub fn args_from_env() -> Args {
+    let strings: Vec<String> = std::env::args().collect();
+    let command = strings.first().map(String::as_str).unwrap_or("land2port");
+    let cli_args = strings.get(1..).unwrap_or(&[]);
+    match parse_args(command, cli_args) {
+        Ok(args) => args,
+        Err(early_exit) => match early_exit.status {
+            Ok(()) => {
+                println!("{}", early_exit.output);
+                std::process::exit(0);
+            }
+            Err(()) => {
+                eprintln!(
+                    "{}\nRun {} --help for more information.",
+                    early_exit.output, command
+                );
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn parse_with_config(config: &str, cli_args: &[&str]) -> Args {
+        let config: ConfigFile = toml::from_str(config).unwrap();
+        let cli_args = to_strings(cli_args);
+        let mut merged_args = config.to_cli_args(&cli_args);
+        merged_args.extend(cli_args);
+        let merged_args: Vec<&str> = merged_args.iter().map(String::as_str).collect();
+        Args::from_args(&["land2port"], &merged_args).unwrap()
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let config = r#"
+            smooth_duration = 2.5
+            smooth_percentage = 20.0
+            headless = true
+            pillarbox = "mirror"
+            object_names = ["head", "face"]
+        "#;
+
+        let args = parse_with_config(config, &["--smooth-percentage", "5.0"]);
+        // The config file overrides defaults, and the command line overrides the config file
+        assert_eq!(args.smooth_duration, 2.5);
+        assert_eq!(args.smooth_percentage, 5.0);
+        assert!(args.headless);
+        assert_eq!(args.pillarbox.to_string(), "mirror");
+        assert_eq!(args.object_names, vec!["head", "face"]);
+        // Options in neither keep their defaults
+        assert_eq!(args.object, "face");
+
+        // Repeated options on the command line replace the whole list from the config file
+        let args = parse_with_config(config, &["--object-names", "person"]);
+        assert_eq!(args.object_names, vec!["person"]);
+    }
+
+    #[test]
+    fn test_missing_config_file() {
+        let cli_args = to_strings(&["--config", "/nonexistent/land2port.toml"]);
+        let early_exit = parse_args("land2port", &cli_args).unwrap_err();
+        assert!(early_exit.status.is_err());
+        assert!(early_exit.output.contains("/nonexistent/land2port.toml"));
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        assert!(toml::from_str::<ConfigFile>("smooth_durration = 2.0").is_err());
+    }
+
+    #[test]
+    fn test_output_config_round_trip() {
+        let args = Args::from_args(
+            &["land2port"],
+            &["--smooth-duration", "3.0", "--pillarbox", "solid:1a2b3c", "--headless"],
+        )
+        .unwrap();
+        let config = ConfigFile::from_args(&args);
+        let saved: ConfigFile = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(saved, config);
+
+        let restored = parse_with_config(&toml::to_string(&config).unwrap(), &[]);
+        assert_eq!(restored.smooth_duration, 3.0);
+        assert_eq!(restored.pillarbox.to_string(), "solid:1a2b3c");
+        assert!(restored.headless);
+        assert_eq!(ConfigFile::from_args(&restored), config);
+    }
+}
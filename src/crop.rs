@@ -1,8 +1,9 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
 use usls::Hbb;
 
 /// Represents a crop area in the image
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CropArea {
     pub x: f32,
     pub y: f32,
@@ -50,11 +51,209 @@ impl CropArea {
         let h_ok = is_within_threshold("height", self.height, other.height);
         x_ok && y_ok && w_ok && h_ok
     }
+
+    /// Parses a crop area from a `"x%,y%,w%,h%"` string of percentages of the frame size
+    ///
+    /// Each value must be in `[0, 100]`, and `x + w` and `y + h` must not exceed 100. The `%`
+    /// signs are optional. The resulting pixel area is clamped to the frame.
+    ///
+    /// # Arguments
+    /// * `s` - The comma-separated percentages
+    /// * `frame_width` - The width of the frame in pixels
+    /// * `frame_height` - The height of the frame in pixels
+    pub fn from_percentage_str(s: &str, frame_width: f32, frame_height: f32) -> Result<CropArea> {
+        let [x, y, w, h] = parse_four_floats(s, true)?;
+
+        for (label, value) in [("x", x), ("y", y), ("width", w), ("height", h)] {
+            if !(0.0..=100.0).contains(&value) {
+                bail!("{} percentage {} in '{}' is outside 0-100", label, value, s);
+            }
+        }
+        // Allow for rounding in user-supplied values like 33.4 + 66.6
+        const TOLERANCE: f32 = 1e-3;
+        if x + w > 100.0 + TOLERANCE || y + h > 100.0 + TOLERANCE {
+            bail!("crop zone '{}' extends past the frame", s);
+        }
+
+        let x = (x / 100.0 * frame_width).min(frame_width);
+        let y = (y / 100.0 * frame_height).min(frame_height);
+        let width = (w / 100.0 * frame_width).min(frame_width - x);
+        let height = (h / 100.0 * frame_height).min(frame_height - y);
+        Ok(CropArea::new(x, y, width, height))
+    }
+
+    /// Parses a crop area from either a `"x,y,w,h"` pixel string or a `"x%,y%,w%,h%"` string
+    ///
+    /// Strings ending with `%` are parsed with `from_percentage_str`; anything else is taken as
+    /// pixel coordinates.
+    pub fn from_zone_str(s: &str, frame_width: f32, frame_height: f32) -> Result<CropArea> {
+        if s.trim_end().ends_with('%') {
+            return CropArea::from_percentage_str(s, frame_width, frame_height);
+        }
+
+        let [x, y, width, height] = parse_four_floats(s, false)?;
+        if x < 0.0 || y < 0.0 || width <= 0.0 || height <= 0.0 {
+            bail!("crop zone '{}' must have a non-negative position and positive size", s);
+        }
+        Ok(CropArea::new(x, y, width, height))
+    }
+
+    /// Creates a full-height crop of width `frame_height * scale` centered on `(cx, cy)`
+    ///
+    /// A scale of 0.75 gives the 3:4 crop used for single heads. The crop is moved inside the
+    /// frame when the center is too close to an edge.
+    ///
+    /// # Arguments
+    /// * `cx` - The horizontal center of the crop
+    /// * `cy` - The vertical center of the crop
+    /// * `scale` - The crop width as a fraction of the frame height
+    /// * `frame_width` - The width of the frame
+    /// * `frame_height` - The height of the frame
+    pub fn from_center_scale(
+        cx: f32,
+        cy: f32,
+        scale: f32,
+        frame_width: f32,
+        frame_height: f32,
+    ) -> CropArea {
+        let width = frame_height * scale;
+        let height = frame_height;
+        let x = clamp_x_for_width(cx - width / 2.0, width, frame_width);
+        let y = clamp_x_for_width(cy - height / 2.0, height, frame_height);
+        CropArea::new(x, y, width, height)
+    }
+
+    /// Creates a crop area covering exactly the bounding box of a head
+    pub fn from_hbb(head: &Hbb) -> CropArea {
+        CropArea::new(
+            head.cx() - head.width() / 2.0,
+            head.cy() - head.height() / 2.0,
+            head.width(),
+            head.height(),
+        )
+    }
+
+    /// Expands a head's bounding box by `padding_factor * max(width, height)` on each side
+    ///
+    /// The padded area is clamped to the frame, so heads near an edge get less padding on that side.
+    ///
+    /// # Arguments
+    /// * `head` - The head to pad
+    /// * `padding_factor` - The padding on each side as a fraction of the head's larger dimension
+    /// * `frame_width` - The width of the frame
+    /// * `frame_height` - The height of the frame
+    pub fn compute_padding(
+        head: &Hbb,
+        padding_factor: f32,
+        frame_width: f32,
+        frame_height: f32,
+    ) -> CropArea {
+        let area = CropArea::from_hbb(head);
+        let padding = padding_factor.max(0.0) * area.width.max(area.height);
+        let x_min = (area.x - padding).max(0.0);
+        let y_min = (area.y - padding).max(0.0);
+        let x_max = (area.x + area.width + padding).min(frame_width);
+        let y_max = (area.y + area.height + padding).min(frame_height);
+        CropArea::new(x_min, y_min, (x_max - x_min).max(0.0), (y_max - y_min).max(0.0))
+    }
+
+    /// Grows this crop area around its center to the given width/height ratio, staying in the frame
+    ///
+    /// Areas that would not fit are shrunk to the largest area of that ratio inside the frame.
+    ///
+    /// # Arguments
+    /// * `aspect` - The target width divided by height, e.g. 9.0 / 4.0
+    /// * `frame_width` - The width of the frame
+    /// * `frame_height` - The height of the frame
+    pub fn expand_to_aspect(&self, aspect: f32, frame_width: f32, frame_height: f32) -> CropArea {
+        let (mut width, mut height) = if self.width / self.height > aspect {
+            (self.width, self.width / aspect)
+        } else {
+            (self.height * aspect, self.height)
+        };
+        if width > frame_width {
+            width = frame_width;
+            height = width / aspect;
+        }
+        if height > frame_height {
+            height = frame_height;
+            width = height * aspect;
+        }
+        let x = clamp_x_for_width(self.x + (self.width - width) / 2.0, width, frame_width);
+        let y = clamp_x_for_width(self.y + (self.height - height) / 2.0, height, frame_height);
+        CropArea::new(x, y, width, height)
+    }
+
+    /// Returns the broadcast safe area of a frame: the frame inset by `margin_percent` on each side
+    ///
+    /// # Arguments
+    /// * `margin_percent` - The inset on each edge as a percentage of the frame size (e.g. 5.0)
+    /// * `frame_width` - The width of the frame
+    /// * `frame_height` - The height of the frame
+    pub fn safe_zone(margin_percent: f32, frame_width: f32, frame_height: f32) -> CropArea {
+        let margin = (margin_percent / 100.0).clamp(0.0, 0.5);
+        CropArea::new(
+            frame_width * margin,
+            frame_height * margin,
+            frame_width * (1.0 - 2.0 * margin),
+            frame_height * (1.0 - 2.0 * margin),
+        )
+    }
+
+    /// Checks whether this crop area lies entirely inside the safe area of the frame
+    ///
+    /// # Arguments
+    /// * `margin_percent` - The safe area inset on each edge as a percentage of the frame size
+    /// * `frame_width` - The width of the frame
+    /// * `frame_height` - The height of the frame
+    pub fn is_within_safe_zone(&self, margin_percent: f32, frame_width: f32, frame_height: f32) -> bool {
+        let zone = CropArea::safe_zone(margin_percent, frame_width, frame_height);
+        // Allow for rounding when the crop sits exactly on the safe area edge
+        const TOLERANCE: f32 = 1e-3;
+        self.x >= zone.x - TOLERANCE
+            && self.y >= zone.y - TOLERANCE
+            && self.x + self.width <= zone.x + zone.width + TOLERANCE
+            && self.y + self.height <= zone.y + zone.height + TOLERANCE
+    }
+
+    /// Formats this crop area as an ffmpeg `crop=w:h:x:y` filter
+    pub fn to_ffmpeg_crop_filter(&self) -> String {
+        format!(
+            "crop={}:{}:{}:{}",
+            self.width as u32, self.height as u32, self.x as u32, self.y as u32
+        )
+    }
+}
+
+/// Parses exactly four comma-separated floats, optionally allowing a trailing `%` on each
+fn parse_four_floats(s: &str, allow_percent: bool) -> Result<[f32; 4]> {
+    let values = s
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            let part = if allow_percent { part.trim_end_matches('%').trim() } else { part };
+            part.parse::<f32>()
+                .map_err(|_| anyhow::anyhow!("invalid number '{}' in crop zone '{}'", part, s))
+        })
+        .collect::<Result<Vec<f32>>>()?;
+
+    match values.as_slice() {
+        [a, b, c, d] if values.iter().all(|v| v.is_finite()) => Ok([*a, *b, *c, *d]),
+        [_, _, _, _] => bail!("crop zone '{}' contains a non-finite value", s),
+        _ => bail!("crop zone '{}' must have four values: x,y,w,h", s),
+    }
 }
 
 // Helper utilities to reduce duplication across crop calculations
+/// Widest head bounding box, as a fraction of the frame width, that six or more similar-sized
+/// heads can span and still be followed instead of centering the crop on the frame
+const MAX_CLUSTERED_HEADS_WIDTH_RATIO: f32 = 0.75;
+
+/// Width of a 3:4 crop as a fraction of the frame height
+const THREE_FOUR_SCALE: f32 = 3.0 / 4.0;
+
 fn compute_three_four_width(frame_height: f32) -> f32 {
-    frame_height * (3.0 / 4.0)
+    frame_height * THREE_FOUR_SCALE
 }
 
 fn clamp_x_for_width(x: f32, width: f32, frame_width: f32) -> f32 {
@@ -68,16 +267,63 @@ fn clamp_x_for_width(x: f32, width: f32, frame_width: f32) -> f32 {
 }
 
 fn make_single_crop_centered(center_x: f32, frame_width: f32, frame_height: f32) -> CropArea {
-    let height = frame_height;
-    let width = compute_three_four_width(frame_height);
-    let x = clamp_x_for_width(center_x - width / 2.0, width, frame_width);
-    CropArea::new(x, 0.0, width, height)
+    CropArea::from_center_scale(
+        center_x,
+        frame_height / 2.0,
+        THREE_FOUR_SCALE,
+        frame_width,
+        frame_height,
+    )
 }
 
 fn center_x_of_bbox(bbox: &CropArea) -> f32 {
     bbox.x + bbox.width / 2.0
 }
 
+/// Calculates the confidence-weighted horizontal center of the given heads
+///
+/// Returns `Σ(cx_i * conf_i) / Σ(conf_i)`, so the center lies closer to the more confident heads.
+/// Heads without a confidence count as 1.0, and the plain mean is used when the confidences sum
+/// to zero.
+pub fn calculate_weighted_center(heads: &[&Hbb]) -> f32 {
+    if heads.is_empty() {
+        return 0.0;
+    }
+    let confidences: Vec<f32> = heads.iter().map(|h| h.confidence().unwrap_or(1.0)).collect();
+    let confidence_sum: f32 = confidences.iter().sum();
+    if confidence_sum <= 0.0 {
+        return heads.iter().map(|h| h.cx()).sum::<f32>() / heads.len() as f32;
+    }
+    heads
+        .iter()
+        .zip(&confidences)
+        .map(|(h, confidence)| h.cx() * confidence)
+        .sum::<f32>()
+        / confidence_sum
+}
+
+/// Returns the horizontal center for a crop of `crop_width` around `heads` and their `bbox`
+///
+/// With `use_weighted_center` and a confidence on every head, this is the weighted center from
+/// `calculate_weighted_center`, limited so a bounding box that fits in the crop stays inside it.
+/// Otherwise it is the center of the bounding box.
+fn crop_center_x(
+    bbox: &CropArea,
+    heads: &[&Hbb],
+    crop_width: f32,
+    use_weighted_center: bool,
+) -> f32 {
+    if !use_weighted_center || heads.is_empty() || heads.iter().any(|h| h.confidence().is_none()) {
+        return center_x_of_bbox(bbox);
+    }
+    let center_x = calculate_weighted_center(heads);
+    if bbox.width <= crop_width {
+        center_x.clamp(bbox.x + bbox.width - crop_width / 2.0, bbox.x + crop_width / 2.0)
+    } else {
+        center_x
+    }
+}
+
 fn half_stack_dims(frame_width: f32, frame_height: f32) -> (f32, f32, f32) {
     let crop_width = frame_width * 0.5;
     let crop_height = crop_width * (8.0 / 9.0);
@@ -105,8 +351,66 @@ fn vertical_y_for_heads(
     }
 }
 
+/// A geometric adjustment applied to every crop area of a computed crop before rendering
+///
+/// Each area is moved by the offsets, scaled around its center, grown by `padding` on every
+/// side and then clamped to the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CropTransform {
+    /// Horizontal shift in pixels
+    pub x_offset: f32,
+    /// Vertical shift in pixels
+    pub y_offset: f32,
+    /// Factor the width and height are multiplied by
+    pub scale_factor: f32,
+    /// Pixels added on each side after scaling
+    pub padding: f32,
+    /// The width of the frame the result is clamped to
+    pub frame_width: f32,
+    /// The height of the frame the result is clamped to
+    pub frame_height: f32,
+}
+
+impl CropTransform {
+    /// Creates a transform that leaves crops unchanged (apart from clamping them to the frame)
+    pub fn identity(frame_width: f32, frame_height: f32) -> CropTransform {
+        CropTransform {
+            x_offset: 0.0,
+            y_offset: 0.0,
+            scale_factor: 1.0,
+            padding: 0.0,
+            frame_width,
+            frame_height,
+        }
+    }
+
+    /// Combines this transform with `other`, applied afterwards, into a single transform
+    ///
+    /// The result matches applying both in turn as long as the intermediate crop stays inside
+    /// the frame, since clamping only happens once. The frame size of `other` is used.
+    pub fn then(&self, other: &CropTransform) -> CropTransform {
+        CropTransform {
+            x_offset: self.x_offset + other.x_offset,
+            y_offset: self.y_offset + other.y_offset,
+            scale_factor: self.scale_factor * other.scale_factor,
+            padding: self.padding * other.scale_factor + other.padding,
+            frame_width: other.frame_width,
+            frame_height: other.frame_height,
+        }
+    }
+
+    /// Applies the transform to a single crop area, which may end up outside the frame
+    fn apply(&self, crop: CropArea) -> CropArea {
+        let cx = crop.x + crop.width / 2.0 + self.x_offset;
+        let cy = crop.y + crop.height / 2.0 + self.y_offset;
+        let width = crop.width * self.scale_factor + 2.0 * self.padding;
+        let height = crop.height * self.scale_factor + 2.0 * self.padding;
+        CropArea::new(cx - width / 2.0, cy - height / 2.0, width, height)
+    }
+}
+
 /// Represents the result of calculating crop areas
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CropResult {
     /// A single crop area
     Single(CropArea),
@@ -114,6 +418,336 @@ pub enum CropResult {
     Stacked(CropArea, CropArea),
     /// Resize the entire frame (for graphic mode)
     Resize(CropArea),
+    /// A digital zoom from `start` to `end`, where `progress` runs from 0.0 (start) to 1.0 (end)
+    ZoomIn {
+        start: CropArea,
+        end: CropArea,
+        progress: f32,
+    },
+}
+
+impl CropResult {
+    /// Calls `f` with every crop area of this crop result
+    ///
+    /// Single and resize crops have one area, stacked crops two (top first) and zoom-ins their
+    /// start and end areas.
+    pub fn for_each_crop(&self, mut f: impl FnMut(&CropArea)) {
+        match self {
+            CropResult::Single(crop) | CropResult::Resize(crop) => f(crop),
+            CropResult::Stacked(crop1, crop2) => {
+                f(crop1);
+                f(crop2);
+            }
+            CropResult::ZoomIn { start, end, .. } => {
+                f(start);
+                f(end);
+            }
+        }
+    }
+
+    /// Calls `f` with a mutable reference to every crop area of this crop result
+    pub fn for_each_crop_mut(&mut self, mut f: impl FnMut(&mut CropArea)) {
+        match self {
+            CropResult::Single(crop) | CropResult::Resize(crop) => f(crop),
+            CropResult::Stacked(crop1, crop2) => {
+                f(crop1);
+                f(crop2);
+            }
+            CropResult::ZoomIn { start, end, .. } => {
+                f(start);
+                f(end);
+            }
+        }
+    }
+
+    /// Returns this crop result with `f` applied to every crop area, keeping the variant
+    pub fn map_crops<F: Fn(CropArea) -> CropArea>(self, f: F) -> CropResult {
+        match self {
+            CropResult::Single(crop) => CropResult::Single(f(crop)),
+            CropResult::Stacked(crop1, crop2) => CropResult::Stacked(f(crop1), f(crop2)),
+            CropResult::Resize(crop) => CropResult::Resize(f(crop)),
+            CropResult::ZoomIn {
+                start,
+                end,
+                progress,
+            } => CropResult::ZoomIn {
+                start: f(start),
+                end: f(end),
+                progress,
+            },
+        }
+    }
+
+    /// Shrinks and moves every crop area so it lies inside the frame
+    pub fn clamp_to_frame(&mut self, frame_width: f32, frame_height: f32) {
+        self.for_each_crop_mut(|crop| {
+            crop.width = crop.width.clamp(0.0, frame_width);
+            crop.height = crop.height.clamp(0.0, frame_height);
+            crop.x = crop.x.clamp(0.0, frame_width - crop.width);
+            crop.y = crop.y.clamp(0.0, frame_height - crop.height);
+        });
+    }
+
+    /// Returns this crop result with `transform` applied to every crop area, clamped to the frame
+    pub fn apply_transform(&self, transform: &CropTransform) -> CropResult {
+        let mut transformed = self.clone().map_crops(|crop| transform.apply(crop));
+        transformed.clamp_to_frame(transform.frame_width, transform.frame_height);
+        transformed
+    }
+
+    /// Gets the fraction of the frame covered by this crop result
+    ///
+    /// For stacked crops the areas of both crops are summed. The result is clamped to `[0.0, 1.0]`.
+    pub fn area_ratio(&self, frame_width: f32, frame_height: f32) -> f32 {
+        let frame_area = frame_width * frame_height;
+        if frame_area <= 0.0 {
+            return 0.0;
+        }
+        let area = |crop: &CropArea| crop.width * crop.height;
+        let crop_area = match self {
+            CropResult::Single(crop) | CropResult::Resize(crop) => area(crop),
+            CropResult::Stacked(crop1, crop2) => area(crop1) + area(crop2),
+            CropResult::ZoomIn {
+                start,
+                end,
+                progress,
+            } => area(&interpolate_crop_area(start, end, *progress)),
+        };
+        (crop_area / frame_area).clamp(0.0, 1.0)
+    }
+
+    /// Checks whether every crop area of this crop result lies inside the safe area of the frame
+    ///
+    /// Zoom-ins are checked at their current interpolated position.
+    pub fn is_within_safe_zone(&self, margin_percent: f32, frame_width: f32, frame_height: f32) -> bool {
+        let is_safe = |crop: &CropArea| crop.is_within_safe_zone(margin_percent, frame_width, frame_height);
+        match self {
+            CropResult::Single(crop) | CropResult::Resize(crop) => is_safe(crop),
+            CropResult::Stacked(crop1, crop2) => is_safe(crop1) && is_safe(crop2),
+            CropResult::ZoomIn {
+                start,
+                end,
+                progress,
+            } => is_safe(&interpolate_crop_area(start, end, *progress)),
+        }
+    }
+
+    /// Checks whether `interpolate` can blend this crop result into `other`
+    ///
+    /// Only single, stacked and resize crops of the same kind can be blended.
+    pub fn can_interpolate(&self, other: &CropResult) -> bool {
+        matches!(
+            (self, other),
+            (CropResult::Single(_), CropResult::Single(_))
+                | (CropResult::Stacked(_, _), CropResult::Stacked(_, _))
+                | (CropResult::Resize(_), CropResult::Resize(_))
+        )
+    }
+
+    /// Linearly interpolates between two crop results
+    ///
+    /// Single, stacked and resize crops of the same kind are interpolated component-wise. Crops
+    /// that cannot be interpolated (see `can_interpolate`) give `self` for `t < 0.5` and `other`
+    /// from `t >= 0.5` on.
+    ///
+    /// # Arguments
+    /// * `other` - The crop result at `t = 1.0`
+    /// * `t` - Interpolation position, clamped to `[0.0, 1.0]`
+    pub fn interpolate(&self, other: &CropResult, t: f32) -> CropResult {
+        match (self, other) {
+            (CropResult::Single(a), CropResult::Single(b)) => {
+                CropResult::Single(interpolate_crop_area(a, b, t))
+            }
+            (CropResult::Resize(a), CropResult::Resize(b)) => {
+                CropResult::Resize(interpolate_crop_area(a, b, t))
+            }
+            (CropResult::Stacked(a1, a2), CropResult::Stacked(b1, b2)) => CropResult::Stacked(
+                interpolate_crop_area(a1, b1, t),
+                interpolate_crop_area(a2, b2, t),
+            ),
+            _ if t >= 0.5 => other.clone(),
+            _ => self.clone(),
+        }
+    }
+
+    /// Converts this crop result to coordinates normalized to `[0, 1]` by the frame size
+    ///
+    /// Zoom-ins are normalized as a single crop at their current zoom position.
+    ///
+    /// # Arguments
+    /// * `frame_width` - The width of the frame in pixels
+    /// * `frame_height` - The height of the frame in pixels
+    pub fn normalize(&self, frame_width: f32, frame_height: f32) -> NormalizedCropResult {
+        let current = self.at_zoom_position();
+        let mut areas = Vec::with_capacity(2);
+        current.for_each_crop(|crop| {
+            areas.push(NormalizedCropArea::from_crop_area(crop, frame_width, frame_height))
+        });
+        match (&current, areas.as_slice()) {
+            (_, [top, bottom]) => NormalizedCropResult::Stacked(top.clone(), bottom.clone()),
+            (CropResult::Resize(_), [area]) => NormalizedCropResult::Resize(area.clone()),
+            (_, [area]) => NormalizedCropResult::Single(area.clone()),
+            _ => unreachable!("a crop result without zoom has one or two crop areas"),
+        }
+    }
+
+    /// Formats this crop result as an ffmpeg video filter graph
+    ///
+    /// Single and resize crops become a plain `crop` filter. Stacked crops split the input, crop
+    /// both areas, scale the bottom crop to the width of the top crop and stack them vertically.
+    pub fn to_ffmpeg_filter(&self) -> String {
+        let mut crops = Vec::with_capacity(2);
+        self.at_zoom_position().for_each_crop(|crop| crops.push(crop.clone()));
+        match crops.as_slice() {
+            [top, bottom] => format!(
+                "split[v1][v2];[v1]{}[top];[v2]{},scale={}:-2[bottom];[top][bottom]vstack",
+                top.to_ffmpeg_crop_filter(),
+                bottom.to_ffmpeg_crop_filter(),
+                top.width as u32
+            ),
+            [crop] => crop.to_ffmpeg_crop_filter(),
+            _ => unreachable!("a crop result without zoom has one or two crop areas"),
+        }
+    }
+
+    /// Returns this crop result with a zoom-in replaced by a single crop at its current position
+    fn at_zoom_position(&self) -> CropResult {
+        match self {
+            CropResult::ZoomIn {
+                start,
+                end,
+                progress,
+            } => CropResult::Single(interpolate_crop_area(start, end, *progress)),
+            crop_result => crop_result.clone(),
+        }
+    }
+}
+
+/// A crop area in coordinates normalized to `[0, 1]` by the frame size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizedCropArea {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl NormalizedCropArea {
+    /// Normalizes a pixel crop area by the frame size
+    pub fn from_crop_area(crop: &CropArea, frame_width: f32, frame_height: f32) -> Self {
+        Self {
+            x: crop.x / frame_width,
+            y: crop.y / frame_height,
+            width: crop.width / frame_width,
+            height: crop.height / frame_height,
+        }
+    }
+
+    /// Converts back to a pixel crop area for the given frame size
+    pub fn to_crop_area(&self, frame_width: f32, frame_height: f32) -> CropArea {
+        CropArea::new(
+            self.x * frame_width,
+            self.y * frame_height,
+            self.width * frame_width,
+            self.height * frame_height,
+        )
+    }
+}
+
+/// A crop result in normalized coordinates, for consumers that do not know the frame size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NormalizedCropResult {
+    /// A single crop area
+    Single(NormalizedCropArea),
+    /// Two crop areas that should be stacked vertically
+    Stacked(NormalizedCropArea, NormalizedCropArea),
+    /// Resize the entire frame (for graphic mode)
+    Resize(NormalizedCropArea),
+}
+
+impl NormalizedCropResult {
+    /// Converts back to a pixel crop result for the given frame size
+    ///
+    /// # Arguments
+    /// * `frame_width` - The width of the frame in pixels
+    /// * `frame_height` - The height of the frame in pixels
+    pub fn denormalize(&self, frame_width: f32, frame_height: f32) -> CropResult {
+        match self {
+            NormalizedCropResult::Single(crop) => {
+                CropResult::Single(crop.to_crop_area(frame_width, frame_height))
+            }
+            NormalizedCropResult::Stacked(crop1, crop2) => CropResult::Stacked(
+                crop1.to_crop_area(frame_width, frame_height),
+                crop2.to_crop_area(frame_width, frame_height),
+            ),
+            NormalizedCropResult::Resize(crop) => {
+                CropResult::Resize(crop.to_crop_area(frame_width, frame_height))
+            }
+        }
+    }
+}
+
+/// Linearly interpolates between two crop areas
+///
+/// # Arguments
+/// * `start` - The crop area at `progress = 0.0`
+/// * `end` - The crop area at `progress = 1.0`
+/// * `progress` - Interpolation position, clamped to `[0.0, 1.0]`
+pub fn interpolate_crop_area(start: &CropArea, end: &CropArea, progress: f32) -> CropArea {
+    let t = progress.clamp(0.0, 1.0);
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    CropArea::new(
+        lerp(start.x, end.x),
+        lerp(start.y, end.y),
+        lerp(start.width, end.width),
+        lerp(start.height, end.height),
+    )
+}
+
+/// Blends from the previous crop towards the latest crop based on detection confidence
+///
+/// When the latest crop is backed by detections at least as confident as the previous crop it
+/// is used immediately. Otherwise the crop only moves by the ratio of the two confidences, so
+/// low-confidence detections pull the crop slowly.
+///
+/// # Arguments
+/// * `prev` - The crop area currently applied
+/// * `latest` - The crop area calculated for the current frame
+/// * `prev_confidence` - Average head confidence behind the previous crop
+/// * `new_confidence` - Average head confidence behind the latest crop
+pub fn confidence_weighted_blend(
+    prev: &CropArea,
+    latest: &CropArea,
+    prev_confidence: f32,
+    new_confidence: f32,
+) -> CropArea {
+    if prev_confidence <= 0.0 || new_confidence >= prev_confidence {
+        return latest.clone();
+    }
+    interpolate_crop_area(prev, latest, new_confidence.max(0.0) / prev_confidence)
+}
+
+/// Moves a crop against the camera motion so the subject stays centered while the camera pans
+///
+/// # Arguments
+/// * `crop` - The crop area to move
+/// * `camera_motion_dx` - Horizontal camera motion in pixels (positive when panning right)
+/// * `camera_motion_dy` - Vertical camera motion in pixels (positive when tilting down)
+/// * `frame_width` - The width of the frame
+/// * `frame_height` - The height of the frame
+///
+/// # Returns
+/// The moved crop area, clamped to the frame
+pub fn calculate_optical_flow_guidance(
+    crop: &CropArea,
+    camera_motion_dx: f32,
+    camera_motion_dy: f32,
+    frame_width: f32,
+    frame_height: f32,
+) -> CropArea {
+    let x = (crop.x - camera_motion_dx).clamp(0.0, (frame_width - crop.width).max(0.0));
+    let y = (crop.y - camera_motion_dy).clamp(0.0, (frame_height - crop.height).max(0.0));
+    CropArea::new(x, y, crop.width, crop.height)
 }
 
 /// Calculates crop area when no heads are detected
@@ -136,30 +770,162 @@ pub fn calculate_no_heads_crop(
     }
 }
 
-/// Calculates crop area for a single head
-pub fn calculate_single_head_crop(frame_width: f32, frame_height: f32, head: &Hbb) -> CropResult {
+/// Grows a head's bounding box by `padding_percent` percent of its diagonal on each side
+///
+/// The padded box is clamped to the frame, so heads at an edge only grow inwards there. The
+/// confidence is kept for confidence-weighted crops.
+///
+/// # Arguments
+/// * `head` - The head to pad
+/// * `padding_percent` - The margin on each side as a percentage of the head's diagonal (e.g. 5.0)
+/// * `frame_width` - The width of the frame
+/// * `frame_height` - The height of the frame
+pub fn pad_head(head: &Hbb, padding_percent: f32, frame_width: f32, frame_height: f32) -> Hbb {
+    if padding_percent <= 0.0 {
+        return head.clone();
+    }
+    let padded = CropArea::compute_padding(
+        head,
+        diagonal_padding_factor(head, padding_percent),
+        frame_width,
+        frame_height,
+    );
+    let padded = Hbb::from_xywh(padded.x, padded.y, padded.width, padded.height);
+    match head.confidence() {
+        Some(confidence) => padded.with_confidence(confidence),
+        None => padded,
+    }
+}
+
+/// Converts a padding of `padding_percent` percent of a head's diagonal into the padding factor
+/// of `CropArea::compute_padding`, which is relative to the head's larger dimension
+fn diagonal_padding_factor(head: &Hbb, padding_percent: f32) -> f32 {
+    let larger_dimension = head.width().max(head.height());
+    if larger_dimension <= 0.0 {
+        return 0.0;
+    }
+    let diagonal = (head.width().powi(2) + head.height().powi(2)).sqrt();
+    diagonal * padding_percent / 100.0 / larger_dimension
+}
+
+/// Pads every head with `pad_head`
+fn pad_heads(heads: &[&Hbb], padding_percent: f32, frame_width: f32, frame_height: f32) -> Vec<Hbb> {
+    heads
+        .iter()
+        .map(|head| pad_head(head, padding_percent, frame_width, frame_height))
+        .collect()
+}
+
+/// Calculates crop area for a single head, padded by `padding_percent` (see `pad_head`)
+pub fn calculate_single_head_crop(
+    frame_width: f32,
+    frame_height: f32,
+    head: &Hbb,
+    padding_percent: f32,
+) -> CropResult {
+    let padded = CropArea::compute_padding(
+        head,
+        diagonal_padding_factor(head, padding_percent),
+        frame_width,
+        frame_height,
+    );
     CropResult::Single(make_single_crop_centered(
-        head.cx(),
+        center_x_of_bbox(&padded),
         frame_width,
         frame_height,
     ))
 }
 
-/// Calculates crop area for two heads
+/// Calculates the per-field median (x, y, width and height separately) of a window of crop areas
+///
+/// For an even number of crops the two middle values are averaged.
+/// Returns an empty crop area when the window is empty.
+pub fn median_crop_area(window: &[CropArea]) -> CropArea {
+    if window.is_empty() {
+        return CropArea::new(0.0, 0.0, 0.0, 0.0);
+    }
+
+    let median = |field: fn(&CropArea) -> f32| -> f32 {
+        let mut values: Vec<f32> = window.iter().map(field).collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let mid = values.len() / 2;
+        if values.len() % 2 == 0 {
+            (values[mid - 1] + values[mid]) / 2.0
+        } else {
+            values[mid]
+        }
+    };
+
+    CropArea::new(
+        median(|c| c.x),
+        median(|c| c.y),
+        median(|c| c.width),
+        median(|c| c.height),
+    )
+}
+
+/// Calculates a zoom crop that starts at the single-head 3:4 crop and ends on a tighter
+/// crop of the same aspect ratio centered on the head
+///
+/// # Arguments
+/// * `frame_width` - Width of the input frame
+/// * `frame_height` - Height of the input frame
+/// * `head` - The head to zoom into
+/// * `zoom_factor` - How much tighter the end crop is than the start crop (e.g. 1.5)
+///
+/// # Returns
+/// A `CropResult::ZoomIn` with `progress` set to 0.0
+pub fn calculate_zoom_in_crop(
+    frame_width: f32,
+    frame_height: f32,
+    head: &Hbb,
+    zoom_factor: f32,
+) -> CropResult {
+    let start = make_single_crop_centered(head.cx(), frame_width, frame_height);
+
+    let zoom_factor = zoom_factor.max(1.0);
+    let end_width = start.width / zoom_factor;
+    let end_height = start.height / zoom_factor;
+    let end_x = clamp_x_for_width(head.cx() - end_width / 2.0, end_width, frame_width);
+    let end_y = (head.cy() - end_height / 2.0)
+        .max(0.0)
+        .min(frame_height - end_height);
+    let end = CropArea::new(end_x, end_y, end_width, end_height);
+
+    CropResult::ZoomIn {
+        start,
+        end,
+        progress: 0.0,
+    }
+}
+
+/// Calculates crop area for two heads, padded by `options.padding_percent` (see `pad_head`)
+///
+/// With `options.use_weighted_center`, a single crop leans toward the more confident head (see
+/// `crop_center_x`).
 pub fn calculate_two_heads_crop(
     use_stack_crop: bool,
     frame_width: f32,
     frame_height: f32,
     head1: &Hbb,
     head2: &Hbb,
+    options: &CropOptions,
 ) -> CropResult {
+    let head1 = &pad_head(head1, options.padding_percent, frame_width, frame_height);
+    let head2 = &pad_head(head2, options.padding_percent, frame_width, frame_height);
+
     // Calculate the bounding box of the two heads
-    let bbox = calculate_bounding_box(&[head1, head2]);
+    let bbox = heads_bounding_box(&[head1, head2], options.weighted_bbox);
 
     // Check if the width of the bounding box is less than or equal to 3/4 of the frame height
     if bbox.width <= frame_height * 0.75 {
         // Return a single crop centered on the bounding box
-        let center_x = center_x_of_bbox(&bbox);
+        let center_x = crop_center_x(
+            &bbox,
+            &[head1, head2],
+            compute_three_four_width(frame_height),
+            options.use_weighted_center,
+        );
         CropResult::Single(make_single_crop_centered(
             center_x,
             frame_width,
@@ -233,13 +999,54 @@ pub fn calculate_two_heads_crop(
     }
 }
 
-/// Calculates crop area for three heads
+/// Calculates a top/bottom stacked crop for two heads above each other, e.g. in aerial shots
+///
+/// Returns `None` unless the heads are more than half the frame height apart vertically and
+/// their bounding box is narrow enough for a 3:4 crop. Each crop is 9:8, spans half the frame
+/// height and is centered horizontally on its head.
+pub fn calculate_vertical_two_heads_crop(
+    frame_width: f32,
+    frame_height: f32,
+    head1: &Hbb,
+    head2: &Hbb,
+) -> Option<CropResult> {
+    if (head1.cy() - head2.cy()).abs() <= frame_height * 0.5 {
+        return None;
+    }
+    let bbox = calculate_bounding_box(&[head1, head2]);
+    if bbox.width > frame_height * 0.75 {
+        return None;
+    }
+
+    let (top_head, bottom_head) = if head1.cy() <= head2.cy() {
+        (head1, head2)
+    } else {
+        (head2, head1)
+    };
+    let crop_height = frame_height / 2.0;
+    let crop_width = crop_height * (9.0 / 8.0);
+    let crop_for_head = |head: &Hbb, y: f32| {
+        let x = clamp_x_for_width(head.cx() - crop_width / 2.0, crop_width, frame_width);
+        CropArea::new(x, y, crop_width, crop_height)
+    };
+    Some(CropResult::Stacked(
+        crop_for_head(top_head, 0.0),
+        crop_for_head(bottom_head, crop_height),
+    ))
+}
+
+/// Calculates crop area for three heads, padded by `options.padding_percent` (see `pad_head`)
 pub fn calculate_three_heads_crop(
     use_stack_crop: bool,
     frame_width: f32,
     frame_height: f32,
     heads: &[&Hbb],
+    options: &CropOptions,
 ) -> CropResult {
+    let padded_heads = pad_heads(heads, options.padding_percent, frame_width, frame_height);
+    let heads: Vec<&Hbb> = padded_heads.iter().collect();
+    let heads = heads.as_slice();
+
     // Check if heads are roughly the same size
     let areas: Vec<f32> = heads.iter().map(|h| h.width() * h.height()).collect();
     let min_area = areas.iter().fold(f32::MAX, |a, &b| a.min(b));
@@ -306,8 +1113,17 @@ pub fn calculate_three_heads_crop(
         return CropResult::Stacked(crop1, crop2);
     }
 
-    // Fall back to the existing logic
-    calculate_four_and_five_heads_crop(use_stack_crop, frame_width, frame_height, heads)
+    // Fall back to the existing logic, the heads are already padded
+    calculate_four_and_five_heads_crop(
+        use_stack_crop,
+        frame_width,
+        frame_height,
+        heads,
+        &CropOptions {
+            padding_percent: 0.0,
+            ..*options
+        },
+    )
 }
 
 /// Calculates crop area for four and five heads
@@ -316,14 +1132,24 @@ pub fn calculate_four_and_five_heads_crop(
     frame_width: f32,
     frame_height: f32,
     heads: &[&Hbb],
+    options: &CropOptions,
 ) -> CropResult {
+    let padded_heads = pad_heads(heads, options.padding_percent, frame_width, frame_height);
+    let heads: Vec<&Hbb> = padded_heads.iter().collect();
+    let heads = heads.as_slice();
+
     // Calculate the bounding box that contains all heads
-    let bbox = calculate_bounding_box(heads);
+    let bbox = heads_bounding_box(heads, options.weighted_bbox);
 
     // If the bounding box width is less than or equal to 3/4 of the frame height,
     // we can fit all heads in a single crop
     if bbox.width <= frame_height * (3.0 / 4.0) {
-        let center_x = center_x_of_bbox(&bbox);
+        let center_x = crop_center_x(
+            &bbox,
+            heads,
+            compute_three_four_width(frame_height),
+            options.use_weighted_center,
+        );
         CropResult::Single(make_single_crop_centered(
             center_x,
             frame_width,
@@ -463,19 +1289,29 @@ pub fn calculate_four_and_five_heads_crop(
     }
 }
 
-/// Calculates crop area for six or more heads
+/// Calculates crop area for six or more heads, padded by `options.padding_percent` (see `pad_head`)
 pub fn calculate_six_or_more_heads_crop(
     use_stack_crop: bool,
     frame_width: f32,
     frame_height: f32,
     heads: &[&Hbb],
+    options: &CropOptions,
 ) -> CropResult {
+    let padded_heads = pad_heads(heads, options.padding_percent, frame_width, frame_height);
+    let heads: Vec<&Hbb> = padded_heads.iter().collect();
+    let heads = heads.as_slice();
+
     // Calculate the bounding box that contains all heads
-    let bbox = calculate_bounding_box(heads);
+    let bbox = heads_bounding_box(heads, options.weighted_bbox);
 
     // Check if the bounding box width is less than or equal to 3/4 of the frame height
     if bbox.width <= frame_height * (3.0 / 4.0) {
-        let center_x = center_x_of_bbox(&bbox);
+        let center_x = crop_center_x(
+            &bbox,
+            heads,
+            compute_three_four_width(frame_height),
+            options.use_weighted_center,
+        );
         CropResult::Single(make_single_crop_centered(
             center_x,
             frame_width,
@@ -527,8 +1363,14 @@ pub fn calculate_six_or_more_heads_crop(
                 }
 
                 // Position second crop to contain remaining heads near their center
-                let remaining_bbox = calculate_bounding_box(&remaining_heads);
-                let mut crop2_x = center_x_of_bbox(&remaining_bbox) - crop_width / 2.0;
+                let remaining_bbox = heads_bounding_box(&remaining_heads, options.weighted_bbox);
+                let remaining_center_x = crop_center_x(
+                    &remaining_bbox,
+                    &remaining_heads,
+                    crop_width,
+                    options.use_weighted_center,
+                );
+                let mut crop2_x = remaining_center_x - crop_width / 2.0;
                 crop2_x = crop2_x.max(0.0).min(frame_width - crop_width);
 
                 // Ensure crops don't overlap too much
@@ -545,11 +1387,23 @@ pub fn calculate_six_or_more_heads_crop(
 
                 CropResult::Stacked(crop1, crop2)
             } else {
-                // Just center a single crop on the large head
-                calculate_single_head_crop(frame_width, frame_height, large_head)
+                // Just center a single crop on the large head, which is already padded
+                calculate_single_head_crop(frame_width, frame_height, large_head, 0.0)
             }
+        } else if bbox.width <= frame_width * MAX_CLUSTERED_HEADS_WIDTH_RATIO {
+            // No large head, but the heads are clustered in part of the frame, so follow them
+            CropResult::Single(make_single_crop_centered(
+                crop_center_x(
+                    &bbox,
+                    heads,
+                    compute_three_four_width(frame_height),
+                    options.use_weighted_center,
+                ),
+                frame_width,
+                frame_height,
+            ))
         } else {
-            // No large head found, call calculate_no_heads_crop with is_graphic = false
+            // Heads spread across the frame, call calculate_no_heads_crop with is_graphic = false
             calculate_no_heads_crop(frame_width, frame_height, false)
         }
     }
@@ -566,24 +1420,68 @@ pub fn calculate_crop_from_largest_head(
         .iter()
         .max_by(|a, b| a.area().partial_cmp(&b.area()).unwrap())
         .unwrap();
-    let head_center_x = largest_head.cx();
 
-    // Set height to match frame height
-    let height = frame_height;
-    // Set width to 3/4 of the height (3:4 aspect ratio)
-    let width = height * (3.0 / 4.0);
+    // Full-height 3:4 crop centered on the largest head
+    CropResult::Single(CropArea::from_center_scale(
+        largest_head.cx(),
+        frame_height / 2.0,
+        THREE_FOUR_SCALE,
+        frame_width,
+        frame_height,
+    ))
+}
+
+/// What to output for frames without any detections
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NoDetectionStrategy {
+    /// Center a 3:4 crop on the frame
+    #[default]
+    CenterCrop,
+    /// Keep the crop of the previous output frame (the center crop if there is none yet)
+    PreviousCrop,
+    /// Leave the frame out of the output video
+    SkipFrame,
+}
 
-    // Calculate initial x position centered on the largest head
-    let mut x = head_center_x - width / 2.0;
+impl std::str::FromStr for NoDetectionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "center" => Ok(NoDetectionStrategy::CenterCrop),
+            "previous" => Ok(NoDetectionStrategy::PreviousCrop),
+            "skip" => Ok(NoDetectionStrategy::SkipFrame),
+            _ => Err(format!(
+                "unsupported no-detection strategy '{}', expected center, previous or skip",
+                s
+            )),
+        }
+    }
+}
 
-    // Clamp x to frame bounds
-    if x < 0.0 {
-        x = 0.0; // Clamp to left edge
-    } else if x + width > frame_width {
-        x = frame_width - width; // Clamp to right edge
+/// Formats the strategy as `--no-detection-strategy` expects it
+impl std::fmt::Display for NoDetectionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoDetectionStrategy::CenterCrop => write!(f, "center"),
+            NoDetectionStrategy::PreviousCrop => write!(f, "previous"),
+            NoDetectionStrategy::SkipFrame => write!(f, "skip"),
+        }
     }
+}
 
-    CropResult::Single(CropArea::new(x, 0.0, width, height))
+/// Settings that control how `calculate_crop_area` frames the detected heads
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CropOptions {
+    /// What to do when there are no heads outside graphic mode
+    pub strategy: NoDetectionStrategy,
+    /// The margin added around each head, as a percentage of its diagonal (see `pad_head`)
+    pub padding_percent: f32,
+    /// Center multi-head crops on the confidence-weighted center of the heads
+    pub use_weighted_center: bool,
+    /// Fit multi-head crops to the confidence-weighted bounding box of the heads (see
+    /// `calculate_bounding_box_weighted`)
+    pub weighted_bbox: bool,
 }
 
 /// Calculates the optimal crop area based on detected heads
@@ -594,50 +1492,63 @@ pub fn calculate_crop_from_largest_head(
 /// * `frame_width` - Width of the input frame
 /// * `frame_height` - Height of the input frame
 /// * `heads` - Vector of head detections that have already been filtered by confidence threshold
+/// * `options` - The no-detection strategy, head padding, centering and bounding box to use
+///
+/// # Returns
+/// The crop, or `None` when there are no heads and the strategy is not `CenterCrop`; the caller
+/// then reuses its previous crop or skips the frame
 pub fn calculate_crop_area(
     use_stack_crop: bool,
     is_graphic: bool,
     frame_width: f32,
     frame_height: f32,
     heads: &[&Hbb],
-) -> Result<CropResult> {
-    match heads.len() {
-        0 => Ok(calculate_no_heads_crop(
+    options: &CropOptions,
+) -> Result<Option<CropResult>> {
+    let crop = match heads.len() {
+        0 if !is_graphic && options.strategy != NoDetectionStrategy::CenterCrop => return Ok(None),
+        0 => calculate_no_heads_crop(
             frame_width,
             frame_height,
             is_graphic,
-        )),
-        1 => Ok(calculate_single_head_crop(
+        ),
+        1 => calculate_single_head_crop(
             frame_width,
             frame_height,
             heads[0],
-        )),
-        2 => Ok(calculate_two_heads_crop(
+            options.padding_percent,
+        ),
+        2 => calculate_two_heads_crop(
             use_stack_crop,
             frame_width,
             frame_height,
             heads[0],
             heads[1],
-        )),
-        3 => Ok(calculate_three_heads_crop(
+            options,
+        ),
+        3 => calculate_three_heads_crop(
             use_stack_crop,
             frame_width,
             frame_height,
             heads,
-        )),
-        4..=5 => Ok(calculate_four_and_five_heads_crop(
+            options,
+        ),
+        4..=5 => calculate_four_and_five_heads_crop(
             use_stack_crop,
             frame_width,
             frame_height,
             heads,
-        )),
-        6.. => Ok(calculate_six_or_more_heads_crop(
+            options,
+        ),
+        6.. => calculate_six_or_more_heads_crop(
             use_stack_crop,
             frame_width,
             frame_height,
             heads,
-        )),
-    }
+            options,
+        ),
+    };
+    Ok(Some(crop))
 }
 
 /// Calculates the bounding box that contains all given heads
@@ -652,115 +1563,1198 @@ pub fn calculate_bounding_box(heads: &[&Hbb]) -> CropArea {
     let mut max_y = f32::MIN;
 
     for head in heads {
-        let xmin = head.cx() - head.width() / 2.0;
-        let ymin = head.cy() - head.height() / 2.0;
-        let xmax = head.cx() + head.width() / 2.0;
-        let ymax = head.cy() + head.height() / 2.0;
+        let area = CropArea::from_hbb(head);
+        min_x = min_x.min(area.x);
+        min_y = min_y.min(area.y);
+        max_x = max_x.max(area.x + area.width);
+        max_y = max_y.max(area.y + area.height);
+    }
+
+    CropArea::new(min_x, min_y, max_x - min_x, max_y - min_y)
+}
+
+/// Calculates a confidence-aware bounding box of the given heads
+///
+/// Each side is a soft minimum or maximum of the head extents instead of the hard one:
+/// `soft_min = Σ(xmin_i * (1 - w_i)) / Σ(1 - w_i)` and `soft_max = Σ(xmax_i * w_i) / Σ(w_i)`,
+/// and likewise for y. Both are weighted averages of the head extents, so the result is never
+/// wider than `calculate_bounding_box`. Falls back to the unweighted bounding box when all weights
+/// are equal, the weights are unusable or the soft box would be inverted.
+///
+/// # Arguments
+/// * `heads` - The heads to bound
+/// * `weights` - One weight per head in `0.0..=1.0`, typically the detection confidence
+pub fn calculate_bounding_box_weighted(heads: &[&Hbb], weights: &[f32]) -> CropArea {
+    if heads.is_empty()
+        || weights.len() != heads.len()
+        || weights.iter().all(|w| (w - weights[0]).abs() <= f32::EPSILON)
+    {
+        return calculate_bounding_box(heads);
+    }
+
+    let weights: Vec<f32> = weights.iter().map(|w| w.clamp(0.0, 1.0)).collect();
+    let max_sum: f32 = weights.iter().sum();
+    let min_sum: f32 = weights.iter().map(|w| 1.0 - w).sum();
+    if max_sum <= 0.0 || min_sum <= 0.0 {
+        return calculate_bounding_box(heads);
+    }
+
+    let soft_min = |extent: fn(&Hbb) -> f32| {
+        heads.iter().zip(&weights).map(|(h, w)| extent(h) * (1.0 - w)).sum::<f32>() / min_sum
+    };
+    let soft_max = |extent: fn(&Hbb) -> f32| {
+        heads.iter().zip(&weights).map(|(h, w)| extent(h) * w).sum::<f32>() / max_sum
+    };
+    let (min_x, max_x) = (soft_min(Hbb::xmin), soft_max(Hbb::xmax));
+    let (min_y, max_y) = (soft_min(Hbb::ymin), soft_max(Hbb::ymax));
+    if min_x > max_x || min_y > max_y {
+        return calculate_bounding_box(heads);
+    }
+
+    CropArea::new(min_x, min_y, max_x - min_x, max_y - min_y)
+}
+
+/// Returns the bounding box of `heads`, weighted by their confidence with `weighted_bbox`
+///
+/// Heads without a confidence count as fully trusted.
+fn heads_bounding_box(heads: &[&Hbb], weighted_bbox: bool) -> CropArea {
+    if !weighted_bbox {
+        return calculate_bounding_box(heads);
+    }
+    let weights: Vec<f32> = heads.iter().map(|h| h.confidence().unwrap_or(1.0)).collect();
+    calculate_bounding_box_weighted(heads, &weights)
+}
+
+/// Determines if two head counts would result in different crop classes
+///
+/// Crop classes are defined as:
+/// - 0 heads
+/// - 1 head  
+/// - 2 heads
+/// - 3 to 5 heads
+/// - More than 5 heads
+///
+/// # Arguments
+/// * `head_count1` - First head count
+/// * `head_count2` - Second head count
+///
+/// # Returns
+/// `true` if the head counts would result in different crop classes, `false` otherwise
+pub fn is_crop_class_same(head_count1: usize, head_count2: usize) -> bool {
+    get_crop_class(head_count1) == get_crop_class(head_count2)
+}
+
+/// Gets the crop class for a given head count
+pub fn get_crop_class(head_count: usize) -> u8 {
+    match head_count {
+        0 => 0,   // 0 heads
+        1 => 1,   // 1 head
+        2 => 2,   // 2 heads
+        3 => 3,   // 3 heads
+        4.. => 4, // 4 or more heads
+    }
+}
+
+/// Builds `frames` crops at `t = 1/(frames+1), ..., frames/(frames+1)` from one crop to another
+///
+/// Crops that cannot be interpolated (e.g. single to stacked) hold the old crop for the first half
+/// and switch to the new one for the rest (see `CropResult::interpolate`).
+pub fn interpolation_crops(from: &CropResult, to: &CropResult, frames: usize) -> Vec<CropResult> {
+    (1..=frames)
+        .map(|i| from.interpolate(to, i as f32 / (frames + 1) as f32))
+        .collect()
+}
+
+/// Builds the blended crops shown while transitioning from one crop to another
+///
+/// Like `interpolation_crops`, but crops that cannot be interpolated get no blended crops, so
+/// crop class changes (e.g. single to stacked) stay immediate.
+pub fn transition_crops(from: &CropResult, to: &CropResult, frames: usize) -> Vec<CropResult> {
+    if from.can_interpolate(to) {
+        interpolation_crops(from, to, frames)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Gets the center point of a crop result
+///
+/// Stacked crops use the midpoint of both crop centers and zoom-ins use the currently
+/// interpolated crop.
+pub fn crop_centroid(crop: &CropResult) -> (f32, f32) {
+    let center = |area: &CropArea| (area.x + area.width / 2.0, area.y + area.height / 2.0);
+    match crop {
+        CropResult::Single(area) | CropResult::Resize(area) => center(area),
+        CropResult::Stacked(crop1, crop2) => {
+            let (x1, y1) = center(crop1);
+            let (x2, y2) = center(crop2);
+            ((x1 + x2) / 2.0, (y1 + y2) / 2.0)
+        }
+        CropResult::ZoomIn {
+            start,
+            end,
+            progress,
+        } => center(&interpolate_crop_area(start, end, *progress)),
+    }
+}
+
+/// Calculates the distance in pixels between the centers of two crop results
+pub fn centroid_distance(crop1: &CropResult, crop2: &CropResult) -> f32 {
+    let (x1, y1) = crop_centroid(crop1);
+    let (x2, y2) = crop_centroid(crop2);
+    ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt()
+}
+
+/// Checks if two crop results are similar based on a threshold percentage
+pub fn is_crop_similar(crop1: &CropResult, crop2: &CropResult, width: f32, threshold: f32) -> bool {
+    match (crop1, crop2) {
+        (CropResult::Single(crop1), CropResult::Single(crop2)) => {
+            crop1.is_within_percentage(crop2, width, threshold)
+        }
+        (CropResult::Stacked(crop1_1, crop1_2), CropResult::Stacked(crop2_1, crop2_2)) => {
+            crop1_1.is_within_percentage(crop2_1, width, threshold)
+                && crop1_2.is_within_percentage(crop2_2, width, threshold)
+        }
+        (CropResult::Resize(crop1), CropResult::Resize(crop2)) => {
+            crop1.is_within_percentage(crop2, width, threshold)
+        }
+        _ => false, // If crop types don't match, use the new crop
+    }
+}
+
+/// Checks whether two crops from different crop classes have the same layout and nearly the
+/// same position, so switching between them would not visibly change the output
+///
+/// For example, the stacked crops for three and four heads are often almost identical when the
+/// fourth person stands next to the others.
+///
+/// # Arguments
+/// * `crop1` - The first crop result
+/// * `crop2` - The second crop result
+/// * `frame_width` - The width of the frame
+/// * `similarity_threshold` - The maximum allowed difference as a percentage of the frame width
+pub fn is_crop_structurally_equivalent(
+    crop1: &CropResult,
+    crop2: &CropResult,
+    frame_width: f32,
+    similarity_threshold: f32,
+) -> bool {
+    std::mem::discriminant(crop1) == std::mem::discriminant(crop2)
+        && is_crop_similar(crop1, crop2, frame_width, similarity_threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_areas_close(a: &CropArea, b: &CropArea) {
+        for (va, vb) in [(a.x, b.x), (a.y, b.y), (a.width, b.width), (a.height, b.height)] {
+            assert!((va - vb).abs() < 1e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_calculate_optical_flow_guidance() {
+        let crop = CropArea::new(600.0, 0.0, 607.5, 1080.0);
+
+        // Camera panning right moves the subject left in the frame
+        let moved = calculate_optical_flow_guidance(&crop, 40.0, 0.0, 1920.0, 1080.0);
+        assert_eq!(moved, CropArea::new(560.0, 0.0, 607.5, 1080.0));
+
+        let moved = calculate_optical_flow_guidance(&crop, -25.0, 0.0, 1920.0, 1080.0);
+        assert_eq!(moved, CropArea::new(625.0, 0.0, 607.5, 1080.0));
+
+        // Clamped to the frame on both sides and vertically
+        let moved = calculate_optical_flow_guidance(&crop, 1000.0, 0.0, 1920.0, 1080.0);
+        assert_eq!(moved.x, 0.0);
+        let moved = calculate_optical_flow_guidance(&crop, -1000.0, 30.0, 1920.0, 1080.0);
+        assert_eq!((moved.x, moved.y), (1920.0 - 607.5, 0.0));
+
+        let small = CropArea::new(100.0, 100.0, 200.0, 300.0);
+        let moved = calculate_optical_flow_guidance(&small, 0.0, -50.0, 1920.0, 1080.0);
+        assert_eq!(moved.y, 150.0);
+    }
+
+    #[test]
+    fn test_normalize_round_trip() {
+        let (fw, fh) = (1920.0, 1080.0);
+        let single = CropResult::Single(CropArea::new(555.0, 0.0, 607.5, 1080.0));
+        let stacked = CropResult::Stacked(
+            CropArea::new(100.0, 50.0, 960.0, 540.0),
+            CropArea::new(960.0, 540.0, 960.0, 540.0),
+        );
+        let resize = CropResult::Resize(CropArea::new(0.0, 0.0, 1920.0, 1080.0));
+
+        match single.normalize(fw, fh) {
+            NormalizedCropResult::Single(area) => {
+                assert!((area.x - 555.0 / 1920.0).abs() < 1e-6);
+                assert!((area.height - 1.0).abs() < 1e-6);
+            }
+            other => panic!("Expected single crop, got {:?}", other),
+        }
+
+        match (single.normalize(fw, fh).denormalize(fw, fh), &single) {
+            (CropResult::Single(a), CropResult::Single(b)) => assert_areas_close(&a, b),
+            _ => panic!("Expected single crop"),
+        }
+        match (stacked.normalize(fw, fh).denormalize(fw, fh), &stacked) {
+            (CropResult::Stacked(a1, a2), CropResult::Stacked(b1, b2)) => {
+                assert_areas_close(&a1, b1);
+                assert_areas_close(&a2, b2);
+            }
+            _ => panic!("Expected stacked crop"),
+        }
+        match (resize.normalize(fw, fh).denormalize(fw, fh), &resize) {
+            (CropResult::Resize(a), CropResult::Resize(b)) => assert_areas_close(&a, b),
+            _ => panic!("Expected resize crop"),
+        }
+
+        // Normalized values can be reused at a different resolution
+        match resize.normalize(fw, fh).denormalize(1280.0, 720.0) {
+            CropResult::Resize(area) => {
+                assert_areas_close(&area, &CropArea::new(0.0, 0.0, 1280.0, 720.0))
+            }
+            _ => panic!("Expected resize crop"),
+        }
+
+        // Zoom-ins normalize to a single crop at their current position
+        let zoom = CropResult::ZoomIn {
+            start: CropArea::new(0.0, 0.0, 800.0, 1000.0),
+            end: CropArea::new(100.0, 100.0, 400.0, 500.0),
+            progress: 0.5,
+        };
+        match zoom.normalize(fw, fh).denormalize(fw, fh) {
+            CropResult::Single(area) => {
+                assert_areas_close(&area, &CropArea::new(50.0, 50.0, 600.0, 750.0))
+            }
+            other => panic!("Expected single crop, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_calculate_bounding_box() {
+        // Test single head
+        let head = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
+        let bbox = calculate_bounding_box(&[&head]);
+        assert!((bbox.x - 300.0).abs() < 1.0);
+        assert!((bbox.y - 300.0).abs() < 1.0);
+        assert!((bbox.width - 100.0).abs() < 1.0);
+        assert!((bbox.height - 100.0).abs() < 1.0);
+
+        // Test two heads
+        let head1 = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
+        let head2 = Hbb::from_xywh(1000.0, 300.0, 100.0, 100.0);
+        let bbox = calculate_bounding_box(&[&head1, &head2]);
+        assert!((bbox.x - 300.0).abs() < 1.0);
+        assert!((bbox.y - 300.0).abs() < 1.0);
+        assert!((bbox.width - 800.0).abs() < 1.0);
+        assert!((bbox.height - 100.0).abs() < 1.0);
+
+        // test two heads with one at right edge
+        let head1 = Hbb::from_xyxy(1063.6982, 335.45892, 1262.3218, 646.60675);
+        let head2 = Hbb::from_xyxy(1846.0652, 228.14204, 1919.9954, 533.70746);
+        let bbox = calculate_bounding_box(&[&head1, &head2]);
+        assert!((bbox.x - 1063.6982).abs() < 1.0);
+        assert!((bbox.y - 228.14204).abs() < 1.0);
+        assert!((bbox.width - 856.2972).abs() < 1.0);
+        assert!((bbox.height - 418.46471).abs() < 1.0);
+
+        // Test three heads in a triangle formation
+        let head1 = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
+        let head2 = Hbb::from_xywh(1000.0, 300.0, 100.0, 100.0);
+        let head3 = Hbb::from_xywh(1000.0, 1000.0, 100.0, 100.0);
+        let bbox = calculate_bounding_box(&[&head1, &head2, &head3]);
+        assert!((bbox.x - 300.0).abs() < 1.0);
+        assert!((bbox.y - 300.0).abs() < 1.0);
+        assert!((bbox.width - 800.0).abs() < 1.0);
+        assert!((bbox.height - 800.0).abs() < 1.0);
+
+        // Test empty vector
+        let bbox = calculate_bounding_box(&[]);
+        assert_eq!(bbox.x, 0.0);
+        assert_eq!(bbox.y, 0.0);
+        assert_eq!(bbox.width, 0.0);
+        assert_eq!(bbox.height, 0.0);
+    }
+
+    #[test]
+    fn test_centroid_distance() {
+        let single = CropResult::Single(CropArea::new(0.0, 0.0, 100.0, 100.0));
+        let moved = CropResult::Single(CropArea::new(30.0, 40.0, 100.0, 100.0));
+        assert_eq!(crop_centroid(&single), (50.0, 50.0));
+        assert_eq!(centroid_distance(&single, &moved), 50.0);
+        assert_eq!(centroid_distance(&single, &single), 0.0);
+
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 100.0, 100.0),
+            CropArea::new(100.0, 100.0, 100.0, 100.0),
+        );
+        assert_eq!(crop_centroid(&stacked), (100.0, 100.0));
+    }
+
+    #[test]
+    fn test_from_percentage_str() {
+        let area = CropArea::from_percentage_str("10%,20%,50%,25%", 1920.0, 1080.0).unwrap();
+        assert_eq!(area, CropArea::new(192.0, 216.0, 960.0, 270.0));
+
+        // The % signs are optional per value
+        let area = CropArea::from_percentage_str("10, 20, 50, 25%", 1920.0, 1080.0).unwrap();
+        assert_eq!(area, CropArea::new(192.0, 216.0, 960.0, 270.0));
+
+        // The whole frame is a valid boundary
+        let area = CropArea::from_percentage_str("0%,0%,100%,100%", 1920.0, 1080.0).unwrap();
+        assert_eq!(area, CropArea::new(0.0, 0.0, 1920.0, 1080.0));
+    }
+
+    #[test]
+    fn test_from_percentage_str_invalid() {
+        assert!(CropArea::from_percentage_str("10%,20%,50%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("10%,20%,50%,25%,5%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("a%,20%,50%,25%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("-5%,20%,50%,25%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("10%,20%,120%,25%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("60%,20%,50%,25%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("10%,80%,50%,25%", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_percentage_str("NaN%,0%,50%,25%", 1920.0, 1080.0).is_err());
+    }
+
+    #[test]
+    fn test_from_percentage_str_boundary_clamping() {
+        // Rounded values that slightly overshoot 100% are accepted and clamped to the frame
+        let area = CropArea::from_percentage_str("33.4%,0%,66.6001%,100%", 1920.0, 1080.0).unwrap();
+        assert!(area.x + area.width <= 1920.0);
+        assert_eq!(area.height, 1080.0);
+    }
+
+    #[test]
+    fn test_from_zone_str() {
+        let pixels = CropArea::from_zone_str("100,50,640,360", 1920.0, 1080.0).unwrap();
+        assert_eq!(pixels, CropArea::new(100.0, 50.0, 640.0, 360.0));
+
+        let percent = CropArea::from_zone_str("50%,50%,50%,50%", 1920.0, 1080.0).unwrap();
+        assert_eq!(percent, CropArea::new(960.0, 540.0, 960.0, 540.0));
+
+        assert!(CropArea::from_zone_str("100,50,0,360", 1920.0, 1080.0).is_err());
+        assert!(CropArea::from_zone_str("100,50,640", 1920.0, 1080.0).is_err());
+    }
+
+    #[test]
+    fn test_transition_crops_single_to_single() {
+        let from = CropResult::Single(CropArea::new(0.0, 0.0, 810.0, 1080.0));
+        let to = CropResult::Single(CropArea::new(800.0, 0.0, 810.0, 1080.0));
+
+        let crops = transition_crops(&from, &to, 3);
+        assert_eq!(crops.len(), 3);
+        let xs: Vec<f32> = crops
+            .iter()
+            .map(|crop| match crop {
+                CropResult::Single(area) => area.x,
+                _ => panic!("Expected single crop"),
+            })
+            .collect();
+        assert_eq!(xs, vec![200.0, 400.0, 600.0]);
+
+        assert!(transition_crops(&from, &to, 0).is_empty());
+    }
+
+    #[test]
+    fn test_transition_crops_incompatible() {
+        let single = CropResult::Single(CropArea::new(0.0, 0.0, 810.0, 1080.0));
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 960.0, 540.0),
+            CropArea::new(960.0, 0.0, 960.0, 540.0),
+        );
+        // A crop class change is immediate during a transition...
+        assert!(!single.can_interpolate(&stacked));
+        assert!(transition_crops(&single, &stacked, 5).is_empty());
+        // ...while draining the history switches halfway through
+        assert_eq!(
+            interpolation_crops(&single, &stacked, 5),
+            vec![
+                single.clone(),
+                single.clone(),
+                stacked.clone(),
+                stacked.clone(),
+                stacked.clone(),
+            ]
+        );
+
+        let stacked_moved = CropResult::Stacked(
+            CropArea::new(100.0, 0.0, 960.0, 540.0),
+            CropArea::new(860.0, 0.0, 960.0, 540.0),
+        );
+        assert!(stacked.can_interpolate(&stacked_moved));
+        assert_eq!(
+            transition_crops(&stacked, &stacked_moved, 1),
+            interpolation_crops(&stacked, &stacked_moved, 1)
+        );
+        match stacked.interpolate(&stacked_moved, 0.5) {
+            CropResult::Stacked(top, bottom) => {
+                assert_eq!(top.x, 50.0);
+                assert_eq!(bottom.x, 910.0);
+            }
+            other => panic!("Expected stacked crop, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_interpolate_mismatched_kinds() {
+        let single = CropResult::Single(CropArea::new(0.0, 0.0, 810.0, 1080.0));
+        let single_moved = CropResult::Single(CropArea::new(400.0, 0.0, 810.0, 1080.0));
+        assert_eq!(
+            single.interpolate(&single_moved, 0.25),
+            CropResult::Single(CropArea::new(100.0, 0.0, 810.0, 1080.0))
+        );
+
+        // Different kinds switch over at the halfway point
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 960.0, 540.0),
+            CropArea::new(960.0, 0.0, 960.0, 540.0),
+        );
+        assert_eq!(single.interpolate(&stacked, 0.49), single);
+        assert_eq!(single.interpolate(&stacked, 0.5), stacked);
+        assert_eq!(stacked.interpolate(&single, 1.0), single);
+    }
+
+    #[test]
+    fn test_from_center_scale() {
+        // A 3:4 crop centered in the frame
+        let crop = CropArea::from_center_scale(960.0, 540.0, 0.75, 1920.0, 1080.0);
+        assert_eq!(crop, CropArea::new(555.0, 0.0, 810.0, 1080.0));
+
+        // Crops near an edge are moved inside the frame
+        let left = CropArea::from_center_scale(100.0, 540.0, 0.75, 1920.0, 1080.0);
+        assert_eq!(left, CropArea::new(0.0, 0.0, 810.0, 1080.0));
+        let right = CropArea::from_center_scale(1900.0, 540.0, 0.5, 1920.0, 1080.0);
+        assert_eq!(right, CropArea::new(1380.0, 0.0, 540.0, 1080.0));
+    }
+
+    #[test]
+    fn test_is_crop_structurally_equivalent_three_to_four_heads() {
+        let frame_width = 1920.0;
+        assert!(!is_crop_class_same(3, 4));
+
+        // The fourth head joins next to the others and barely moves the stacked crops
+        let three_heads = CropResult::Stacked(
+            CropArea::new(400.0, 0.0, 960.0, 540.0),
+            CropArea::new(400.0, 540.0, 960.0, 540.0),
+        );
+        let four_heads = CropResult::Stacked(
+            CropArea::new(420.0, 0.0, 980.0, 540.0),
+            CropArea::new(410.0, 540.0, 970.0, 540.0),
+        );
+        assert!(is_crop_structurally_equivalent(&three_heads, &four_heads, frame_width, 10.0));
+
+        // A fourth head far from the others moves the crops
+        let spread_out = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 1600.0, 540.0),
+            CropArea::new(200.0, 540.0, 1600.0, 540.0),
+        );
+        assert!(!is_crop_structurally_equivalent(&three_heads, &spread_out, frame_width, 10.0));
+
+        // Different layouts are never equivalent
+        let single = CropResult::Single(CropArea::new(400.0, 0.0, 960.0, 1080.0));
+        assert!(!is_crop_structurally_equivalent(&three_heads, &single, frame_width, 10.0));
+    }
+
+    #[test]
+    fn test_for_each_crop() {
+        let a = CropArea::new(0.0, 0.0, 100.0, 200.0);
+        let b = CropArea::new(300.0, 0.0, 100.0, 200.0);
+        let crops = [
+            (CropResult::Single(a.clone()), vec![a.clone()]),
+            (CropResult::Resize(a.clone()), vec![a.clone()]),
+            (CropResult::Stacked(a.clone(), b.clone()), vec![a.clone(), b.clone()]),
+            (
+                CropResult::ZoomIn {
+                    start: a.clone(),
+                    end: b.clone(),
+                    progress: 0.5,
+                },
+                vec![a.clone(), b.clone()],
+            ),
+        ];
+
+        for (crop_result, expected) in crops {
+            let mut visited = Vec::new();
+            crop_result.for_each_crop(|crop| visited.push(crop.clone()));
+            assert_eq!(visited, expected);
+
+            let mut shifted = crop_result.clone();
+            shifted.for_each_crop_mut(|crop| crop.x += 10.0);
+            let mut shifted_x = Vec::new();
+            shifted.for_each_crop(|crop| shifted_x.push(crop.x));
+            assert_eq!(shifted_x, expected.iter().map(|crop| crop.x + 10.0).collect::<Vec<_>>());
+
+            // map_crops keeps the variant and applies the same transform
+            let mapped = crop_result.map_crops(|crop| CropArea { x: crop.x + 10.0, ..crop });
+            assert_eq!(mapped, shifted);
+        }
+    }
+
+    #[test]
+    fn test_crop_transform_identity() {
+        let crop = CropResult::Stacked(
+            CropArea::new(100.0, 0.0, 600.0, 540.0),
+            CropArea::new(900.0, 540.0, 600.0, 540.0),
+        );
+        assert_eq!(crop.apply_transform(&CropTransform::identity(1920.0, 1080.0)), crop);
+    }
+
+    #[test]
+    fn test_crop_transform_translate() {
+        let crop = CropResult::Single(CropArea::new(500.0, 100.0, 400.0, 600.0));
+        let transform = CropTransform {
+            x_offset: 50.0,
+            y_offset: -20.0,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+        assert_eq!(
+            crop.apply_transform(&transform),
+            CropResult::Single(CropArea::new(550.0, 80.0, 400.0, 600.0))
+        );
+
+        // Moving past the edge is clamped
+        let transform = CropTransform {
+            x_offset: -1000.0,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+        assert_eq!(
+            crop.apply_transform(&transform),
+            CropResult::Single(CropArea::new(0.0, 100.0, 400.0, 600.0))
+        );
+    }
+
+    #[test]
+    fn test_crop_transform_scale_and_pad() {
+        let crop = CropResult::Single(CropArea::new(500.0, 100.0, 400.0, 600.0));
+        let scale = CropTransform {
+            scale_factor: 0.5,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+        assert_eq!(
+            crop.apply_transform(&scale),
+            CropResult::Single(CropArea::new(600.0, 250.0, 200.0, 300.0))
+        );
+
+        let pad = CropTransform {
+            padding: 10.0,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+        assert_eq!(
+            crop.apply_transform(&pad),
+            CropResult::Single(CropArea::new(490.0, 90.0, 420.0, 620.0))
+        );
+
+        // Growing beyond the frame is clamped to the frame size
+        let grow = CropTransform {
+            scale_factor: 3.0,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+        assert_eq!(
+            crop.apply_transform(&grow),
+            CropResult::Single(CropArea::new(100.0, 0.0, 1200.0, 1080.0))
+        );
+    }
+
+    #[test]
+    fn test_crop_transform_then() {
+        let crop = CropResult::Single(CropArea::new(500.0, 100.0, 400.0, 600.0));
+        let first = CropTransform {
+            x_offset: 40.0,
+            scale_factor: 0.5,
+            padding: 10.0,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+        let second = CropTransform {
+            y_offset: 30.0,
+            scale_factor: 2.0,
+            padding: 5.0,
+            ..CropTransform::identity(1920.0, 1080.0)
+        };
+
+        let chained = crop.apply_transform(&first.then(&second));
+        let in_turn = crop.apply_transform(&first).apply_transform(&second);
+        match (chained, in_turn) {
+            (CropResult::Single(a), CropResult::Single(b)) => assert_areas_close(&a, &b),
+            other => panic!("Expected single crops, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clamp_to_frame() {
+        let mut single = CropResult::Single(CropArea::new(-50.0, 20.0, 810.0, 1080.0));
+        single.clamp_to_frame(1920.0, 1080.0);
+        assert_eq!(single, CropResult::Single(CropArea::new(0.0, 0.0, 810.0, 1080.0)));
+
+        let mut stacked = CropResult::Stacked(
+            CropArea::new(1500.0, 0.0, 600.0, 540.0),
+            CropArea::new(100.0, 700.0, 600.0, 540.0),
+        );
+        stacked.clamp_to_frame(1920.0, 1080.0);
+        assert_eq!(
+            stacked,
+            CropResult::Stacked(
+                CropArea::new(1320.0, 0.0, 600.0, 540.0),
+                CropArea::new(100.0, 540.0, 600.0, 540.0),
+            )
+        );
+
+        // Oversized crops shrink to the frame
+        let mut resize = CropResult::Resize(CropArea::new(10.0, 10.0, 2000.0, 1200.0));
+        resize.clamp_to_frame(1920.0, 1080.0);
+        assert_eq!(resize, CropResult::Resize(CropArea::new(0.0, 0.0, 1920.0, 1080.0)));
+
+        // Both ends of a zoom are clamped and the progress is kept
+        let mut zoom = CropResult::ZoomIn {
+            start: CropArea::new(-100.0, 0.0, 810.0, 1080.0),
+            end: CropArea::new(1700.0, 900.0, 400.0, 500.0),
+            progress: 0.25,
+        };
+        zoom.clamp_to_frame(1920.0, 1080.0);
+        assert_eq!(
+            zoom,
+            CropResult::ZoomIn {
+                start: CropArea::new(0.0, 0.0, 810.0, 1080.0),
+                end: CropArea::new(1520.0, 580.0, 400.0, 500.0),
+                progress: 0.25,
+            }
+        );
+    }
+
+    #[test]
+    fn test_expand_to_aspect() {
+        // A small corner scoreboard grows around its center to 9:4
+        let zone = CropArea::new(100.0, 50.0, 180.0, 60.0);
+        let expanded = zone.expand_to_aspect(9.0 / 4.0, 1920.0, 1080.0);
+        assert_eq!(expanded, CropArea::new(100.0, 40.0, 180.0, 80.0));
+
+        // Areas at the frame edge are moved inside it
+        let zone = CropArea::new(0.0, 0.0, 90.0, 80.0);
+        let expanded = zone.expand_to_aspect(9.0 / 4.0, 1920.0, 1080.0);
+        assert_eq!(expanded, CropArea::new(0.0, 0.0, 180.0, 80.0));
+
+        // Areas larger than the frame allows are shrunk
+        let zone = CropArea::new(0.0, 0.0, 1920.0, 1080.0);
+        let expanded = zone.expand_to_aspect(9.0 / 4.0, 1920.0, 1080.0);
+        assert_areas_close(&expanded, &CropArea::new(0.0, 113.333, 1920.0, 853.333));
+    }
+
+    #[test]
+    fn test_from_hbb() {
+        let head = Hbb::from_cxcywh(500.0, 300.0, 100.0, 120.0);
+        assert_eq!(CropArea::from_hbb(&head), CropArea::new(450.0, 240.0, 100.0, 120.0));
+    }
+
+    #[test]
+    fn test_compute_padding() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+        let head = Hbb::from_cxcywh(500.0, 300.0, 100.0, 120.0);
+
+        // No padding keeps the head box
+        assert_eq!(
+            CropArea::compute_padding(&head, 0.0, frame_width, frame_height),
+            CropArea::from_hbb(&head)
+        );
+
+        // A small factor pads each side by a fraction of the larger dimension (120 * 0.25 = 30)
+        assert_eq!(
+            CropArea::compute_padding(&head, 0.25, frame_width, frame_height),
+            CropArea::new(420.0, 210.0, 160.0, 180.0)
+        );
+
+        // A large factor is clamped to the frame
+        assert_eq!(
+            CropArea::compute_padding(&head, 5.0, frame_width, frame_height),
+            CropArea::new(0.0, 0.0, 1150.0, 960.0)
+        );
+
+        // A head in the corner only gets padding on the sides away from the edges
+        let corner = Hbb::from_cxcywh(1870.0, 1030.0, 100.0, 100.0);
+        assert_eq!(
+            CropArea::compute_padding(&corner, 0.5, frame_width, frame_height),
+            CropArea::new(1770.0, 930.0, 150.0, 150.0)
+        );
+        let corner = Hbb::from_xywh(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(
+            CropArea::compute_padding(&corner, 0.5, frame_width, frame_height),
+            CropArea::new(0.0, 0.0, 150.0, 150.0)
+        );
+    }
+
+    #[test]
+    fn test_single_head_crop_padding_at_frame_edges() {
+        let (frame_width, frame_height) = (1920.0, 1080.0);
+        let single_area = |head: &Hbb, padding_percent: f32| {
+            match calculate_single_head_crop(frame_width, frame_height, head, padding_percent) {
+                CropResult::Single(area) => area,
+                crop => panic!("expected a single crop, got {:?}", crop),
+            }
+        };
+
+        // Padding is symmetric away from the edges, so the crop does not move
+        let head = Hbb::from_cxcywh(960.0, 400.0, 100.0, 120.0);
+        assert_eq!(single_area(&head, 50.0), single_area(&head, 0.0));
+
+        // At the edges the padding only grows inwards and the crop stays in the frame
+        let left = Hbb::from_xywh(0.0, 400.0, 100.0, 100.0);
+        let area = single_area(&left, 50.0);
+        assert_eq!(area.x, 0.0);
+        assert_eq!(area.height, frame_height);
+
+        let right = Hbb::from_xywh(1820.0, 400.0, 100.0, 100.0);
+        let area = single_area(&right, 50.0);
+        assert_eq!(area.x + area.width, frame_width);
+        assert_eq!(area.height, frame_height);
+    }
+
+    #[test]
+    fn test_from_center_scale_matches_inline_crops() {
+        // The inline calculation the single-head crops used before `from_center_scale`
+        let inline_crop = |center_x: f32, frame_width: f32, frame_height: f32| {
+            let height = frame_height;
+            let width = height * (3.0 / 4.0);
+            let mut x = center_x - width / 2.0;
+            if x < 0.0 {
+                x = 0.0;
+            } else if x + width > frame_width {
+                x = frame_width - width;
+            }
+            CropArea::new(x, 0.0, width, height)
+        };
+
+        for (frame_width, frame_height) in [(1920.0, 1080.0), (1280.0, 720.0), (3840.0, 2160.0)] {
+            for center_x in [0.0, 150.0, frame_width / 2.0, frame_width - 150.0, frame_width] {
+                let expected = inline_crop(center_x, frame_width, frame_height);
+                assert_eq!(make_single_crop_centered(center_x, frame_width, frame_height), expected);
+
+                let head = Hbb::from_cxcywh(center_x, frame_height / 3.0, 80.0, 100.0);
+                assert_eq!(
+                    calculate_single_head_crop(frame_width, frame_height, &head, 0.0),
+                    CropResult::Single(expected.clone())
+                );
+                let small_head = Hbb::from_cxcywh(frame_width / 4.0, frame_height / 3.0, 20.0, 30.0);
+                assert_eq!(
+                    calculate_crop_from_largest_head(frame_width, frame_height, &[&small_head, &head]),
+                    CropResult::Single(expected)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_safe_zone() {
+        let zone = CropArea::safe_zone(5.0, 1920.0, 1080.0);
+        assert_eq!(zone, CropArea::new(96.0, 54.0, 1728.0, 972.0));
+        let zone = CropArea::safe_zone(10.0, 1920.0, 1080.0);
+        assert_eq!(zone, CropArea::new(192.0, 108.0, 1536.0, 864.0));
+
+        // A crop inside the 5% safe area falls outside the 10% one
+        let crop = CropArea::new(100.0, 60.0, 600.0, 900.0);
+        assert!(crop.is_within_safe_zone(5.0, 1920.0, 1080.0));
+        assert!(!crop.is_within_safe_zone(10.0, 1920.0, 1080.0));
+
+        // Crops exactly on the safe area edge are inside
+        assert!(CropArea::new(192.0, 108.0, 1536.0, 864.0).is_within_safe_zone(10.0, 1920.0, 1080.0));
+
+        // Full-height crops extend past the top and bottom margins
+        let full_height = CropResult::Single(CropArea::new(656.25, 0.0, 607.5, 1080.0));
+        assert!(!full_height.is_within_safe_zone(5.0, 1920.0, 1080.0));
+        let stacked = CropResult::Stacked(
+            CropArea::new(200.0, 120.0, 400.0, 300.0),
+            CropArea::new(1300.0, 120.0, 400.0, 300.0),
+        );
+        assert!(stacked.is_within_safe_zone(10.0, 1920.0, 1080.0));
+    }
+
+    #[test]
+    fn test_area_ratio() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+
+        let single = CropResult::Single(CropArea::new(555.0, 0.0, 810.0, 1080.0));
+        assert!((single.area_ratio(frame_width, frame_height) - 810.0 / 1920.0).abs() < 1e-6);
+
+        let resize = CropResult::Resize(CropArea::new(0.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(resize.area_ratio(frame_width, frame_height), 1.0);
+
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 960.0, 540.0),
+            CropArea::new(960.0, 540.0, 480.0, 270.0),
+        );
+        assert!((stacked.area_ratio(frame_width, frame_height) - 0.3125).abs() < 1e-6);
+
+        // Overlapping stacked crops can exceed the frame area and are clamped
+        let overlapping = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 1920.0, 1080.0),
+            CropArea::new(0.0, 0.0, 1920.0, 1080.0),
+        );
+        assert_eq!(overlapping.area_ratio(frame_width, frame_height), 1.0);
+
+        assert_eq!(single.area_ratio(0.0, 1080.0), 0.0);
+    }
+
+    #[test]
+    fn test_to_ffmpeg_crop_filter() {
+        let crop = CropArea::new(656.4, 0.0, 607.5, 1080.0);
+        assert_eq!(crop.to_ffmpeg_crop_filter(), "crop=607:1080:656:0");
+    }
+
+    #[test]
+    fn test_crop_result_to_ffmpeg_filter() {
+        let single = CropResult::Single(CropArea::new(100.0, 0.0, 608.0, 1080.0));
+        assert_eq!(single.to_ffmpeg_filter(), "crop=608:1080:100:0");
+
+        let resize = CropResult::Resize(CropArea::new(0.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(resize.to_ffmpeg_filter(), "crop=1920:1080:0:0");
+
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 960.0, 540.0),
+            CropArea::new(960.0, 540.0, 480.0, 270.0),
+        );
+        assert_eq!(
+            stacked.to_ffmpeg_filter(),
+            "split[v1][v2];[v1]crop=960:540:0:0[top];\
+             [v2]crop=480:270:960:540,scale=960:-2[bottom];[top][bottom]vstack"
+        );
+
+        let zoom = CropResult::ZoomIn {
+            start: CropArea::new(0.0, 0.0, 800.0, 1000.0),
+            end: CropArea::new(100.0, 100.0, 400.0, 500.0),
+            progress: 0.5,
+        };
+        assert_eq!(zoom.to_ffmpeg_filter(), "crop=600:750:50:50");
+    }
+
+    #[test]
+    fn test_calculate_bounding_box_weighted() {
+        let head1 = Hbb::from_xywh(100.0, 300.0, 100.0, 100.0);
+        let head2 = Hbb::from_xywh(500.0, 300.0, 100.0, 100.0);
+        let head3 = Hbb::from_xywh(1000.0, 400.0, 100.0, 100.0);
+        let heads = [&head1, &head2, &head3];
+        let weights = [0.9, 0.3, 0.9];
+
+        let unweighted = calculate_bounding_box(&heads);
+        let weighted = calculate_bounding_box_weighted(&heads, &weights);
+
+        // soft_min = Σ(min * (1 - w)) / Σ(1 - w), soft_max = Σ(max * w) / Σ(w)
+        let soft_min_x = (100.0 * 0.1 + 500.0 * 0.7 + 1000.0 * 0.1) / 0.9;
+        let soft_max_x = (200.0 * 0.9 + 600.0 * 0.3 + 1100.0 * 0.9) / 2.1;
+        let soft_min_y = (300.0 * 0.1 + 300.0 * 0.7 + 400.0 * 0.1) / 0.9;
+        let soft_max_y = (400.0 * 0.9 + 400.0 * 0.3 + 500.0 * 0.9) / 2.1;
+        assert!((weighted.x - soft_min_x).abs() < 0.01);
+        assert!((weighted.x + weighted.width - soft_max_x).abs() < 0.01);
+        assert!((weighted.y - soft_min_y).abs() < 0.01);
+        assert!((weighted.y + weighted.height - soft_max_y).abs() < 0.01);
+
+        // The soft box lies inside the unweighted one
+        assert!(weighted.width < unweighted.width);
+        assert!(weighted.height < unweighted.height);
+        assert!(weighted.x >= unweighted.x);
+        assert!(weighted.x + weighted.width <= unweighted.x + unweighted.width);
+    }
+
+    #[test]
+    fn test_calculate_bounding_box_weighted_fallback() {
+        let head1 = Hbb::from_xywh(100.0, 300.0, 100.0, 100.0);
+        let head2 = Hbb::from_xywh(1000.0, 300.0, 100.0, 100.0);
+        let heads = [&head1, &head2];
+        let unweighted = calculate_bounding_box(&heads);
+
+        // Equal weights and mismatched weights use the unweighted box
+        assert_eq!(calculate_bounding_box_weighted(&heads, &[0.8, 0.8]), unweighted);
+        assert_eq!(calculate_bounding_box_weighted(&heads, &[1.0, 1.0]), unweighted);
+        assert_eq!(calculate_bounding_box_weighted(&heads, &[0.8]), unweighted);
+        assert_eq!(calculate_bounding_box_weighted(&heads, &[0.0, 0.0]), unweighted);
+        assert_eq!(
+            calculate_bounding_box_weighted(&[], &[]),
+            CropArea::new(0.0, 0.0, 0.0, 0.0)
+        );
+
+        // A confident head left of a weak one inverts the soft box, so it is not used
+        assert_eq!(calculate_bounding_box_weighted(&heads, &[0.9, 0.2]), unweighted);
+    }
+
+    #[test]
+    fn test_weighted_bbox_crop() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+        let head1 = Hbb::from_cxcywh(500.0, 400.0, 100.0, 100.0).with_confidence(0.2);
+        let head2 = Hbb::from_cxcywh(1400.0, 400.0, 100.0, 100.0).with_confidence(0.9);
+        let crop = |weighted_bbox| {
+            calculate_crop_area(
+                true,
+                false,
+                frame_width,
+                frame_height,
+                &[&head1, &head2],
+                &CropOptions {
+                    weighted_bbox,
+                    ..CropOptions::default()
+                },
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        // Unweighted, the heads are too far apart for one crop
+        assert!(matches!(crop(false), CropResult::Stacked(_, _)));
+
+        // Weighted, the soft box fits and a single crop is centered on it
+        let soft_min_x = (450.0 * 0.8 + 1350.0 * 0.1) / 0.9;
+        let soft_max_x = (550.0 * 0.2 + 1450.0 * 0.9) / 1.1;
+        match crop(true) {
+            CropResult::Single(area) => {
+                let center_x = area.x + area.width / 2.0;
+                assert!((center_x - (soft_min_x + soft_max_x) / 2.0).abs() < 0.1);
+            }
+            other => panic!("Expected single crop, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_calculate_weighted_center() {
+        let head1 = Hbb::from_cxcywh(400.0, 400.0, 100.0, 100.0).with_confidence(0.95);
+        let head2 = Hbb::from_cxcywh(800.0, 400.0, 100.0, 100.0).with_confidence(0.71);
+        let expected = (400.0 * 0.95 + 800.0 * 0.71) / (0.95 + 0.71);
+        assert!((calculate_weighted_center(&[&head1, &head2]) - expected).abs() < 0.01);
+        assert!(calculate_weighted_center(&[&head1, &head2]) < 600.0);
+
+        // Without confidences every head counts the same
+        let head1 = Hbb::from_cxcywh(400.0, 400.0, 100.0, 100.0);
+        let head2 = Hbb::from_cxcywh(800.0, 400.0, 100.0, 100.0);
+        assert_eq!(calculate_weighted_center(&[&head1, &head2]), 600.0);
+    }
+
+    #[test]
+    fn test_weighted_center_shifts_toward_confident_head() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+        let single_center = |crop: CropResult| match crop {
+            CropResult::Single(crop) => crop.x + crop.width / 2.0,
+            other => panic!("Expected single crop, got {:?}", other),
+        };
+
+        // Two heads: the crop leans toward the more confident left head
+        let head1 = Hbb::from_cxcywh(800.0, 540.0, 100.0, 100.0).with_confidence(0.95);
+        let head2 = Hbb::from_cxcywh(1100.0, 540.0, 100.0, 100.0).with_confidence(0.71);
+        let two_heads = |use_weighted_center| {
+            single_center(calculate_two_heads_crop(
+                true,
+                frame_width,
+                frame_height,
+                &head1,
+                &head2,
+                &CropOptions {
+                    use_weighted_center,
+                    ..CropOptions::default()
+                },
+            ))
+        };
+        assert!((two_heads(false) - 950.0).abs() < 1.0);
+        assert!(two_heads(true) < two_heads(false) - 10.0);
+        // Both heads stay in the crop
+        let crop_width = compute_three_four_width(frame_height);
+        assert!(two_heads(true) - crop_width / 2.0 <= head1.xmin());
+        assert!(two_heads(true) + crop_width / 2.0 >= head2.xmax());
+
+        // Three heads fall back to the four and five heads path
+        let head3 = Hbb::from_cxcywh(900.0, 540.0, 100.0, 100.0).with_confidence(0.5);
+        let head4 = Hbb::from_cxcywh(1000.0, 540.0, 100.0, 100.0).with_confidence(0.5);
+        let head5 = Hbb::from_cxcywh(800.0, 540.0, 100.0, 100.0).with_confidence(0.9);
+        let three_heads = |use_weighted_center| {
+            single_center(
+                calculate_crop_area(
+                    false,
+                    false,
+                    frame_width,
+                    frame_height,
+                    &[&head5, &head3, &head4],
+                    &CropOptions {
+                        use_weighted_center,
+                        ..CropOptions::default()
+                    },
+                )
+                .unwrap()
+                .unwrap(),
+            )
+        };
+        assert!(three_heads(true) < three_heads(false) - 10.0);
+
+        // Without confidences the bounding box center is kept
+        let head1 = Hbb::from_cxcywh(800.0, 540.0, 100.0, 100.0);
+        let head2 = Hbb::from_cxcywh(1100.0, 540.0, 100.0, 100.0).with_confidence(0.71);
+        let crop = calculate_two_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions {
+                use_weighted_center: true,
+                ..CropOptions::default()
+            },
+        );
+        assert!((single_center(crop) - 950.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_no_detection_strategy() {
+        let heads: Vec<&Hbb> = vec![];
+        let center = calculate_crop_area(
+            true,
+            false,
+            1920.0,
+            1080.0,
+            &heads,
+            &CropOptions::default(),
+        )
+        .unwrap();
+        assert!(matches!(center, Some(CropResult::Single(_))));
+
+        for strategy in [NoDetectionStrategy::PreviousCrop, NoDetectionStrategy::SkipFrame] {
+            let crop = calculate_crop_area(
+                true,
+                false,
+                1920.0,
+                1080.0,
+                &heads,
+                &CropOptions {
+                    strategy,
+                    ..CropOptions::default()
+                },
+            )
+            .unwrap();
+            assert!(crop.is_none());
+            // Graphic frames are resized regardless of the strategy
+            let crop = calculate_crop_area(
+                true,
+                true,
+                1920.0,
+                1080.0,
+                &heads,
+                &CropOptions {
+                    strategy,
+                    ..CropOptions::default()
+                },
+            )
+            .unwrap();
+            assert!(matches!(crop, Some(CropResult::Resize(_))));
+        }
+
+        assert_eq!("previous".parse(), Ok(NoDetectionStrategy::PreviousCrop));
+        assert_eq!("Skip".parse(), Ok(NoDetectionStrategy::SkipFrame));
+        assert!("blank".parse::<NoDetectionStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_median_crop_area_suppresses_outlier() {
+        let window = vec![
+            CropArea::new(500.0, 0.0, 810.0, 1080.0),
+            CropArea::new(510.0, 0.0, 810.0, 1080.0),
+            CropArea::new(1100.0, 0.0, 810.0, 1080.0), // Outlier detection
+            CropArea::new(505.0, 0.0, 810.0, 1080.0),
+            CropArea::new(515.0, 0.0, 810.0, 1080.0),
+        ];
 
-        min_x = min_x.min(xmin);
-        min_y = min_y.min(ymin);
-        max_x = max_x.max(xmax);
-        max_y = max_y.max(ymax);
+        let median = median_crop_area(&window);
+        assert_eq!(median, CropArea::new(510.0, 0.0, 810.0, 1080.0));
     }
 
-    CropArea::new(min_x, min_y, max_x - min_x, max_y - min_y)
-}
+    #[test]
+    fn test_median_crop_area_even_and_empty() {
+        let window = vec![
+            CropArea::new(100.0, 10.0, 800.0, 1000.0),
+            CropArea::new(200.0, 20.0, 900.0, 1080.0),
+        ];
+        assert_eq!(
+            median_crop_area(&window),
+            CropArea::new(150.0, 15.0, 850.0, 1040.0)
+        );
+        assert_eq!(median_crop_area(&[]), CropArea::new(0.0, 0.0, 0.0, 0.0));
+    }
 
-/// Determines if two head counts would result in different crop classes
-///
-/// Crop classes are defined as:
-/// - 0 heads
-/// - 1 head  
-/// - 2 heads
-/// - 3 to 5 heads
-/// - More than 5 heads
-///
-/// # Arguments
-/// * `head_count1` - First head count
-/// * `head_count2` - Second head count
-///
-/// # Returns
-/// `true` if the head counts would result in different crop classes, `false` otherwise
-pub fn is_crop_class_same(head_count1: usize, head_count2: usize) -> bool {
-    // Helper function to get crop class for a given head count
-    fn get_crop_class(head_count: usize) -> u8 {
-        match head_count {
-            0 => 0,   // 0 heads
-            1 => 1,   // 1 head
-            2 => 2,   // 2 heads
-            3 => 3,   // 3 heads
-            4.. => 4, // 4 or more heads
-        }
+    #[test]
+    fn test_confidence_weighted_blend_high_confidence_transitions_immediately() {
+        let prev = CropArea::new(0.0, 0.0, 810.0, 1080.0);
+        let latest = CropArea::new(1000.0, 0.0, 810.0, 1080.0);
+
+        assert_eq!(confidence_weighted_blend(&prev, &latest, 0.6, 0.9), latest);
+        assert_eq!(confidence_weighted_blend(&prev, &latest, 0.8, 0.8), latest);
+        // Without a previous confidence there is nothing to weigh against
+        assert_eq!(confidence_weighted_blend(&prev, &latest, 0.0, 0.3), latest);
     }
 
-    get_crop_class(head_count1) == get_crop_class(head_count2)
-}
+    #[test]
+    fn test_confidence_weighted_blend_low_confidence_blends_slowly() {
+        let prev = CropArea::new(0.0, 0.0, 810.0, 1080.0);
+        let latest = CropArea::new(1000.0, 0.0, 810.0, 1080.0);
 
-/// Checks if two crop results are similar based on a threshold percentage
-pub fn is_crop_similar(crop1: &CropResult, crop2: &CropResult, width: f32, threshold: f32) -> bool {
-    match (crop1, crop2) {
-        (CropResult::Single(crop1), CropResult::Single(crop2)) => {
-            crop1.is_within_percentage(crop2, width, threshold)
-        }
-        (CropResult::Stacked(crop1_1, crop1_2), CropResult::Stacked(crop2_1, crop2_2)) => {
-            crop1_1.is_within_percentage(crop2_1, width, threshold)
-                && crop1_2.is_within_percentage(crop2_2, width, threshold)
-        }
-        (CropResult::Resize(crop1), CropResult::Resize(crop2)) => {
-            crop1.is_within_percentage(crop2, width, threshold)
-        }
-        _ => false, // If crop types don't match, use the new crop
-    }
-}
+        let blended = confidence_weighted_blend(&prev, &latest, 0.8, 0.2);
+        assert_eq!(blended, CropArea::new(250.0, 0.0, 810.0, 1080.0));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // Lower confidence keeps the crop closer to the previous one
+        let slower = confidence_weighted_blend(&prev, &latest, 0.8, 0.1);
+        assert!(slower.x < blended.x);
+        assert!(slower.x > prev.x);
+    }
 
     #[test]
-    fn test_calculate_bounding_box() {
-        // Test single head
-        let head = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
-        let bbox = calculate_bounding_box(&[&head]);
-        assert!((bbox.x - 300.0).abs() < 1.0);
-        assert!((bbox.y - 300.0).abs() < 1.0);
-        assert!((bbox.width - 100.0).abs() < 1.0);
-        assert!((bbox.height - 100.0).abs() < 1.0);
+    fn test_interpolate_crop_area() {
+        let start = CropArea::new(0.0, 0.0, 810.0, 1080.0);
+        let end = CropArea::new(200.0, 100.0, 540.0, 720.0);
 
-        // Test two heads
-        let head1 = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
-        let head2 = Hbb::from_xywh(1000.0, 300.0, 100.0, 100.0);
-        let bbox = calculate_bounding_box(&[&head1, &head2]);
-        assert!((bbox.x - 300.0).abs() < 1.0);
-        assert!((bbox.y - 300.0).abs() < 1.0);
-        assert!((bbox.width - 800.0).abs() < 1.0);
-        assert!((bbox.height - 100.0).abs() < 1.0);
+        let mid = interpolate_crop_area(&start, &end, 0.5);
+        assert_eq!(mid, CropArea::new(100.0, 50.0, 675.0, 900.0));
 
-        // test two heads with one at right edge
-        let head1 = Hbb::from_xyxy(1063.6982, 335.45892, 1262.3218, 646.60675);
-        let head2 = Hbb::from_xyxy(1846.0652, 228.14204, 1919.9954, 533.70746);
-        let bbox = calculate_bounding_box(&[&head1, &head2]);
-        assert!((bbox.x - 1063.6982).abs() < 1.0);
-        assert!((bbox.y - 228.14204).abs() < 1.0);
-        assert!((bbox.width - 856.2972).abs() < 1.0);
-        assert!((bbox.height - 418.46471).abs() < 1.0);
+        // Progress is clamped to the [0, 1] range
+        assert_eq!(interpolate_crop_area(&start, &end, -1.0), start);
+        assert_eq!(interpolate_crop_area(&start, &end, 2.0), end);
+    }
 
-        // Test three heads in a triangle formation
-        let head1 = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
-        let head2 = Hbb::from_xywh(1000.0, 300.0, 100.0, 100.0);
-        let head3 = Hbb::from_xywh(1000.0, 1000.0, 100.0, 100.0);
-        let bbox = calculate_bounding_box(&[&head1, &head2, &head3]);
-        assert!((bbox.x - 300.0).abs() < 1.0);
-        assert!((bbox.y - 300.0).abs() < 1.0);
-        assert!((bbox.width - 800.0).abs() < 1.0);
-        assert!((bbox.height - 800.0).abs() < 1.0);
+    #[test]
+    fn test_calculate_zoom_in_crop() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+        let head = Hbb::from_cxcywh(960.0, 400.0, 100.0, 100.0);
+
+        match calculate_zoom_in_crop(frame_width, frame_height, &head, 1.5) {
+            CropResult::ZoomIn {
+                start,
+                end,
+                progress,
+            } => {
+                assert_eq!(progress, 0.0);
+
+                // Start is the regular single-head crop
+                assert!((start.height - frame_height).abs() < 1.0);
+                assert!((start.x + start.width / 2.0 - 960.0).abs() < 1.0);
+
+                // End is 1.5x tighter, keeps the 3:4 ratio and is centered on the head
+                assert!((end.width - start.width / 1.5).abs() < 1.0);
+                assert!((end.height - start.height / 1.5).abs() < 1.0);
+                assert!((end.x + end.width / 2.0 - 960.0).abs() < 1.0);
+                assert!((end.y + end.height / 2.0 - 400.0).abs() < 1.0);
+
+                // Halfway through the zoom sits between both crops
+                let mid = interpolate_crop_area(&start, &end, 0.5);
+                assert!((mid.width - (start.width + end.width) / 2.0).abs() < 1.0);
+                assert!((mid.height - (start.height + end.height) / 2.0).abs() < 1.0);
+                assert!((mid.y - end.y / 2.0).abs() < 1.0);
+            }
+            _ => panic!("Expected zoom in crop"),
+        }
 
-        // Test empty vector
-        let bbox = calculate_bounding_box(&[]);
-        assert_eq!(bbox.x, 0.0);
-        assert_eq!(bbox.y, 0.0);
-        assert_eq!(bbox.width, 0.0);
-        assert_eq!(bbox.height, 0.0);
+        // A head near the top edge keeps the end crop within the frame
+        let head = Hbb::from_cxcywh(960.0, 20.0, 100.0, 100.0);
+        match calculate_zoom_in_crop(frame_width, frame_height, &head, 2.0) {
+            CropResult::ZoomIn { end, .. } => {
+                assert!(end.y >= 0.0);
+                assert!(end.y + end.height <= frame_height);
+            }
+            _ => panic!("Expected zoom in crop"),
+        }
     }
 
     #[test]
@@ -822,7 +2816,7 @@ mod tests {
 
         // Test centered head
         let head = Hbb::from_cxcywh(frame_width / 2.0, frame_height / 2.0, 100.0, 100.0);
-        let crop = calculate_single_head_crop(frame_width, frame_height, &head);
+        let crop = calculate_single_head_crop(frame_width, frame_height, &head, 0.0);
 
         match crop {
             CropResult::Single(crop) => {
@@ -847,7 +2841,7 @@ mod tests {
 
         // Test head on far left
         let head = Hbb::from_cxcywh(50.0, frame_height / 2.0, 100.0, 100.0);
-        let crop = calculate_single_head_crop(frame_width, frame_height, &head);
+        let crop = calculate_single_head_crop(frame_width, frame_height, &head, 0.0);
 
         match crop {
             CropResult::Single(crop) => {
@@ -872,7 +2866,7 @@ mod tests {
 
         // Test head on far right
         let head = Hbb::from_cxcywh(frame_width - 50.0, frame_height / 2.0, 100.0, 100.0);
-        let crop = calculate_single_head_crop(frame_width, frame_height, &head);
+        let crop = calculate_single_head_crop(frame_width, frame_height, &head, 0.0);
 
         match crop {
             CropResult::Single(crop) => {
@@ -904,7 +2898,14 @@ mod tests {
         // Test close heads - heads are within 3/4 of frame height
         let head1 = Hbb::from_xywh(300.0, 300.0, 100.0, 100.0);
         let head2 = Hbb::from_xywh(450.0, 300.0, 100.0, 100.0);
-        let crop = calculate_two_heads_crop(true, frame_width, frame_height, &head1, &head2);
+        let crop = calculate_two_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -943,7 +2944,14 @@ mod tests {
         // Test far heads - heads are more than 3/4 of frame height apart
         let head1 = Hbb::from_cxcywh(frame_width / 4.0, frame_height / 2.0, 100.0, 100.0);
         let head2 = Hbb::from_cxcywh(3.0 * frame_width / 4.0, frame_height / 2.0, 100.0, 100.0);
-        let crop = calculate_two_heads_crop(true, frame_width, frame_height, &head1, &head2);
+        let crop = calculate_two_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -990,7 +2998,14 @@ mod tests {
         // Test with one head at the top and one at the bottom
         let head1 = Hbb::from_cxcywh(frame_width / 4.0, 50.0, 100.0, 100.0); // Head near top
         let head2 = Hbb::from_cxcywh(3.0 * frame_width / 4.0, frame_height - 50.0, 100.0, 100.0); // Head near bottom
-        let crop = calculate_two_heads_crop(true, frame_width, frame_height, &head1, &head2);
+        let crop = calculate_two_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1041,7 +3056,14 @@ mod tests {
         // Second head is far to the right, ensuring the bounding box is wider than 3/4 of frame height
         let head2 = Hbb::from_cxcywh(frame_width - 200.0, frame_height / 2.0, 100.0, 100.0);
 
-        let crop = calculate_two_heads_crop(true, frame_width, frame_height, &head1, &head2);
+        let crop = calculate_two_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1092,7 +3114,14 @@ mod tests {
         let head1 = Hbb::from_xyxy(1063.6982, 335.45892, 1262.3218, 646.60675);
         let head2 = Hbb::from_xyxy(1846.0652, 228.14204, 1919.9954, 533.70746);
 
-        let crop = calculate_two_heads_crop(true, frame_width, frame_height, &head1, &head2);
+        let crop = calculate_two_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1155,7 +3184,13 @@ mod tests {
         let head3 = Hbb::from_cxcywh(frame_width / 2.0 + 100.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3];
 
-        let crop = calculate_four_and_five_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_four_and_five_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -1198,7 +3233,13 @@ mod tests {
         let head3 = Hbb::from_cxcywh(1800.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3];
 
-        let crop = calculate_four_and_five_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_four_and_five_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1272,7 +3313,13 @@ mod tests {
             &head_right_bottom2,
         ];
 
-        let crop = calculate_four_and_five_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_four_and_five_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1309,7 +3356,13 @@ mod tests {
         let head3 = Hbb::from_cxcywh(1600.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3];
 
-        let crop = calculate_four_and_five_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_four_and_five_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1487,14 +3540,32 @@ mod tests {
 
         // Test no heads
         let heads: Vec<&Hbb> = vec![];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &heads).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
 
         // Test single head
         let head = Hbb::from_cxcywh(frame_width / 2.0, frame_height / 2.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
 
         // Test two heads
@@ -1503,7 +3574,16 @@ mod tests {
         let head2 = Hbb::from_cxcywh(3.0 * frame_width / 4.0, frame_height / 2.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head1, &head2];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Stacked(_, _)));
 
         // Test three heads
@@ -1519,7 +3599,16 @@ mod tests {
         )
         .with_confidence(0.9);
         let hbbs = vec![&head1, &head2, &head3];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Stacked(_, _)));
 
         // Test more than five heads
@@ -1546,7 +3635,16 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width - 100.0, frame_height - 100.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head1, &head2, &head3, &head4, &head5, &head6];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
     }
 
@@ -1557,14 +3655,32 @@ mod tests {
 
         // Test no heads with graphic mode
         let heads: Vec<&Hbb> = vec![];
-        let crop = calculate_crop_area(true, true, frame_width, frame_height, &heads).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Resize(_)));
 
         // Test single head with graphic mode (should still be Single, not Resize)
         let head = Hbb::from_cxcywh(frame_width / 2.0, frame_height / 2.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head];
-        let crop = calculate_crop_area(true, true, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            true,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
 
         // Test more than five heads with graphic mode
@@ -1591,7 +3707,16 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width - 100.0, frame_height - 100.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head1, &head2, &head3, &head4, &head5, &head6];
-        let crop = calculate_crop_area(true, true, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            true,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
     }
 
@@ -1644,6 +3769,31 @@ mod tests {
         assert!(!crop1.is_within_percentage(&crop2, frame_width, threshold));
     }
 
+    #[test]
+    fn test_calculate_vertical_two_heads_crop() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+
+        let head1 = Hbb::from_cxcywh(960.0, 930.0, 100.0, 100.0);
+        let head2 = Hbb::from_cxcywh(960.0, 150.0, 100.0, 100.0);
+        let crop = calculate_vertical_two_heads_crop(frame_width, frame_height, &head1, &head2);
+        assert_eq!(
+            crop,
+            Some(CropResult::Stacked(
+                CropArea::new(656.25, 0.0, 607.5, 540.0),
+                CropArea::new(656.25, 540.0, 607.5, 540.0),
+            ))
+        );
+
+        // Heads closer than half the frame height keep the regular layouts
+        let head2 = Hbb::from_cxcywh(960.0, 500.0, 100.0, 100.0);
+        assert_eq!(calculate_vertical_two_heads_crop(frame_width, frame_height, &head1, &head2), None);
+
+        // Heads too far apart horizontally do not fit a 3:4 crop
+        let head2 = Hbb::from_cxcywh(100.0, 150.0, 100.0, 100.0);
+        assert_eq!(calculate_vertical_two_heads_crop(frame_width, frame_height, &head1, &head2), None);
+    }
+
     #[test]
     fn test_calculate_two_heads_crop_far_no_stack() {
         let frame_width = 1920.0;
@@ -1652,7 +3802,14 @@ mod tests {
         // Test far heads with use_stack_crop = false
         let head1 = Hbb::from_cxcywh(frame_width / 4.0, frame_height / 2.0, 100.0, 100.0);
         let head2 = Hbb::from_cxcywh(3.0 * frame_width / 4.0, frame_height / 2.0, 100.0, 100.0);
-        let crop = calculate_two_heads_crop(false, frame_width, frame_height, &head1, &head2);
+        let crop = calculate_two_heads_crop(
+            false,
+            frame_width,
+            frame_height,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -1685,7 +3842,13 @@ mod tests {
         let head3 = Hbb::from_cxcywh(1800.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3];
 
-        let crop = calculate_four_and_five_heads_crop(false, frame_width, frame_height, &heads);
+        let crop = calculate_four_and_five_heads_crop(
+            false,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -1720,7 +3883,13 @@ mod tests {
         let head2 = Hbb::from_xyxy(531.13, 213.28334, 704.7175, 470.2871);
         let head3 = Hbb::from_xyxy(943.43054, 278.49518, 1161.655, 579.9011);
         let heads = vec![&head1, &head2, &head3];
-        let crop = calculate_three_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_three_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
         match crop {
             CropResult::Stacked(crop1, crop2) => {
                 // First crop should be optimized for two heads (80% height, 9:6 aspect ratio)
@@ -1779,7 +3948,13 @@ mod tests {
         let head2 = Hbb::from_xyxy(864.88776, 344.61285, 1026.0613, 568.9608);
         let head3 = Hbb::from_xyxy(1477.2578, 277.67084, 1673.3591, 527.8382);
         let heads = vec![&head1, &head2, &head3];
-        let crop = calculate_three_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_three_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
         match crop {
             CropResult::Stacked(crop1, crop2) => {
                 // First crop should be optimized for two heads (80% height, 9:6 aspect ratio)
@@ -1841,7 +4016,13 @@ mod tests {
         let head3 = Hbb::from_cxcywh(1520.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3];
 
-        let crop = calculate_three_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_three_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -1903,7 +4084,13 @@ mod tests {
         let head3 = Hbb::from_cxcywh(1520.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3];
 
-        let crop = calculate_three_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_three_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         // Should fall back to the four_and_five_heads logic
         // Since heads are far apart, should get stacked crops with default dimensions
@@ -1932,7 +4119,16 @@ mod tests {
         let head2 = Hbb::from_cxcywh(3.0 * frame_width / 4.0, frame_height / 2.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head1, &head2];
-        let crop = calculate_crop_area(false, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            false,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
 
         // Test three heads with use_stack_crop = false
@@ -1948,7 +4144,16 @@ mod tests {
         )
         .with_confidence(0.9);
         let hbbs = vec![&head1, &head2, &head3];
-        let crop = calculate_crop_area(false, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            false,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
     }
 
@@ -2036,7 +4241,13 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width / 2.0 + 100.0, frame_height / 2.0, 100.0, 100.0);
         let heads = vec![&head1, &head2, &head3, &head4, &head5, &head6];
 
-        let crop = calculate_six_or_more_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_six_or_more_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -2083,7 +4294,13 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width / 2.0, frame_height / 2.0, 300.0, 300.0);
         let heads = vec![&head1, &head2, &head3, &head4, &head5, &head6];
 
-        let crop = calculate_six_or_more_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_six_or_more_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Stacked(crop1, crop2) => {
@@ -2135,7 +4352,13 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width / 2.0, frame_height / 2.0, 300.0, 300.0);
         let heads = vec![&head1, &head2, &head3, &head4, &head5, &head6];
 
-        let crop = calculate_six_or_more_heads_crop(false, frame_width, frame_height, &heads);
+        let crop = calculate_six_or_more_heads_crop(
+            false,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -2196,7 +4419,13 @@ mod tests {
             &head20, &head21,
         ];
 
-        let crop = calculate_six_or_more_heads_crop(true, frame_width, frame_height, &heads);
+        let crop = calculate_six_or_more_heads_crop(
+            true,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions::default(),
+        );
 
         match crop {
             CropResult::Single(crop) => {
@@ -2225,6 +4454,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_calculate_six_or_more_heads_crop_clustered_no_large_head() {
+        let frame_width = 1920.0;
+        let frame_height = 1080.0;
+
+        // The left part of the 21-head audience: too wide for the direct bbox crop but clustered
+        // in the left half of the frame, with no head large enough to be the focus
+        let head2 = Hbb::from_xyxy(165.4296, 204.68535, 231.6294, 278.2456);
+        let head3 = Hbb::from_xyxy(269.37784, 235.31018, 334.4793, 320.63513);
+        let head4 = Hbb::from_xyxy(497.31262, 304.38455, 545.0585, 366.63437);
+        let head8 = Hbb::from_xyxy(1004.2882, 279.04803, 1053.5553, 333.7185);
+        let head11 = Hbb::from_xyxy(747.9746, 290.0324, 799.1831, 354.62122);
+        let head12 = Hbb::from_xyxy(64.98474, 272.07635, 135.15686, 358.80164);
+        let head13 = Hbb::from_xyxy(548.1885, 238.84857, 596.61804, 293.81744);
+        let head14 = Hbb::from_xyxy(404.89105, 273.33435, 455.97382, 324.4703);
+        let head15 = Hbb::from_xyxy(640.2843, 268.06158, 691.5074, 330.74475);
+        let head16 = Hbb::from_xyxy(792.7516, 244.46857, 846.63715, 314.46362);
+        let head18 = Hbb::from_xyxy(904.9985, 297.55618, 951.48126, 358.47745);
+        let head19 = Hbb::from_xyxy(327.26227, 255.41493, 377.07877, 321.57587);
+        let head20 = Hbb::from_xyxy(680.5171, 259.2168, 719.5209, 310.3493);
+        let head21 = Hbb::from_xyxy(129.4746, 170.53577, 184.49347, 230.3624);
+        let heads = vec![
+            &head2, &head3, &head4, &head8, &head11, &head12, &head13, &head14, &head15, &head16,
+            &head18, &head19, &head20, &head21,
+        ];
+
+        let bbox = calculate_bounding_box(&heads);
+        assert!(bbox.width > frame_height * 0.75 && bbox.width <= frame_width * 0.75);
+        let bbox_center_x = bbox.x + bbox.width / 2.0;
+
+        for use_stack_crop in [true, false] {
+            match calculate_six_or_more_heads_crop(
+                use_stack_crop,
+                frame_width,
+                frame_height,
+                &heads,
+                &CropOptions::default(),
+            ) {
+                CropResult::Single(crop) => {
+                    assert!((crop.width - frame_height * 0.75).abs() < 1.0);
+                    assert!((crop.height - frame_height).abs() < 1.0);
+                    // Centered on the heads, not on the frame
+                    assert!((crop.x + crop.width / 2.0 - bbox_center_x).abs() < 1.0);
+                }
+                _ => panic!("Expected single crop for clustered heads"),
+            }
+        }
+    }
+
     #[test]
     fn test_calculate_crop_area_six_or_more_heads() {
         let frame_width = 1920.0;
@@ -2254,7 +4532,16 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width - 100.0, frame_height - 100.0, 100.0, 100.0)
             .with_confidence(0.9);
         let hbbs = vec![&head1, &head2, &head3, &head4, &head5, &head6];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
         assert!(matches!(crop, CropResult::Single(_)));
 
         // Test six heads with one large head
@@ -2272,7 +4559,104 @@ mod tests {
         let head6 = Hbb::from_cxcywh(frame_width / 2.0, frame_height / 2.0, 300.0, 300.0)
             .with_confidence(0.9);
         let hbbs = vec![&head1, &head2, &head3, &head4, &head5, &head6];
-        let crop = calculate_crop_area(true, false, frame_width, frame_height, &hbbs).unwrap();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &hbbs,
+            &CropOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(crop, CropResult::Stacked(_, _)));
+    }
+
+    #[test]
+    fn test_pad_head_clamps_to_frame() {
+        // A 100x100 head gets 5% of its 141.4 px diagonal on each side
+        let head = Hbb::from_xywh(500.0, 400.0, 100.0, 100.0).with_confidence(0.8);
+        let padded = pad_head(&head, 5.0, 1920.0, 1080.0);
+        assert!((padded.xmin() - 492.93).abs() < 0.01);
+        assert!((padded.width() - 114.14).abs() < 0.01);
+        assert_eq!(padded.confidence(), Some(0.8));
+        assert_eq!(pad_head(&head, 0.0, 1920.0, 1080.0).xmin(), 500.0);
+
+        // A head in the top-left corner only grows into the frame
+        let corner = Hbb::from_xywh(0.0, 0.0, 100.0, 100.0);
+        let padded = pad_head(&corner, 50.0, 1920.0, 1080.0);
+        assert_eq!(padded.xmin(), 0.0);
+        assert_eq!(padded.ymin(), 0.0);
+        assert!((padded.xmax() - 170.71).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_head_padding_keeps_crop_in_frame() {
+        let (frame_width, frame_height) = (1920.0, 1080.0);
+        let edge_head = Hbb::from_xywh(1800.0, 300.0, 120.0, 150.0);
+        for padding_percent in [0.0, 5.0, 100.0] {
+            let crop = calculate_single_head_crop(frame_width, frame_height, &edge_head, padding_percent);
+            let CropResult::Single(area) = crop else {
+                panic!("expected a single crop, got {:?}", crop);
+            };
+            assert!(area.x >= 0.0);
+            assert!(area.x + area.width <= frame_width + f32::EPSILON);
+            assert_eq!(area.height, frame_height);
+        }
+
+        let heads = [
+            Hbb::from_xywh(1700.0, 100.0, 200.0, 200.0),
+            Hbb::from_xywh(1750.0, 700.0, 150.0, 150.0),
+            Hbb::from_xywh(20.0, 500.0, 100.0, 100.0),
+        ];
+        let heads: Vec<&Hbb> = heads.iter().collect();
+        let crop = calculate_crop_area(
+            true,
+            false,
+            frame_width,
+            frame_height,
+            &heads,
+            &CropOptions {
+                padding_percent: 100.0,
+                ..CropOptions::default()
+            },
+        )
+        .unwrap()
+        .unwrap();
+        crop.for_each_crop(|area| {
+            assert!(area.x >= 0.0 && area.y >= 0.0);
+            assert!(area.x + area.width <= frame_width + 0.01);
+            assert!(area.y + area.height <= frame_height + 0.01);
+        });
+    }
+
+    #[test]
+    fn test_head_padding_can_switch_two_heads_to_stacked() {
+        // The heads span 800 px, just inside the 810 px single crop of a 1080p frame
+        let head1 = Hbb::from_xywh(500.0, 400.0, 100.0, 100.0);
+        let head2 = Hbb::from_xywh(1200.0, 400.0, 100.0, 100.0);
+        let crop = calculate_two_heads_crop(
+            true,
+            1920.0,
+            1080.0,
+            &head1,
+            &head2,
+            &CropOptions::default(),
+        );
+        assert!(matches!(crop, CropResult::Single(_)));
+
+        // 5% padding adds about 7 px on each side, which no longer fits
+        let crop = calculate_two_heads_crop(
+            true,
+            1920.0,
+            1080.0,
+            &head1,
+            &head2,
+            &CropOptions {
+                padding_percent: 5.0,
+                ..CropOptions::default()
+            },
+        );
         assert!(matches!(crop, CropResult::Stacked(_, _)));
     }
 }
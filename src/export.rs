@@ -0,0 +1,205 @@
+use crate::crop::{self, CropArea, CropResult};
+use crate::history::{CropHistory, KeyframeCrop};
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Header row of the crop CSV
+const CROP_CSV_HEADER: &str = "frame_index,timestamp_s,crop_type,crop1_x,crop1_y,crop1_w,crop1_h,crop2_x,crop2_y,crop2_w,crop2_h,object_count";
+
+/// Crops within this percentage of the frame width of a keyframe are saved as part of it
+const CROP_SEQUENCE_SIMILARITY_PERCENT: f32 = 0.5;
+
+/// The crop applied to one output frame
+#[derive(Debug, Clone, PartialEq)]
+struct CropRecord {
+    frame_index: u64,
+    timestamp_s: f64,
+    crop: CropResult,
+    object_count: usize,
+}
+
+impl CropRecord {
+    /// Formats the record as a CSV row, leaving the crop2 fields empty unless the crop is stacked
+    ///
+    /// Zoom-in crops are written as the area shown at their current progress.
+    fn to_csv_row(&self) -> String {
+        let (crop_type, crop1, crop2) = match &self.crop {
+            CropResult::Single(area) => ("single", area.clone(), None),
+            CropResult::Stacked(top, bottom) => ("stacked", top.clone(), Some(bottom)),
+            CropResult::Resize(area) => ("resize", area.clone(), None),
+            CropResult::ZoomIn { start, end, progress } => {
+                ("zoom", crop::interpolate_crop_area(start, end, *progress), None)
+            }
+        };
+        let crop2 = crop2.map(format_area).unwrap_or_else(|| ",,,".to_string());
+        format!(
+            "{},{:.3},{},{},{},{}",
+            self.frame_index,
+            self.timestamp_s,
+            crop_type,
+            format_area(&crop1),
+            crop2,
+            self.object_count
+        )
+    }
+}
+
+/// Formats a crop area as the `x,y,w,h` CSV fields
+fn format_area(area: &CropArea) -> String {
+    format!("{:.2},{:.2},{:.2},{:.2}", area.x, area.y, area.width, area.height)
+}
+
+/// Collects the crop of every output frame for `--export-crops` and writes them as CSV
+pub struct CropExporter {
+    path: PathBuf,
+    records: Vec<CropRecord>,
+}
+
+impl CropExporter {
+    /// Creates an exporter that writes to `path` when flushed
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Buffers the crop applied to a frame
+    ///
+    /// # Arguments
+    /// * `frame_index` - The index of the frame in the source video
+    /// * `timestamp_s` - The time of the frame in seconds
+    /// * `crop` - The crop applied to the frame
+    /// * `object_count` - The number of objects detected in the frame
+    pub fn record(&mut self, frame_index: u64, timestamp_s: f64, crop: &CropResult, object_count: usize) {
+        self.records.push(CropRecord {
+            frame_index,
+            timestamp_s,
+            crop: crop.clone(),
+            object_count,
+        });
+    }
+
+    /// Writes the header row and every buffered record to the CSV file
+    pub fn flush(&self) -> Result<()> {
+        let file = File::create(&self.path)
+            .with_context(|| format!("Failed to create crop export: {}", self.path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", CROP_CSV_HEADER)?;
+        for record in &self.records {
+            writeln!(writer, "{}", record.to_csv_row())?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write crop export: {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Returns the path the CSV is written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Saves the crop sequence in `history` as JSON keyframes for `--save-crop-sequence`
+///
+/// Runs of near-identical crops are stored as one keyframe, which shrinks the sequence of steady
+/// footage 10-100x. Every frame of a run is restored with the crop the run starts with.
+///
+/// # Arguments
+/// * `path` - The JSON file to write
+/// * `history` - The crop of every frame, oldest first
+/// * `frame_width` - The width of the frames, used to compare crops
+pub fn save_crop_sequence(path: &Path, history: &CropHistory, frame_width: f32) -> Result<()> {
+    let keyframes = history.merge_similar_consecutive(frame_width, CROP_SEQUENCE_SIMILARITY_PERCENT);
+    let json = serde_json::to_string(&keyframes).context("Failed to serialize crop sequence")?;
+    fs::write(path, json)
+        .with_context(|| format!("Failed to write crop sequence: {}", path.display()))
+}
+
+/// Loads a crop sequence saved with `save_crop_sequence`, with one frame per frame of the sequence
+pub fn load_crop_sequence(path: &Path) -> Result<CropHistory> {
+    let json = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read crop sequence: {}", path.display()))?;
+    let keyframes: Vec<KeyframeCrop> = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse crop sequence: {}", path.display()))?;
+    Ok(CropHistory::from_keyframes(&keyframes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crop_exporter_writes_csv() {
+        let path = std::env::temp_dir().join(format!("land2port_crops_{}.csv", std::process::id()));
+        let mut exporter = CropExporter::new(&path);
+        exporter.record(1, 0.0, &CropResult::Single(CropArea::new(656.25, 0.0, 607.5, 1080.0)), 1);
+        exporter.record(
+            2,
+            1.0 / 30.0,
+            &CropResult::Stacked(
+                CropArea::new(0.0, 0.0, 960.0, 540.0),
+                CropArea::new(960.0, 540.0, 960.0, 540.0),
+            ),
+            2,
+        );
+        exporter.record(
+            3,
+            2.0 / 30.0,
+            &CropResult::ZoomIn {
+                start: CropArea::new(0.0, 0.0, 200.0, 100.0),
+                end: CropArea::new(100.0, 50.0, 100.0, 50.0),
+                progress: 0.5,
+            },
+            0,
+        );
+        exporter.flush().unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CROP_CSV_HEADER);
+        assert_eq!(lines[1], "1,0.000,single,656.25,0.00,607.50,1080.00,,,,,1");
+        assert_eq!(
+            lines[2],
+            "2,0.033,stacked,0.00,0.00,960.00,540.00,960.00,540.00,960.00,540.00,2"
+        );
+        assert_eq!(lines[3], "3,0.067,zoom,50.00,25.00,150.00,75.00,,,,,0");
+        assert!(lines.iter().all(|line| line.split(',').count() == 12));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_crop_sequence_round_trip() {
+        let path = std::env::temp_dir().join(format!("land2port_sequence_{}.json", std::process::id()));
+        let placeholder = usls::Image::from(::image::RgbImage::new(1, 1));
+        let left = CropResult::Single(CropArea::new(100.0, 0.0, 607.5, 1080.0));
+        let right = CropResult::Single(CropArea::new(1200.0, 0.0, 607.5, 1080.0));
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 960.0, 540.0),
+            CropArea::new(960.0, 0.0, 960.0, 540.0),
+        );
+        let mut history = CropHistory::new();
+        for (crop, object_count, frames) in [(&left, 1, 60), (&right, 1, 30), (&stacked, 2, 10)] {
+            for _ in 0..frames {
+                history.add(crop.clone(), placeholder.clone(), object_count);
+            }
+        }
+
+        save_crop_sequence(&path, &history, 1920.0).unwrap();
+        let keyframes: Vec<KeyframeCrop> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(keyframes.len(), 3);
+
+        let loaded = load_crop_sequence(&path).unwrap();
+        assert_eq!(loaded.len(), 100);
+        let original: Vec<(&CropResult, usize)> =
+            history.peek_iter().map(|frame| (&frame.crop, frame.object_count)).collect();
+        let restored: Vec<(&CropResult, usize)> =
+            loaded.peek_iter().map(|frame| (&frame.crop, frame.object_count)).collect();
+        assert_eq!(restored, original);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 use usls::Image;
-use crate::crop::CropResult;
+use crate::crop::{self, CropResult};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 /// A structure to hold frame data including crop, image, and head count
 #[derive(Clone)]
@@ -9,34 +11,86 @@ pub struct FrameData {
     pub object_count: usize,
 }
 
+/// A run of consecutive frames that share a similar crop
+///
+/// Frames `start_frame..end_frame` (end exclusive) all use `crop`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyframeCrop {
+    pub start_frame: usize,
+    pub end_frame: usize,
+    pub crop: CropResult,
+    pub object_count: usize,
+}
+
 /// A structure to maintain a history of frame data
+///
+/// A read cursor allows replaying buffered frames for debugging. It normally sits at the end
+/// of the history and follows new frames while it is there.
 pub struct CropHistory {
-    frames: Vec<FrameData>,
+    frames: VecDeque<FrameData>,
+    read_cursor: usize,
 }
 
 impl CropHistory {
     /// Create a new empty history
     pub fn new() -> Self {
-        Self { frames: Vec::new() }
+        Self {
+            frames: VecDeque::new(),
+            read_cursor: 0,
+        }
     }
 
     /// Add a new frame to the history
     pub fn add(&mut self, crop: CropResult, image: Image, object_count: usize) {
-        self.frames.push(FrameData { crop, image, object_count });
+        if self.read_cursor == self.frames.len() {
+            self.read_cursor += 1;
+        }
+        self.frames.push_back(FrameData { crop, image, object_count });
     }
 
     /// Remove and return the first frame from the history
     pub fn pop_front(&mut self) -> Option<FrameData> {
-        if self.frames.is_empty() {
-            None
-        } else {
-            Some(self.frames.remove(0))
-        }
+        let frame = self.frames.pop_front()?;
+        self.read_cursor = self.read_cursor.saturating_sub(1);
+        Some(frame)
+    }
+
+    /// Moves the read cursor back by `n` frames without removing any, stopping at the oldest
+    pub fn rewind(&mut self, n: usize) {
+        self.read_cursor = self.read_cursor.saturating_sub(n);
+    }
+
+    /// Returns the frame at the read cursor and advances it, or `None` at the end of the history
+    pub fn read_next(&mut self) -> Option<&FrameData> {
+        let frame = self.frames.get(self.read_cursor)?;
+        self.read_cursor += 1;
+        Some(frame)
+    }
+
+    /// Moves the read cursor back to the end of the history
+    pub fn reset_cursor(&mut self) {
+        self.read_cursor = self.frames.len();
     }
 
     /// Get a reference to the first frame without removing it
     pub fn peek_front(&self) -> Option<&FrameData> {
-        self.frames.first()
+        self.frames.front()
+    }
+
+    /// Get a reference to the frame `n` positions from the newest without removing it
+    ///
+    /// `peek_back_n(0)` is the most recently added frame, `peek_back_n(1)` the one before it.
+    pub fn peek_back_n(&self, n: usize) -> Option<&FrameData> {
+        let index = self.frames.len().checked_sub(n + 1)?;
+        self.frames.get(index)
+    }
+
+    /// Returns the newest `n` frames (all frames if there are fewer), oldest first
+    ///
+    /// Takes `&mut self` to lay the buffered frames out contiguously.
+    pub fn window_from_back(&mut self, n: usize) -> &[FrameData] {
+        let frames = self.frames.make_contiguous();
+        &frames[frames.len().saturating_sub(n)..]
     }
 
     /// Get the number of frames in the history
@@ -48,4 +102,409 @@ impl CropHistory {
     pub fn is_empty(&self) -> bool {
         self.frames.is_empty()
     }
-} 
\ No newline at end of file
+
+    /// Compresses the history into runs of consecutive frames with similar crops
+    ///
+    /// A run continues while each crop is similar to the crop at the start of the run and the
+    /// object count is unchanged. Only crops are kept, so images are not part of the result.
+    ///
+    /// # Arguments
+    /// * `frame_width` - The width of the frame, used for similarity
+    /// * `threshold_percent` - The similarity threshold percentage (see `crop::is_crop_similar`)
+    pub fn merge_similar_consecutive(
+        &self,
+        frame_width: f32,
+        threshold_percent: f32,
+    ) -> Vec<KeyframeCrop> {
+        let mut keyframes: Vec<KeyframeCrop> = Vec::new();
+
+        for (index, frame) in self.frames.iter().enumerate() {
+            if let Some(last) = keyframes.last_mut() {
+                if last.object_count == frame.object_count
+                    && crop::is_crop_similar(&frame.crop, &last.crop, frame_width, threshold_percent)
+                {
+                    last.end_frame = index + 1;
+                    continue;
+                }
+            }
+            keyframes.push(KeyframeCrop {
+                start_frame: index,
+                end_frame: index + 1,
+                crop: frame.crop.clone(),
+                object_count: frame.object_count,
+            });
+        }
+
+        keyframes
+    }
+
+    /// Expands keyframe runs back into a history with one frame per frame index
+    ///
+    /// Keyframes do not store images, so every restored frame carries a 1x1 placeholder image.
+    /// Gaps between keyframes are skipped.
+    pub fn from_keyframes(keyframes: &[KeyframeCrop]) -> CropHistory {
+        let placeholder = Image::from(image::RgbImage::new(1, 1));
+        let mut history = CropHistory::new();
+        for keyframe in keyframes {
+            for _ in keyframe.start_frame..keyframe.end_frame {
+                history.add(keyframe.crop.clone(), placeholder.clone(), keyframe.object_count);
+            }
+        }
+        history
+    }
+
+    /// Returns `target_len` evenly spaced frames, oldest first, for summarizing a long history
+    ///
+    /// Frames are taken every `len / target_len` frames starting with the oldest. A history
+    /// shorter than `target_len` is returned in full.
+    pub fn sample_at(&self, target_len: usize) -> Vec<&FrameData> {
+        if target_len == 0 {
+            return Vec::new();
+        }
+        let stride = (self.frames.len() / target_len).max(1);
+        self.frames.iter().step_by(stride).take(target_len).collect()
+    }
+
+    /// Returns each crop with its position in the history, from 0.0 (oldest) to 1.0 (newest)
+    ///
+    /// A single frame is at position 0.0.
+    pub fn time_ordered_crop_sequence(&self) -> Vec<(&CropResult, f32)> {
+        let last_index = self.frames.len().saturating_sub(1).max(1) as f32;
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| (&frame.crop, index as f32 / last_index))
+            .collect()
+    }
+
+    /// Iterate over the frames in insertion order without removing them
+    pub fn peek_iter(&self) -> CropHistoryIter<'_> {
+        CropHistoryIter {
+            frames: &self.frames,
+            index: 0,
+        }
+    }
+
+    /// Iterate over the frames matching a predicate, in insertion order
+    pub fn filter_iter<'a, P>(&'a self, predicate: P) -> impl Iterator<Item = &'a FrameData>
+    where
+        P: FnMut(&&'a FrameData) -> bool,
+    {
+        self.peek_iter().filter(predicate)
+    }
+}
+
+/// Borrowing iterator over the frames of a `CropHistory`, oldest first
+pub struct CropHistoryIter<'a> {
+    frames: &'a VecDeque<FrameData>,
+    index: usize,
+}
+
+impl<'a> Iterator for CropHistoryIter<'a> {
+    type Item = &'a FrameData;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.get(self.index)?;
+        self.index += 1;
+        Some(frame)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.frames.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for CropHistoryIter<'_> {}
+
+impl<'a> IntoIterator for &'a CropHistory {
+    type Item = &'a FrameData;
+    type IntoIter = CropHistoryIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.peek_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crop::CropArea;
+    use image::RgbImage;
+
+    fn add_frame(history: &mut CropHistory, x: f32, object_count: usize) {
+        let crop = CropResult::Single(CropArea::new(x, 0.0, 810.0, 1080.0));
+        history.add(crop, Image::from(RgbImage::new(2, 2)), object_count);
+    }
+
+    fn crop_x(frame: &FrameData) -> f32 {
+        match &frame.crop {
+            CropResult::Single(area) => area.x,
+            _ => panic!("Expected single crop"),
+        }
+    }
+
+    #[test]
+    fn test_iteration_order() {
+        let mut history = CropHistory::new();
+        add_frame(&mut history, 10.0, 1);
+        add_frame(&mut history, 20.0, 2);
+        add_frame(&mut history, 30.0, 1);
+
+        let xs: Vec<f32> = (&history).into_iter().map(crop_x).collect();
+        assert_eq!(xs, vec![10.0, 20.0, 30.0]);
+
+        let mut count = 0;
+        for frame in &history {
+            assert_eq!(crop_x(frame), 10.0 * (count + 1) as f32);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        // Iterating does not consume the history
+        assert_eq!(history.len(), 3);
+        assert_eq!(crop_x(history.peek_front().unwrap()), 10.0);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let mut history = CropHistory::new();
+        assert_eq!(history.peek_iter().size_hint(), (0, Some(0)));
+
+        add_frame(&mut history, 10.0, 1);
+        add_frame(&mut history, 20.0, 1);
+        let mut iter = history.peek_iter();
+        assert_eq!(iter.len(), 2);
+        iter.next();
+        assert_eq!(iter.size_hint(), (1, Some(1)));
+        iter.next();
+        assert!(iter.next().is_none());
+        assert_eq!(iter.len(), 0);
+    }
+
+    #[test]
+    fn test_filter_iter() {
+        let mut history = CropHistory::new();
+        add_frame(&mut history, 10.0, 1);
+        add_frame(&mut history, 20.0, 2);
+        add_frame(&mut history, 30.0, 1);
+        add_frame(&mut history, 40.0, 3);
+
+        let single_head: Vec<f32> = history
+            .filter_iter(|frame| frame.object_count == 1)
+            .map(crop_x)
+            .collect();
+        assert_eq!(single_head, vec![10.0, 30.0]);
+
+        assert_eq!(history.filter_iter(|frame| frame.object_count > 5).count(), 0);
+    }
+
+    #[test]
+    fn test_merge_identical_crops_into_one_keyframe() {
+        let mut history = CropHistory::new();
+        for _ in 0..100 {
+            add_frame(&mut history, 555.0, 1);
+        }
+
+        let keyframes = history.merge_similar_consecutive(1920.0, 10.0);
+        assert_eq!(keyframes.len(), 1);
+        assert_eq!((keyframes[0].start_frame, keyframes[0].end_frame), (0, 100));
+
+        let restored = CropHistory::from_keyframes(&keyframes);
+        assert_eq!(restored.len(), 100);
+        assert!(restored.peek_iter().all(|frame| crop_x(frame) == 555.0 && frame.object_count == 1));
+    }
+
+    #[test]
+    fn test_merge_similar_consecutive_runs() {
+        let mut history = CropHistory::new();
+        add_frame(&mut history, 100.0, 1);
+        add_frame(&mut history, 110.0, 1); // similar to the run start
+        add_frame(&mut history, 900.0, 1); // new position
+        add_frame(&mut history, 900.0, 2); // new object count
+        add_frame(&mut history, 905.0, 2);
+
+        let keyframes = history.merge_similar_consecutive(1920.0, 5.0);
+        let runs: Vec<(usize, usize)> = keyframes.iter().map(|k| (k.start_frame, k.end_frame)).collect();
+        assert_eq!(runs, vec![(0, 2), (2, 3), (3, 5)]);
+
+        // Frames in a run take the crop of the run start
+        let restored = CropHistory::from_keyframes(&keyframes);
+        let xs: Vec<f32> = restored.peek_iter().map(crop_x).collect();
+        assert_eq!(xs, vec![100.0, 100.0, 900.0, 900.0, 900.0]);
+
+        assert!(CropHistory::new().merge_similar_consecutive(1920.0, 5.0).is_empty());
+    }
+
+    #[test]
+    fn test_sample_at_even_stride() {
+        let mut history = CropHistory::new();
+        for i in 0..90 {
+            add_frame(&mut history, i as f32, 1);
+        }
+
+        let xs: Vec<f32> = history.sample_at(10).into_iter().map(crop_x).collect();
+        assert_eq!(xs, vec![0.0, 9.0, 18.0, 27.0, 36.0, 45.0, 54.0, 63.0, 72.0, 81.0]);
+
+        // Strides are rounded down, so the samples still start at the oldest frame
+        let xs: Vec<f32> = history.sample_at(4).into_iter().map(crop_x).collect();
+        assert_eq!(xs, vec![0.0, 22.0, 44.0, 66.0]);
+        assert!(history.sample_at(0).is_empty());
+    }
+
+    #[test]
+    fn test_sample_at_short_history() {
+        let mut history = CropHistory::new();
+        add_frame(&mut history, 10.0, 1);
+        add_frame(&mut history, 20.0, 1);
+        add_frame(&mut history, 30.0, 1);
+
+        let xs: Vec<f32> = history.sample_at(10).into_iter().map(crop_x).collect();
+        assert_eq!(xs, vec![10.0, 20.0, 30.0]);
+
+        let mut single = CropHistory::new();
+        add_frame(&mut single, 10.0, 1);
+        assert_eq!(single.sample_at(5).len(), 1);
+        assert!(CropHistory::new().sample_at(5).is_empty());
+    }
+
+    #[test]
+    fn test_time_ordered_crop_sequence() {
+        let mut history = CropHistory::new();
+        for x in [10.0, 20.0, 30.0, 40.0, 50.0] {
+            add_frame(&mut history, x, 1);
+        }
+        let positions: Vec<f32> = history
+            .time_ordered_crop_sequence()
+            .into_iter()
+            .map(|(_, position)| position)
+            .collect();
+        assert_eq!(positions, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+
+        let mut single = CropHistory::new();
+        add_frame(&mut single, 10.0, 1);
+        let sequence = single.time_ordered_crop_sequence();
+        assert_eq!(sequence.len(), 1);
+        assert_eq!(sequence[0].1, 0.0);
+    }
+
+    #[test]
+    fn test_peek_back_n() {
+        let mut history = CropHistory::new();
+        assert!(history.peek_back_n(0).is_none());
+
+        for x in [10.0, 20.0, 30.0] {
+            add_frame(&mut history, x, 1);
+        }
+        assert_eq!(history.peek_back_n(0).map(crop_x), Some(30.0));
+        assert_eq!(history.peek_back_n(1).map(crop_x), Some(20.0));
+        assert_eq!(history.peek_back_n(2).map(crop_x), Some(10.0));
+        assert!(history.peek_back_n(3).is_none());
+
+        history.pop_front();
+        assert_eq!(history.peek_back_n(1).map(crop_x), Some(20.0));
+        assert!(history.peek_back_n(2).is_none());
+    }
+
+    #[test]
+    fn test_window_from_back() {
+        let mut history = CropHistory::new();
+        assert!(history.window_from_back(2).is_empty());
+
+        for x in [10.0, 20.0, 30.0, 40.0] {
+            add_frame(&mut history, x, 1);
+        }
+        // Wrap the ring buffer so the frames are not stored contiguously
+        history.pop_front();
+        add_frame(&mut history, 50.0, 1);
+
+        let xs: Vec<f32> = history.window_from_back(2).iter().map(crop_x).collect();
+        assert_eq!(xs, vec![40.0, 50.0]);
+        let xs: Vec<f32> = history.window_from_back(10).iter().map(crop_x).collect();
+        assert_eq!(xs, vec![20.0, 30.0, 40.0, 50.0]);
+        assert!(history.window_from_back(0).is_empty());
+    }
+
+    #[test]
+    fn test_window_from_back_start_middle_and_end() {
+        let mut history = CropHistory::new();
+        for x in [10.0, 20.0, 30.0, 40.0, 50.0, 60.0] {
+            add_frame(&mut history, x, 1);
+        }
+        history.pop_front();
+        add_frame(&mut history, 70.0, 1);
+
+        for n in 1..=history.len() {
+            let xs: Vec<f32> = history.window_from_back(n).iter().map(crop_x).collect();
+            assert_eq!(xs.len(), n);
+            // The window starts n - 1 frames from the back and ends at the newest frame
+            assert_eq!(xs[0], crop_x(history.peek_back_n(n - 1).unwrap()));
+            assert_eq!(xs[n / 2], crop_x(history.peek_back_n(n - 1 - n / 2).unwrap()));
+            assert_eq!(xs[n - 1], crop_x(history.peek_back_n(0).unwrap()));
+        }
+
+        let xs: Vec<f32> = history.window_from_back(5).iter().map(crop_x).collect();
+        assert_eq!((xs[0], xs[2], xs[4]), (30.0, 50.0, 70.0));
+    }
+
+    #[test]
+    fn test_rewind_and_read_next() {
+        let mut history = CropHistory::new();
+        for x in [10.0, 20.0, 30.0, 40.0] {
+            add_frame(&mut history, x, 1);
+        }
+
+        // The cursor starts at the end
+        assert!(history.read_next().is_none());
+
+        history.rewind(2);
+        assert_eq!(history.read_next().map(crop_x), Some(30.0));
+        assert_eq!(history.read_next().map(crop_x), Some(40.0));
+        assert!(history.read_next().is_none());
+        assert_eq!(history.len(), 4);
+
+        history.rewind(1);
+        history.reset_cursor();
+        assert!(history.read_next().is_none());
+    }
+
+    #[test]
+    fn test_read_cursor_bounds() {
+        let mut history = CropHistory::new();
+        add_frame(&mut history, 10.0, 1);
+        add_frame(&mut history, 20.0, 1);
+
+        // Rewinding past the oldest frame stops there
+        history.rewind(10);
+        assert_eq!(history.read_next().map(crop_x), Some(10.0));
+
+        // Popping the front keeps the cursor on the same frame
+        history.pop_front();
+        assert_eq!(history.read_next().map(crop_x), Some(20.0));
+
+        // A cursor at the end follows newly added frames
+        add_frame(&mut history, 30.0, 1);
+        assert!(history.read_next().is_none());
+        history.rewind(1);
+        assert_eq!(history.read_next().map(crop_x), Some(30.0));
+    }
+
+    #[test]
+    fn test_pop_front_is_constant_time() {
+        let mut history = CropHistory::new();
+        for i in 0..1000 {
+            add_frame(&mut history, i as f32, 1);
+        }
+
+        // Popped frames are collected so freeing their images is not part of the measurement
+        let start = std::time::Instant::now();
+        let popped: Vec<FrameData> = std::iter::from_fn(|| history.pop_front()).collect();
+        let elapsed = start.elapsed();
+
+        assert_eq!(popped.len(), 1000);
+        assert_eq!(crop_x(&popped[0]), 0.0);
+        assert_eq!(crop_x(&popped[999]), 999.0);
+        assert!(history.is_empty());
+        assert!(elapsed < std::time::Duration::from_millis(1), "popping took {:?}", elapsed);
+    }
+}
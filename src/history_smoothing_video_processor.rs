@@ -1,12 +1,63 @@
 use crate::cli::Args;
+use crate::config;
 use crate::crop;
 use crate::history;
-use crate::image::CutDetector;
+use crate::image::{self, CutDetector};
 use crate::video_processor::VideoProcessor;
 use crate::video_processor_utils;
-use anyhow::Result;
+use crate::video_processor_utils::CropRenderer;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::Path;
 use usls::Viewer;
 
+/// How much tighter the end of a zoom-in is than the single-head crop
+const ZOOM_IN_FACTOR: f32 = 1.5;
+
+/// Number of frames the motion speed average spans for adaptive smoothing
+const MOTION_EMA_FRAMES: usize = 30;
+
+/// Consecutive frames the crop must move in the same direction before crop prediction kicks in
+const CROP_TREND_FRAMES: usize = 3;
+
+/// Frame rate used for timestamps until the source frame rate is known
+const DEFAULT_FRAME_RATE: f64 = 30.0;
+
+/// Callback invoked when the smoothed crop changes, with the old crop, the new crop and the
+/// timestamp of the frame in seconds
+pub type CropChangeCallback = Box<dyn Fn(&crop::CropResult, &crop::CropResult, f64) + Send>;
+
+/// Why the smoothed crop changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TransitionReason {
+    /// A scene cut was detected
+    Cut,
+    /// The number of heads moved the crop into a different crop class
+    ClassChange,
+    /// The heads moved within the same crop class
+    PositionChange,
+    /// Buffered frames were flushed with a crop other than the pending change
+    HistoryFlush,
+    /// The first crop of the video
+    Initialization,
+}
+
+/// A change of the smoothed crop, recorded for `--log-transitions`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CropTransitionEvent {
+    /// The frame the change was applied at
+    pub frame: u64,
+    /// The timestamp of the frame in seconds
+    pub timestamp_sec: f32,
+    /// Why the crop changed
+    pub reason: TransitionReason,
+    /// The crop before the change (the new crop for `Initialization`)
+    pub old_crop: crop::CropResult,
+    /// The crop after the change
+    pub new_crop: crop::CropResult,
+}
+
 /// Video processor that handles cropping with history smoothing
 pub struct HistorySmoothingVideoProcessor {
     previous_crop: Option<crop::CropResult>,
@@ -14,6 +65,20 @@ pub struct HistorySmoothingVideoProcessor {
     last_image: Option<usls::Image>,
     history: history::CropHistory,
     cut_detector: CutDetector,
+    zoom_in_crop: Option<crop::CropResult>,
+    zoom_in_frame: usize,
+    motion_speed_ema: Option<f32>,
+    interpolated_transition_frames: usize,
+    interpolation_frames: usize,
+    frame_count: u64,
+    on_crop_change: Option<CropChangeCallback>,
+    use_crop_prediction: bool,
+    recent_crops: VecDeque<crop::CropArea>,
+    object_count_change_debounce_frames: usize,
+    frames_since_count_change: usize,
+    crop_change_log: Vec<CropTransitionEvent>,
+    last_timestamp_sec: f64,
+    frame_rate: f64,
 }
 
 impl HistorySmoothingVideoProcessor {
@@ -24,7 +89,240 @@ impl HistorySmoothingVideoProcessor {
             previous_object_count: 0,
             last_image: None,
             history: history::CropHistory::new(),
-            cut_detector: CutDetector::new(args.cut_similarity, args.cut_start),
+            cut_detector: CutDetector::new(
+                args.cut_similarity,
+                args.cut_start,
+                args.cut_detection_method,
+            )
+            .with_warmup_frames(config::cut_warmup_frames(args)),
+            zoom_in_crop: None,
+            zoom_in_frame: 0,
+            motion_speed_ema: None,
+            interpolated_transition_frames: args.interpolated_transition_frames,
+            interpolation_frames: args.interpolation_frames,
+            frame_count: 0,
+            on_crop_change: None,
+            use_crop_prediction: args.use_crop_prediction,
+            recent_crops: VecDeque::with_capacity(CROP_TREND_FRAMES + 1),
+            object_count_change_debounce_frames: args.object_count_debounce_frames,
+            frames_since_count_change: 0,
+            crop_change_log: Vec::new(),
+            last_timestamp_sec: 0.0,
+            frame_rate: DEFAULT_FRAME_RATE,
+        }
+    }
+
+    /// Returns every crop transition so far, in order
+    pub fn crop_change_log(&self) -> &[CropTransitionEvent] {
+        &self.crop_change_log
+    }
+
+    /// Appends a crop transition at the current frame to the change log
+    fn log_transition(
+        &mut self,
+        reason: TransitionReason,
+        old_crop: &crop::CropResult,
+        new_crop: &crop::CropResult,
+        timestamp_sec: f64,
+    ) {
+        self.crop_change_log.push(CropTransitionEvent {
+            frame: self.frame_count.saturating_sub(1),
+            timestamp_sec: timestamp_sec as f32,
+            reason,
+            old_crop: old_crop.clone(),
+            new_crop: new_crop.clone(),
+        });
+    }
+
+    /// Logs the frames left in the history being flushed with the previous crop at the end of
+    /// the video, dropping the change they were buffered for
+    fn log_history_flush(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        if let Some(prev_crop) = self.previous_crop.clone() {
+            self.log_transition(TransitionReason::HistoryFlush, &prev_crop, &prev_crop, self.last_timestamp_sec);
+        }
+    }
+
+    /// Saves the last `--replay-last-n-frames` buffered frames cropped with `--replay-crop`
+    ///
+    /// Debug aid for checking how a different crop would have looked. Without `--replay-crop`
+    /// the crop chosen for each frame is used. The history itself is left untouched.
+    fn replay_history(&mut self, args: &Args) -> Result<()> {
+        std::fs::create_dir_all(&args.replay_dir)?;
+        println!(
+            "Replaying the last {} of {} buffered frames to: {}",
+            args.replay_last_n_frames.min(self.history.len()),
+            self.history.len(),
+            args.replay_dir
+        );
+
+        self.history.rewind(args.replay_last_n_frames);
+        let mut replay_index = 0;
+        while let Some(frame) = self.history.read_next() {
+            let crop = match &args.replay_crop {
+                Some(zone) => crop::CropResult::Single(crop::CropArea::from_zone_str(
+                    zone,
+                    frame.image.width() as f32,
+                    frame.image.height() as f32,
+                )?),
+                None => frame.crop.clone(),
+            };
+            let path = std::path::Path::new(&args.replay_dir)
+                .join(format!("replay_{:06}.jpg", replay_index));
+            video_processor_utils::save_cropped_frame(
+                &frame.image,
+                &crop,
+                args.pillarbox,
+                image::RenderOptions {
+                    filter: args.resize_filter,
+                    sharpen: image::Sharpen::new(args.sharpen_amount, args.sharpen_radius),
+                    ..image::RenderOptions::default()
+                },
+                &path,
+            )?;
+            replay_index += 1;
+        }
+        self.history.reset_cursor();
+        Ok(())
+    }
+
+    /// Calls `callback` with the old crop, the new crop and the frame timestamp in seconds
+    /// whenever the smoothed crop changes
+    pub fn with_on_crop_change(
+        mut self,
+        callback: impl Fn(&crop::CropResult, &crop::CropResult, f64) + Send + 'static,
+    ) -> Self {
+        self.on_crop_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Stores the smoothed crop, logging the transition and notifying the crop change callback if
+    /// it differs from the last one
+    fn update_previous_crop(
+        &mut self,
+        crop_result: crop::CropResult,
+        timestamp_sec: f64,
+        reason: TransitionReason,
+    ) {
+        match self.previous_crop.take() {
+            None => self.log_transition(TransitionReason::Initialization, &crop_result, &crop_result, timestamp_sec),
+            Some(previous_crop) => {
+                if previous_crop != crop_result {
+                    if let Some(callback) = &self.on_crop_change {
+                        callback(&previous_crop, &crop_result, timestamp_sec);
+                    }
+                    self.log_transition(reason, &previous_crop, &crop_result, timestamp_sec);
+                }
+            }
+        }
+        self.previous_crop = Some(crop_result);
+    }
+
+    /// Predicts the next single crop once the recent crops have moved the same way for
+    /// `CROP_TREND_FRAMES` frames
+    fn predicted_crop(&self, frame_width: f32, frame_height: f32) -> Option<crop::CropArea> {
+        if self.recent_crops.len() <= CROP_TREND_FRAMES {
+            return None;
+        }
+        let crops: Vec<crop::CropArea> = self.recent_crops.iter().cloned().collect();
+        if !video_processor_utils::is_consistent_crop_trend(&crops) {
+            return None;
+        }
+        let n = crops.len();
+        Some(video_processor_utils::predict_crop_area(
+            &crops[n - 3],
+            &crops[n - 2],
+            &crops[n - 1],
+            frame_width,
+            frame_height,
+        ))
+    }
+
+    /// Records the latest single crop for trend detection, restarting the trend on other crops
+    fn record_crop_trend(&mut self, latest_crop: &crop::CropResult) {
+        match latest_crop {
+            crop::CropResult::Single(area) => {
+                if self.recent_crops.len() > CROP_TREND_FRAMES {
+                    self.recent_crops.pop_front();
+                }
+                self.recent_crops.push_back(area.clone());
+            }
+            _ => self.recent_crops.clear(),
+        }
+    }
+
+    /// Counts consecutive frames whose object count is in a different crop class than the smoothed
+    /// crop, returning `true` while the change is still within the debounce grace period
+    ///
+    /// # Arguments
+    /// * `frames_since_count_change` - The running count, reset once the class matches again
+    /// * `debounce_frames` - Frames a class change must last before it is accepted (0 disables)
+    /// * `is_count_changed` - Whether this frame's object count changes the crop class
+    fn is_count_change_debounced(
+        frames_since_count_change: &mut usize,
+        debounce_frames: usize,
+        is_count_changed: bool,
+    ) -> bool {
+        if !is_count_changed {
+            *frames_since_count_change = 0;
+            return false;
+        }
+        *frames_since_count_change += 1;
+        *frames_since_count_change <= debounce_frames
+    }
+
+    /// Wraps the chosen crop in a zoom-in after a no-heads to single-head transition
+    ///
+    /// The zoom advances one step per frame for `--zoom-in-frames` frames and then holds,
+    /// and is dropped as soon as the smoothed crop moves away from the zoom start.
+    fn apply_zoom_in(
+        &mut self,
+        crop_result: &crop::CropResult,
+        objects: &[&usls::Hbb],
+        object_count: usize,
+        frame_width: f32,
+        frame_height: f32,
+        args: &Args,
+    ) -> crop::CropResult {
+        if args.zoom_in_frames == 0 {
+            return crop_result.clone();
+        }
+
+        let is_single = matches!(crop_result, crop::CropResult::Single(_));
+        if self.previous_object_count == 0 && object_count == 1 && is_single && objects.len() == 1 {
+            video_processor_utils::debug_println(format_args!("starting zoom in"));
+            self.zoom_in_crop = Some(crop::calculate_zoom_in_crop(
+                frame_width,
+                frame_height,
+                objects[0],
+                ZOOM_IN_FACTOR,
+            ));
+            self.zoom_in_frame = 0;
+        }
+
+        let (start, end) = match &self.zoom_in_crop {
+            Some(crop::CropResult::ZoomIn { start, end, .. }) => (start.clone(), end.clone()),
+            _ => return crop_result.clone(),
+        };
+
+        let is_on_zoom_start = crop::is_crop_similar(
+            crop_result,
+            &crop::CropResult::Single(start.clone()),
+            frame_width,
+            args.smooth_percentage,
+        );
+        if !is_on_zoom_start {
+            self.zoom_in_crop = None;
+            return crop_result.clone();
+        }
+
+        self.zoom_in_frame = (self.zoom_in_frame + 1).min(args.zoom_in_frames);
+        crop::CropResult::ZoomIn {
+            start,
+            end,
+            progress: self.zoom_in_frame as f32 / args.zoom_in_frames as f32,
         }
     }
 }
@@ -38,28 +336,88 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
         objects: &[&usls::Hbb],
         args: &Args,
         viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
         smooth_duration_frames: usize,
     ) -> Result<()> {
+        let timestamp_sec = self.frame_count as f64 / self.frame_rate;
+        self.frame_count += 1;
+        self.last_timestamp_sec = timestamp_sec;
+
+        let smooth_duration_frames = if args.adaptive_smoothing {
+            if let Some(prev_crop) = &self.previous_crop {
+                let motion_speed = crop::centroid_distance(prev_crop, latest_crop);
+                self.motion_speed_ema = Some(video_processor_utils::update_motion_ema(
+                    self.motion_speed_ema,
+                    motion_speed,
+                    MOTION_EMA_FRAMES,
+                ));
+            }
+            let adaptive_frames = video_processor_utils::adaptive_smooth_duration_frames(
+                smooth_duration_frames,
+                self.motion_speed_ema.unwrap_or(0.0),
+                args.max_expected_speed,
+            );
+            video_processor_utils::debug_println(format_args!(
+                "motion_speed_ema: {:?}, adaptive smooth_duration_frames: {}",
+                self.motion_speed_ema, adaptive_frames
+            ));
+            adaptive_frames
+        } else {
+            smooth_duration_frames
+        };
+
         let current_object_count = objects.len();
+        // With a confirmed pan, a crop on the predicted path is taken without waiting for history
+        let predicted_crop = if self.use_crop_prediction {
+            self.predicted_crop(img.width() as f32, img.height() as f32)
+        } else {
+            None
+        };
         // Compare with previous crop if it exists
         let mut object_count = current_object_count;
+        let mut transition_reason = TransitionReason::PositionChange;
         let crop_result: Option<crop::CropResult> = if let Some(prev_crop) = &self.previous_crop {
-            let is_same_class =
-                crop::is_crop_class_same(current_object_count, self.previous_object_count);
             let is_latest_crop_similar = crop::is_crop_similar(
                 latest_crop,
                 prev_crop,
                 img.width() as f32,
                 args.smooth_percentage,
             );
+            // Multi-head classes (e.g. three and four heads) often share nearly the same crop,
+            // so someone joining the group does not need a class change
+            let is_multi_head = current_object_count >= 2 && self.previous_object_count >= 2;
+            let is_same_class =
+                crop::is_crop_class_same(current_object_count, self.previous_object_count)
+                    || (is_multi_head
+                        && crop::is_crop_structurally_equivalent(
+                            latest_crop,
+                            prev_crop,
+                            img.width() as f32,
+                            args.smooth_percentage,
+                        ));
             let is_cut = if let Some(ref last_image) = self.last_image {
                 self.cut_detector.is_cut(last_image, img)?
             } else {
                 true
             };
+            // Brief object count changes, like someone stepping out of frame, keep the current crop
+            let is_count_change_held = Self::is_count_change_debounced(
+                &mut self.frames_since_count_change,
+                self.object_count_change_debounce_frames,
+                !is_cut && !is_same_class,
+            );
+            let is_predicted_crop = predicted_crop.is_some_and(|predicted| {
+                crop::is_crop_similar(
+                    latest_crop,
+                    &crop::CropResult::Single(predicted),
+                    img.width() as f32,
+                    args.smooth_percentage,
+                )
+            });
 
             if is_cut {
                 video_processor_utils::debug_println(format_args!("is_cut"));
+                self.recent_crops.clear();
                 if !self.history.is_empty() {
                     while let Some(frame) = self.history.pop_front() {
                         video_processor_utils::process_and_display_crop(
@@ -67,11 +425,31 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
                             prev_crop,
                             viewer,
                             args.headless,
+                            args.pillarbox,
+                            renderer,
                         )?;
                     }
                 }
                 object_count = current_object_count;
+                transition_reason = TransitionReason::Cut;
                 Some(latest_crop.clone())
+            } else if is_count_change_held {
+                video_processor_utils::debug_println(format_args!(
+                    "is_count_change_held: {} frames",
+                    self.frames_since_count_change
+                ));
+                while let Some(frame) = self.history.pop_front() {
+                    video_processor_utils::process_and_display_crop(
+                        &frame.image,
+                        prev_crop,
+                        viewer,
+                        args.headless,
+                        args.pillarbox,
+                        renderer,
+                    )?;
+                }
+                object_count = self.previous_object_count;
+                Some(prev_crop.clone())
             } else if is_same_class && is_latest_crop_similar {
                 video_processor_utils::debug_println(format_args!(
                     "is_same_class && is_latest_crop_similar"
@@ -83,11 +461,29 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
                             prev_crop,
                             viewer,
                             args.headless,
+                            args.pillarbox,
+                            renderer,
                         )?;
                     }
                 }
                 object_count = self.previous_object_count;
                 Some(prev_crop.clone())
+            } else if is_same_class && is_predicted_crop {
+                video_processor_utils::debug_println(format_args!(
+                    "is_same_class && is_predicted_crop"
+                ));
+                while let Some(frame) = self.history.pop_front() {
+                    video_processor_utils::process_and_display_crop(
+                        &frame.image,
+                        prev_crop,
+                        viewer,
+                        args.headless,
+                        args.pillarbox,
+                        renderer,
+                    )?;
+                }
+                object_count = current_object_count;
+                Some(latest_crop.clone())
             } else {
                 // Handle crop change without borrowing self mutably
                 let mut crop_result: Option<crop::CropResult> = None;
@@ -127,19 +523,49 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
                     ));
 
                     if is_change_crop_similar && is_change_object_count_similar {
-                        if self.history.len() == smooth_duration_frames {
+                        // Only commit once the two newest buffered frames agree on the new crop
+                        let is_recent_change_consistent =
+                            match (self.history.peek_back_n(0), self.history.peek_back_n(1)) {
+                                (Some(newest), Some(before_newest)) => crop::is_crop_similar(
+                                    &newest.crop,
+                                    &before_newest.crop,
+                                    img.width() as f32,
+                                    args.smooth_percentage,
+                                ),
+                                _ => true,
+                            };
+                        video_processor_utils::debug_println(format_args!(
+                            "is_recent_change_consistent: {:?}",
+                            is_recent_change_consistent
+                        ));
+
+                        // The adaptive duration can shrink below the current history length
+                        if self.history.len() >= smooth_duration_frames && is_recent_change_consistent {
+                            // Blend the first buffered frames from the previous crop to the new one
+                            let transition = crop::transition_crops(
+                                prev_crop,
+                                &change_crop,
+                                self.interpolated_transition_frames,
+                            );
+                            let mut frame_crops = transition.iter();
                             while let Some(frame) = self.history.pop_front() {
                                 video_processor_utils::process_and_display_crop(
                                     &frame.image,
-                                    &change_crop,
+                                    frame_crops.next().unwrap_or(&change_crop),
                                     viewer,
                                     args.headless,
+                                    args.pillarbox,
+                                    renderer,
                                 )?;
                             }
+                            if !crop::is_crop_class_same(change_object_count, self.previous_object_count) {
+                                transition_reason = TransitionReason::ClassChange;
+                            }
                             crop_result = Some(change_crop);
                         } else {
+                            // Keep this frame's own crop so the newest frames can be compared
                             self.history
-                                .add(change_crop.clone(), img.clone(), change_object_count);
+                                .add(latest_crop.clone(), img.clone(), change_object_count);
                         }
                     } else {
                         // Choose crop based on whether prev_crop is stacked or resized and change_crop isn't
@@ -152,14 +578,24 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
                             }
                             _ => prev_crop,
                         };
+                        // Ease the drained frames from the previous crop to the one being kept
+                        let drain = crop::interpolation_crops(
+                            prev_crop,
+                            crop_to_use,
+                            self.interpolation_frames,
+                        );
+                        let mut frame_crops = drain.iter();
                         while let Some(frame) = self.history.pop_front() {
                             video_processor_utils::process_and_display_crop(
                                 &frame.image,
-                                crop_to_use,
+                                frame_crops.next().unwrap_or(crop_to_use),
                                 viewer,
                                 args.headless,
+                                args.pillarbox,
+                                renderer,
                             )?;
                         }
+                        transition_reason = TransitionReason::HistoryFlush;
                         crop_result = Some(crop_to_use.clone());
                     }
                 }
@@ -171,19 +607,63 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
         };
 
         self.last_image = Some(img.clone());
+        if self.use_crop_prediction {
+            self.record_crop_trend(latest_crop);
+        }
         if let Some(crop_result) = crop_result {
-            self.previous_crop = Some(crop_result.clone());
+            let output_crop = self.apply_zoom_in(
+                &crop_result,
+                objects,
+                object_count,
+                img.width() as f32,
+                img.height() as f32,
+                args,
+            );
+            self.update_previous_crop(crop_result, timestamp_sec, transition_reason);
             self.previous_object_count = object_count;
             video_processor_utils::process_and_display_crop(
                 img,
-                &crop_result,
+                &output_crop,
                 viewer,
                 args.headless,
+                args.pillarbox,
+                renderer,
             )?;
         }
         Ok(())
     }
 
+    /// Returns the crop most recently applied to an output frame
+    fn last_applied_crop(&self) -> Option<&crop::CropResult> {
+        self.previous_crop.as_ref()
+    }
+
+    /// Restores the previous crop from a resumed run
+    fn restore_previous_crop(&mut self, crop: crop::CropResult) {
+        self.previous_crop = Some(crop);
+    }
+
+    /// Uses the source frame rate for transition and webhook timestamps
+    fn set_frame_rate(&mut self, frame_rate: f64) {
+        if frame_rate > 0.0 {
+            self.frame_rate = frame_rate;
+        }
+    }
+
+    /// Writes the crop change log as JSON
+    fn write_transition_log(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self.crop_change_log())
+            .context("Failed to serialize crop transitions")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write transition log: {}", path.display()))?;
+        println!(
+            "✓ {} crop transitions saved to: {}",
+            self.crop_change_log.len(),
+            path.display()
+        );
+        Ok(())
+    }
+
     /// Override debug info to include history-specific information
     fn print_debug_info(
         &self,
@@ -200,6 +680,25 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
             "history length: {:?}",
             self.history.len()
         ));
+        if video_processor_utils::is_debug_enabled() {
+            let mut class_distribution = std::collections::BTreeMap::new();
+            for frame in &self.history {
+                *class_distribution
+                    .entry(crop::get_crop_class(frame.object_count))
+                    .or_insert(0usize) += 1;
+            }
+            video_processor_utils::debug_println(format_args!(
+                "history crop class distribution: {:?}",
+                class_distribution
+            ));
+            video_processor_utils::debug_println(format_args!(
+                "history frames with {} objects: {}",
+                objects.len(),
+                self.history
+                    .filter_iter(|frame| frame.object_count == objects.len())
+                    .count()
+            ));
+        }
         video_processor_utils::debug_println(format_args!(
             "current_object_count: {}, previous_object_count: {}",
             objects.len(),
@@ -208,14 +707,24 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
     }
 
     /// Finalizes processing by handling any remaining frames in history
-    fn finalize_processing(&mut self, args: &Args, viewer: &mut Viewer) -> Result<()> {
+    fn finalize_processing(
+        &mut self,
+        args: &Args,
+        viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
+    ) -> Result<()> {
+        if args.replay_last_n_frames > 0 {
+            self.replay_history(args)?;
+        }
+
         // Process any remaining frames in the history
         if !self.history.is_empty() {
             video_processor_utils::debug_println(format_args!(
                 "Finalizing processing: {} frames remaining in history",
                 self.history.len()
             ));
-            
+            self.log_history_flush();
+
             // Use the previous crop for all remaining frames
             if let Some(prev_crop) = &self.previous_crop {
                 while let Some(frame) = self.history.pop_front() {
@@ -224,6 +733,8 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
                         prev_crop,
                         viewer,
                         args.headless,
+                        args.pillarbox,
+                        renderer,
                     )?;
                 }
             }
@@ -231,3 +742,186 @@ impl VideoProcessor for HistorySmoothingVideoProcessor {
         Ok(())
     }
 }
+
+/// Builds a crop change callback that POSTs each change as JSON to a webhook URL
+///
+/// Requests are sent on the tokio runtime in the background so they never stall processing.
+/// Failed requests are reported and otherwise ignored.
+///
+/// # Arguments
+/// * `url` - The webhook URL
+pub fn webhook_crop_change_callback(
+    url: String,
+) -> impl Fn(&crop::CropResult, &crop::CropResult, f64) + Send + 'static {
+    let client = reqwest::Client::new();
+    let runtime = tokio::runtime::Handle::try_current().ok();
+    move |old_crop, new_crop, timestamp_sec| {
+        let Some(runtime) = &runtime else {
+            println!("Warning: no async runtime available, skipping crop change webhook");
+            return;
+        };
+        let payload = serde_json::json!({
+            "old_crop": old_crop,
+            "new_crop": new_crop,
+            "timestamp_sec": timestamp_sec,
+        });
+        let request = client.post(&url).json(&payload);
+        runtime.spawn(async move {
+            let result = request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                println!("Warning: crop change webhook failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argh::FromArgs;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_on_crop_change_fires_once_per_distinct_change() {
+        let args = Args::from_args(&["land2port"], &[]).unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let mut processor = HistorySmoothingVideoProcessor::new(&args).with_on_crop_change(
+            move |old_crop, new_crop, _timestamp_sec| {
+                assert_ne!(old_crop, new_crop);
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+
+        let left = crop::CropResult::Single(crop::CropArea::new(0.0, 0.0, 607.5, 1080.0));
+        let right = crop::CropResult::Single(crop::CropArea::new(1312.5, 0.0, 607.5, 1080.0));
+        let resize = crop::CropResult::Resize(crop::CropArea::new(0.0, 0.0, 1920.0, 1080.0));
+
+        // The first crop has nothing to change from, and repeats are not changes
+        let reason = TransitionReason::PositionChange;
+        processor.update_previous_crop(left.clone(), 0.0, reason);
+        processor.update_previous_crop(left, 0.1, reason);
+        processor.update_previous_crop(right.clone(), 0.2, reason);
+        processor.update_previous_crop(right, 0.3, reason);
+        processor.update_previous_crop(resize, 0.4, reason);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_crop_prediction_follows_pan() {
+        let args = Args::from_args(&["land2port"], &["--use-crop-prediction"]).unwrap();
+        let mut processor = HistorySmoothingVideoProcessor::new(&args);
+
+        // Five frames of a presenter walking right, 20px per frame
+        let pan: Vec<crop::CropResult> = (0..5)
+            .map(|i| {
+                crop::CropResult::Single(crop::CropArea::new(
+                    100.0 + 20.0 * i as f32,
+                    0.0,
+                    607.5,
+                    1080.0,
+                ))
+            })
+            .collect();
+
+        for (i, frame_crop) in pan.iter().enumerate() {
+            let predicted = processor.predicted_crop(1920.0, 1080.0);
+            if i <= CROP_TREND_FRAMES {
+                // No trend has been confirmed yet
+                assert!(predicted.is_none());
+            } else {
+                assert_eq!(crop::CropResult::Single(predicted.unwrap()), *frame_crop);
+            }
+            processor.record_crop_trend(frame_crop);
+        }
+
+        // Any other crop type restarts the trend
+        processor.record_crop_trend(&crop::CropResult::Resize(crop::CropArea::new(
+            0.0, 0.0, 1920.0, 1080.0,
+        )));
+        assert!(processor.predicted_crop(1920.0, 1080.0).is_none());
+    }
+
+    #[test]
+    fn test_object_count_debounce_holds_brief_changes() {
+        let args =
+            Args::from_args(&["land2port"], &["--object-count-debounce-frames", "10"]).unwrap();
+        let mut processor = HistorySmoothingVideoProcessor::new(&args);
+        let debounce = processor.object_count_change_debounce_frames;
+        let mut is_held = |is_count_changed: bool| {
+            HistorySmoothingVideoProcessor::is_count_change_debounced(
+                &mut processor.frames_since_count_change,
+                debounce,
+                is_count_changed,
+            )
+        };
+
+        // A head that disappears for 5 frames never changes the crop class
+        assert!(!is_held(false));
+        for _ in 0..5 {
+            assert!(is_held(true));
+        }
+        assert!(!is_held(false));
+
+        // A change that lasts longer than the grace period is accepted on the 11th frame
+        for _ in 0..10 {
+            assert!(is_held(true));
+        }
+        assert!(!is_held(true));
+    }
+
+    #[test]
+    fn test_crop_change_log_records_transitions() {
+        let args = Args::from_args(&["land2port"], &[]).unwrap();
+        let mut processor = HistorySmoothingVideoProcessor::new(&args);
+
+        let left = crop::CropResult::Single(crop::CropArea::new(0.0, 0.0, 607.5, 1080.0));
+        let right = crop::CropResult::Single(crop::CropArea::new(1312.5, 0.0, 607.5, 1080.0));
+        let stacked = crop::CropResult::Stacked(
+            crop::CropArea::new(0.0, 0.0, 960.0, 540.0),
+            crop::CropArea::new(960.0, 0.0, 960.0, 540.0),
+        );
+
+        // Five frames: start, hold, move, a cut to a two-head crop, hold
+        let frames = [
+            (left.clone(), TransitionReason::PositionChange),
+            (left.clone(), TransitionReason::PositionChange),
+            (right.clone(), TransitionReason::PositionChange),
+            (stacked.clone(), TransitionReason::Cut),
+            (stacked.clone(), TransitionReason::PositionChange),
+        ];
+        processor.set_frame_rate(25.0);
+        for (crop_result, reason) in frames {
+            processor.frame_count += 1;
+            let timestamp_sec = (processor.frame_count - 1) as f64 / processor.frame_rate;
+            processor.update_previous_crop(crop_result, timestamp_sec, reason);
+        }
+
+        // Frames still buffered at the end of the video are flushed with the previous crop
+        processor
+            .history
+            .add(left.clone(), usls::Image::from(::image::RgbImage::new(4, 4)), 1);
+        processor.log_history_flush();
+
+        let log = processor.crop_change_log();
+        let reasons: Vec<TransitionReason> = log.iter().map(|event| event.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                TransitionReason::Initialization,
+                TransitionReason::PositionChange,
+                TransitionReason::Cut,
+                TransitionReason::HistoryFlush,
+            ]
+        );
+        assert_eq!(log[1].frame, 2);
+        assert_eq!(log[1].timestamp_sec, 0.08);
+        assert_eq!((&log[1].old_crop, &log[1].new_crop), (&left, &right));
+        assert_eq!((&log[2].old_crop, &log[2].new_crop), (&right, &stacked));
+    }
+}
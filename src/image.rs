@@ -1,14 +1,558 @@
-use crate::crop::CropResult;
+use crate::crop::{CropArea, CropResult};
 use crate::video_processor_utils;
 use anyhow::Result;
-use image::{RgbImage, imageops::resize};
+use image::{GrayImage, RgbImage, RgbaImage, imageops::resize};
+use imageproc::corners::corners_fast9;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::path::Path;
 use usls::Image;
 
+/// Number of histogram bins per channel used for histogram cut detection
+pub const DEFAULT_HISTOGRAM_BINS: usize = 256;
+
+/// Method used to compare consecutive frames for cut detection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CutDetectionMethod {
+    /// Pixel-level `image_compare::rgb_hybrid_compare`
+    RgbHybrid,
+    /// Cosine similarity of per-channel color histograms (much faster on large frames)
+    ColorHistogram,
+    /// Hamming similarity of 64-bit perceptual hashes (robust to compression artifacts)
+    PHash,
+}
+
+impl std::str::FromStr for CutDetectionMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rgb" => Ok(CutDetectionMethod::RgbHybrid),
+            "histogram" => Ok(CutDetectionMethod::ColorHistogram),
+            "phash" => Ok(CutDetectionMethod::PHash),
+            _ => Err(format!(
+                "unsupported cut detection method '{}', expected rgb, histogram or phash",
+                s
+            )),
+        }
+    }
+}
+
+/// Formats the method as `--cut-detection-method` expects it
+impl std::fmt::Display for CutDetectionMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CutDetectionMethod::RgbHybrid => write!(f, "rgb"),
+            CutDetectionMethod::ColorHistogram => write!(f, "histogram"),
+            CutDetectionMethod::PHash => write!(f, "phash"),
+        }
+    }
+}
+
+/// Filter used to scale crops to the output size
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResizeFilter {
+    /// Sharpest result, but the slowest
+    #[default]
+    Lanczos3,
+    /// Bicubic, close to Lanczos3 quality at a fraction of the cost
+    CatmullRom,
+    /// Bilinear
+    Triangle,
+    /// Nearest neighbor, fastest but blocky
+    Nearest,
+}
+
+impl ResizeFilter {
+    /// Returns the matching `image` crate filter
+    pub fn filter_type(self) -> image::imageops::FilterType {
+        match self {
+            ResizeFilter::Lanczos3 => image::imageops::FilterType::Lanczos3,
+            ResizeFilter::CatmullRom => image::imageops::FilterType::CatmullRom,
+            ResizeFilter::Triangle => image::imageops::FilterType::Triangle,
+            ResizeFilter::Nearest => image::imageops::FilterType::Nearest,
+        }
+    }
+}
+
+impl std::str::FromStr for ResizeFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lanczos3" => Ok(ResizeFilter::Lanczos3),
+            "catmullrom" => Ok(ResizeFilter::CatmullRom),
+            "triangle" => Ok(ResizeFilter::Triangle),
+            "nearest" => Ok(ResizeFilter::Nearest),
+            _ => Err(format!(
+                "unsupported resize filter '{}', expected lanczos3, catmullrom, triangle or nearest",
+                s
+            )),
+        }
+    }
+}
+
+/// Formats the filter as `--resize-filter` expects it
+impl std::fmt::Display for ResizeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResizeFilter::Lanczos3 => write!(f, "lanczos3"),
+            ResizeFilter::CatmullRom => write!(f, "catmullrom"),
+            ResizeFilter::Triangle => write!(f, "triangle"),
+            ResizeFilter::Nearest => write!(f, "nearest"),
+        }
+    }
+}
+
+/// Gaussian radius used by `--sharpen-amount` when `--sharpen-radius` is not given
+pub const DEFAULT_SHARPEN_RADIUS: f32 = 1.0;
+
+/// Unsharp-mask settings applied to crops after they are scaled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sharpen {
+    /// Strength of the sharpening, 0.0 disables it
+    pub amount: f32,
+    /// Sigma of the Gaussian blur subtracted from the crop
+    pub radius: f32,
+}
+
+impl Sharpen {
+    /// Creates sharpening settings from `--sharpen-amount` and `--sharpen-radius`
+    pub fn new(amount: f32, radius: f32) -> Self {
+        Self { amount, radius }
+    }
+
+    /// Returns true if the settings change the image
+    pub fn is_enabled(&self) -> bool {
+        self.amount > 0.0 && self.radius > 0.0
+    }
+
+    /// Sharpens `img`, returning it untouched when sharpening is disabled
+    fn apply(self, img: RgbImage) -> RgbImage {
+        if self.is_enabled() {
+            sharpen_image(&img, self.amount, self.radius)
+        } else {
+            img
+        }
+    }
+}
+
+/// Defaults to no sharpening
+impl Default for Sharpen {
+    fn default() -> Self {
+        Self::new(0.0, DEFAULT_SHARPEN_RADIUS)
+    }
+}
+
+/// Sharpens an image with an unsharp mask
+///
+/// Every channel becomes `original + amount * (original - blurred)`, where `blurred` is the
+/// image blurred with a Gaussian of sigma `radius`. Upscaled crops lose fine detail, and this
+/// brings back some of the edge contrast.
+///
+/// # Arguments
+/// * `img` - The image to sharpen
+/// * `amount` - Strength of the sharpening, 0.0 returns an identical image
+/// * `radius` - Sigma of the Gaussian blur
+pub fn sharpen_image(img: &RgbImage, amount: f32, radius: f32) -> RgbImage {
+    if amount <= 0.0 || radius <= 0.0 {
+        return img.clone();
+    }
+    let blurred = imageproc::filter::gaussian_blur_f32(img, radius);
+    let mut sharpened = img.clone();
+    for (pixel, blurred_pixel) in sharpened.pixels_mut().zip(blurred.pixels()) {
+        for (channel, blurred_channel) in pixel.0.iter_mut().zip(blurred_pixel.0) {
+            let original = *channel as f32;
+            let value = original + amount * (original - blurred_channel as f32);
+            *channel = value.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    sharpened
+}
+
+/// Blur radius used when `--pillarbox blur` is given without a radius
+pub const DEFAULT_PILLARBOX_BLUR_RADIUS: f32 = 20.0;
+
+/// How the empty space around a single or resized crop is filled in the portrait output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PillarboxMode {
+    /// Leave the padding black
+    Black,
+    /// Fill the padding with a blurred, scaled copy of the source frame
+    BlurredSource { radius: f32 },
+    /// Reflect the crop at its top and bottom edges into the padding
+    Mirror,
+    /// Fill the padding with a solid RGB color
+    SolidColor(u8, u8, u8),
+}
+
+impl std::str::FromStr for PillarboxMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        let (mode, value) = match lower.split_once(':') {
+            Some((mode, value)) => (mode, Some(value)),
+            None => (lower.as_str(), None),
+        };
+
+        match (mode, value) {
+            ("black", None) => Ok(PillarboxMode::Black),
+            ("mirror", None) => Ok(PillarboxMode::Mirror),
+            ("blur", None) => Ok(PillarboxMode::BlurredSource {
+                radius: DEFAULT_PILLARBOX_BLUR_RADIUS,
+            }),
+            ("blur", Some(radius)) => match radius.parse::<f32>() {
+                Ok(radius) if radius > 0.0 => Ok(PillarboxMode::BlurredSource { radius }),
+                _ => Err(format!("invalid blur radius '{}'", radius)),
+            },
+            ("solid", Some(color)) => {
+                let hex = color.trim_start_matches('#');
+                let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or(""), 16);
+                match (hex.len(), channel(0), channel(2), channel(4)) {
+                    (6, Ok(r), Ok(g), Ok(b)) => Ok(PillarboxMode::SolidColor(r, g, b)),
+                    _ => Err(format!("invalid solid color '{}', expected RRGGBB", color)),
+                }
+            }
+            _ => Err(format!(
+                "unsupported pillarbox mode '{}', expected black, blur[:radius], mirror or solid:RRGGBB",
+                s
+            )),
+        }
+    }
+}
+
+/// Formats the mode as `--pillarbox` expects it, so it parses back to the same mode
+impl std::fmt::Display for PillarboxMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PillarboxMode::Black => write!(f, "black"),
+            PillarboxMode::BlurredSource { radius } => write!(f, "blur:{}", radius),
+            PillarboxMode::Mirror => write!(f, "mirror"),
+            PillarboxMode::SolidColor(r, g, b) => write!(f, "solid:{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+/// Builds the background of a padded portrait frame before the crop is overlaid
+///
+/// # Arguments
+/// * `source` - The full source frame
+/// * `width` - The output width
+/// * `height` - The output height
+/// * `mode` - How the padding should be filled
+fn create_pillarbox_background(
+    source: &RgbImage,
+    width: u32,
+    height: u32,
+    mode: PillarboxMode,
+) -> RgbImage {
+    match mode {
+        PillarboxMode::Black | PillarboxMode::Mirror => RgbImage::new(width, height),
+        PillarboxMode::SolidColor(r, g, b) => {
+            RgbImage::from_pixel(width, height, image::Rgb([r, g, b]))
+        }
+        PillarboxMode::BlurredSource { radius } => {
+            // Blur a downscaled copy and scale it back up, which looks the same as blurring at
+            // full resolution but is much cheaper
+            const DOWNSCALE: u32 = 8;
+            let small_width = (width / DOWNSCALE).max(1);
+            let small_height = (height / DOWNSCALE).max(1);
+
+            // Scale the source to cover the output, then center crop it
+            let scale = (small_width as f32 / source.width() as f32)
+                .max(small_height as f32 / source.height() as f32);
+            let cover_width = ((source.width() as f32 * scale).ceil() as u32).max(small_width);
+            let cover_height = ((source.height() as f32 * scale).ceil() as u32).max(small_height);
+            let mut cover = resize(
+                source,
+                cover_width,
+                cover_height,
+                image::imageops::FilterType::Triangle,
+            );
+            let small = image::imageops::crop(
+                &mut cover,
+                (cover_width - small_width) / 2,
+                (cover_height - small_height) / 2,
+                small_width,
+                small_height,
+            )
+            .to_image();
+
+            let blurred = image::imageops::blur(&small, radius / DOWNSCALE as f32);
+            resize(&blurred, width, height, image::imageops::FilterType::Triangle)
+        }
+    }
+}
+
+/// Reflects the overlaid crop at its top and bottom edges into the padding
+///
+/// # Arguments
+/// * `result` - The output frame with the crop already overlaid
+/// * `y_offset` - The row where the crop starts
+/// * `content_height` - The height of the crop in the output frame
+fn mirror_padding(result: &mut RgbImage, y_offset: u32, content_height: u32) {
+    if content_height == 0 {
+        return;
+    }
+    let content_end = (y_offset + content_height).min(result.height());
+
+    for y in 0..y_offset {
+        let source_y = (y_offset + (y_offset - 1 - y) % content_height).min(content_end - 1);
+        for x in 0..result.width() {
+            let pixel = *result.get_pixel(x, source_y);
+            result.put_pixel(x, y, pixel);
+        }
+    }
+    for y in content_end..result.height() {
+        let source_y = content_end - 1 - (y - content_end) % content_height;
+        for x in 0..result.width() {
+            let pixel = *result.get_pixel(x, source_y);
+            result.put_pixel(x, y, pixel);
+        }
+    }
+}
+
+/// Side length of the grayscale thumbnail the perceptual hash is computed from
+const PHASH_SIZE: usize = 32;
+
+/// Side length of the block of low-frequency DCT coefficients kept for the perceptual hash
+const PHASH_BLOCK: usize = 8;
+
+/// Computes the 64-bit perceptual hash (pHash) of an image
+///
+/// The image is reduced to a 32x32 grayscale thumbnail and transformed with a DCT. Each bit of
+/// the hash tells whether one of the 8x8 lowest-frequency coefficients is above the median of
+/// the non-DC coefficients, so small pixel-level differences such as compression artifacts
+/// rarely change the hash.
+pub fn compute_phash(img: &RgbImage) -> u64 {
+    let gray = image::DynamicImage::ImageRgb8(img.clone()).to_luma8();
+    let thumbnail = resize(
+        &gray,
+        PHASH_SIZE as u32,
+        PHASH_SIZE as u32,
+        image::imageops::FilterType::Triangle,
+    );
+
+    let mut cosines = [[0.0f64; PHASH_SIZE]; PHASH_BLOCK];
+    for (u, row) in cosines.iter_mut().enumerate() {
+        for (x, cosine) in row.iter_mut().enumerate() {
+            *cosine = ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI
+                / (2 * PHASH_SIZE) as f64)
+                .cos();
+        }
+    }
+
+    let mut coefficients = [0.0f64; PHASH_BLOCK * PHASH_BLOCK];
+    for v in 0..PHASH_BLOCK {
+        for u in 0..PHASH_BLOCK {
+            let mut sum = 0.0;
+            for (x, y, pixel) in thumbnail.enumerate_pixels() {
+                sum += pixel[0] as f64 * cosines[u][x as usize] * cosines[v][y as usize];
+            }
+            coefficients[v * PHASH_BLOCK + u] = sum;
+        }
+    }
+
+    // The DC coefficient only reflects overall brightness, so it is left out of the median
+    let mut ac: Vec<f64> = coefficients[1..].to_vec();
+    ac.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = (ac[ac.len() / 2 - 1] + ac[ac.len() / 2]) / 2.0;
+
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, coefficient)| **coefficient > median)
+        .fold(0u64, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// Counts the bits that differ between two hashes
+fn hamming_distance(h1: u64, h2: u64) -> u32 {
+    (h1 ^ h2).count_ones()
+}
+
+/// Computes the similarity of two perceptual hashes, from 0.0 (every bit differs) to 1.0 (equal)
+pub fn phash_similarity(h1: u64, h2: u64) -> f64 {
+    1.0 - (hamming_distance(h1, h2) as f64 / 64.0)
+}
+
+/// Computes the similarity of two images from their per-channel color histograms
+///
+/// # Arguments
+/// * `img1` - The first image to compare
+/// * `img2` - The second image to compare
+/// * `bins` - Number of histogram bins per channel (256 gives one bin per intensity)
+///
+/// # Returns
+/// The cosine similarity of the concatenated R, G and B histograms, from 0.0 to 1.0
+pub fn color_histogram_similarity(img1: &RgbImage, img2: &RgbImage, bins: usize) -> f64 {
+    let bins = bins.clamp(1, 256);
+
+    let histogram = |img: &RgbImage| -> Vec<f64> {
+        let mut hist = vec![0.0; bins * 3];
+        for pixel in img.pixels() {
+            for channel in 0..3 {
+                let bin = pixel[channel] as usize * bins / 256;
+                hist[channel * bins + bin] += 1.0;
+            }
+        }
+        hist
+    };
+
+    let hist1 = histogram(img1);
+    let hist2 = histogram(img2);
+
+    let dot: f64 = hist1.iter().zip(&hist2).map(|(a, b)| a * b).sum();
+    let norm1 = hist1.iter().map(|a| a * a).sum::<f64>().sqrt();
+    let norm2 = hist2.iter().map(|b| b * b).sum::<f64>().sqrt();
+
+    if norm1 == 0.0 || norm2 == 0.0 {
+        return 0.0;
+    }
+    dot / (norm1 * norm2)
+}
+
+/// Width frames are downscaled to before estimating background motion
+const MOTION_ANALYSIS_WIDTH: u32 = 320;
+
+/// FAST corner intensity threshold used for motion estimation
+const MOTION_CORNER_THRESHOLD: u8 = 20;
+
+/// Maximum number of corners tracked from the previous frame (strongest first)
+const MAX_MOTION_CORNERS: usize = 200;
+
+/// Maximum corner displacement between frames, in downscaled pixels
+const MOTION_SEARCH_RADIUS: i64 = 12;
+
+/// Half size of the square patch compared when matching corners
+const MOTION_PATCH_RADIUS: i64 = 3;
+
+/// Fewest corner matches needed to report a motion estimate
+const MIN_MOTION_MATCHES: usize = 5;
+
+/// Converts an image to grayscale at the motion analysis width
+fn motion_analysis_frame(image: &Image, width: u32) -> GrayImage {
+    let gray = image::DynamicImage::ImageRgb8(image.to_rgb8()).to_luma8();
+    let height = (width as f32 * gray.height() as f32 / gray.width().max(1) as f32) as u32;
+    resize(&gray, width, height.max(1), image::imageops::FilterType::Triangle)
+}
+
+/// Sum of absolute differences between the patches around two points
+///
+/// Returns `None` if either patch does not fit inside its image.
+fn patch_difference(
+    prev: &GrayImage,
+    prev_point: (i64, i64),
+    curr: &GrayImage,
+    curr_point: (i64, i64),
+) -> Option<u32> {
+    let fits = |image: &GrayImage, (x, y): (i64, i64)| {
+        x >= MOTION_PATCH_RADIUS
+            && y >= MOTION_PATCH_RADIUS
+            && x + MOTION_PATCH_RADIUS < image.width() as i64
+            && y + MOTION_PATCH_RADIUS < image.height() as i64
+    };
+    if !fits(prev, prev_point) || !fits(curr, curr_point) {
+        return None;
+    }
+
+    let mut difference = 0u32;
+    for dy in -MOTION_PATCH_RADIUS..=MOTION_PATCH_RADIUS {
+        for dx in -MOTION_PATCH_RADIUS..=MOTION_PATCH_RADIUS {
+            let a = prev.get_pixel((prev_point.0 + dx) as u32, (prev_point.1 + dy) as u32)[0];
+            let b = curr.get_pixel((curr_point.0 + dx) as u32, (curr_point.1 + dy) as u32)[0];
+            difference += a.abs_diff(b) as u32;
+        }
+    }
+    Some(difference)
+}
+
+/// Median of a non-empty list of values
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    values[values.len() / 2]
+}
+
+/// Estimates how far the background moved between two frames using sparse corner matching
+///
+/// FAST corners are detected in downscaled grayscale copies of both frames. Each of the
+/// strongest corners in `prev` is matched to the nearby corner in `curr` with the most similar
+/// surrounding patch, and the median displacement is taken as the background motion, so a
+/// moving subject covering a minority of the corners does not skew the estimate.
+///
+/// # Arguments
+/// * `prev` - The previous frame
+/// * `curr` - The current frame (same size as `prev`)
+///
+/// # Returns
+/// The apparent `(dx, dy)` displacement of the background in full-resolution pixels, or
+/// `(0.0, 0.0)` when too few corners could be matched. A camera panning right moves the
+/// background left, giving a negative `dx`.
+pub fn estimate_background_motion(prev: &Image, curr: &Image) -> Result<(f32, f32)> {
+    if prev.width() != curr.width() || prev.height() != curr.height() {
+        anyhow::bail!(
+            "Frame sizes differ: {}x{} vs {}x{}",
+            prev.width(),
+            prev.height(),
+            curr.width(),
+            curr.height()
+        );
+    }
+
+    let analysis_width = MOTION_ANALYSIS_WIDTH.min(prev.width()).max(1);
+    let scale = prev.width() as f32 / analysis_width as f32;
+    let prev_gray = motion_analysis_frame(prev, analysis_width);
+    let curr_gray = motion_analysis_frame(curr, analysis_width);
+
+    let strongest = |image: &GrayImage| {
+        let mut corners = corners_fast9(image, MOTION_CORNER_THRESHOLD);
+        corners.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        corners
+    };
+    let prev_corners = strongest(&prev_gray);
+    let curr_corners = strongest(&curr_gray);
+
+    let mut dxs = Vec::new();
+    let mut dys = Vec::new();
+    for corner in prev_corners.iter().take(MAX_MOTION_CORNERS) {
+        let prev_point = (corner.x as i64, corner.y as i64);
+        let best_match = curr_corners
+            .iter()
+            .map(|candidate| (candidate.x as i64, candidate.y as i64))
+            .filter(|(x, y)| {
+                (x - prev_point.0).abs() <= MOTION_SEARCH_RADIUS
+                    && (y - prev_point.1).abs() <= MOTION_SEARCH_RADIUS
+            })
+            .filter_map(|curr_point| {
+                patch_difference(&prev_gray, prev_point, &curr_gray, curr_point)
+                    .map(|difference| (difference, curr_point))
+            })
+            .min_by_key(|(difference, _)| *difference);
+
+        if let Some((_, (x, y))) = best_match {
+            dxs.push((x - prev_point.0) as f32);
+            dys.push((y - prev_point.1) as f32);
+        }
+    }
+
+    if dxs.len() < MIN_MOTION_MATCHES {
+        return Ok((0.0, 0.0));
+    }
+    Ok((median(&mut dxs) * scale, median(&mut dys) * scale))
+}
+
 /// Stateful cut detector that maintains previous similarity scores
 pub struct CutDetector {
     pub previous_score: Option<f64>,
     similarity_threshold: f64,
     previous_similarity_threshold: f64,
+    method: CutDetectionMethod,
+    warmup_frames: usize,
+    frames_seen: usize,
 }
 
 impl CutDetector {
@@ -17,14 +561,31 @@ impl CutDetector {
     /// # Arguments
     /// * `similarity_threshold` - The threshold below which a cut is detected (default: 0.15)
     /// * `previous_similarity_threshold` - The threshold above which the previous score must be to consider a cut (default: 0.7)
-    pub fn new(similarity_threshold: f64, previous_similarity_threshold: f64) -> Self {
+    /// * `method` - How frames are compared; histogram scores run higher than rgb scores, so thresholds may need tuning
+    pub fn new(
+        similarity_threshold: f64,
+        previous_similarity_threshold: f64,
+        method: CutDetectionMethod,
+    ) -> Self {
         Self {
             previous_score: None,
             similarity_threshold,
             previous_similarity_threshold,
+            method,
+            warmup_frames: 0,
+            frames_seen: 0,
         }
     }
 
+    /// Suppresses cut detection for the first `warmup_frames` comparisons
+    ///
+    /// The very first comparison still reports a cut so processors initialize their state.
+    /// Similarity scores are tracked during warm-up so detection resumes with a valid history.
+    pub fn with_warmup_frames(mut self, warmup_frames: usize) -> Self {
+        self.warmup_frames = warmup_frames;
+        self
+    }
+
     /// Determines if there is a cut between two images by comparing their similarity
     /// with the previous score to avoid false positives
     ///
@@ -40,9 +601,14 @@ impl CutDetector {
         let rgb1 = image1.to_rgb8();
         let rgb2 = image2.to_rgb8();
         
-        // Use rgb_image_compare to get the similarity score
-        let similarity = image_compare::rgb_hybrid_compare(&rgb1, &rgb2)?;
-        let current_score = similarity.score;
+        // Score the similarity with the configured method
+        let current_score = match self.method {
+            CutDetectionMethod::RgbHybrid => image_compare::rgb_hybrid_compare(&rgb1, &rgb2)?.score,
+            CutDetectionMethod::ColorHistogram => {
+                color_histogram_similarity(&rgb1, &rgb2, DEFAULT_HISTOGRAM_BINS)
+            }
+            CutDetectionMethod::PHash => phash_similarity(compute_phash(&rgb1), compute_phash(&rgb2)),
+        };
 
         video_processor_utils::debug_println(format_args!("similarity: {:?}", current_score));
         
@@ -60,27 +626,211 @@ impl CutDetector {
         
         // Update previous score for next comparison
         self.previous_score = Some(current_score);
+
+        // Detections are unreliable while the model warms up
+        let frame = self.frames_seen;
+        self.frames_seen += 1;
+        if frame < self.warmup_frames {
+            return Ok(frame == 0);
+        }
         
         Ok(is_cut)
     }
 }
 
 
+/// A frame dimension or offset rounded down to an even number, as required by H.264 encoding
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvenDimension(u32);
+
+impl EvenDimension {
+    /// Rounds `v` down to the nearest even number
+    pub fn new(v: u32) -> Self {
+        Self(v & !1)
+    }
+
+    /// Returns the even value
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// Number of resized crops kept by `ImageCache` when `--image-cache-size` is not given
+pub const DEFAULT_IMAGE_CACHE_SIZE: usize = 8;
+
+/// Identifies a resized crop in an `ImageCache`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CropCacheKey {
+    pub crop_hash: u64,
+    pub target_width: u32,
+}
+
+/// A cached crop: the source pixels it was resized from and the resized result
+struct CachedCrop {
+    source: RgbImage,
+    scaled: RgbImage,
+}
+
+/// Hashes the position and size of a crop area with FNV-1a
+///
+/// Crops only hash equal when all four fields are bit-for-bit identical.
+pub fn compute_crop_hash(crop: &CropArea) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for field in [crop.x, crop.y, crop.width, crop.height] {
+        for byte in field.to_bits().to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Caches the last resized crops so a stable crop is not downscaled again every frame
+///
+/// An entry is only reused when the cropped source pixels are unchanged, so moving content
+/// inside a stable crop is always resized again.
+pub struct ImageCache {
+    capacity: usize,
+    entries: LruCache<CropCacheKey, CachedCrop>,
+    hits: u64,
+}
+
+impl ImageCache {
+    /// Creates a cache holding up to `capacity` resized crops (0 disables caching)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: LruCache::new(NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN)),
+            hits: 0,
+        }
+    }
+
+    /// Number of resizes that were served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Resizes `cropped` to `width`x`height` with `filter`, reusing a cached result if possible
+    fn resize(
+        &mut self,
+        crop: &CropArea,
+        cropped: RgbImage,
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+    ) -> RgbImage {
+        if self.capacity == 0 {
+            return resize(&cropped, width, height, filter.filter_type());
+        }
+
+        let key = CropCacheKey {
+            crop_hash: compute_crop_hash(crop),
+            target_width: width,
+        };
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.scaled.height() == height && entry.source == cropped {
+                self.hits += 1;
+                return entry.scaled.clone();
+            }
+        }
+
+        let scaled = resize(&cropped, width, height, filter.filter_type());
+        self.entries.put(
+            key,
+            CachedCrop {
+                source: cropped,
+                scaled: scaled.clone(),
+            },
+        );
+        scaled
+    }
+}
+
+/// Layout of a stacked crop that shows a scoreboard above the ball action
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreboardBallLayout {
+    /// The scoreboard fills the top quarter of the output and the ball the bottom three quarters
+    QuarterScoreboard,
+}
+
+impl ScoreboardBallLayout {
+    /// Width/height ratio of the scoreboard crop, a quarter of a 9:16 frame
+    pub const SCOREBOARD_ASPECT: f32 = 9.0 / 4.0;
+    /// Width/height ratio of the ball crop, three quarters of a 9:16 frame
+    pub const BALL_ASPECT: f32 = 9.0 / 12.0;
+
+    /// Recognizes a stacked scoreboard and ball crop from the aspect ratios of its two crops
+    pub fn from_crops(crop1: &CropArea, crop2: &CropArea) -> Option<Self> {
+        let is_scoreboard = (crop1.width / crop1.height - Self::SCOREBOARD_ASPECT).abs() < 0.1;
+        let is_ball = (crop2.width / crop2.height - Self::BALL_ASPECT).abs() < 0.05;
+        (is_scoreboard && is_ball).then_some(ScoreboardBallLayout::QuarterScoreboard)
+    }
+
+    /// Splits the output height into the scoreboard (top) and ball (bottom) heights
+    pub fn split_heights(self, target_height: u32) -> (u32, u32) {
+        match self {
+            ScoreboardBallLayout::QuarterScoreboard => {
+                let top_height = target_height / 4;
+                (top_height, target_height - top_height)
+            }
+        }
+    }
+}
+
+/// Resizes a crop with `filter`, through the cache when one is given, then sharpens it
+fn resize_crop(
+    crop: &CropArea,
+    cropped: RgbImage,
+    width: u32,
+    height: u32,
+    filter: ResizeFilter,
+    sharpen: Sharpen,
+    cache: Option<&mut ImageCache>,
+) -> RgbImage {
+    let resized = match cache {
+        Some(cache) => cache.resize(crop, cropped, width, height, filter),
+        None => resize(&cropped, width, height, filter.filter_type()),
+    };
+    sharpen.apply(resized)
+}
+
+/// How `create_cropped_image` scales and finishes the crops
+#[derive(Clone, Copy, Default)]
+pub struct RenderOptions<'a> {
+    /// The filter used to scale the crops
+    pub filter: ResizeFilter,
+    /// The sharpening applied to the crops after they are scaled
+    pub sharpen: Sharpen,
+    /// Blended onto the finished frame when given
+    pub watermark: Option<&'a Watermark>,
+}
+
 /// Creates a new image by cropping the input image according to the crop result
 ///
 /// # Arguments
 /// * `image` - The input image to crop
 /// * `crop_result` - The crop result specifying how to crop the image
 /// * `target_width` - The desired width of the output image
+/// * `pillarbox` - How the padding around single and resized crops is filled
+/// * `options` - How the crops are scaled and finished
+/// * `cache` - Reuses resized crops from previous frames when the crop and its pixels are unchanged
 ///
 /// # Returns
 /// A new image containing either a single 9:16 crop or two crops stacked vertically:
 /// - For three heads: top crop (9:6) + bottom crop (9:10) = 9:16 final image
+/// - For a scoreboard and ball: top crop (9:4) + bottom crop (9:12) = 9:16 final image
 /// - For other cases: two equal crops stacked to create 9:16 final image
 pub fn create_cropped_image(
     image: &Image,
     crop_result: &CropResult,
     target_width: u32,
+    pillarbox: PillarboxMode,
+    options: RenderOptions,
+    mut cache: Option<&mut ImageCache>,
 ) -> Result<Image> {
     // Get the underlying RgbImage
     let mut rgb_image = image.to_rgb8();
@@ -89,35 +839,48 @@ pub fn create_cropped_image(
         CropResult::Single(crop) => {
             // For a single crop, crop the image to the specified area
             // Ensure even dimensions for video encoding compatibility
-            let x = (crop.x as u32) & !1; // Make even
-            let y = (crop.y as u32) & !1; // Make even
-            let width = (crop.width as u32) & !1; // Make even
-            let height = (crop.height as u32) & !1; // Make even
+            let x = EvenDimension::new(crop.x as u32).get();
+            let y = EvenDimension::new(crop.y as u32).get();
+            let width = EvenDimension::new(crop.width as u32).get();
+            let height = EvenDimension::new(crop.height as u32).get();
 
             // Use imageops::crop to get the cropped region
             let cropped = image::imageops::crop(&mut rgb_image, x, y, width, height).to_image();
 
             // Scale the cropped image to match target width if needed
             let scaled = if cropped.width() != target_width {
-                resize(
-                    &cropped,
+                resize_crop(
+                    crop,
+                    cropped,
                     target_width,
-                    ((target_width as f32 * (height as f32 / width as f32)) as u32) & !1, // Ensure even height
-                    image::imageops::FilterType::Lanczos3,
+                    EvenDimension::new((target_width as f32 * (height as f32 / width as f32)) as u32)
+                        .get(),
+                    options.filter,
+                    options.sharpen,
+                    cache,
                 )
             } else {
                 cropped
             };
 
-            // Create a new image with 9:16 aspect ratio and black background
-            let output_height = ((target_width as f32 * (16.0 / 9.0)) as u32) & !1; // Ensure even height
-            let mut result = RgbImage::new(target_width, output_height);
+            // Create a new image with 9:16 aspect ratio and a pillarbox background
+            let output_height =
+                EvenDimension::new((target_width as f32 * (16.0 / 9.0)) as u32).get();
+            let mut result =
+                create_pillarbox_background(&rgb_image, target_width, output_height, pillarbox);
 
             // Calculate y offset (1/16 of the height)
             let y_offset = output_height / 16;
 
             // Overlay the scaled image at the calculated y offset
             image::imageops::overlay(&mut result, &scaled, 0, y_offset as i64);
+            if pillarbox == PillarboxMode::Mirror {
+                mirror_padding(&mut result, y_offset, scaled.height());
+            }
+
+            if let Some(watermark) = options.watermark {
+                watermark.apply(&mut result);
+            }
 
             // Convert back to usls::Image
             Ok(Image::from(result))
@@ -132,19 +895,19 @@ pub fn create_cropped_image(
             // Ensure even dimensions for video encoding compatibility
             let crop1_img = image::imageops::crop(
                 &mut rgb_image,
-                (crop1.x as u32) & !1, // Make even
-                (crop1.y as u32) & !1, // Make even
-                (crop1.width as u32) & !1, // Make even
-                (crop1.height as u32) & !1, // Make even
+                EvenDimension::new(crop1.x as u32).get(),
+                EvenDimension::new(crop1.y as u32).get(),
+                EvenDimension::new(crop1.width as u32).get(),
+                EvenDimension::new(crop1.height as u32).get(),
             )
             .to_image();
 
             let crop2_img = image::imageops::crop(
                 &mut rgb_image,
-                (crop2.x as u32) & !1, // Make even
-                (crop2.y as u32) & !1, // Make even
-                (crop2.width as u32) & !1, // Make even
-                (crop2.height as u32) & !1, // Make even
+                EvenDimension::new(crop2.x as u32).get(),
+                EvenDimension::new(crop2.y as u32).get(),
+                EvenDimension::new(crop2.width as u32).get(),
+                EvenDimension::new(crop2.height as u32).get(),
             )
             .to_image();
 
@@ -155,7 +918,12 @@ pub fn create_cropped_image(
             let crop1_aspect = crop1.width / crop1.height;
             let crop2_aspect = crop2.width / crop2.height;
             
-            let (top_height, bottom_height) = if (crop1_aspect - 1.5).abs() < 0.1 && (crop2_aspect - 0.9).abs() < 0.1 {
+            let (top_height, bottom_height) = if let Some(layout) =
+                ScoreboardBallLayout::from_crops(crop1, crop2)
+            {
+                // Scoreboard above the ball: 4/16 and 12/16
+                layout.split_heights(target_height)
+            } else if (crop1_aspect - 1.5).abs() < 0.1 && (crop2_aspect - 0.9).abs() < 0.1 {
                 // Special case: 9:6 and 9:10 crops (three heads case)
                 // Scale proportionally: 6/16 and 10/16
                 let top_height = (target_height as f32 * (6.0 / 16.0)) as u32;
@@ -169,18 +937,24 @@ pub fn create_cropped_image(
             };
             
             // Scale both crops to fit the target width and their calculated heights
-            let scaled1 = resize(
-                &crop1_img,
+            let scaled1 = resize_crop(
+                crop1,
+                crop1_img,
                 target_width,
                 top_height,
-                image::imageops::FilterType::Lanczos3,
+                options.filter,
+                options.sharpen,
+                cache.as_deref_mut(),
             );
 
-            let scaled2 = resize(
-                &crop2_img,
+            let scaled2 = resize_crop(
+                crop2,
+                crop2_img,
                 target_width,
                 bottom_height,
-                image::imageops::FilterType::Lanczos3,
+                options.filter,
+                options.sharpen,
+                cache,
             );
 
             // Create a new image with 9:16 aspect ratio
@@ -192,6 +966,10 @@ pub fn create_cropped_image(
             // Copy the second crop to the bottom portion
             image::imageops::overlay(&mut result, &scaled2, 0, top_height as i64);
 
+            if let Some(watermark) = options.watermark {
+                watermark.apply(&mut result);
+            }
+
             // Convert back to usls::Image
             Ok(Image::from(result))
         }
@@ -199,48 +977,578 @@ pub fn create_cropped_image(
             // For resize, we want to resize the entire frame to the target width
             // The crop area should cover the entire frame (x=0, y=0, width=frame_width, height=frame_height)
             // Ensure even dimensions for video encoding compatibility
-            let x = (crop.x as u32) & !1; // Make even
-            let y = (crop.y as u32) & !1; // Make even
-            let width = (crop.width as u32) & !1; // Make even
-            let height = (crop.height as u32) & !1; // Make even
+            let x = EvenDimension::new(crop.x as u32).get();
+            let y = EvenDimension::new(crop.y as u32).get();
+            let width = EvenDimension::new(crop.width as u32).get();
+            let height = EvenDimension::new(crop.height as u32).get();
 
             // Use imageops::crop to get the cropped region (should be the entire frame)
             let cropped = image::imageops::crop(&mut rgb_image, x, y, width, height).to_image();
 
             // Scale the cropped image to match target width if needed
             let scaled = if cropped.width() != target_width {
-                resize(
-                    &cropped,
+                resize_crop(
+                    crop,
+                    cropped,
                     target_width,
-                    ((target_width as f32 * (height as f32 / width as f32)) as u32) & !1, // Ensure even height
-                    image::imageops::FilterType::Lanczos3,
+                    EvenDimension::new((target_width as f32 * (height as f32 / width as f32)) as u32)
+                        .get(),
+                    options.filter,
+                    options.sharpen,
+                    cache,
                 )
             } else {
                 cropped
             };
 
-            // Create a new image with 9:16 aspect ratio and black background
-            let output_height = ((target_width as f32 * (16.0 / 9.0)) as u32) & !1; // Ensure even height
-            let mut result = RgbImage::new(target_width, output_height);
+            // Create a new image with 9:16 aspect ratio and a pillarbox background
+            let output_height =
+                EvenDimension::new((target_width as f32 * (16.0 / 9.0)) as u32).get();
+            let mut result =
+                create_pillarbox_background(&rgb_image, target_width, output_height, pillarbox);
 
             // Calculate y offset (1/8 of the height)
             let y_offset = output_height / 8;
 
             // Overlay the scaled image at the calculated y offset
             image::imageops::overlay(&mut result, &scaled, 0, y_offset as i64);
+            if pillarbox == PillarboxMode::Mirror {
+                mirror_padding(&mut result, y_offset, scaled.height());
+            }
+
+            if let Some(watermark) = options.watermark {
+                watermark.apply(&mut result);
+            }
 
             // Convert back to usls::Image
             Ok(Image::from(result))
         }
+        CropResult::ZoomIn {
+            start,
+            end,
+            progress,
+        } => {
+            // Render the interpolated zoom position like a single crop
+            let crop = crate::crop::interpolate_crop_area(start, end, *progress);
+            create_cropped_image(
+                image,
+                &CropResult::Single(crop),
+                target_width,
+                pillarbox,
+                options,
+                cache,
+            )
+        }
+    }
+}
+
+/// Blends a transparent image onto `base` with its top-left corner at (`x`, `y`)
+///
+/// Unlike `imageops::overlay` onto an RGB image, this honors the overlay's alpha channel, so
+/// PNG watermarks with transparency are blended instead of pasted. Pixels outside `base` are
+/// skipped.
+///
+/// # Arguments
+/// * `base` - The image to draw on
+/// * `overlay` - The image to blend in
+/// * `x` - The horizontal position of the overlay in `base`, may be negative
+/// * `y` - The vertical position of the overlay in `base`, may be negative
+/// * `opacity` - Multiplies the overlay's alpha, from 0.0 (invisible) to 1.0
+pub fn alpha_composite(base: &mut RgbImage, overlay: &RgbaImage, x: i64, y: i64, opacity: f32) {
+    let opacity = opacity.clamp(0.0, 1.0);
+    for (overlay_x, overlay_y, pixel) in overlay.enumerate_pixels() {
+        let (base_x, base_y) = (x + overlay_x as i64, y + overlay_y as i64);
+        if base_x < 0 || base_y < 0 || base_x >= base.width() as i64 || base_y >= base.height() as i64 {
+            continue;
+        }
+        let weight = opacity * pixel[3] as f32 / 255.0;
+        let base_pixel = base.get_pixel_mut(base_x as u32, base_y as u32);
+        for channel in 0..3 {
+            let blended = base_pixel[channel] as f32 * (1.0 - weight) + pixel[channel] as f32 * weight;
+            base_pixel[channel] = blended.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Distance in pixels between a corner watermark and the frame edges
+pub const WATERMARK_MARGIN: u32 = 20;
+
+/// Where a watermark is placed on the output frame
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+    /// The watermark's top-left corner at (`x`, `y`)
+    Custom { x: u32, y: u32 },
+}
+
+impl WatermarkPosition {
+    /// Returns the top-left corner of a `width`x`height` watermark on a
+    /// `frame_width`x`frame_height` frame
+    ///
+    /// The corner may be negative when the watermark is larger than the frame.
+    pub fn offset(self, frame_width: u32, frame_height: u32, width: u32, height: u32) -> (i64, i64) {
+        let margin = WATERMARK_MARGIN as i64;
+        let right = frame_width as i64 - width as i64 - margin;
+        let bottom = frame_height as i64 - height as i64 - margin;
+        match self {
+            WatermarkPosition::TopLeft => (margin, margin),
+            WatermarkPosition::TopRight => (right, margin),
+            WatermarkPosition::BottomLeft => (margin, bottom),
+            WatermarkPosition::BottomRight => (right, bottom),
+            WatermarkPosition::Center => (
+                (frame_width as i64 - width as i64) / 2,
+                (frame_height as i64 - height as i64) / 2,
+            ),
+            WatermarkPosition::Custom { x, y } => (x as i64, y as i64),
+        }
     }
 }
 
+impl std::str::FromStr for WatermarkPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        match lower.split_once(':') {
+            Some(("custom", value)) => {
+                let coordinate = |v: Option<&str>| v.and_then(|v| v.trim().parse::<u32>().ok());
+                let mut parts = value.split(',');
+                match (coordinate(parts.next()), coordinate(parts.next()), parts.next()) {
+                    (Some(x), Some(y), None) => Ok(WatermarkPosition::Custom { x, y }),
+                    _ => Err(format!("invalid watermark position '{}', expected custom:X,Y", value)),
+                }
+            }
+            Some(_) => Err(format!("unsupported watermark position '{}'", s)),
+            None => match lower.as_str() {
+                "top-left" => Ok(WatermarkPosition::TopLeft),
+                "top-right" => Ok(WatermarkPosition::TopRight),
+                "bottom-left" => Ok(WatermarkPosition::BottomLeft),
+                "bottom-right" => Ok(WatermarkPosition::BottomRight),
+                "center" => Ok(WatermarkPosition::Center),
+                _ => Err(format!(
+                    "unsupported watermark position '{}', expected top-left, top-right, bottom-left, bottom-right, center or custom:X,Y",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// Formats the position as `--watermark-position` expects it
+impl std::fmt::Display for WatermarkPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatermarkPosition::TopLeft => write!(f, "top-left"),
+            WatermarkPosition::TopRight => write!(f, "top-right"),
+            WatermarkPosition::BottomLeft => write!(f, "bottom-left"),
+            WatermarkPosition::BottomRight => write!(f, "bottom-right"),
+            WatermarkPosition::Center => write!(f, "center"),
+            WatermarkPosition::Custom { x, y } => write!(f, "custom:{},{}", x, y),
+        }
+    }
+}
+
+/// A watermark image loaded once and blended onto every output frame
+#[derive(Debug, Clone)]
+pub struct Watermark {
+    image: RgbaImage,
+    position: WatermarkPosition,
+    opacity: f32,
+}
+
+impl Watermark {
+    /// Loads a watermark image, keeping its alpha channel
+    ///
+    /// # Arguments
+    /// * `path` - The watermark image, usually a PNG with transparency
+    /// * `position` - Where the watermark is placed on the frame
+    /// * `opacity` - Multiplies the watermark's alpha, from 0.0 (invisible) to 1.0
+    pub fn load(path: &Path, position: WatermarkPosition, opacity: f32) -> Result<Self> {
+        if !(0.0..=1.0).contains(&opacity) {
+            anyhow::bail!("Watermark opacity must be between 0.0 and 1.0, got {}", opacity);
+        }
+        let image = image::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load watermark {}: {}", path.display(), e))?
+            .to_rgba8();
+        Ok(Self {
+            image,
+            position,
+            opacity,
+        })
+    }
+
+    /// Blends the watermark onto `image`
+    pub fn apply(&self, image: &mut RgbImage) {
+        let (x, y) = self.position.offset(
+            image.width(),
+            image.height(),
+            self.image.width(),
+            self.image.height(),
+        );
+        alpha_composite(image, &self.image, x, y, self.opacity);
+    }
+}
+
+/// Loads a watermark image and blends it onto `image`
+///
+/// Use `Watermark` instead when the same watermark is applied to many frames.
+///
+/// # Arguments
+/// * `image` - The frame to draw on
+/// * `watermark_path` - The watermark image, usually a PNG with transparency
+/// * `position` - Where the watermark is placed on the frame
+/// * `opacity` - Multiplies the watermark's alpha, from 0.0 (invisible) to 1.0
+pub fn overlay_watermark(
+    image: &mut RgbImage,
+    watermark_path: &Path,
+    position: WatermarkPosition,
+    opacity: f32,
+) -> Result<()> {
+    Watermark::load(watermark_path, position, opacity)?.apply(image);
+    Ok(())
+}
+
+/// Draws the outline of a rectangle, `thickness` pixels wide on its inside
+///
+/// Parts of the rectangle outside `image` are skipped.
+pub fn draw_rect_outline(
+    image: &mut RgbImage,
+    area: &CropArea,
+    thickness: u32,
+    color: image::Rgb<u8>,
+) {
+    let x0 = area.x.round() as i64;
+    let y0 = area.y.round() as i64;
+    let x1 = (area.x + area.width).round() as i64 - 1;
+    let y1 = (area.y + area.height).round() as i64 - 1;
+    let thickness = thickness.max(1) as i64;
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let is_edge = x - x0 < thickness || x1 - x < thickness || y - y0 < thickness || y1 - y < thickness;
+            if is_edge && x >= 0 && y >= 0 && x < image.width() as i64 && y < image.height() as i64 {
+                image.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+/// Width of a built-in font glyph in font pixels
+const GLYPH_WIDTH: u32 = 5;
+/// Height of a built-in font glyph in font pixels
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Returns the rows of a 5x7 glyph, most significant of the low five bits leftmost
+///
+/// Only digits, `.`, `:` and the uppercase letters used by debug overlays are included.
+fn glyph(c: char) -> Option<[u8; GLYPH_HEIGHT as usize]> {
+    let rows = match c {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11110, 0b00001, 0b00001, 0b01110, 0b00001, 0b00001, 0b11110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Returns the width in pixels of `text` drawn by `draw_text` at `scale`
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let chars = text.chars().count() as u32;
+    (chars * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale.max(1)
+}
+
+/// Draws `text` with its top-left corner at (`x`, `y`) using a built-in 5x7 pixel font
+///
+/// Each font pixel becomes a `scale` x `scale` square. Lowercase letters are drawn as
+/// uppercase, characters without a glyph leave a blank space and pixels outside `image` are
+/// skipped.
+pub fn draw_text(image: &mut RgbImage, text: &str, x: u32, y: u32, scale: u32, color: image::Rgb<u8>) {
+    let scale = scale.max(1);
+    for (index, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c.to_ascii_uppercase()) else {
+            continue;
+        };
+        let glyph_x = x + index as u32 * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (glyph_x + col * scale + dx, y + row as u32 * scale + dy);
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the areas `draw_crop_overlay` outlines for a crop, using the current area of a zoom-in
+fn overlay_areas(crop: &CropResult) -> Vec<CropArea> {
+    match crop {
+        CropResult::ZoomIn { start, end, progress } => {
+            vec![crate::crop::interpolate_crop_area(start, end, *progress)]
+        }
+        _ => {
+            let mut areas = Vec::new();
+            crop.for_each_crop(|area| areas.push(area.clone()));
+            areas
+        }
+    }
+}
+
+/// Outlines a crop area with `imageproc`, `thickness` pixels wide on its inside
+fn draw_overlay_rect(image: &mut RgbImage, area: &CropArea, thickness: u32, color: image::Rgb<u8>) {
+    for inset in 0..thickness {
+        let width = area.width.round() as i64 - 2 * inset as i64;
+        let height = area.height.round() as i64 - 2 * inset as i64;
+        if width <= 0 || height <= 0 {
+            break;
+        }
+        let rect = imageproc::rect::Rect::at(area.x.round() as i32 + inset as i32, area.y.round() as i32 + inset as i32)
+            .of_size(width as u32, height as u32);
+        imageproc::drawing::draw_hollow_rect_mut(image, rect, color);
+    }
+}
+
+/// Draws a crop decision on a copy of the full frame, for `--debug-overlay`
+///
+/// The crop is outlined in green and `previous_crop` in yellow. The object count and the
+/// detection confidences are written in the top-left corner.
+///
+/// # Arguments
+/// * `image` - The full source frame
+/// * `crop` - The crop applied to the frame
+/// * `previous_crop` - The crop of the previous frame, if it should be shown
+/// * `confidences` - The confidences of the objects detected in the frame
+pub fn draw_crop_overlay(
+    image: &Image,
+    crop: &CropResult,
+    previous_crop: Option<&CropResult>,
+    confidences: &[f32],
+) -> Image {
+    let mut frame = image.to_rgb8();
+
+    // Lines and text grow with the frame so they stay readable on full-resolution frames
+    let scale = (frame.width() / 480).max(1);
+    if let Some(previous_crop) = previous_crop {
+        for area in overlay_areas(previous_crop) {
+            draw_overlay_rect(&mut frame, &area, scale, image::Rgb([255, 255, 0]));
+        }
+    }
+    for area in overlay_areas(crop) {
+        draw_overlay_rect(&mut frame, &area, scale, image::Rgb([0, 255, 0]));
+    }
+
+    let white = image::Rgb([255, 255, 255]);
+    let margin = 2 * scale;
+    let line_height = (GLYPH_HEIGHT + 3) * scale;
+    draw_text(&mut frame, &format!("OBJECTS: {}", confidences.len()), margin, margin, scale, white);
+    if !confidences.is_empty() {
+        let confidences: Vec<String> = confidences.iter().map(|c| format!("{:.2}", c)).collect();
+        let label = format!("CONF: {}", confidences.join(" "));
+        draw_text(&mut frame, &label, margin, margin + line_height, scale, white);
+    }
+
+    Image::from(frame)
+}
+
+/// Width in pixels of a thumbnail grid (contact sheet)
+const THUMBNAIL_GRID_WIDTH: u32 = 1920;
+
+/// Arranges frames into a `cols` x `rows` grid, row by row
+///
+/// Each cell is `1920 / cols` pixels wide with the aspect ratio of the first frame. Frames beyond
+/// `cols * rows` are left out and missing cells stay black.
+pub fn build_thumbnail_grid(frames: &[Image], cols: usize, rows: usize) -> Result<RgbImage> {
+    if cols == 0 || rows == 0 || cols as u32 > THUMBNAIL_GRID_WIDTH {
+        anyhow::bail!("Invalid thumbnail grid size {}x{}", cols, rows);
+    }
+    let Some(first) = frames.first() else {
+        anyhow::bail!("No frames to build a thumbnail grid from");
+    };
+
+    let cell_width = THUMBNAIL_GRID_WIDTH / cols as u32;
+    let cell_height =
+        ((cell_width as f32 * first.height() as f32 / first.width().max(1) as f32) as u32).max(1);
+    let mut grid = RgbImage::new(cell_width * cols as u32, cell_height * rows as u32);
+    for (index, frame) in frames.iter().take(cols * rows).enumerate() {
+        let cell = resize(
+            &frame.to_rgb8(),
+            cell_width,
+            cell_height,
+            image::imageops::FilterType::Triangle,
+        );
+        let x = (index % cols) as u32 * cell_width;
+        let y = (index / cols) as u32 * cell_height;
+        image::imageops::overlay(&mut grid, &cell, x as i64, y as i64);
+    }
+    Ok(grid)
+}
+
+/// Saves a `cols` x `rows` contact sheet of frames as a JPEG (see `build_thumbnail_grid`)
+pub fn generate_thumbnail_grid(
+    frames: &[Image],
+    cols: usize,
+    rows: usize,
+    output: &std::path::Path,
+) -> Result<()> {
+    build_thumbnail_grid(frames, cols, rows)?
+        .save(output)
+        .map_err(|e| anyhow::anyhow!("Failed to save thumbnail grid to {}: {}", output.display(), e))
+}
+
+/// Width in pixels of the line separating the two halves of a side-by-side preview
+const PREVIEW_SEPARATOR_WIDTH: u32 = 2;
+
+/// Creates a debug preview with the original frame on the left and the cropped frame on the right
+///
+/// # Arguments
+/// * `original` - The full source frame
+/// * `cropped` - The cropped output frame
+/// * `target_width` - The width of the preview; each half is `target_width / 2` wide
+///
+/// # Returns
+/// An image `target_width` wide and as tall as the taller scaled half, with a white separator line
+pub fn create_side_by_side_preview(
+    original: &Image,
+    cropped: &Image,
+    target_width: u32,
+) -> Result<Image> {
+    let half_width = target_width / 2;
+    if half_width == 0 {
+        anyhow::bail!("Preview width must be at least 2, got {}", target_width);
+    }
+
+    let scale_to_half = |image: &Image| {
+        let rgb = image.to_rgb8();
+        let height =
+            (half_width as f32 * rgb.height() as f32 / rgb.width().max(1) as f32) as u32;
+        let height = height.max(1);
+        resize(&rgb, half_width, height, image::imageops::FilterType::Triangle)
+    };
+    let left = scale_to_half(original);
+    let right = scale_to_half(cropped);
+
+    let mut canvas = RgbImage::new(target_width, left.height().max(right.height()));
+    image::imageops::overlay(&mut canvas, &left, 0, 0);
+    image::imageops::overlay(&mut canvas, &right, half_width as i64, 0);
+
+    // Separator line centered on the boundary between the halves
+    let separator_start = half_width.saturating_sub(PREVIEW_SEPARATOR_WIDTH / 2);
+    for x in separator_start..(separator_start + PREVIEW_SEPARATOR_WIDTH).min(target_width) {
+        for y in 0..canvas.height() {
+            canvas.put_pixel(x, y, image::Rgb([255, 255, 255]));
+        }
+    }
+
+    Ok(Image::from(canvas))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::crop::CropArea;
     use usls::Image;
 
+    /// Builds a frame of randomly shaded 6x6 blocks, shifted by `(shift_x, shift_y)` pixels
+    fn textured_frame(width: u32, height: u32, shift_x: i64, shift_y: i64) -> Image {
+        let shade = |bx: i64, by: i64| {
+            let hash = (bx.wrapping_mul(73_856_093) ^ by.wrapping_mul(19_349_663)) as u64;
+            (hash.wrapping_mul(2_654_435_761) >> 24) as u8
+        };
+        let frame = RgbImage::from_fn(width, height, |x, y| {
+            let value = shade(
+                (x as i64 - shift_x).div_euclid(6),
+                (y as i64 - shift_y).div_euclid(6),
+            );
+            image::Rgb([value, value, value])
+        });
+        Image::from(frame)
+    }
+
+    #[test]
+    fn test_estimate_background_motion() {
+        let prev = textured_frame(320, 180, 0, 0);
+
+        let (dx, dy) = estimate_background_motion(&prev, &prev).unwrap();
+        assert_eq!((dx, dy), (0.0, 0.0));
+
+        let curr = textured_frame(320, 180, 5, -3);
+        let (dx, dy) = estimate_background_motion(&prev, &curr).unwrap();
+        assert!((dx - 5.0).abs() <= 1.0, "dx = {}", dx);
+        assert!((dy + 3.0).abs() <= 1.0, "dy = {}", dy);
+
+        // Motion is reported in full-resolution pixels when frames are downscaled for analysis
+        let prev = textured_frame(640, 360, 0, 0);
+        let curr = textured_frame(640, 360, -10, 0);
+        let (dx, dy) = estimate_background_motion(&prev, &curr).unwrap();
+        assert!((dx + 10.0).abs() <= 2.0, "dx = {}", dx);
+        assert!(dy.abs() <= 2.0, "dy = {}", dy);
+
+        // A featureless frame has no corners to track
+        let flat = Image::from(RgbImage::from_pixel(320, 180, image::Rgb([90, 90, 90])));
+        assert_eq!(estimate_background_motion(&flat, &flat).unwrap(), (0.0, 0.0));
+
+        let small = textured_frame(160, 90, 0, 0);
+        assert!(estimate_background_motion(&prev, &small).is_err());
+    }
+
+    #[test]
+    fn test_side_by_side_preview() {
+        let original = Image::from(RgbImage::from_pixel(1920, 1080, image::Rgb([20, 120, 220])));
+        let cropped = Image::from(RgbImage::from_pixel(608, 1080, image::Rgb([220, 60, 20])));
+
+        let preview = create_side_by_side_preview(&original, &cropped, 800).unwrap().to_rgb8();
+        assert_eq!(preview.width(), 800);
+        // The portrait half is the taller one
+        assert_eq!(preview.height(), (400.0 * 1080.0 / 608.0) as u32);
+
+        // Each half shows its own source, separated by a white line
+        assert_ne!(preview.get_pixel(100, 100), preview.get_pixel(700, 100));
+        assert_eq!(*preview.get_pixel(100, 100), image::Rgb([20, 120, 220]));
+        assert_eq!(*preview.get_pixel(700, 100), image::Rgb([220, 60, 20]));
+        assert_eq!(*preview.get_pixel(400, 100), image::Rgb([255, 255, 255]));
+
+        assert!(create_side_by_side_preview(&original, &cropped, 1).is_err());
+    }
+
     #[test]
     fn test_single_crop() {
         // Create a test image with sufficient height for the crop
@@ -263,7 +1571,15 @@ mod tests {
         let crop_result = CropResult::Single(crop);
 
         // Create the cropped image with target width of 1080
-        let cropped = create_cropped_image(&image, &crop_result, 1080).unwrap();
+        let cropped = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
 
         // Verify dimensions - should be 9:16 aspect ratio
         assert_eq!(cropped.width(), 1080); // Width matches target width
@@ -306,7 +1622,15 @@ mod tests {
         let crop_result = CropResult::Stacked(crop1, crop2);
 
         // Create the cropped image with target width of 1080
-        let cropped = create_cropped_image(&image, &crop_result, 1080).unwrap();
+        let cropped = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
 
         // Verify dimensions - should be 9:16 aspect ratio
         assert_eq!(cropped.width(), 1080); // Width matches target width
@@ -347,7 +1671,15 @@ mod tests {
         let crop_result = CropResult::Stacked(crop1, crop2);
 
         // Create the cropped image with target width of 1080
-        let cropped = create_cropped_image(&image, &crop_result, 1080).unwrap();
+        let cropped = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
 
         // Verify dimensions - should be 9:16 aspect ratio
         assert_eq!(cropped.width(), 1080); // Width matches target width
@@ -359,9 +1691,165 @@ mod tests {
         // and the shorter/wider crop should take less vertical space
     }
 
+    #[test]
+    fn test_zoom_in_crop() {
+        let image = Image::from(RgbImage::new(1920, 1080));
+
+        // Halfway between a 3:4 full-height crop and a 2x tighter crop
+        let crop_result = CropResult::ZoomIn {
+            start: CropArea::new(555.0, 0.0, 810.0, 1080.0),
+            end: CropArea::new(757.5, 270.0, 405.0, 540.0),
+            progress: 0.5,
+        };
+
+        let cropped = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
+
+        // Output keeps the 9:16 frame regardless of zoom progress
+        assert_eq!(cropped.width(), 1080);
+        assert_eq!(cropped.height(), 1920);
+    }
+
+    /// Builds a 1920x1080 frame whose red channel encodes the row and whose top rows are green
+    fn pillarbox_test_image() -> Image {
+        let mut rgb_image = RgbImage::new(1920, 1080);
+        for (_, y, pixel) in rgb_image.enumerate_pixels_mut() {
+            *pixel = if y < 100 {
+                image::Rgb([0, 255, 0])
+            } else {
+                image::Rgb([(y % 256) as u8, 0, 0])
+            };
+        }
+        Image::from(rgb_image)
+    }
+
+    #[test]
+    fn test_pillarbox_mode_from_str() {
+        assert_eq!("black".parse::<PillarboxMode>(), Ok(PillarboxMode::Black));
+        assert_eq!("Mirror".parse::<PillarboxMode>(), Ok(PillarboxMode::Mirror));
+        assert_eq!(
+            "blur".parse::<PillarboxMode>(),
+            Ok(PillarboxMode::BlurredSource {
+                radius: DEFAULT_PILLARBOX_BLUR_RADIUS
+            })
+        );
+        assert_eq!(
+            "blur:8".parse::<PillarboxMode>(),
+            Ok(PillarboxMode::BlurredSource { radius: 8.0 })
+        );
+        assert_eq!(
+            "solid:#FF8000".parse::<PillarboxMode>(),
+            Ok(PillarboxMode::SolidColor(255, 128, 0))
+        );
+        assert!("solid:xyz".parse::<PillarboxMode>().is_err());
+        assert!("blur:-1".parse::<PillarboxMode>().is_err());
+        assert!("stripes".parse::<PillarboxMode>().is_err());
+    }
+
+    #[test]
+    fn test_pillarbox_black_and_solid() {
+        let image = pillarbox_test_image();
+        let crop_result = CropResult::Single(CropArea::new(360.0, 0.0, 810.0, 1080.0));
+
+        let black = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
+        let black = black.to_rgb8();
+        assert_eq!(black.get_pixel(540, 10), &image::Rgb([0, 0, 0]));
+        assert_eq!(black.get_pixel(540, 1900), &image::Rgb([0, 0, 0]));
+
+        let solid = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::SolidColor(10, 20, 30),
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
+        let solid = solid.to_rgb8();
+        assert_eq!(solid.get_pixel(540, 10), &image::Rgb([10, 20, 30]));
+        assert_eq!(solid.get_pixel(540, 1900), &image::Rgb([10, 20, 30]));
+        // The crop itself starts 1/16 down and is unaffected
+        assert_eq!(solid.get_pixel(540, 1920 / 16 + 5), &image::Rgb([0, 255, 0]));
+    }
+
+    #[test]
+    fn test_pillarbox_mirror() {
+        let image = pillarbox_test_image();
+        let crop_result = CropResult::Single(CropArea::new(360.0, 0.0, 810.0, 1080.0));
+
+        let cropped = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Mirror,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
+        let cropped = cropped.to_rgb8();
+        let y_offset = 1920 / 16;
+
+        // The top padding reflects the green top edge of the crop
+        for k in 0..y_offset {
+            assert_eq!(
+                cropped.get_pixel(540, y_offset - 1 - k),
+                cropped.get_pixel(540, y_offset + k)
+            );
+        }
+        assert_eq!(cropped.get_pixel(540, 0), &image::Rgb([0, 255, 0]));
+
+        // The bottom padding reflects the bottom edge of the crop
+        let content_end = y_offset + 1440;
+        for k in 0..(1920 - content_end) {
+            assert_eq!(
+                cropped.get_pixel(540, content_end + k),
+                cropped.get_pixel(540, content_end - 1 - k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_pillarbox_blurred_source() {
+        // A uniform red frame blurs to the same red
+        let rgb_image = RgbImage::from_pixel(1920, 1080, image::Rgb([200, 0, 0]));
+        let image = Image::from(rgb_image);
+        let crop_result = CropResult::Single(CropArea::new(360.0, 0.0, 810.0, 1080.0));
+
+        let cropped = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::BlurredSource { radius: 20.0 },
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
+        let cropped = cropped.to_rgb8();
+
+        for y in [0, 60, 1700, 1919] {
+            let pixel = cropped.get_pixel(540, y);
+            assert!(pixel[0] > 180 && pixel[1] < 20 && pixel[2] < 20, "pixel {:?}", pixel);
+        }
+    }
+
     #[test]
     fn test_cut_detector() {
-        let mut detector = CutDetector::new(0.15, 0.7);
+        let mut detector = CutDetector::new(0.15, 0.7, CutDetectionMethod::RgbHybrid);
         
         // Create two identical images
         let mut rgb_image1 = RgbImage::new(100, 100);
@@ -402,6 +1890,115 @@ mod tests {
         assert!(is_cut == (detector.previous_score.unwrap() < 0.15));
     }
 
+    fn gradient_image(width: u32, height: u32, inverted: bool) -> RgbImage {
+        let mut rgb_image = RgbImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g) = ((x % 256) as u8, (y % 256) as u8);
+                let pixel = if inverted {
+                    image::Rgb([255 - r, 255 - g, 0])
+                } else {
+                    image::Rgb([r, g, 128])
+                };
+                rgb_image.put_pixel(x, y, pixel);
+            }
+        }
+        rgb_image
+    }
+
+    #[test]
+    fn test_phash_similarity() {
+        let frame = textured_frame(320, 180, 0, 0).to_rgb8();
+        let same = textured_frame(320, 180, 0, 0).to_rgb8();
+        assert_eq!(phash_similarity(compute_phash(&frame), compute_phash(&same)), 1.0);
+
+        // A completely different scene flips most of the hash
+        let mut cut = frame.clone();
+        image::imageops::invert(&mut cut);
+        assert!(phash_similarity(compute_phash(&frame), compute_phash(&cut)) < 0.5);
+
+        assert_eq!(phash_similarity(0, u64::MAX), 0.0);
+        assert_eq!("phash".parse::<CutDetectionMethod>(), Ok(CutDetectionMethod::PHash));
+    }
+
+    #[test]
+    fn test_color_histogram_similarity() {
+        let image1 = gradient_image(100, 100, false);
+        let image2 = gradient_image(100, 100, false);
+        let similarity = color_histogram_similarity(&image1, &image2, DEFAULT_HISTOGRAM_BINS);
+        assert!((similarity - 1.0).abs() < 1e-9);
+
+        // A completely different color distribution scores lower
+        let image3 = gradient_image(100, 100, true);
+        let similarity = color_histogram_similarity(&image1, &image3, DEFAULT_HISTOGRAM_BINS);
+        assert!(similarity < 0.9);
+
+        // Empty images have no distribution to compare
+        let empty = RgbImage::new(0, 0);
+        assert_eq!(color_histogram_similarity(&empty, &image1, 16), 0.0);
+    }
+
+    #[test]
+    fn test_cut_detector_warmup() {
+        // Solid frames sharing no channel values have zero histogram similarity
+        let image1 = Image::from(RgbImage::from_pixel(100, 100, image::Rgb([255, 255, 0])));
+        let image2 = Image::from(RgbImage::from_pixel(100, 100, image::Rgb([0, 0, 255])));
+
+        // Without warm-up, completely different frames are a cut
+        let mut detector = CutDetector::new(0.15, 0.7, CutDetectionMethod::ColorHistogram);
+        assert!(detector.is_cut(&image1, &image2).unwrap());
+
+        // The first comparison initializes state, the rest of the warm-up is suppressed
+        let mut detector = CutDetector::new(0.15, 0.7, CutDetectionMethod::ColorHistogram)
+            .with_warmup_frames(3);
+        assert!(detector.is_cut(&image1, &image1).unwrap());
+        assert!(!detector.is_cut(&image1, &image2).unwrap());
+        assert!(!detector.is_cut(&image2, &image1).unwrap());
+        assert!(detector.previous_score.is_some());
+
+        // After warm-up, cuts are detected again
+        assert!(!detector.is_cut(&image1, &image1).unwrap());
+        assert!(detector.is_cut(&image1, &image2).unwrap());
+    }
+
+    #[test]
+    fn test_cut_detector_histogram() {
+        let mut detector = CutDetector::new(0.15, 0.7, CutDetectionMethod::ColorHistogram);
+        let image1 = Image::from(gradient_image(100, 100, false));
+        let image2 = Image::from(gradient_image(100, 100, false));
+
+        assert!(!detector.is_cut(&image1, &image2).unwrap());
+        assert!((detector.previous_score.unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn benchmark_cut_detection_methods() {
+        let image1 = Image::from(gradient_image(1280, 720, false));
+        let image2 = Image::from(gradient_image(1280, 720, true));
+        let iterations = 3;
+
+        let mut rgb_detector = CutDetector::new(0.3, 0.8, CutDetectionMethod::RgbHybrid);
+        let start = std::time::Instant::now();
+        for _ in 0..iterations {
+            rgb_detector.is_cut(&image1, &image2).unwrap();
+        }
+        let rgb_duration = start.elapsed();
+
+        let mut histogram_detector = CutDetector::new(0.3, 0.8, CutDetectionMethod::ColorHistogram);
+        let start = std::time::Instant::now();
+        for _ in 0..iterations {
+            histogram_detector.is_cut(&image1, &image2).unwrap();
+        }
+        let histogram_duration = start.elapsed();
+
+        println!("Cut detection benchmark ({} iterations, 1280x720):", iterations);
+        println!("  RGB hybrid time: {:?}", rgb_duration);
+        println!("  Histogram time: {:?}", histogram_duration);
+
+        // Histogram comparison should be faster than pixel-level comparison
+        assert!(histogram_duration < rgb_duration);
+    }
+
     #[test]
     fn test_resize_crop() {
         // Create a test image
@@ -424,7 +2021,15 @@ mod tests {
         let crop_result = CropResult::Resize(crop);
 
         // Create the resized image with target width of 1080
-        let resized = create_cropped_image(&image, &crop_result, 1080).unwrap();
+        let resized = create_cropped_image(
+            &image,
+            &crop_result,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
 
         // Verify dimensions - should be 9:16 aspect ratio
         assert_eq!(resized.width(), 1080); // Width matches target width
@@ -443,4 +2048,318 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_scoreboard_ball_layout() {
+        let scoreboard = CropArea::new(100.0, 40.0, 180.0, 80.0);
+        let ball = CropArea::new(600.0, 0.0, 810.0, 1080.0);
+        let layout = ScoreboardBallLayout::from_crops(&scoreboard, &ball).unwrap();
+
+        for target_height in [1920, 1706, 853] {
+            let (top_height, bottom_height) = layout.split_heights(target_height);
+            assert_eq!(top_height + bottom_height, target_height);
+            assert_eq!(top_height, target_height / 4);
+        }
+
+        // Head crops are not mistaken for a scoreboard layout
+        let top = CropArea::new(0.0, 0.0, 960.0, 640.0);
+        let bottom = CropArea::new(0.0, 0.0, 900.0, 1000.0);
+        assert_eq!(ScoreboardBallLayout::from_crops(&top, &bottom), None);
+
+        let image = Image::from(gradient_image(1920, 1080, false));
+        let stacked = CropResult::Stacked(scoreboard, ball);
+        let result = create_cropped_image(
+            &image,
+            &stacked,
+            1080,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!((result.width(), result.height()), (1080, 1920));
+    }
+
+    /// Peak signal-to-noise ratio between two equally sized images, in dB
+    fn psnr(a: &RgbImage, b: &RgbImage) -> f64 {
+        let squared_error: f64 = a
+            .as_raw()
+            .iter()
+            .zip(b.as_raw())
+            .map(|(&x, &y)| (x as f64 - y as f64).powi(2))
+            .sum();
+        let mse = squared_error / a.as_raw().len() as f64;
+        10.0 * (255.0 * 255.0 / mse).log10()
+    }
+
+    #[test]
+    fn test_resize_filter() {
+        assert_eq!("CatmullRom".parse(), Ok(ResizeFilter::CatmullRom));
+        assert_eq!("nearest".parse(), Ok(ResizeFilter::Nearest));
+        assert!("bicubic".parse::<ResizeFilter>().is_err());
+
+        let image = Image::from(gradient_image(1920, 1080, false));
+        let crop_result = CropResult::Single(CropArea::new(555.0, 0.0, 810.0, 1080.0));
+        let render = |filter| {
+            create_cropped_image(
+                &image,
+                &crop_result,
+                1080,
+                PillarboxMode::Black,
+                RenderOptions {
+                    filter,
+                    ..RenderOptions::default()
+                },
+                None,
+            )
+            .unwrap()
+            .to_rgb8()
+        };
+        let lanczos = render(ResizeFilter::Lanczos3);
+        let catmull_rom = render(ResizeFilter::CatmullRom);
+        assert_eq!(lanczos.dimensions(), catmull_rom.dimensions());
+        assert!(psnr(&lanczos, &catmull_rom) > 30.0);
+    }
+
+    #[test]
+    fn test_sharpen_image() {
+        let stripes = RgbImage::from_fn(64, 64, |x, y| {
+            let value = if (x / 4 + y / 8) % 2 == 0 { 80 } else { 176 };
+            image::Rgb([value, value / 2, 255 - value])
+        });
+        let mean_abs_diff = |a: &RgbImage, b: &RgbImage| {
+            let total: f64 = a
+                .as_raw()
+                .iter()
+                .zip(b.as_raw())
+                .map(|(&x, &y)| (x as f64 - y as f64).abs())
+                .sum();
+            total / a.as_raw().len() as f64
+        };
+
+        assert_eq!(sharpen_image(&stripes, 0.0, 1.0), stripes);
+        let diffs: Vec<f64> = [0.25, 0.5, 1.0, 2.0]
+            .iter()
+            .map(|&amount| mean_abs_diff(&sharpen_image(&stripes, amount, 1.0), &stripes))
+            .collect();
+        assert!(diffs[0] > 0.0);
+        assert!(diffs.windows(2).all(|pair| pair[1] > pair[0]), "{:?}", diffs);
+
+        // Sharpening runs after the crop is scaled in every crop variant
+        let image = Image::from(gradient_image(1920, 1080, false));
+        let render = |crop_result: &CropResult, sharpen| {
+            create_cropped_image(
+                &image,
+                crop_result,
+                1080,
+                PillarboxMode::Black,
+                RenderOptions {
+                    sharpen,
+                    ..RenderOptions::default()
+                },
+                None,
+            )
+            .unwrap()
+            .to_rgb8()
+        };
+        for crop_result in [
+            CropResult::Single(CropArea::new(555.0, 0.0, 810.0, 1080.0)),
+            CropResult::Resize(CropArea::new(0.0, 0.0, 1920.0, 1080.0)),
+            CropResult::Stacked(
+                CropArea::new(0.0, 0.0, 960.0, 540.0),
+                CropArea::new(960.0, 540.0, 960.0, 540.0),
+            ),
+        ] {
+            assert_eq!(
+                render(&crop_result, Sharpen::new(0.0, 1.0)),
+                render(&crop_result, Sharpen::default())
+            );
+            assert_ne!(
+                render(&crop_result, Sharpen::new(1.5, 1.0)),
+                render(&crop_result, Sharpen::default())
+            );
+        }
+    }
+
+    #[test]
+    fn test_alpha_composite() {
+        let mut base = RgbImage::from_pixel(20, 20, image::Rgb([255, 255, 255]));
+        let overlay = RgbaImage::from_pixel(10, 10, image::Rgba([255, 0, 0, 128]));
+        alpha_composite(&mut base, &overlay, 5, 5, 1.0);
+
+        // Half-transparent red on white is pink
+        let pink = base.get_pixel(10, 10);
+        for (channel, expected) in pink.0.iter().zip([255u8, 127, 127]) {
+            assert!(channel.abs_diff(expected) <= 2, "{:?}", pink);
+        }
+        // Outside the overlay the background is untouched
+        assert_eq!(base.get_pixel(2, 2), &image::Rgb([255, 255, 255]));
+
+        // Opacity scales the overlay's alpha, and parts outside the base are skipped
+        let mut base = RgbImage::from_pixel(20, 20, image::Rgb([255, 255, 255]));
+        alpha_composite(&mut base, &overlay, -5, -5, 0.0);
+        assert_eq!(base.get_pixel(0, 0), &image::Rgb([255, 255, 255]));
+    }
+
+    #[test]
+    fn test_overlay_watermark() {
+        let path = std::env::temp_dir().join(format!("land2port_watermark_{}.png", std::process::id()));
+        RgbaImage::from_pixel(10, 10, image::Rgba([200, 0, 100, 255]))
+            .save(&path)
+            .unwrap();
+        let position = WatermarkPosition::Custom { x: 5, y: 5 };
+        let overlay = |opacity| {
+            let mut frame = RgbImage::from_pixel(40, 40, image::Rgb([0, 100, 200]));
+            overlay_watermark(&mut frame, &path, position, opacity).unwrap();
+            frame
+        };
+
+        let transparent = overlay(0.0);
+        assert_eq!(transparent, RgbImage::from_pixel(40, 40, image::Rgb([0, 100, 200])));
+        let half = overlay(0.5);
+        assert_eq!(half.get_pixel(10, 10), &image::Rgb([100, 50, 150]));
+        assert_eq!(half.get_pixel(4, 4), &image::Rgb([0, 100, 200]));
+        let opaque = overlay(1.0);
+        assert_eq!(opaque.get_pixel(5, 5), &image::Rgb([200, 0, 100]));
+        assert_eq!(opaque.get_pixel(14, 14), &image::Rgb([200, 0, 100]));
+        assert_eq!(opaque.get_pixel(15, 15), &image::Rgb([0, 100, 200]));
+
+        let mut frame = RgbImage::new(40, 40);
+        assert!(overlay_watermark(&mut frame, &path, position, 1.5).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(overlay_watermark(&mut frame, &path, position, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_watermark_position() {
+        assert_eq!(WatermarkPosition::TopLeft.offset(1080, 1920, 100, 50), (20, 20));
+        assert_eq!(WatermarkPosition::TopRight.offset(1080, 1920, 100, 50), (960, 20));
+        assert_eq!(WatermarkPosition::BottomLeft.offset(1080, 1920, 100, 50), (20, 1850));
+        assert_eq!(WatermarkPosition::BottomRight.offset(1080, 1920, 100, 50), (960, 1850));
+        assert_eq!(WatermarkPosition::Center.offset(1080, 1920, 100, 50), (490, 935));
+        assert_eq!(WatermarkPosition::Custom { x: 7, y: 9 }.offset(1080, 1920, 100, 50), (7, 9));
+
+        for position in [
+            WatermarkPosition::TopLeft,
+            WatermarkPosition::BottomRight,
+            WatermarkPosition::Center,
+            WatermarkPosition::Custom { x: 7, y: 9 },
+        ] {
+            assert_eq!(position.to_string().parse(), Ok(position));
+        }
+        assert_eq!("Top-Right".parse(), Ok(WatermarkPosition::TopRight));
+        assert!("custom:7".parse::<WatermarkPosition>().is_err());
+        assert!("middle".parse::<WatermarkPosition>().is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_grid_dimensions() {
+        let frames: Vec<Image> = (0..5)
+            .map(|i| Image::from(RgbImage::from_pixel(1080, 1920, image::Rgb([50 * i, 0, 0]))))
+            .collect();
+
+        let grid = build_thumbnail_grid(&frames, 4, 2).unwrap();
+        let (cell_width, cell_height) = (480, 853);
+        assert_eq!(grid.dimensions(), (4 * cell_width, 2 * cell_height));
+        // The fifth frame starts the second row and the cells after it stay black
+        assert_eq!(grid.get_pixel(10, cell_height + 10), &image::Rgb([200, 0, 0]));
+        assert_eq!(grid.get_pixel(cell_width + 10, cell_height + 10), &image::Rgb([0, 0, 0]));
+
+        let grid = build_thumbnail_grid(&frames, 3, 1).unwrap();
+        assert_eq!(grid.dimensions(), (3 * 640, 1137));
+
+        assert!(build_thumbnail_grid(&frames, 0, 2).is_err());
+        assert!(build_thumbnail_grid(&[], 4, 2).is_err());
+    }
+
+    #[test]
+    fn test_even_dimension() {
+        assert_eq!(EvenDimension::new(5).get(), 4);
+        assert_eq!(EvenDimension::new(4).get(), 4);
+        assert_eq!(EvenDimension::new(1).get(), 0);
+        assert_eq!(EvenDimension::new(0).get(), 0);
+    }
+
+    #[test]
+    fn test_image_cache_hits_identical_crops() {
+        let image = textured_frame(1920, 1080, 0, 0);
+        let crop_result = CropResult::Single(CropArea::new(360.0, 0.0, 810.0, 1080.0));
+        let mut cache = ImageCache::new(DEFAULT_IMAGE_CACHE_SIZE);
+
+        let first = create_cropped_image(
+            &image,
+            &crop_result,
+            720,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            Some(&mut cache),
+        )
+        .unwrap();
+        assert_eq!(cache.hits(), 0);
+        let second = create_cropped_image(
+            &image,
+            &crop_result,
+            720,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            Some(&mut cache),
+        )
+        .unwrap();
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(first.to_rgb8(), second.to_rgb8());
+
+        // Same crop over different pixels must be resized again
+        let shifted = textured_frame(1920, 1080, 3, 0);
+        create_cropped_image(
+            &shifted,
+            &crop_result,
+            720,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            Some(&mut cache),
+        )
+        .unwrap();
+        assert_eq!(cache.hits(), 1);
+
+        // A different crop misses
+        let moved = CropResult::Single(CropArea::new(400.0, 0.0, 810.0, 1080.0));
+        create_cropped_image(
+            &shifted,
+            &moved,
+            720,
+            PillarboxMode::Black,
+            RenderOptions::default(),
+            Some(&mut cache),
+        )
+        .unwrap();
+        assert_eq!(cache.hits(), 1);
+
+        assert_ne!(
+            compute_crop_hash(&CropArea::new(360.0, 0.0, 810.0, 1080.0)),
+            compute_crop_hash(&CropArea::new(400.0, 0.0, 810.0, 1080.0))
+        );
+    }
+
+    #[test]
+    fn test_draw_crop_overlay() {
+        let frame = Image::from(RgbImage::from_pixel(200, 100, image::Rgb([128, 128, 128])));
+        let crop = CropResult::Single(CropArea::new(100.0, 0.0, 56.0, 100.0));
+        let previous = CropResult::Single(CropArea::new(20.0, 0.0, 56.0, 100.0));
+
+        let overlay = draw_crop_overlay(&frame, &crop, Some(&previous), &[0.91, 0.45]).to_rgb8();
+        // The full frame is kept rather than cropped
+        assert_eq!(overlay.dimensions(), (200, 100));
+        assert_eq!(*overlay.get_pixel(100, 50), image::Rgb([0, 255, 0]));
+        assert_eq!(*overlay.get_pixel(155, 50), image::Rgb([0, 255, 0]));
+        assert_eq!(*overlay.get_pixel(20, 50), image::Rgb([255, 255, 0]));
+        assert_eq!(*overlay.get_pixel(130, 50), image::Rgb([128, 128, 128]));
+        // Object count and confidence labels in the top-left corner
+        let has_text = |y: u32| (0..100).any(|x| *overlay.get_pixel(x, y) == image::Rgb([255, 255, 255]));
+        assert!(has_text(4));
+        assert!(has_text(14));
+
+        let without_previous = draw_crop_overlay(&frame, &crop, None, &[]).to_rgb8();
+        assert_eq!(*without_previous.get_pixel(20, 50), image::Rgb([128, 128, 128]));
+        assert!(!(0..100).any(|x| *without_previous.get_pixel(x, 14) == image::Rgb([255, 255, 255])));
+    }
 }
@@ -0,0 +1,220 @@
+use crate::cli::Args;
+use crate::crop;
+use crate::video_processor::VideoProcessor;
+use crate::video_processor_utils;
+use crate::video_processor_utils::CropRenderer;
+use anyhow::Result;
+use usls::Viewer;
+
+/// A 1-D constant-velocity Kalman filter with state `[position, velocity]` and position measurements
+#[derive(Debug, Clone)]
+pub struct KalmanFilter1D {
+    position: f32,
+    velocity: f32,
+    covariance: [[f32; 2]; 2],
+    process_noise: f32,
+    measurement_noise: f32,
+    initialized: bool,
+}
+
+impl KalmanFilter1D {
+    /// Creates a filter that starts at the first measurement
+    ///
+    /// # Arguments
+    /// * `process_noise` - How much the position and velocity may change between frames (Q)
+    /// * `measurement_noise` - How noisy the measured positions are (R)
+    pub fn new(process_noise: f32, measurement_noise: f32) -> Self {
+        Self {
+            position: 0.0,
+            velocity: 0.0,
+            covariance: [[0.0; 2]; 2],
+            process_noise,
+            measurement_noise,
+            initialized: false,
+        }
+    }
+
+    /// Predicts one frame ahead, corrects with `measurement` and returns the filtered position
+    pub fn update(&mut self, measurement: f32) -> f32 {
+        if !self.initialized {
+            self.position = measurement;
+            self.velocity = 0.0;
+            self.covariance = [[self.measurement_noise, 0.0], [0.0, 1.0]];
+            self.initialized = true;
+            return self.position;
+        }
+
+        // Predict with x = F x and P = F P F^T + Q, where F = [[1, 1], [0, 1]]
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        self.position += self.velocity;
+        let p00 = p00 + p01 + p10 + p11 + self.process_noise;
+        let p01 = p01 + p11;
+        let p10 = p10 + p11;
+        let p11 = p11 + self.process_noise;
+
+        // Correct with the measured position (H = [1, 0])
+        let innovation_variance = p00 + self.measurement_noise;
+        let gain_position = p00 / innovation_variance;
+        let gain_velocity = p10 / innovation_variance;
+        let innovation = measurement - self.position;
+        self.position += gain_position * innovation;
+        self.velocity += gain_velocity * innovation;
+        self.covariance = [
+            [(1.0 - gain_position) * p00, (1.0 - gain_position) * p01],
+            [p10 - gain_velocity * p00, p11 - gain_velocity * p01],
+        ];
+        self.position
+    }
+}
+
+/// Video processor that smooths single crops with Kalman filters for the position and width
+///
+/// The height follows from the filtered width and the aspect ratio of the measured crop, so the
+/// crop keeps the output aspect ratio. Unlike history smoothing no frames are buffered, so the
+/// output has no lookahead delay. Stacked, resize and zoom-in crops are passed through and restart
+/// the filters.
+pub struct KalmanSmoothingVideoProcessor {
+    filters: Option<[KalmanFilter1D; 3]>,
+    process_noise: f32,
+    measurement_noise: f32,
+    previous_crop: Option<crop::CropResult>,
+}
+
+impl KalmanSmoothingVideoProcessor {
+    /// Creates a Kalman smoothing video processor
+    ///
+    /// # Arguments
+    /// * `process_noise` - The process noise (Q) of each filter; higher follows movement faster
+    /// * `measurement_noise` - The measurement noise (R) of each filter; higher smooths more
+    pub fn new(process_noise: f32, measurement_noise: f32) -> Self {
+        Self {
+            filters: None,
+            process_noise,
+            measurement_noise,
+            previous_crop: None,
+        }
+    }
+
+    /// Filters a single crop area, clamping the result to the frame
+    fn filter_crop_area(
+        &mut self,
+        area: &crop::CropArea,
+        frame_width: f32,
+        frame_height: f32,
+    ) -> crop::CropArea {
+        let (process_noise, measurement_noise) = (self.process_noise, self.measurement_noise);
+        let filters = self
+            .filters
+            .get_or_insert_with(|| std::array::from_fn(|_| KalmanFilter1D::new(process_noise, measurement_noise)));
+
+        let aspect_ratio = area.width / area.height.max(1.0);
+        let width = filters[2]
+            .update(area.width)
+            .clamp(1.0, frame_width.min(frame_height * aspect_ratio));
+        let height = width / aspect_ratio;
+        let x = filters[0].update(area.x).clamp(0.0, frame_width - width);
+        let y = filters[1].update(area.y).clamp(0.0, frame_height - height);
+        crop::CropArea::new(x, y, width, height)
+    }
+}
+
+impl VideoProcessor for KalmanSmoothingVideoProcessor {
+    /// Processes a single frame, filtering single crops and passing other crops through
+    fn process_frame_with_smoothing(
+        &mut self,
+        img: &usls::Image,
+        latest_crop: &crop::CropResult,
+        _objects: &[&usls::Hbb],
+        args: &Args,
+        viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
+        _smooth_duration_frames: usize,
+    ) -> Result<()> {
+        let crop_result = match latest_crop {
+            crop::CropResult::Single(area) => crop::CropResult::Single(self.filter_crop_area(
+                area,
+                img.width() as f32,
+                img.height() as f32,
+            )),
+            _ => {
+                self.filters = None;
+                latest_crop.clone()
+            }
+        };
+        video_processor_utils::debug_println(format_args!("Using Kalman crop: {:?}", crop_result));
+
+        video_processor_utils::process_and_display_crop(img, &crop_result, viewer, args.headless, args.pillarbox, renderer)?;
+        self.previous_crop = Some(crop_result);
+        Ok(())
+    }
+
+    /// Returns the crop most recently applied to an output frame
+    fn last_applied_crop(&self) -> Option<&crop::CropResult> {
+        self.previous_crop.as_ref()
+    }
+
+    /// Restores the previous crop from a resumed run
+    fn restore_previous_crop(&mut self, crop: crop::CropResult) {
+        self.previous_crop = Some(crop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kalman_filter_converges_on_step() {
+        let mut filter = KalmanFilter1D::new(1.0, 10.0);
+        assert_eq!(filter.update(0.0), 0.0);
+
+        // The first filtered values lag the step instead of jumping to it
+        let first = filter.update(100.0);
+        assert!(first > 0.0 && first < 100.0);
+
+        let mut position = first;
+        for _ in 1..10 {
+            position = filter.update(100.0);
+        }
+        assert!((position - 100.0).abs() < 2.0, "position {} after 10 steps", position);
+    }
+
+    #[test]
+    fn test_kalman_filter_holds_constant_input() {
+        let mut filter = KalmanFilter1D::new(1.0, 10.0);
+        for _ in 0..10 {
+            assert_eq!(filter.update(540.0), 540.0);
+        }
+    }
+
+    #[test]
+    fn test_filter_crop_area_stays_in_frame() {
+        let mut processor = KalmanSmoothingVideoProcessor::new(1.0, 10.0);
+        processor.filter_crop_area(&crop::CropArea::new(0.0, 0.0, 607.5, 1080.0), 1920.0, 1080.0);
+
+        // A jump to the right edge approaches it without leaving the frame
+        let right = crop::CropArea::new(1312.5, 0.0, 607.5, 1080.0);
+        let mut filtered = processor.filter_crop_area(&right, 1920.0, 1080.0);
+        assert!(filtered.x < right.x);
+        for _ in 0..20 {
+            filtered = processor.filter_crop_area(&right, 1920.0, 1080.0);
+            assert!(filtered.x + filtered.width <= 1920.0);
+        }
+        assert!((filtered.x - right.x).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_filter_crop_area_keeps_aspect_ratio() {
+        let mut processor = KalmanSmoothingVideoProcessor::new(1.0, 10.0);
+        processor.filter_crop_area(&crop::CropArea::new(656.0, 0.0, 607.5, 1080.0), 1920.0, 1080.0);
+
+        // A zoom-in shrinks the crop while the width and height stay in proportion
+        let zoomed = crop::CropArea::new(800.0, 200.0, 303.75, 540.0);
+        for _ in 0..5 {
+            let filtered = processor.filter_crop_area(&zoomed, 1920.0, 1080.0);
+            assert!(filtered.width < 607.5);
+            assert!((filtered.width / filtered.height - 607.5 / 1080.0).abs() < 1e-4);
+            assert!(filtered.y + filtered.height <= 1080.0);
+        }
+    }
+}
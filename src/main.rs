@@ -6,11 +6,15 @@ use crate::video_processor::VideoProcessor;
 
 mod audio;
 mod ball_video_processor;
+mod batch;
 mod cli;
 mod config;
+mod config_file;
 mod crop;
+mod export;
 mod history;
 mod image;
+mod kalman_smoothing_video_processor;
 mod progress;
 mod transcript;
 mod history_smoothing_video_processor;
@@ -26,45 +30,435 @@ fn create_output_dir() -> Result<String> {
     Ok(output_dir)
 }
 
+/// Chooses the processor based on object type and smoothing preference
+fn build_processor(args: &cli::Args) -> Result<Box<dyn VideoProcessor>> {
+    if args.object == "ball" {
+        let mut processor = ball_video_processor::BallVideoProcessor::new(args);
+        if args.ball_zone.is_some() || args.ball_scoreboard_zone.is_some() {
+            let metadata = audio::probe_video_metadata(&args.source)?;
+            let (frame_width, frame_height) = (metadata.width as f32, metadata.height as f32);
+            if let Some(zone) = &args.ball_zone {
+                let zone = crop::CropArea::from_zone_str(zone, frame_width, frame_height)?;
+                println!("Restricting ball detection to zone: {:?}", zone);
+                processor = processor.with_zone_of_interest(zone);
+            }
+            if let Some(zone) = &args.ball_scoreboard_zone {
+                let zone = crop::CropArea::from_zone_str(zone, frame_width, frame_height)?;
+                println!("Showing scoreboard zone above the ball: {:?}", zone);
+                processor = processor.with_scoreboard_zone(zone, frame_width, frame_height);
+            }
+        }
+        Ok(Box::new(processor))
+    } else if args.use_simple_smoothing {
+        let mut processor = simple_smoothing_video_processor::SimpleSmoothingVideoProcessor::new();
+        if let Some(window) = args.simple_median_window {
+            processor = processor.with_median_filter(window);
+        }
+        if args.simple_lookahead_frames > 0 {
+            processor = processor.with_lookahead(args.simple_lookahead_frames);
+        }
+        if args.confidence_blend {
+            processor = processor.with_confidence_blend();
+        }
+        Ok(Box::new(processor))
+    } else if args.use_kalman_smoothing {
+        if args.smooth_duration <= 0.0 {
+            anyhow::bail!("--use-kalman-smoothing cannot be combined with --smooth-duration 0, which disables smoothing");
+        }
+        Ok(Box::new(kalman_smoothing_video_processor::KalmanSmoothingVideoProcessor::new(
+            args.kalman_process_noise,
+            args.kalman_measurement_noise,
+        )))
+    } else {
+        let mut processor =
+            history_smoothing_video_processor::HistorySmoothingVideoProcessor::new(args);
+        if let Some(url) = &args.crop_change_webhook {
+            println!("Sending crop changes to webhook: {}", url);
+            processor = processor.with_on_crop_change(
+                history_smoothing_video_processor::webhook_crop_change_callback(url.clone()),
+            );
+        }
+        Ok(Box::new(processor))
+    }
+}
+
+/// Trims captions taken from the whole source to the subclip so they line up with the output
+fn trim_captions_to_subclip(srt_path: &str, subclip: &audio::Subclip) -> Result<()> {
+    if subclip.is_full() {
+        return Ok(());
+    }
+    let start_ms = (subclip.start_sec * 1000.0).round() as u64;
+    let end_ms = subclip.end_sec.map(|end_sec| (end_sec * 1000.0).round() as u64);
+    let srt_content = fs::read_to_string(srt_path)?;
+    fs::write(srt_path, transcript::trim_srt_to_range(&srt_content, start_ms, end_ms))?;
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args: cli::Args = argh::from_env();
+    let args = config_file::args_from_env();
 
-    // Create timestamped output directory
-    let output_dir = create_output_dir()?;
-    println!("Created output directory: {}", output_dir);
+    if let Some(path) = &args.output_config {
+        config_file::ConfigFile::from_args(&args).save(Path::new(path))?;
+        println!("✓ Options saved to: {}", path);
+    }
 
-    let processed_video = format!("{}/processed_video.mp4", output_dir);
+    if args.list_eps {
+        println!("Available execution providers:");
+        for provider in audio::detect_available_execution_providers() {
+            println!("  {}", provider);
+        }
+        return Ok(());
+    }
 
-    // If adding captions, prepare audio/transcription artifacts first
-    let (extracted_audio, srt_path) = if args.add_captions {
-        // Verify ffmpeg is installed
-        audio::check_ffmpeg_installed()?;
+    if args.batch_dir.is_some() || args.batch_glob.is_some() {
+        return process_batch(args).await;
+    }
+
+    // Reuse the interrupted run's directory when resuming, otherwise create a timestamped one
+    let output_dir = if let Some(resume_dir) = &args.resume_from {
+        println!("Resuming processing in output directory: {}", resume_dir);
+        resume_dir.clone()
+    } else {
+        let output_dir = create_output_dir()?;
+        println!("Created output directory: {}", output_dir);
+        output_dir
+    };
+    process_source(args, output_dir, None).await?;
+    Ok(())
+}
+
+/// Processes every file of `--batch-dir` or `--batch-glob` into a subdirectory of one
+/// timestamped output directory, skipping files that fail
+///
+/// With `--concat-output` the final videos of the files that succeeded are joined in batch order.
+async fn process_batch(args: cli::Args) -> Result<()> {
+    if args.resume_from.is_some() {
+        anyhow::bail!("--resume-from cannot be combined with --batch-dir or --batch-glob");
+    }
+    if args.concat_output.is_some() {
+        audio::check_dependencies(true, false)?;
+    }
+
+    let mut files = Vec::new();
+    if let Some(dir) = &args.batch_dir {
+        files.extend(batch::find_batch_videos(Path::new(dir))?);
+    }
+    if let Some(pattern) = &args.batch_glob {
+        files.extend(batch::glob_batch_videos(pattern)?);
+    }
+    files.sort();
+    files.dedup();
+    if files.is_empty() {
+        anyhow::bail!("No video files found to process in batch mode");
+    }
+
+    let batch_dir = create_output_dir()?;
+    println!("Processing {} files into: {}", files.len(), batch_dir);
+    let concat_output = args.concat_output.clone();
+    let summary = batch::BatchProcessor::new(files, args)
+        .run(Path::new(&batch_dir), |args, output_dir, progress| {
+            process_source(args, output_dir, Some(progress))
+        })
+        .await?;
+    println!(
+        "✓ Batch completed: {} succeeded, {} failed. Summary saved to: {}/{}",
+        summary.succeeded.len(),
+        summary.failed.len(),
+        batch_dir,
+        batch::BATCH_SUMMARY_FILE_NAME
+    );
+
+    if let Some(concat_output) = &concat_output {
+        let final_videos = summary.final_videos();
+        if final_videos.is_empty() {
+            println!("Warning: no final videos were written, skipping --concat-output");
+        } else {
+            println!("Concatenating {} batch outputs...", final_videos.len());
+            audio::concat_videos(&final_videos, concat_output)?;
+            println!("✓ Batch outputs concatenated: {}", concat_output);
+        }
+    }
+    Ok(())
+}
+
+/// Processes `args.source` into `output_dir`, from audio extraction to the final video
+///
+/// In a batch, frame progress goes to `batch_progress` instead of a progress bar per video.
+/// Returns the path of the final video, or `None` when no video was written (`--first-frame-only`
+/// and `--dry-run`).
+async fn process_source(
+    mut args: cli::Args,
+    output_dir: String,
+    batch_progress: Option<progress::SharedProgressTracker>,
+) -> Result<Option<String>> {
+    let processed_video = if args.resume_from.is_some() {
+        format!("{}/processed_video_resumed.mp4", output_dir)
+    } else {
+        format!("{}/processed_video.mp4", output_dir)
+    };
+
+    // Phones store portrait video with a rotate tag; read it when no rotation was given
+    if args.source_rotation.is_none() && audio::check_ffprobe_installed().is_ok() {
+        // A missing or unreadable rotate tag should not stop the run
+        let rotation = match audio::probe_video_rotation(&args.source) {
+            Ok(rotation) => rotation,
+            Err(e) => {
+                println!("Warning: could not read source rotation, assuming 0 degrees: {}", e);
+                0
+            }
+        };
+        if rotation != 0 {
+            println!("Detected source rotation of {} degrees", rotation);
+            args.source_rotation = Some(rotation);
+        }
+    }
+    let source_rotation = args.source_rotation.unwrap_or(0);
+    if ![0, 90, 180, 270].contains(&source_rotation) {
+        anyhow::bail!(
+            "--source-rotation must be 0, 90, 180 or 270, got {}",
+            source_rotation
+        );
+    }
+    if args.crop_scale <= 0.0 || args.crop_scale > 1.0 {
+        anyhow::bail!("--crop-scale must be above 0 and at most 1, got {}", args.crop_scale);
+    }
+    if args.icc_profile_path.is_some() && args.strip_icc_profile {
+        anyhow::bail!("--icc-profile cannot be combined with --strip-icc-profile");
+    }
+
+    // Verify the external tools needed by the enabled features are installed
+    let requires_ffmpeg = source_rotation != 0
+        || (!args.first_frame_only
+            && (args.add_captions
+                || args.output_codec != audio::VideoCodec::H264
+                || args.output_quality.is_some()
+                || args.icc_profile_path.is_some()
+                || args.strip_icc_profile
+                || args.concat_output.is_some()
+                || args.resume_from.is_some()
+                || args.output_fps > 0.0
+                || args.verify_input));
+    let requires_ffprobe = args.captions_from_chapters
+        || args.use_embedded_subtitles
+        || args.ball_zone.is_some()
+        || args.ball_scoreboard_zone.is_some()
+        || (args.add_captions && args.audio_track_index > 0)
+        || args.start_time.is_some()
+        || args.end_time.is_some()
+        || args.resume_from.is_some()
+        || (!args.first_frame_only && args.output_fps > 0.0);
+    audio::check_dependencies(requires_ffmpeg, requires_ffprobe)?;
+
+    // Check the output codec and quality before spending time on processing
+    let encoding = audio::VideoEncoding {
+        codec: args.output_codec,
+        crf: args.output_quality,
+    };
+    if !encoding.is_default() {
+        encoding.validate()?;
+    }
+
+    // Check the --start-time and --end-time range against the source duration
+    let subclip = audio::Subclip::new(args.start_time, args.end_time);
+    if !subclip.is_full() {
+        let metadata = audio::probe_video_metadata(&args.source)?;
+        subclip.validate(metadata.duration_sec)?;
+    }
+
+    // Audio, chapters and subtitles are read from the source as given, the rotated or resumed
+    // copies made below only keep what the processor needs
+    let media_source = args.source.clone();
 
+    // Bake the rotation into the frames so detection and cropping see the video upright
+    if source_rotation != 0 {
+        let rotated_source = format!("{}/rotated_source.mp4", output_dir);
+        println!("Normalizing {} degree source rotation...", source_rotation);
+        audio::normalize_rotation(&args.source, &rotated_source, source_rotation)?;
+        args.source = rotated_source;
+    }
+
+    // Seek past the frames the interrupted run already wrote and move the video's range to
+    // match. The audio and captions start at the same point through the subclip, unless the
+    // segments are joined with --concat-output and the audio covers both.
+    let subclip = if let Some(resume_dir) = &args.resume_from {
+        let state = audio::read_progress_file(resume_dir)?;
+        let resume_sec = audio::frame_seek_time(state.last_frame, audio::get_video_fps(&args.source)?);
+        let resumed_source = format!("{}/resumed_source.mp4", output_dir);
+        println!("Seeking to frame {} ({:.3}s)...", state.last_frame, resume_sec);
+        audio::seek_video(&args.source, resume_sec, &resumed_source)?;
+        args.source = resumed_source;
+
+        let video_subclip = subclip.shifted(resume_sec as f32);
+        if video_subclip.end_sec.is_some_and(|end_sec| end_sec <= 0.0) {
+            anyhow::bail!("The interrupted run already reached --end-time, nothing is left to resume");
+        }
+        args.start_time = Some(video_subclip.start_sec).filter(|start_sec| *start_sec > 0.0);
+        args.end_time = video_subclip.end_sec;
+        if args.concat_output.is_some() {
+            subclip
+        } else {
+            subclip.resumed_at(resume_sec as f32)
+        }
+    } else {
+        subclip
+    };
+
+    // Only save a thumbnail of the first frame, skipping audio and captions
+    if args.first_frame_only {
+        let mut processor = build_processor(&args)?;
+        processor.save_first_frame(&args, &output_dir)?;
+        return Ok(None);
+    }
+
+    if args.dry_run && args.add_captions {
+        println!("Dry run: skipping audio extraction, transcription and caption burning");
+    }
+
+    // If adding captions, prepare audio/transcription artifacts first
+    let (extracted_audio, srt_path) = if args.add_captions && !args.dry_run {
         let extracted_audio = format!("{}/extracted_audio.mp4", output_dir);
-        let compressed_audio = format!("{}/compressed_audio.mp3", output_dir);
+        let compressed_audio = format!(
+            "{}/compressed_audio.{}",
+            output_dir,
+            args.audio_codec.extension()
+        );
         let srt_path = format!("{}/transcript.srt", output_dir);
 
         // Extract audio from the source video
-        println!("Extracting audio from video...");
-        audio::extract_audio(&args.source, &extracted_audio)?;
+        if args.audio_track_index > 0 {
+            let track_count = audio::count_audio_tracks(&media_source)?;
+            if args.audio_track_index >= track_count {
+                anyhow::bail!(
+                    "--audio-track-index {} is out of range, the source has {} audio track(s)",
+                    args.audio_track_index,
+                    track_count
+                );
+            }
+            println!("Extracting audio track {} from video...", args.audio_track_index);
+            audio::extract_audio_track(&media_source, args.audio_track_index, &extracted_audio, &subclip)?;
+        } else {
+            println!("Extracting audio from video...");
+            audio::extract_audio(&media_source, &extracted_audio, &subclip)?;
+        }
         println!("✓ Audio extracted successfully to: {}", extracted_audio);
 
-        // Compress the extracted audio to MP3
-        println!("Compressing audio to MP3...");
-        audio::compress_to_mp3(&extracted_audio, &compressed_audio)?;
-        println!("✓ Audio compressed to MP3: {}", compressed_audio);
-
-        // Transcribe audio
-        println!("Transcribing audio to: {}", srt_path);
-        let transcript_config = transcript::TranscriptConfig::default();
-        transcript::transcribe_audio(
-            Path::new(&compressed_audio),
-            Path::new(&srt_path),
-            &transcript_config,
-        )
-        .await?;
-        println!("✓ Transcription completed successfully");
+        if args.captions_from_chapters {
+            // Use chapter titles as captions instead of transcribing speech
+            println!("Generating captions from chapters: {}", srt_path);
+            let chapters = audio::extract_chapters(&media_source)?;
+            if chapters.is_empty() {
+                anyhow::bail!("--captions-from-chapters was set but the source has no chapters");
+            }
+            fs::write(&srt_path, transcript::generate_chapter_srt(&chapters))?;
+            trim_captions_to_subclip(&srt_path, &subclip)?;
+            println!("✓ Generated captions for {} chapters", chapters.len());
+        } else if args.use_embedded_subtitles {
+            // Use the subtitles shipped with the source instead of transcribing speech
+            let stream_count = audio::count_subtitle_streams(&media_source)?;
+            if args.subtitle_stream_index >= stream_count {
+                anyhow::bail!(
+                    "--subtitle-stream-index {} is out of range, the source has {} subtitle stream(s)",
+                    args.subtitle_stream_index,
+                    stream_count
+                );
+            }
+            println!("Extracting subtitle stream {}: {}", args.subtitle_stream_index, srt_path);
+            if audio::is_closed_caption_stream(&media_source, args.subtitle_stream_index)? {
+                let cc_path = format!("{}/closed_captions.scc", output_dir);
+                audio::extract_subtitle_stream(&media_source, args.subtitle_stream_index, &cc_path)?;
+                audio::convert_cc_to_srt(&cc_path, &srt_path)?;
+            } else {
+                audio::extract_subtitle_stream(&media_source, args.subtitle_stream_index, &srt_path)?;
+            }
+            trim_captions_to_subclip(&srt_path, &subclip)?;
+            println!("✓ Embedded subtitles extracted successfully");
+        } else {
+            // Even out the loudness of quiet or very dynamic recordings before transcription
+            let transcription_audio = if args.normalize_audio {
+                let normalized_audio = format!("{}/normalized_audio.wav", output_dir);
+                println!("Normalizing audio loudness to {} LUFS...", args.target_lufs);
+                audio::normalize_audio(&extracted_audio, &normalized_audio, args.target_lufs)?;
+                println!("✓ Audio normalized: {}", normalized_audio);
+                normalized_audio
+            } else {
+                extracted_audio.clone()
+            };
+
+            // Pad the audio sent for transcription so caption timestamps match the original video
+            let transcription_audio = if args.audio_pad_start > 0.0 || args.audio_pad_end > 0.0 {
+                let padded_audio = format!("{}/padded_audio.mp4", output_dir);
+                println!("Padding audio with silence...");
+                audio::add_silence_padding(
+                    &transcription_audio,
+                    &padded_audio,
+                    args.audio_pad_start,
+                    args.audio_pad_end,
+                )?;
+                println!("✓ Audio padded: {}", padded_audio);
+                padded_audio
+            } else {
+                transcription_audio
+            };
+
+            // Compress the extracted audio with the selected codec
+            match args.audio_codec {
+                audio::AudioCodec::Mp3 => {
+                    println!("Compressing audio to MP3...");
+                    audio::compress_to_mp3(&transcription_audio, &compressed_audio)?;
+                    println!("✓ Audio compressed to MP3: {}", compressed_audio);
+                }
+                audio::AudioCodec::Aac => {
+                    println!("Compressing audio to AAC...");
+                    audio::compress_to_aac(
+                        &transcription_audio,
+                        &compressed_audio,
+                        audio::DEFAULT_AAC_BITRATE_KBPS,
+                    )?;
+                    println!("✓ Audio compressed to AAC: {}", compressed_audio);
+                }
+            }
+
+            // Transcribe audio
+            println!("Transcribing audio to: {}", srt_path);
+            let mut transcript_config = transcript::TranscriptConfig {
+                split_long_cues_ms: args.split_long_cues,
+                ..Default::default()
+            }
+            .with_backend(transcript::TranscriptBackend::from_name(
+                &args.transcription_backend,
+                args.whisper_model_path.as_deref(),
+            )?);
+            transcript_config = match args.transcript_language.as_deref() {
+                Some("auto") | None => transcript_config.auto_detect_language(),
+                Some(language) => transcript_config.with_language(language),
+            };
+            if let Some(temperature) = args.transcript_temperature {
+                if !(0.0..=1.0).contains(&temperature) {
+                    anyhow::bail!("--transcript-temperature must be between 0 and 1, got {}", temperature);
+                }
+                transcript_config = transcript_config.with_temperature(temperature);
+            }
+            transcript::transcribe_audio(
+                Path::new(&compressed_audio),
+                Path::new(&srt_path),
+                &transcript_config,
+            )
+            .await?;
+            println!("✓ Transcription completed successfully");
+        }
+
+        // Save WebVTT captions before the SRT is rewritten with ASS styling for burning
+        if args.caption_format == transcript::CaptionFormat::Vtt {
+            let vtt_path = format!("{}/transcript.{}", output_dir, args.caption_format.extension());
+            fs::write(&vtt_path, transcript::convert_srt_to_vtt(&fs::read_to_string(&srt_path)?))?;
+            println!("✓ WebVTT captions saved to: {}", vtt_path);
+        }
+
+        if args.srt_convert_html || args.srt_strip_html {
+            audio::rewrite_srt_html(&srt_path, args.srt_convert_html, args.srt_strip_html)?;
+            println!("✓ Caption formatting tags processed");
+        }
 
         (Some(extracted_audio), Some(srt_path))
     } else {
@@ -72,33 +466,91 @@ async fn main() -> Result<()> {
     };
 
 
-    // Choose processor based on object type and smoothing preference
     println!("Starting video processing...");
-    if args.object == "ball" {
-        let mut processor = ball_video_processor::BallVideoProcessor::new(&args);
-        processor.process_video(&args, &processed_video)?;
-    } else if args.use_simple_smoothing {
-        let mut processor = simple_smoothing_video_processor::SimpleSmoothingVideoProcessor::new();
-        processor.process_video(&args, &processed_video)?;
-    } else {
-        let mut processor = history_smoothing_video_processor::HistorySmoothingVideoProcessor::new(&args);
-        processor.process_video(&args, &processed_video)?;
+    let mut processor = build_processor(&args)?;
+    let mut processor_config = video_processor::VideoProcessorBuilder::new();
+    if let Some(batch_progress) = batch_progress {
+        processor_config = processor_config.with_batch_progress(batch_progress);
     }
+    processor.process_video(&args, &processed_video, processor_config.build())?;
     println!("✓ Video processing completed");
 
+    // Nothing was written, so there is no video to post-process
+    if args.dry_run {
+        println!("✓ Dry run completed, no output video was written");
+        return Ok(None);
+    }
 
-    if args.add_captions {
+    // Join the segment written before the interruption with the resumed one first, so frame
+    // rate conversion, encoding and captions treat both segments the same
+    let processed_video = match (&args.resume_from, &args.concat_output) {
+        (Some(resume_dir), Some(_)) => {
+            let interrupted_video = format!("{}/processed_video.mp4", resume_dir);
+            let joined_video = format!("{}/processed_video_joined.mp4", output_dir);
+            println!("Concatenating the interrupted and resumed video segments...");
+            audio::concat_videos(&[&interrupted_video, &processed_video], &joined_video)?;
+            println!("✓ Video segments concatenated: {}", joined_video);
+            joined_video
+        }
+        _ => processed_video,
+    };
+
+    // Resample the processed video for deliveries at a different frame rate
+    let processed_video = if args.output_fps > 0.0 {
+        let converted_video = format!("{}/processed_video_converted_fps.mp4", output_dir);
+        println!("Converting video to {} fps...", args.output_fps);
+        audio::convert_frame_rate(&processed_video, &converted_video, args.output_fps, &encoding)?;
+        let fps = audio::get_video_fps(&converted_video)?;
+        if (fps - args.output_fps).abs() > 0.01 {
+            anyhow::bail!(
+                "Frame rate conversion produced {:.3} fps instead of {:.3} fps",
+                fps,
+                args.output_fps
+            );
+        }
+        println!("✓ Video converted to {:.3} fps", fps);
+        converted_video
+    } else {
+        processed_video
+    };
+
+    // The viewer writes H.264, so re-encode when nothing later re-encodes with --output-codec
+    let processed_video = if !encoding.is_default()
+        && args.output_fps <= 0.0
+        && !args.add_captions
+        && args.icc_profile_path.is_none()
+    {
+        let encoded_video = format!("{}/processed_video_{}.mp4", output_dir, encoding.codec);
+        println!("Encoding video as {}...", encoding.codec);
+        audio::encode_video(&processed_video, &encoded_video, &encoding)?;
+        println!("✓ Video encoded: {}", encoded_video);
+        encoded_video
+    } else {
+        processed_video
+    };
+
+
+
+    let final_video = if args.add_captions {
+        let video_only = format!("{}/video_only.mp4", output_dir);
         let captioned_video = format!("{}/captioned_video.mp4", output_dir);
         let final_video = format!("{}/final_output.mp4", output_dir);
+
+        // Drop any audio from the processed video so only the extracted track ends up in the output
+        audio::extract_video_without_audio(&processed_video, &video_only)?;
     
         // Burn captions into the video
         println!("Burning captions into video...");
-        let caption_style = audio::CaptionStyle::default();
+        let caption_style = audio::CaptionStyle {
+            vertical_position: args.caption_position,
+            ..Default::default()
+        };
         audio::burn_captions(
-            &processed_video,
+            &video_only,
             &srt_path.as_ref().unwrap(),
             &captioned_video,
             Some(caption_style),
+            &encoding,
         )?;
         println!("✓ Captions burned successfully");
 
@@ -109,23 +561,47 @@ async fn main() -> Result<()> {
             "✓ Audio added successfully. Final video saved to: {}",
             final_video
         );
-
-        // Copy final video to output_filepath if specified
-        if !args.output_filepath.is_empty() {
-            println!("Copying final video to: {}", args.output_filepath);
-            fs::copy(&final_video, &args.output_filepath)?;
-            println!("✓ Final video copied successfully to: {}", args.output_filepath);
-        }
+        final_video
     } else {
         println!("✓ Processed video saved to: {}", processed_video);
-        
-        // Copy processed video to output_filepath if specified
-        if !args.output_filepath.is_empty() {
-            println!("Copying processed video to: {}", args.output_filepath);
-            fs::copy(&processed_video, &args.output_filepath)?;
-            println!("✓ Processed video copied successfully to: {}", args.output_filepath);
-        }
+        processed_video.clone()
+    };
+
+    // Tag the output with broadcast color metadata if requested
+    let final_video = if let Some(icc_path) = &args.icc_profile_path {
+        let color_tagged_video = format!("{}/color_tagged_output.mp4", output_dir);
+        println!("Embedding color profile: {}", icc_path);
+        audio::embed_icc_profile(&final_video, icc_path, &color_tagged_video, &encoding)?;
+        println!(
+            "✓ Color profile embedded. Final video saved to: {}",
+            color_tagged_video
+        );
+        color_tagged_video
+    } else if args.strip_icc_profile {
+        let stripped_video = format!("{}/color_stripped_output.mp4", output_dir);
+        println!("Stripping color profile...");
+        audio::strip_icc_profile(&final_video, &stripped_video, encoding.codec)?;
+        println!(
+            "✓ Color profile stripped. Final video saved to: {}",
+            stripped_video
+        );
+        stripped_video
+    } else {
+        final_video
+    };
+
+    // Copy final video to output_filepath if specified
+    if !args.output_filepath.is_empty() {
+        println!("Copying final video to: {}", args.output_filepath);
+        fs::copy(&final_video, &args.output_filepath)?;
+        println!("✓ Final video copied successfully to: {}", args.output_filepath);
     }
 
-    Ok(())
+    if let Some(concat_output) = &args.concat_output {
+        println!("Copying concatenated video to: {}", concat_output);
+        fs::copy(&final_video, concat_output)?;
+        println!("✓ Concatenated video copied successfully to: {}", concat_output);
+    }
+
+    Ok(Some(final_video))
 }
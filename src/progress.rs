@@ -1,6 +1,16 @@
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Instant;
 
+/// A progress tracker shared between the processing loop and the progress reporter
+pub type SharedProgressTracker = Arc<Mutex<VideoProgressTracker>>;
+
+/// Locks a shared progress tracker, recovering it if the progress reporter panicked
+pub fn lock_progress(tracker: &Mutex<VideoProgressTracker>) -> MutexGuard<'_, VideoProgressTracker> {
+    tracker.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// Progress tracker for video processing operations
 pub struct VideoProgressTracker {
     progress_bar: ProgressBar,
@@ -8,6 +18,17 @@ pub struct VideoProgressTracker {
     total_frames: Option<u64>,
     frame_rate: f64,
     processed_frames: u64,
+    batch: Option<BatchProgress>,
+}
+
+/// Outer progress state when tracking a batch of videos
+struct BatchProgress {
+    // Kept alive so both bars stay attached to the same terminal area
+    _multi_progress: MultiProgress,
+    video_bar: ProgressBar,
+    total_videos: usize,
+    current_video: Option<usize>,
+    completed_videos: usize,
 }
 
 impl VideoProgressTracker {
@@ -30,6 +51,7 @@ impl VideoProgressTracker {
             total_frames: Some(total_frames),
             frame_rate,
             processed_frames: 0,
+            batch: None,
         }
     }
 
@@ -51,9 +73,81 @@ impl VideoProgressTracker {
             total_frames: None,
             frame_rate,
             processed_frames: 0,
+            batch: None,
+        }
+    }
+
+    /// Creates a progress tracker for a batch of videos
+    ///
+    /// An outer bar shows which video is being processed (e.g. `[2/5] video_name.mp4`) and an
+    /// inner bar shows frame progress across the whole batch, so it does not reset between videos.
+    ///
+    /// # Arguments
+    /// * `total_videos` - Number of videos in the batch
+    /// * `total_estimated_frames` - Estimated number of frames across all videos
+    /// * `frame_rate` - Frame rate used to convert frames to video time
+    pub fn new_batch(total_videos: usize, total_estimated_frames: u64, frame_rate: f64) -> Self {
+        let multi_progress = MultiProgress::new();
+
+        let video_bar = multi_progress.add(ProgressBar::new(total_videos as u64));
+        let video_style = ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.yellow/blue}] {msg}")
+            .unwrap()
+            .progress_chars("#>-");
+        video_bar.set_style(video_style);
+        video_bar.set_message(format!("[0/{}] Starting...", total_videos));
+
+        let tracker = Self::new(total_estimated_frames, frame_rate, "batch");
+        let progress_bar = multi_progress.add(tracker.progress_bar.clone());
+
+        Self {
+            progress_bar,
+            batch: Some(BatchProgress {
+                _multi_progress: multi_progress,
+                video_bar,
+                total_videos,
+                current_video: None,
+                completed_videos: 0,
+            }),
+            ..tracker
         }
     }
 
+    /// Marks the start of a video in a batch
+    ///
+    /// # Arguments
+    /// * `index` - Zero-based index of the video in the batch
+    /// * `video_name` - Name shown next to the batch counter
+    pub fn begin_video(&mut self, index: usize, video_name: &str) {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.current_video = Some(index);
+            batch
+                .video_bar
+                .set_message(format!("[{}/{}] {}", index + 1, batch.total_videos, video_name));
+        }
+    }
+
+    /// Marks the current video in a batch as completed
+    pub fn end_video(&mut self) {
+        let Some(batch) = self.batch.as_mut() else {
+            return;
+        };
+        if batch.current_video.take().is_some() {
+            batch.completed_videos += 1;
+            batch.video_bar.inc(1);
+        }
+    }
+
+    /// Gets the number of completed videos in a batch (0 when not tracking a batch)
+    pub fn completed_videos(&self) -> usize {
+        self.batch.as_ref().map_or(0, |batch| batch.completed_videos)
+    }
+
+    /// Gets the zero-based index of the video currently being processed in a batch
+    pub fn current_video(&self) -> Option<usize> {
+        self.batch.as_ref().and_then(|batch| batch.current_video)
+    }
+
     /// Updates the progress by one frame
     pub fn update_frame(&mut self) {
         self.processed_frames += 1;
@@ -162,6 +256,12 @@ impl VideoProgressTracker {
         };
         
         self.progress_bar.finish_with_message(message);
+        if let Some(batch) = &self.batch {
+            batch.video_bar.finish_with_message(format!(
+                "Completed {}/{} videos",
+                batch.completed_videos, batch.total_videos
+            ));
+        }
     }
 
     /// Gets the total number of frames
@@ -178,6 +278,94 @@ impl VideoProgressTracker {
     pub fn frame_rate(&self) -> f64 {
         self.frame_rate
     }
+
+    /// Serializes the current progress as a single line of JSON for scripts and dashboards
+    ///
+    /// `total_frames` and `eta_sec` are `null` when the total frame count is unknown.
+    pub fn to_json_summary(&self) -> String {
+        let elapsed_sec = self.start_time.elapsed().as_secs_f64();
+        let avg_fps = if elapsed_sec > 0.0 {
+            self.processed_frames as f64 / elapsed_sec
+        } else {
+            0.0
+        };
+        let eta_sec = self
+            .total_frames
+            .filter(|_| avg_fps > 0.0)
+            .map(|total_frames| total_frames.saturating_sub(self.processed_frames) as f64 / avg_fps);
+
+        serde_json::json!({
+            "processed_frames": self.processed_frames,
+            "total_frames": self.total_frames,
+            "elapsed_sec": elapsed_sec,
+            "avg_fps": avg_fps,
+            "eta_sec": eta_sec,
+            "current_video_time": self.get_current_time(),
+        })
+        .to_string()
+    }
+}
+
+/// Writes the JSON progress summary whenever the process receives SIGUSR1
+///
+/// Summaries go to `pipe_path` (e.g. a named pipe created with `mkfifo`) when given, otherwise
+/// to stderr. The listener runs on the tokio runtime and stops once the tracker is dropped.
+/// Only supported on Unix; elsewhere a warning is printed.
+///
+/// # Arguments
+/// * `tracker` - The tracker to report on
+/// * `pipe_path` - Where to write the summaries instead of stderr
+pub fn register_signal_handler(tracker: SharedProgressTracker, pipe_path: Option<String>) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            println!("Warning: no async runtime available, skipping progress signal handler");
+            return;
+        };
+        let tracker = Arc::downgrade(&tracker);
+        runtime.spawn(async move {
+            let mut signals = match signal(SignalKind::user_defined1()) {
+                Ok(signals) => signals,
+                Err(e) => {
+                    println!("Warning: failed to listen for SIGUSR1: {}", e);
+                    return;
+                }
+            };
+            while signals.recv().await.is_some() {
+                let Some(tracker) = tracker.upgrade() else {
+                    break;
+                };
+                let summary = match tracker.lock() {
+                    Ok(tracker) => tracker.to_json_summary(),
+                    Err(_) => break,
+                };
+                if let Err(e) = write_progress_summary(&summary, pipe_path.as_deref()) {
+                    println!("Warning: failed to write progress summary: {}", e);
+                }
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (tracker, pipe_path);
+        println!("Warning: progress reporting on SIGUSR1 is only supported on Unix");
+    }
+}
+
+/// Writes one progress summary line to the pipe, or to stderr without one
+fn write_progress_summary(summary: &str, pipe_path: Option<&str>) -> std::io::Result<()> {
+    match pipe_path {
+        Some(path) => {
+            let mut pipe = std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)?;
+            writeln!(pipe, "{}", summary)
+        }
+        None => writeln!(std::io::stderr(), "{}", summary),
+    }
 }
 
 /// Formats a duration in seconds to h:mm:ss format
@@ -214,4 +402,61 @@ mod tests {
         assert_eq!(tracker.frame_rate(), 30.0);
         assert_eq!(tracker.processed_frames(), 0);
     }
+
+    #[test]
+    fn test_to_json_summary() {
+        let mut tracker = VideoProgressTracker::new(3000, 30.0, "test video");
+        tracker.update_frames(1800);
+        let summary: serde_json::Value = serde_json::from_str(&tracker.to_json_summary()).unwrap();
+        assert_eq!(summary["processed_frames"], 1800);
+        assert_eq!(summary["total_frames"], 3000);
+        assert_eq!(summary["current_video_time"], "1:00");
+        assert!(summary["elapsed_sec"].as_f64().unwrap() >= 0.0);
+        assert!(summary["avg_fps"].is_number());
+
+        // Without a total frame count there is no ETA
+        let mut tracker = VideoProgressTracker::new_unknown_total(25.0, "test video");
+        tracker.update_frames(50);
+        let summary: serde_json::Value = serde_json::from_str(&tracker.to_json_summary()).unwrap();
+        assert_eq!(summary["processed_frames"], 50);
+        assert!(summary["total_frames"].is_null());
+        assert!(summary["eta_sec"].is_null());
+        assert_eq!(summary["current_video_time"], "0:02");
+    }
+
+    #[test]
+    fn test_batch_counter_advances() {
+        let mut tracker = VideoProgressTracker::new_batch(3, 900, 30.0);
+        assert_eq!(tracker.total_frames(), Some(900));
+        assert_eq!(tracker.completed_videos(), 0);
+        assert_eq!(tracker.current_video(), None);
+
+        tracker.begin_video(0, "first.mp4");
+        assert_eq!(tracker.current_video(), Some(0));
+        tracker.update_frames(300);
+        tracker.end_video();
+        assert_eq!(tracker.completed_videos(), 1);
+        assert_eq!(tracker.current_video(), None);
+
+        // Ending without a started video does not advance the counter
+        tracker.end_video();
+        assert_eq!(tracker.completed_videos(), 1);
+
+        tracker.begin_video(1, "second.mp4");
+        tracker.update_frames(300);
+        tracker.end_video();
+        assert_eq!(tracker.completed_videos(), 2);
+
+        // Frame progress accumulates across videos
+        assert_eq!(tracker.processed_frames(), 600);
+    }
+
+    #[test]
+    fn test_single_video_tracker_ignores_batch_calls() {
+        let mut tracker = VideoProgressTracker::new(100, 30.0, "test video");
+        tracker.begin_video(0, "video.mp4");
+        tracker.end_video();
+        assert_eq!(tracker.completed_videos(), 0);
+        assert_eq!(tracker.current_video(), None);
+    }
 }
@@ -1,20 +1,136 @@
 use crate::cli::Args;
 use crate::crop;
 use crate::video_processor_utils;
+use crate::video_processor_utils::CropRenderer;
 use crate::video_processor::VideoProcessor;
 use anyhow::Result;
+use std::collections::VecDeque;
 use usls::Viewer;
 
 /// Video processor that handles cropping with simple smoothing (no history)
 pub struct SimpleSmoothingVideoProcessor {
-    previous_crop: Option<crop::CropResult>
+    previous_crop: Option<crop::CropResult>,
+    median_window: Option<usize>,
+    median_buffer: VecDeque<crop::CropArea>,
+    lookahead_frames: usize,
+    history_buffer: VecDeque<(usls::Image, crop::CropResult)>,
+    confidence_blend: bool,
+    previous_confidence: f32,
 }
 
 impl SimpleSmoothingVideoProcessor {
     /// Creates a new simple smoothing video processor
     pub fn new() -> Self {
         Self {
-            previous_crop: None
+            previous_crop: None,
+            median_window: None,
+            median_buffer: VecDeque::new(),
+            lookahead_frames: 0,
+            history_buffer: VecDeque::new(),
+            confidence_blend: false,
+            previous_confidence: 0.0,
+        }
+    }
+
+    /// Blends towards dissimilar single crops by detection confidence instead of snapping to them
+    pub fn with_confidence_blend(mut self) -> Self {
+        self.confidence_blend = true;
+        self
+    }
+
+    /// Returns the crop to use for a dissimilar latest crop, blending single crops by confidence
+    /// when enabled, and records the confidence behind the returned crop
+    fn blend_by_confidence(
+        &mut self,
+        prev_crop: &crop::CropResult,
+        latest_crop: &crop::CropResult,
+        new_confidence: Option<f32>,
+    ) -> crop::CropResult {
+        let Some(new_confidence) = new_confidence else {
+            self.previous_confidence = 0.0;
+            return latest_crop.clone();
+        };
+        match (self.confidence_blend, prev_crop, latest_crop) {
+            (true, crop::CropResult::Single(prev), crop::CropResult::Single(latest)) => {
+                let blended = crop::confidence_weighted_blend(prev, latest, self.previous_confidence, new_confidence);
+                // The blended crop is only partly backed by the new heads
+                if new_confidence < self.previous_confidence {
+                    let progress = new_confidence / self.previous_confidence;
+                    self.previous_confidence += (new_confidence - self.previous_confidence) * progress;
+                } else {
+                    self.previous_confidence = new_confidence;
+                }
+                crop::CropResult::Single(blended)
+            }
+            _ => {
+                self.previous_confidence = new_confidence;
+                latest_crop.clone()
+            }
+        }
+    }
+
+    /// Delays output by `frames` frames so each crop decision can see the following crops
+    ///
+    /// A crop change is only applied when it persists through the lookahead window, which
+    /// suppresses single-frame detection glitches. Takes precedence over the median filter.
+    pub fn with_lookahead(mut self, frames: usize) -> Self {
+        self.lookahead_frames = frames;
+        self.history_buffer = VecDeque::with_capacity(frames + 1);
+        self
+    }
+
+    /// Emits the oldest buffered frame using the crops buffered after it as lookahead
+    fn emit_buffered_frame(
+        &mut self,
+        args: &Args,
+        viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
+    ) -> Result<()> {
+        let Some((img, crop_result)) = self.history_buffer.pop_front() else {
+            return Ok(());
+        };
+
+        let mut window = Vec::with_capacity(self.history_buffer.len() + 1);
+        window.push(crop_result);
+        window.extend(self.history_buffer.iter().map(|(_, crop)| crop.clone()));
+
+        let chosen = video_processor_utils::choose_lookahead_crop(
+            self.previous_crop.as_ref(),
+            &window,
+            img.width() as f32,
+            args.smooth_percentage,
+        )
+        .unwrap_or_else(|| window[0].clone());
+        video_processor_utils::debug_println(format_args!("Using lookahead crop: {:?}", chosen));
+        video_processor_utils::process_and_display_crop(&img, &chosen, viewer, args.headless, args.pillarbox, renderer)?;
+        self.previous_crop = Some(chosen);
+        Ok(())
+    }
+
+    /// Uses the per-field median of the last `window` single crops instead of threshold gating
+    pub fn with_median_filter(mut self, window: usize) -> Self {
+        self.median_window = Some(window.max(1));
+        self.median_buffer = VecDeque::with_capacity(window.max(1));
+        self
+    }
+
+    /// Pushes a single crop into the median window and returns the median crop,
+    /// or clears the window and returns `None` for other crop types
+    fn median_filtered_crop(&mut self, latest_crop: &crop::CropResult) -> Option<crop::CropResult> {
+        let window = self.median_window?;
+        match latest_crop {
+            crop::CropResult::Single(area) => {
+                if self.median_buffer.len() == window {
+                    self.median_buffer.pop_front();
+                }
+                self.median_buffer.push_back(area.clone());
+                let areas: Vec<crop::CropArea> = self.median_buffer.iter().cloned().collect();
+                Some(crop::CropResult::Single(crop::median_crop_area(&areas)))
+            }
+            _ => {
+                self.median_buffer.clear();
+                None
+            }
         }
     }
 }
@@ -25,11 +141,29 @@ impl VideoProcessor for SimpleSmoothingVideoProcessor {
         &mut self,
         img: &usls::Image,
         latest_crop: &crop::CropResult,
-        _objects: &[&usls::Hbb],
+        objects: &[&usls::Hbb],
         args: &Args,
         viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
         _smooth_duration_frames: usize,
     ) -> Result<()> {
+        // With lookahead, buffer the frame and emit the oldest one once the window is full
+        if self.lookahead_frames > 0 {
+            self.history_buffer.push_back((img.clone(), latest_crop.clone()));
+            if self.history_buffer.len() > self.lookahead_frames {
+                self.emit_buffered_frame(args, viewer, renderer)?;
+            }
+            return Ok(());
+        }
+
+        // Median filtering replaces threshold gating for single crops when enabled
+        if let Some(median_crop) = self.median_filtered_crop(latest_crop) {
+            video_processor_utils::debug_println(format_args!("Using median crop: {:?}", median_crop));
+            video_processor_utils::process_and_display_crop(img, &median_crop, viewer, args.headless, args.pillarbox, renderer)?;
+            self.previous_crop = Some(median_crop);
+            return Ok(());
+        }
+
         // Compare with previous crop if it exists and determine which crop to use
         let (crop_result, should_use_previous) = if let Some(prev_crop) = &self.previous_crop {
             let is_latest_crop_similar = crop::is_crop_similar(
@@ -44,10 +178,12 @@ impl VideoProcessor for SimpleSmoothingVideoProcessor {
                 (prev_crop.clone(), true)
             } else {
                 video_processor_utils::debug_println(format_args!("Using latest crop (not similar)"));
-                (latest_crop.clone(), false)
+                let prev_crop = prev_crop.clone();
+                (self.blend_by_confidence(&prev_crop, latest_crop, average_confidence(objects)), false)
             }
         } else {
             video_processor_utils::debug_println(format_args!("No previous crop, using latest crop"));
+            self.previous_confidence = average_confidence(objects).unwrap_or(0.0);
             (latest_crop.clone(), false)
         };
 
@@ -59,7 +195,30 @@ impl VideoProcessor for SimpleSmoothingVideoProcessor {
         }
 
         // Process and display the chosen crop
-        video_processor_utils::process_and_display_crop(img, &crop_result, viewer, args.headless)?;
+        video_processor_utils::process_and_display_crop(img, &crop_result, viewer, args.headless, args.pillarbox, renderer)?;
+        Ok(())
+    }
+
+    /// Returns the crop most recently applied to an output frame
+    fn last_applied_crop(&self) -> Option<&crop::CropResult> {
+        self.previous_crop.as_ref()
+    }
+
+    /// Restores the previous crop from a resumed run
+    fn restore_previous_crop(&mut self, crop: crop::CropResult) {
+        self.previous_crop = Some(crop);
+    }
+
+    /// Flushes the frames still held in the lookahead buffer
+    fn finalize_processing(
+        &mut self,
+        args: &Args,
+        viewer: &mut Viewer,
+        renderer: &mut CropRenderer,
+    ) -> Result<()> {
+        while !self.history_buffer.is_empty() {
+            self.emit_buffered_frame(args, viewer, renderer)?;
+        }
         Ok(())
     }
 
@@ -70,3 +229,54 @@ impl VideoProcessor for SimpleSmoothingVideoProcessor {
     }
 }
 
+/// Averages the confidence of the detected heads, or `None` when there are none
+fn average_confidence(objects: &[&usls::Hbb]) -> Option<f32> {
+    if objects.is_empty() {
+        return None;
+    }
+    let total: f32 = objects.iter().map(|h| h.confidence().unwrap_or(1.0)).sum();
+    Some(total / objects.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(x: f32) -> crop::CropResult {
+        crop::CropResult::Single(crop::CropArea::new(x, 0.0, 810.0, 1080.0))
+    }
+
+    #[test]
+    fn test_blend_by_confidence_high_confidence_transitions_immediately() {
+        let mut processor = SimpleSmoothingVideoProcessor::new().with_confidence_blend();
+        processor.previous_confidence = 0.5;
+
+        let chosen = processor.blend_by_confidence(&single(0.0), &single(1000.0), Some(0.9));
+        assert_eq!(chosen, single(1000.0));
+        assert_eq!(processor.previous_confidence, 0.9);
+    }
+
+    #[test]
+    fn test_blend_by_confidence_low_confidence_blends_slowly() {
+        let mut processor = SimpleSmoothingVideoProcessor::new().with_confidence_blend();
+        processor.previous_confidence = 0.8;
+
+        let chosen = processor.blend_by_confidence(&single(0.0), &single(1000.0), Some(0.4));
+        assert_eq!(chosen, single(500.0));
+        // The confidence drops towards the new heads, so repeated low detections keep moving the crop
+        assert!(processor.previous_confidence < 0.8 && processor.previous_confidence > 0.4);
+
+        let next = processor.blend_by_confidence(&single(500.0), &single(1000.0), Some(0.4));
+        let crop::CropResult::Single(area) = next else { panic!("expected a single crop") };
+        assert!(area.x > 500.0 && area.x < 1000.0);
+    }
+
+    #[test]
+    fn test_blend_by_confidence_disabled_snaps() {
+        let mut processor = SimpleSmoothingVideoProcessor::new();
+        processor.previous_confidence = 0.8;
+
+        let chosen = processor.blend_by_confidence(&single(0.0), &single(1000.0), Some(0.1));
+        assert_eq!(chosen, single(1000.0));
+    }
+}
@@ -1,41 +1,226 @@
+#[cfg(feature = "local-whisper")]
+use crate::audio;
+use crate::audio::Chapter;
 use anyhow::{Result, anyhow};
 use openai_api_rs::v1::audio::{AudioTranscriptionRequest, WHISPER_1};
 use openai_api_rs::v1::api::OpenAIClient;
 use std::path::Path;
+#[cfg(feature = "local-whisper")]
+use std::path::PathBuf;
 use std::env;
 use std::fs;
+#[cfg(feature = "local-whisper")]
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+/// Where audio is transcribed
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TranscriptBackend {
+    /// The OpenAI Whisper API, which needs `OPENAI_API_KEY`
+    #[default]
+    OpenAI,
+    /// A local Whisper model in GGML format, run with whisper.cpp
+    #[cfg(feature = "local-whisper")]
+    Local { model_path: PathBuf },
+}
+
+impl TranscriptBackend {
+    /// Builds the backend selected by `--transcription-backend`
+    ///
+    /// # Arguments
+    /// * `name` - `openai` or `local`
+    /// * `model_path` - The GGML model file, required for `local`
+    pub fn from_name(name: &str, model_path: Option<&str>) -> Result<Self> {
+        match (name.to_lowercase().as_str(), model_path) {
+            ("openai", _) => Ok(TranscriptBackend::OpenAI),
+            #[cfg(feature = "local-whisper")]
+            ("local", Some(model_path)) => Ok(TranscriptBackend::Local {
+                model_path: PathBuf::from(model_path),
+            }),
+            #[cfg(feature = "local-whisper")]
+            ("local", None) => Err(anyhow!("--transcription-backend local requires --whisper-model-path")),
+            #[cfg(not(feature = "local-whisper"))]
+            ("local", _) => Err(anyhow!(
+                "--transcription-backend local needs land2port built with the local-whisper feature"
+            )),
+            _ => Err(anyhow!(
+                "unsupported transcription backend '{}', expected openai or local",
+                name
+            )),
+        }
+    }
+}
 
 pub struct TranscriptConfig {
+    /// Which Whisper runs the transcription
+    pub backend: TranscriptBackend,
     pub api_key: String,
     pub model: String,
+    /// Split cues longer than this many milliseconds before writing (0 = disabled)
+    pub split_long_cues_ms: u32,
+    /// ISO-639-1 source language (e.g. "en"); Whisper detects it when `None`
+    pub language: Option<String>,
+    /// Sampling temperature; the API default is used when `None`
+    pub temperature: Option<f32>,
 }
 
 impl Default for TranscriptConfig {
     fn default() -> Self {
         Self {
+            backend: TranscriptBackend::default(),
             api_key: env::var("OPENAI_API_KEY").unwrap_or_default(),
             model: WHISPER_1.to_string(),
+            split_long_cues_ms: 0,
+            language: None,
+            temperature: None,
         }
     }
 }
 
-pub async fn transcribe_audio(audio_path: &Path, output_path: &Path, config: &TranscriptConfig) -> Result<()> {
-    let mut client = OpenAIClient::builder()
-        .with_api_key(&config.api_key)
-        .build()
-        .map_err(|e| anyhow!("Failed to create OpenAI client: {}", e))?;
-    
+impl TranscriptConfig {
+    /// Forces the source language instead of letting Whisper detect it
+    pub fn with_language(mut self, lang: &str) -> Self {
+        self.language = Some(lang.to_string());
+        self
+    }
+
+    /// Lets Whisper detect the source language from the audio
+    pub fn auto_detect_language(mut self) -> Self {
+        self.language = None;
+        self
+    }
+
+    /// Sets the Whisper sampling temperature
+    pub fn with_temperature(mut self, temp: f32) -> Self {
+        self.temperature = Some(temp);
+        self
+    }
+
+    /// Selects where the audio is transcribed
+    pub fn with_backend(mut self, backend: TranscriptBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+}
+
+/// Builds the Whisper request for an audio file, asking for SRT output
+fn build_transcription_request(audio_path: &Path, config: &TranscriptConfig) -> AudioTranscriptionRequest {
     let mut request = AudioTranscriptionRequest::new(
         audio_path.to_string_lossy().to_string(),
         config.model.clone(),
     );
     request.response_format = Some("srt".to_string());
+    request.language = config.language.clone();
+    request.temperature = config.temperature;
+    request
+}
+
+/// Transcribes audio with the OpenAI Whisper API, returning SRT content
+async fn openai_transcribe_audio(audio_path: &Path, config: &TranscriptConfig) -> Result<String> {
+    let mut client = OpenAIClient::builder()
+        .with_api_key(&config.api_key)
+        .build()
+        .map_err(|e| anyhow!("Failed to create OpenAI client: {}", e))?;
+    
+    let request = build_transcription_request(audio_path, config);
 
     let response = client.audio_transcription_raw(request)
         .await
         .map_err(|e| anyhow!("Failed to transcribe audio: {}", e))?;
     
-    let srt_content = String::from_utf8_lossy(&response).to_string();
+    Ok(String::from_utf8_lossy(&response).to_string())
+}
+
+/// Formats Whisper segments as SRT, matching the API's SRT output
+///
+/// Segment times are in Whisper's 10 ms units. Segments without text are left out.
+#[cfg(feature = "local-whisper")]
+fn whisper_segments_to_srt(segments: &[(i64, i64, String)]) -> String {
+    segments
+        .iter()
+        .filter(|(_, _, text)| !text.trim().is_empty())
+        .enumerate()
+        .map(|(i, (start, end, text))| {
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                format_srt_timestamp((*start).max(0) as u64 * 10),
+                format_srt_timestamp((*end).max(0) as u64 * 10),
+                text.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Transcribes audio with a local Whisper model, returning SRT content
+///
+/// The audio is decoded with ffmpeg, so any format ffmpeg reads works. No API key or network
+/// access is needed.
+///
+/// # Arguments
+/// * `model_path` - The Whisper model file in GGML format, e.g. `ggml-base.en.bin`
+/// * `audio_path` - The audio to transcribe
+/// * `config` - The language and temperature to use
+#[cfg(feature = "local-whisper")]
+pub fn local_transcribe_audio(model_path: &Path, audio_path: &Path, config: &TranscriptConfig) -> Result<String> {
+    let samples = audio::decode_whisper_samples(&audio_path.to_string_lossy())?;
+
+    let context = WhisperContext::new_with_params(
+        &model_path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .map_err(|e| anyhow!("Failed to load Whisper model {}: {}", model_path.display(), e))?;
+    let mut state = context
+        .create_state()
+        .map_err(|e| anyhow!("Failed to create Whisper state: {}", e))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(config.language.as_deref().unwrap_or("auto")));
+    if let Some(temperature) = config.temperature {
+        params.set_temperature(temperature);
+    }
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+
+    state
+        .full(params, &samples)
+        .map_err(|e| anyhow!("Failed to transcribe audio: {}", e))?;
+
+    let segment_count = state
+        .full_n_segments()
+        .map_err(|e| anyhow!("Failed to read Whisper segments: {}", e))?;
+    let mut segments = Vec::with_capacity(segment_count.max(0) as usize);
+    for i in 0..segment_count {
+        let text = state
+            .full_get_segment_text(i)
+            .map_err(|e| anyhow!("Failed to read Whisper segment text: {}", e))?;
+        let start = state
+            .full_get_segment_t0(i)
+            .map_err(|e| anyhow!("Failed to read Whisper segment start: {}", e))?;
+        let end = state
+            .full_get_segment_t1(i)
+            .map_err(|e| anyhow!("Failed to read Whisper segment end: {}", e))?;
+        segments.push((start, end, text));
+    }
+
+    Ok(whisper_segments_to_srt(&segments))
+}
+
+/// Transcribes audio to an SRT file with the configured backend
+pub async fn transcribe_audio(audio_path: &Path, output_path: &Path, config: &TranscriptConfig) -> Result<()> {
+    let mut srt_content = match &config.backend {
+        TranscriptBackend::OpenAI => openai_transcribe_audio(audio_path, config).await?,
+        #[cfg(feature = "local-whisper")]
+        TranscriptBackend::Local { model_path } => local_transcribe_audio(model_path, audio_path, config)?,
+    };
+    if config.split_long_cues_ms > 0 {
+        srt_content = split_long_srt_cues(&srt_content, config.split_long_cues_ms);
+    }
+
+    // Catch malformed captions here rather than as a cryptic ffmpeg error when burning them in
+    validate_srt_format(&srt_content)
+        .map_err(|e| anyhow!("Transcription produced invalid SRT: {}", e))?;
     
     // Create parent directories if they don't exist
     if let Some(parent) = output_path.parent() {
@@ -48,4 +233,657 @@ pub async fn transcribe_audio(audio_path: &Path, output_path: &Path, config: &Tr
         .map_err(|e| anyhow!("Failed to write SRT file: {}", e))?;
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// A single parsed SRT cue with times in milliseconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct SrtCue {
+    pub index: usize,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Parses an SRT timestamp of the form `HH:MM:SS,mmm` into milliseconds
+fn parse_srt_timestamp(timestamp: &str) -> Option<u64> {
+    let (hms, millis) = timestamp.trim().split_once(',')?;
+    let mut parts = hms.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: u64 = parts.next()?.parse().ok()?;
+    let millis: u64 = millis.parse().ok()?;
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
+
+/// Formats milliseconds as an SRT timestamp of the form `HH:MM:SS,mmm`
+fn format_srt_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+/// Parses SRT content into cues, skipping blocks without a valid timing line
+fn parse_srt_cues(srt_content: &str) -> Vec<SrtCue> {
+    let normalized = srt_content.replace("\r\n", "\n");
+    let mut cues = Vec::new();
+
+    for block in normalized.split("\n\n") {
+        let lines: Vec<&str> = block.lines().filter(|l| !l.trim().is_empty()).collect();
+        let Some(timing_index) = lines.iter().position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let Some((start, end)) = lines[timing_index].split_once("-->") else {
+            continue;
+        };
+        let (Some(start_ms), Some(end_ms)) = (parse_srt_timestamp(start), parse_srt_timestamp(end))
+        else {
+            continue;
+        };
+
+        cues.push(SrtCue {
+            index: cues.len() + 1,
+            start_ms,
+            end_ms,
+            text: lines[timing_index + 1..].join("\n"),
+        });
+    }
+
+    cues
+}
+
+/// Strictly parses SRT content, checking every cue
+///
+/// Cue indices must run from 1 without gaps, each cue must not end before it starts or start
+/// before the previous cue ends, and its text must not be empty. Content without any cues (e.g.
+/// a silent transcription) and zero-length cues are valid, `burn_captions` handles both.
+///
+/// # Returns
+/// * `Result<Vec<SrtCue>>` - The parsed cues, or an error listing every violation
+pub fn validate_srt_format(content: &str) -> Result<Vec<SrtCue>> {
+    let normalized = content.replace("\r\n", "\n");
+    let mut cues: Vec<SrtCue> = Vec::new();
+    let mut violations = Vec::new();
+
+    let blocks = normalized.split("\n\n").filter(|block| !block.trim().is_empty());
+    for (position, block) in blocks.enumerate() {
+        let expected_index = position + 1;
+        let mut lines = block.trim_matches('\n').lines();
+        let index_line = lines.next().unwrap_or_default().trim();
+        let Ok(index) = index_line.parse::<usize>() else {
+            violations.push(format!("cue {}: invalid index '{}'", expected_index, index_line));
+            continue;
+        };
+        if index != expected_index {
+            violations.push(format!("cue {}: index {} is out of sequence", expected_index, index));
+        }
+
+        let timing_line = lines.next().unwrap_or_default();
+        let timing = timing_line
+            .split_once("-->")
+            .and_then(|(start, end)| Some((parse_srt_timestamp(start)?, parse_srt_timestamp(end)?)));
+        let Some((start_ms, end_ms)) = timing else {
+            violations.push(format!("cue {}: invalid timing line '{}'", index, timing_line.trim()));
+            continue;
+        };
+        if start_ms > end_ms {
+            violations.push(format!("cue {}: ends before it starts", index));
+        }
+        if let Some(previous) = cues.last() {
+            if start_ms < previous.end_ms {
+                violations.push(format!("cue {}: overlaps cue {}", index, previous.index));
+            }
+        }
+
+        let text = lines.collect::<Vec<_>>().join("\n");
+        if text.trim().is_empty() {
+            violations.push(format!("cue {}: text is empty", index));
+        }
+
+        cues.push(SrtCue {
+            index,
+            start_ms,
+            end_ms,
+            text,
+        });
+    }
+
+    if !violations.is_empty() {
+        return Err(anyhow!(
+            "{} problem(s) found:\n  {}",
+            violations.len(),
+            violations.join("\n  ")
+        ));
+    }
+    Ok(cues)
+}
+
+/// Splits text into sentences, keeping the terminating `.`, `?` or `!` with each sentence
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        if word.ends_with(['.', '?', '!']) {
+            sentences.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        sentences.push(current);
+    }
+
+    sentences
+}
+
+/// Splits text into `parts` chunks with a roughly equal number of words
+fn split_words_evenly(text: &str, parts: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let parts = parts.clamp(1, words.len().max(1));
+    (0..parts)
+        .map(|i| words[i * words.len() / parts..(i + 1) * words.len() / parts].join(" "))
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+/// Splits SRT cues that last longer than `max_duration_ms` into shorter cues
+///
+/// Long cue text is split at sentence boundaries (`.`, `?`, `!`). A long cue made of a single
+/// sentence is split into evenly sized word groups instead. The original duration is distributed
+/// among the parts in proportion to their text length and all cues are re-numbered.
+///
+/// # Arguments
+/// * `srt_content` - The SRT content to process
+/// * `max_duration_ms` - The longest allowed cue duration in milliseconds
+///
+/// # Returns
+/// * `String` - The SRT content with long cues split
+pub fn split_long_srt_cues(srt_content: &str, max_duration_ms: u32) -> String {
+    let max_duration_ms = max_duration_ms as u64;
+    let mut output_cues = Vec::new();
+
+    for cue in parse_srt_cues(srt_content) {
+        let duration = cue.end_ms.saturating_sub(cue.start_ms);
+        if max_duration_ms == 0 || duration <= max_duration_ms {
+            output_cues.push(cue);
+            continue;
+        }
+
+        let mut parts = split_sentences(&cue.text);
+        if parts.len() < 2 {
+            parts = split_words_evenly(&cue.text, duration.div_ceil(max_duration_ms) as usize);
+        }
+        if parts.len() < 2 {
+            output_cues.push(cue);
+            continue;
+        }
+
+        let total_chars: u64 = parts.iter().map(|p| p.chars().count() as u64).sum();
+        let mut start_ms = cue.start_ms;
+        let mut consumed_chars = 0;
+        let part_count = parts.len();
+        for (i, text) in parts.into_iter().enumerate() {
+            consumed_chars += text.chars().count() as u64;
+            let end_ms = if i + 1 == part_count {
+                cue.end_ms
+            } else {
+                cue.start_ms + duration * consumed_chars / total_chars.max(1)
+            };
+            output_cues.push(SrtCue {
+                index: output_cues.len() + 1,
+                start_ms,
+                end_ms,
+                text,
+            });
+            start_ms = end_ms;
+        }
+    }
+
+    format_srt_cues(&output_cues)
+}
+
+/// Keeps the cues inside a time range and moves them to start from the beginning of the range
+///
+/// Cues overlapping the edges of the range are cut to fit, so captions taken from the full source
+/// line up with a video trimmed with `--start-time` and `--end-time`.
+///
+/// # Arguments
+/// * `srt_content` - The SRT content to trim
+/// * `start_ms` - The start of the range in milliseconds
+/// * `end_ms` - The end of the range in milliseconds, or `None` for no end
+///
+/// # Returns
+/// * `String` - The SRT content of the range, re-numbered from 1
+pub fn trim_srt_to_range(srt_content: &str, start_ms: u64, end_ms: Option<u64>) -> String {
+    let end_ms = end_ms.unwrap_or(u64::MAX);
+    let cues: Vec<SrtCue> = parse_srt_cues(srt_content)
+        .into_iter()
+        .filter(|cue| cue.end_ms > start_ms && cue.start_ms < end_ms)
+        .map(|cue| SrtCue {
+            start_ms: cue.start_ms.max(start_ms) - start_ms,
+            end_ms: cue.end_ms.min(end_ms) - start_ms,
+            ..cue
+        })
+        .collect();
+    format_srt_cues(&cues)
+}
+
+/// Formats cues as SRT content, numbering them from 1
+fn format_srt_cues(cues: &[SrtCue]) -> String {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                format_srt_timestamp(cue.start_ms),
+                format_srt_timestamp(cue.end_ms),
+                cue.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How long a chapter title stays on screen
+const CHAPTER_CAPTION_DURATION_MS: u64 = 3000;
+
+/// Generates SRT captions that show each chapter title at the start of its chapter
+///
+/// Each cue lasts 3 seconds, or until the end of the chapter if it is shorter.
+///
+/// # Arguments
+/// * `chapters` - The chapters to caption
+///
+/// # Returns
+/// * `String` - The SRT content with one cue per chapter
+pub fn generate_chapter_srt(chapters: &[Chapter]) -> String {
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let start_ms = (chapter.start_sec.max(0.0) * 1000.0).round() as u64;
+            let chapter_end_ms = (chapter.end_sec.max(0.0) * 1000.0).round() as u64;
+            let end_ms = (start_ms + CHAPTER_CAPTION_DURATION_MS).min(chapter_end_ms.max(start_ms));
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                format_srt_timestamp(start_ms),
+                format_srt_timestamp(end_ms),
+                chapter.title
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format of the caption file saved next to the output video
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CaptionFormat {
+    /// SubRip, also used to burn the captions in
+    #[default]
+    Srt,
+    /// WebVTT, for HTML5 `<track>` elements and web players
+    Vtt,
+}
+
+impl CaptionFormat {
+    /// File extension for captions in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            CaptionFormat::Srt => "srt",
+            CaptionFormat::Vtt => "vtt",
+        }
+    }
+}
+
+impl std::str::FromStr for CaptionFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "srt" => Ok(CaptionFormat::Srt),
+            "vtt" | "webvtt" => Ok(CaptionFormat::Vtt),
+            _ => Err(format!("unsupported caption format '{}', expected srt or vtt", s)),
+        }
+    }
+}
+
+/// Formats the caption format as `--caption-format` expects it
+impl std::fmt::Display for CaptionFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// Parses an SRT timestamp with any number of fractional digits, rounded to milliseconds
+///
+/// Accepts both `,` and `.` before the fraction, so `00:00:01,2345` gives 1235 ms.
+fn parse_caption_timestamp_ms(timestamp: &str) -> Option<u64> {
+    let timestamp = timestamp.trim();
+    let (hms, fraction) = timestamp
+        .split_once([',', '.'])
+        .unwrap_or((timestamp, "0"));
+    let mut parts = hms.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: u64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // Milliseconds are the first three digits, rounded half up by the fourth
+    let digits = format!("{:0<4}", fraction);
+    let millis: u64 = digits[..3].parse().ok()?;
+    let round_up = u64::from(digits.as_bytes()[3] >= b'5');
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis + round_up)
+}
+
+/// Formats milliseconds as a WebVTT timestamp of the form `HH:MM:SS.mmm`
+///
+/// Hours are not wrapped at 24, so captions past the first day keep counting up.
+fn format_vtt_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+/// Converts SRT captions to WebVTT
+///
+/// Adds the `WEBVTT` header, rewrites the timestamps to `HH:MM:SS.mmm` and keeps the SRT cue
+/// numbers as cue identifiers. Multi-line cue text is kept as is and blocks without a valid
+/// timing line are dropped.
+pub fn convert_srt_to_vtt(srt: &str) -> String {
+    let normalized = srt.replace("\r\n", "\n");
+    let mut vtt = String::from("WEBVTT\n");
+
+    for block in normalized.split("\n\n") {
+        let lines: Vec<&str> = block.lines().filter(|l| !l.trim().is_empty()).collect();
+        let Some(timing_index) = lines.iter().position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let Some((start, end)) = lines[timing_index].split_once("-->") else {
+            continue;
+        };
+        // Drop SRT position hints such as `X1:100` after the end time
+        let end = end.split_whitespace().next().unwrap_or("");
+        let (Some(start_ms), Some(end_ms)) =
+            (parse_caption_timestamp_ms(start), parse_caption_timestamp_ms(end))
+        else {
+            continue;
+        };
+
+        vtt.push('\n');
+        if let Some(identifier) = lines[..timing_index].first() {
+            vtt.push_str(identifier.trim());
+            vtt.push('\n');
+        }
+        vtt.push_str(&format!(
+            "{} --> {}\n",
+            format_vtt_timestamp(start_ms),
+            format_vtt_timestamp(end_ms)
+        ));
+        for line in &lines[timing_index + 1..] {
+            vtt.push_str(line);
+            vtt.push('\n');
+        }
+    }
+
+    vtt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_backend_from_name() {
+        assert_eq!(TranscriptBackend::from_name("openai", None).unwrap(), TranscriptBackend::OpenAI);
+        assert!(TranscriptBackend::from_name("azure", None).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "local-whisper"))]
+    fn test_local_backend_needs_feature() {
+        assert!(TranscriptBackend::from_name("local", Some("ggml-tiny.bin")).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "local-whisper")]
+    fn test_local_transcript_backend_from_name() {
+        assert_eq!(
+            TranscriptBackend::from_name("Local", Some("ggml-tiny.bin")).unwrap(),
+            TranscriptBackend::Local {
+                model_path: PathBuf::from("ggml-tiny.bin")
+            }
+        );
+        assert!(TranscriptBackend::from_name("local", None).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "local-whisper")]
+    fn test_whisper_segments_to_srt() {
+        let segments = vec![
+            (0, 250, " Hello there.".to_string()),
+            (250, 250, "  ".to_string()),
+            (250, 6125, " How are you?".to_string()),
+        ];
+        let srt = whisper_segments_to_srt(&segments);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:02,500\nHello there.\n\n2\n00:00:02,500 --> 00:01:01,250\nHow are you?\n"
+        );
+        // Local transcripts pass the same checks as API transcripts
+        assert_eq!(validate_srt_format(&srt).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_transcription_request_language_and_temperature() {
+        let config = TranscriptConfig {
+            api_key: String::new(),
+            ..Default::default()
+        }
+        .with_language("fr")
+        .with_temperature(0.2);
+        let request = build_transcription_request(Path::new("audio.mp3"), &config);
+        assert_eq!(request.file, "audio.mp3");
+        assert_eq!(request.response_format.as_deref(), Some("srt"));
+        assert_eq!(request.language.as_deref(), Some("fr"));
+        assert_eq!(request.temperature, Some(0.2));
+
+        let request =
+            build_transcription_request(Path::new("audio.mp3"), &config.auto_detect_language());
+        assert_eq!(request.language, None);
+        assert_eq!(request.temperature, Some(0.2));
+    }
+
+    #[test]
+    fn test_srt_timestamp_round_trip() {
+        assert_eq!(parse_srt_timestamp("01:02:03,456"), Some(3_723_456));
+        assert_eq!(format_srt_timestamp(3_723_456), "01:02:03,456");
+        assert_eq!(parse_srt_timestamp("garbage"), None);
+    }
+
+    #[test]
+    fn test_split_long_srt_cues_at_sentences() {
+        let srt = "1\n00:00:00,000 --> 00:00:10,000\n\
+                   One two three four five. Six seven eight nine ten?\n\
+                   Eleven twelve thirteen fourteen fifteen! Sixteen seventeen eighteen nineteen twenty.\n\n\
+                   2\n00:00:10,500 --> 00:00:12,000\nShort cue.\n";
+
+        let result = split_long_srt_cues(srt, 4000);
+        let cues = parse_srt_cues(&result);
+
+        assert_eq!(cues.len(), 5);
+        assert_eq!(cues[0].text, "One two three four five.");
+        assert_eq!(cues[3].text, "Sixteen seventeen eighteen nineteen twenty.");
+        assert_eq!(cues[0].start_ms, 0);
+        assert_eq!(cues[3].end_ms, 10_000);
+        for pair in cues.windows(2) {
+            assert!(pair[0].end_ms <= pair[1].start_ms);
+        }
+        for cue in &cues[..4] {
+            assert!(cue.end_ms - cue.start_ms <= 4000);
+        }
+
+        // Following cues are re-numbered and untouched
+        assert!(result.contains("5\n00:00:10,500 --> 00:00:12,000\nShort cue."));
+    }
+
+    #[test]
+    fn test_trim_srt_to_range() {
+        let srt = "1\n00:00:01,000 --> 00:00:03,000\nBefore.\n\n\
+                   2\n00:00:09,000 --> 00:00:12,000\nAcross the start.\n\n\
+                   3\n00:00:15,000 --> 00:00:16,500\nInside.\n\n\
+                   4\n00:00:19,000 --> 00:00:22,000\nAcross the end.\n\n\
+                   5\n00:00:25,000 --> 00:00:26,000\nAfter.\n";
+
+        let cues = parse_srt_cues(&trim_srt_to_range(srt, 10_000, Some(20_000)));
+        let ranges: Vec<(u64, u64)> = cues.iter().map(|c| (c.start_ms, c.end_ms)).collect();
+        assert_eq!(ranges, vec![(0, 2000), (5000, 6500), (9000, 10_000)]);
+        assert_eq!(cues[0].index, 1);
+        assert_eq!(cues[0].text, "Across the start.");
+
+        // Without an end everything from the start on is kept
+        assert_eq!(parse_srt_cues(&trim_srt_to_range(srt, 10_000, None)).len(), 4);
+    }
+
+    #[test]
+    fn test_split_long_srt_cues_without_sentence_boundary() {
+        let words: Vec<String> = (1..=20).map(|i| format!("word{}", i)).collect();
+        let srt = format!("1\n00:00:00,000 --> 00:00:10,000\n{}\n", words.join(" "));
+
+        let cues = parse_srt_cues(&split_long_srt_cues(&srt, 4000));
+
+        assert_eq!(cues.len(), 3);
+        assert_eq!(cues[0].start_ms, 0);
+        assert_eq!(cues[2].end_ms, 10_000);
+        let rejoined: Vec<&str> = cues.iter().flat_map(|c| c.text.split_whitespace()).collect();
+        assert_eq!(rejoined.len(), 20);
+    }
+
+    #[test]
+    fn test_generate_chapter_srt() {
+        let chapters = vec![
+            Chapter { start_sec: 0.0, end_sec: 95.5, title: "Introduction".to_string() },
+            Chapter { start_sec: 95.5, end_sec: 97.0, title: "Quick Recap".to_string() },
+            Chapter { start_sec: 97.0, end_sec: 3725.25, title: "Deep Dive".to_string() },
+        ];
+
+        assert_eq!(
+            generate_chapter_srt(&chapters),
+            "1\n00:00:00,000 --> 00:00:03,000\nIntroduction\n\n\
+             2\n00:01:35,500 --> 00:01:37,000\nQuick Recap\n\n\
+             3\n00:01:37,000 --> 00:01:40,000\nDeep Dive\n"
+        );
+        assert_eq!(generate_chapter_srt(&[]), "");
+    }
+
+    #[test]
+    fn test_split_long_srt_cues_disabled() {
+        let srt = "1\n00:00:00,000 --> 00:00:10,000\nOne. Two.\n";
+        assert_eq!(parse_srt_cues(&split_long_srt_cues(srt, 0)).len(), 1);
+    }
+
+    #[test]
+    fn test_validate_srt_format_valid() {
+        let srt = "1\r\n00:00:00,000 --> 00:00:02,500\r\nHello there.\r\n\r\n\
+                   2\r\n00:00:02,500 --> 00:00:04,000\r\nTwo\r\nlines\r\n\r\n\
+                   3\r\n00:00:04,000 --> 00:00:04,000\r\nBlink\r\n";
+        let cues = validate_srt_format(srt).unwrap();
+        assert_eq!(
+            cues,
+            vec![
+                SrtCue { index: 1, start_ms: 0, end_ms: 2500, text: "Hello there.".to_string() },
+                SrtCue { index: 2, start_ms: 2500, end_ms: 4000, text: "Two\nlines".to_string() },
+                // Zero-length cues are allowed
+                SrtCue { index: 3, start_ms: 4000, end_ms: 4000, text: "Blink".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_srt_format_empty() {
+        // A silent transcription has no cues, burn_captions copies the video for it
+        assert!(validate_srt_format("").unwrap().is_empty());
+        assert!(validate_srt_format("\n\n").unwrap().is_empty());
+
+        let srt = "1\n00:00:00,000 --> 00:00:01,000\n\n";
+        let error = validate_srt_format(srt).unwrap_err().to_string();
+        assert!(error.contains("cue 1: text is empty"), "{}", error);
+    }
+
+    #[test]
+    fn test_validate_srt_format_overlapping_timestamps() {
+        let srt = "1\n00:00:00,000 --> 00:00:03,000\nFirst\n\n\
+                   2\n00:00:02,000 --> 00:00:04,000\nSecond\n\n\
+                   3\n00:00:06,000 --> 00:00:05,000\nThird\n";
+        let error = validate_srt_format(srt).unwrap_err().to_string();
+        // Every violation is listed
+        assert!(error.contains("2 problem(s)"), "{}", error);
+        assert!(error.contains("cue 2: overlaps cue 1"), "{}", error);
+        assert!(error.contains("cue 3: ends before it starts"), "{}", error);
+    }
+
+    #[test]
+    fn test_validate_srt_format_non_sequential_index() {
+        let srt = "1\n00:00:00,000 --> 00:00:01,000\nFirst\n\n\
+                   3\n00:00:01,000 --> 00:00:02,000\nSecond\n\n\
+                   x\n00:00:02,000 --> 00:00:03,000\nThird\n";
+        let error = validate_srt_format(srt).unwrap_err().to_string();
+        assert!(error.contains("cue 2: index 3 is out of sequence"), "{}", error);
+        assert!(error.contains("cue 3: invalid index 'x'"), "{}", error);
+    }
+
+    #[test]
+    fn test_convert_srt_to_vtt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\n\r\n2\r\n00:00:03,000 --> 00:00:05,000 X1:100 X2:200\r\nTwo\r\nlines\r\n";
+        assert_eq!(
+            convert_srt_to_vtt(srt),
+            "WEBVTT\n\n1\n00:00:01.000 --> 00:00:02.500\nHello\n\n2\n00:00:03.000 --> 00:00:05.000\nTwo\nlines\n"
+        );
+
+        // Cues without a number have no identifier, and broken cues are dropped
+        let srt = "00:00:01,000 --> 00:00:02,000\nNo id\n\n3\nnot a timing line\nText\n";
+        assert_eq!(
+            convert_srt_to_vtt(srt),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nNo id\n"
+        );
+        assert_eq!(convert_srt_to_vtt(""), "WEBVTT\n");
+    }
+
+    #[test]
+    fn test_vtt_timestamp_edge_cases() {
+        // Sub-millisecond fractions round to the nearest millisecond, carrying into seconds
+        assert_eq!(parse_caption_timestamp_ms("00:00:01,2345"), Some(1235));
+        assert_eq!(parse_caption_timestamp_ms("00:00:01,9996"), Some(2000));
+        assert_eq!(parse_caption_timestamp_ms("00:00:01,5"), Some(1500));
+        assert_eq!(parse_caption_timestamp_ms("00:00:01.250"), Some(1250));
+
+        // Rounding at the end of the first day rolls over to hour 24 instead of wrapping
+        let ms = parse_caption_timestamp_ms("23:59:59,9999").unwrap();
+        assert_eq!(format_vtt_timestamp(ms), "24:00:00.000");
+        assert_eq!(format_vtt_timestamp(100 * 3_600_000 + 1), "100:00:00.001");
+
+        assert_eq!(parse_caption_timestamp_ms("00:01,000"), None);
+        assert_eq!(parse_caption_timestamp_ms("00:00:01,"), None);
+        assert_eq!(parse_caption_timestamp_ms("00:00:01,-5"), None);
+    }
+
+    #[test]
+    fn test_caption_format_from_str() {
+        assert_eq!("SRT".parse::<CaptionFormat>(), Ok(CaptionFormat::Srt));
+        assert_eq!("webvtt".parse::<CaptionFormat>(), Ok(CaptionFormat::Vtt));
+        assert!("ass".parse::<CaptionFormat>().is_err());
+        assert_eq!(CaptionFormat::Vtt.to_string(), "vtt");
+    }
+}
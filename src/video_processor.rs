@@ -1,43 +1,124 @@
+use crate::audio;
 use crate::cli::Args;
 use crate::config;
 use crate::crop;
-use crate::progress::VideoProgressTracker;
+use crate::export::{self, CropExporter};
+use crate::history;
+use crate::image;
+use crate::progress::{self, SharedProgressTracker, VideoProgressTracker, lock_progress};
 use crate::video_processor_utils;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use ndarray::Axis;
-use std::process::Command;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use usls::{
-    Annotator, Config, DType, DataLoader, Style, Viewer, perf,
+    Annotator, Config, DType, DataLoader, Style, Viewer, Y, perf,
     models::{Clip, YOLO},
 };
 
-/// Gets the total frame count from a video file using ffprobe
-fn get_video_frame_count(video_path: &str) -> Result<u64> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v", "quiet",
-            "-select_streams", "v:0",
-            "-show_entries", "stream=nb_frames",
-            "-of", "csv=p=0",
-            video_path
-        ])
-        .output()?;
-    
-    if output.status.success() {
-        let frame_count_str = String::from_utf8(output.stdout)?;
-        let frame_count = frame_count_str.trim().parse::<u64>()?;
-        Ok(frame_count)
-    } else {
-        Err(anyhow::anyhow!("Failed to get frame count from video"))
+/// Number of frames between writes of the resumable progress file
+const PROGRESS_SAVE_INTERVAL: u64 = 100;
+
+/// Transforms a frame, e.g. for color grading or a logo overlay
+pub type FrameTransformFn = Box<dyn Fn(&usls::Image) -> Result<usls::Image> + Send>;
+
+/// Optional frame transforms applied around the crop by `VideoProcessor::process_video`
+#[derive(Default)]
+pub struct VideoProcessorConfig {
+    /// Applied to every source frame before detection
+    pub pre_processor: Option<FrameTransformFn>,
+    /// Applied to every output frame after the crop is rendered
+    pub post_processor: Option<FrameTransformFn>,
+    /// Batch progress to advance instead of showing a progress bar for this video alone
+    pub batch_progress: Option<SharedProgressTracker>,
+}
+
+impl VideoProcessorConfig {
+    /// Applies the pre-processor to a batch of source frames
+    pub fn pre_process(&self, images: Vec<usls::Image>) -> Result<Vec<usls::Image>> {
+        match &self.pre_processor {
+            Some(pre_processor) => images.iter().map(|image| pre_processor(image)).collect(),
+            None => Ok(images),
+        }
+    }
+}
+
+/// Builds a `VideoProcessorConfig`
+#[derive(Default)]
+pub struct VideoProcessorBuilder {
+    config: VideoProcessorConfig,
+}
+
+impl VideoProcessorBuilder {
+    /// Creates a builder without frame transforms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Transforms every source frame before detection
+    pub fn with_pre_processor(
+        mut self,
+        pre_processor: impl Fn(&usls::Image) -> Result<usls::Image> + Send + 'static,
+    ) -> Self {
+        self.config.pre_processor = Some(Box::new(pre_processor));
+        self
+    }
+
+    /// Transforms every output frame after the crop is rendered
+    pub fn with_post_processor(
+        mut self,
+        post_processor: impl Fn(&usls::Image) -> Result<usls::Image> + Send + 'static,
+    ) -> Self {
+        self.config.post_processor = Some(Box::new(post_processor));
+        self
+    }
+
+    /// Reports frame progress to the tracker of a batch of videos
+    pub fn with_batch_progress(mut self, tracker: SharedProgressTracker) -> Self {
+        self.config.batch_progress = Some(tracker);
+        self
+    }
+
+    /// Returns the configured frame transforms
+    pub fn build(self) -> VideoProcessorConfig {
+        self.config
     }
 }
 
 /// Base trait for video processors that handle cropping with different smoothing strategies
 pub trait VideoProcessor {
     /// Processes a video with cropping and smoothing
-    fn process_video(&mut self, args: &Args, processed_video: &str) -> Result<()> {
-        let config = config::build_config(&args)?;
-        let mut model = YOLO::new(config.commit()?)?;
+    ///
+    /// # Arguments
+    /// * `args` - The command line arguments
+    /// * `processed_video` - Where to write the processed video
+    /// * `config` - Frame transforms applied before detection and after cropping
+    fn process_video(
+        &mut self,
+        args: &Args,
+        processed_video: &str,
+        mut config: VideoProcessorConfig,
+    ) -> Result<()> {
+        let output_dir = Path::new(processed_video)
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_else(|| ".".to_string());
+        self.validate_output_path(&output_dir, args.min_free_gb)?;
+
+        if args.verify_input {
+            println!("Verifying input video...");
+            audio::verify_video_integrity(&args.source)?;
+            println!("✓ Input video verified");
+        }
+
+        let area_thresholds = video_processor_utils::PerClassThresholds::parse(
+            args.object_area_threshold,
+            &args.class_area_threshold,
+        )?;
+        let watermark = load_watermark(args)?;
+        let model_config = config::build_model_config(args)?;
+        let mut model = YOLO::new(model_config.commit()?)?;
 
         let clip_config = Config::mobileclip_s0()
             .with_dtype_all(DType::Fp16)
@@ -56,6 +137,22 @@ pub trait VideoProcessor {
         ];
         let feats_text = clip_model.encode_texts(&texts)?.norm(1)?;
 
+        // Restore state from an interrupted run if requested; the source was already seeked to
+        // its last written frame, so progress is saved relative to that frame
+        let resume_frame = if let Some(resume_dir) = &args.resume_from {
+            let state = audio::read_progress_file(resume_dir)?;
+            println!("Resuming from frame {}", state.last_frame);
+            if let Some(last_crop) = state.last_crop {
+                self.restore_previous_crop(last_crop);
+            }
+            state.last_frame
+        } else {
+            0
+        };
+        let mut output_frames = video_processor_utils::OutputFrameTracker::default();
+        let mut frame_index: u64 = 0;
+        let subclip = audio::Subclip::new(args.start_time, args.end_time);
+
         // build dataloader
         let data_loader = DataLoader::new(&args.source)?
             .with_batch(model.batch() as _)
@@ -68,33 +165,59 @@ pub trait VideoProcessor {
         } else {
             0
         };
+        self.set_frame_rate(frame_rate as f64);
+
+        // Read video metadata up front to reject unsupported inputs and size the progress bar
+        let metadata = audio::probe_video_metadata(&args.source).ok();
+        if let Some(metadata) = &metadata {
+            audio::check_resolution_supported(metadata)?;
+            println!(
+                "Video info: {}x{} {} ({:.1}s)",
+                metadata.width, metadata.height, metadata.video_codec, metadata.duration_sec
+            );
+        }
+        let total_frames = metadata
+            .as_ref()
+            .map(|metadata| metadata.total_frames())
+            .filter(|frames| *frames > 0);
 
-        // Try to get total frame count from video file
-        let total_frames = get_video_frame_count(&args.source).ok();
-        
         println!("Video info: {:.1} FPS", frame_rate);
         if let Some(frames) = total_frames {
             println!("Total frames: {}", frames);
         }
         
-        // Create progress tracker
-        let mut progress_tracker = if let Some(total_frames) = total_frames {
-            VideoProgressTracker::new(
-                total_frames,
-                frame_rate as f64,
-                &format!("{} detection", args.object)
-            )
+        // Create progress tracker, shared with the SIGUSR1 progress reporter. In a batch the
+        // batch tracker already reports, and is finished once every video is processed.
+        let is_batch = config.batch_progress.is_some();
+        let progress_tracker = if let Some(batch_progress) = config.batch_progress.take() {
+            batch_progress
         } else {
-            VideoProgressTracker::new_unknown_total(
-                frame_rate as f64,
-                &format!("{} detection", args.object)
-            )
+            let progress_tracker = if let Some(total_frames) = total_frames {
+                VideoProgressTracker::new(
+                    total_frames,
+                    frame_rate as f64,
+                    &format!("{} detection", args.object)
+                )
+            } else {
+                VideoProgressTracker::new_unknown_total(
+                    frame_rate as f64,
+                    &format!("{} detection", args.object)
+                )
+            };
+            let progress_tracker = Arc::new(Mutex::new(progress_tracker));
+            progress::register_signal_handler(
+                Arc::clone(&progress_tracker),
+                args.progress_json_pipe.clone(),
+            );
+            progress_tracker
         };
 
         let mut viewer = Viewer::default()
             .with_window_scale(0.5)
-            .with_fps(frame_rate as usize)
-            .with_saveout(processed_video.to_string());
+            .with_fps(frame_rate as usize);
+        if !args.dry_run {
+            viewer = viewer.with_saveout(processed_video.to_string());
+        }
 
         // build annotator
         let annotator = Annotator::default()
@@ -105,8 +228,60 @@ pub trait VideoProcessor {
                     .with_palette(&usls::Color::palette_coco_80()),
             );
 
+        if let Some(preview_dir) = &args.debug_side_by_side {
+            std::fs::create_dir_all(preview_dir)?;
+        }
+        if let Some(debug_frame_dir) = &args.debug_frame_output {
+            std::fs::create_dir_all(debug_frame_dir)?;
+        }
+
+        // Reuses resized crops while the crop and the frame content stay the same, and applies
+        // the post-processor to every output frame
+        let mut renderer = video_processor_utils::CropRenderer::new(args.image_cache_size)
+            .with_post_processor(config.post_processor.take())
+            .with_resize_filter(args.resize_filter)
+            .with_sharpen(image::Sharpen::new(args.sharpen_amount, args.sharpen_radius))
+            .with_watermark(watermark)
+            .with_perf_stats(args.perf_stats)
+            .with_debug_overlay(args.debug_overlay, args.smooth_percentage)
+            .with_dry_run(args.dry_run)
+            .with_thumbnails(
+                args.thumbnail_interval,
+                args.thumbnail_grid_cols * args.thumbnail_grid_rows,
+            );
+
+        // Skips CLIP classification for --clip-classify-every-n-frames frames after each check
+        let mut graphic_check_cache =
+            video_processor_utils::GraphicCheckCache::new(args.clip_classify_interval);
+
+        // Tracks whether the crop is currently below --min-crop-coverage, to warn once per run of frames
+        let mut is_low_coverage = false;
+
+        // Last crop and frame, used to follow the camera with --camera-motion-compensation
+        let mut tracked_crop: Option<crop::CropResult> = None;
+        let mut previous_frame: Option<usls::Image> = None;
+
+        // Crop of the last output frame, reused for empty frames with --no-detection-strategy previous
+        let mut previous_output_crop: Option<crop::CropResult> = None;
+
+        // Collects the applied crop of every frame for --export-crops
+        let mut crop_exporter = args.export_crops.as_ref().map(CropExporter::new);
+
+        // Crops replayed with --load-crop-sequence, and the calculated crops for
+        // --save-crop-sequence and the --perf-stats overview, which only need a placeholder
+        // image per frame
+        let mut loaded_crops = args
+            .load_crop_sequence
+            .as_ref()
+            .map(|path| export::load_crop_sequence(Path::new(path)))
+            .transpose()?;
+        let mut crop_sequence = (args.save_crop_sequence.is_some() || args.perf_stats)
+            .then(history::CropHistory::new);
+        let crop_sequence_placeholder = usls::Image::from(::image::RgbImage::new(1, 1));
+        let mut crop_sequence_frame_width = 0.0;
+
         // Common video processing logic
-        for images in data_loader {
+        'batches: for images in data_loader {
             if viewer.is_window_exist() && !viewer.is_window_open() {
                 break;
             }
@@ -118,54 +293,183 @@ pub trait VideoProcessor {
                 }
             }
 
+            // Skip whole batches before --start-time without running detection
+            if subclip.is_before_start(frame_index + images.len() as u64 - 1, frame_rate) {
+                frame_index += images.len() as u64;
+                lock_progress(&progress_tracker).update_frames(images.len() as u64);
+                continue;
+            }
+            if subclip.is_past_end(frame_index, frame_rate) {
+                break;
+            }
+
+            let images = config.pre_process(images)?;
+            let forward_start = Instant::now();
             let detections = model.forward(&images)?;
+            if let Some(stats) = renderer.perf_stats_mut() {
+                stats.add_model_forward(forward_start.elapsed(), images.len());
+            }
 
             for (image, detection) in images.iter().zip(detections.iter()) {
                 // Update progress for each frame
-                progress_tracker.update_frame();
+                lock_progress(&progress_tracker).update_frame();
+                frame_index += 1;
+                if subclip.is_before_start(frame_index - 1, frame_rate) {
+                    continue;
+                }
+                if subclip.is_past_end(frame_index - 1, frame_rate) {
+                    break 'batches;
+                }
                 // Calculate crop areas based on the detection results first
-                let objects = video_processor_utils::extract_objects_above_threshold(
-                    detection,
-                    &args.object,
-                    args.object_prob_threshold,
-                    args.object_area_threshold,
-                    image.width() as f32,
-                    image.height() as f32,
-                );
+                let objects =
+                    extract_frame_objects(args, image, detection, &area_thresholds, self.zone_of_interest());
 
-                let is_graphic = if objects.len() == 0 && args.keep_graphic {
-                    let feats_image = clip_model.encode_images(&[image.clone()])?.norm(1)?;
-
-                    // use image to query texts
-                    let matrix = (feats_image * 100.).dot2(&feats_text)?.softmax(1)?;
-                    let mut id = 0;
-                    let mut score = 0.0;
-                    for (_i, row) in matrix.axis_iter(Axis(0)).enumerate() {
-                        if let Some((item_id, &item_score)) = row
-                            .iter()
-                            .enumerate()
-                            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
-                        {
-                            id = item_id;
-                            score = item_score;
-                            video_processor_utils::debug_println(format_args!("({}) <=> ({})", item_score * 100.0, &texts[item_id]));
+                let is_graphic = if args.keep_graphic {
+                    graphic_check_cache.is_graphic(frame_index, !objects.is_empty(), || {
+                        let feats_image = clip_model.encode_images(&[image.clone()])?.norm(1)?;
+
+                        // use image to query texts
+                        let matrix = (feats_image * 100.).dot2(&feats_text)?.softmax(1)?;
+                        let mut id = 0;
+                        let mut score = 0.0;
+                        for (_i, row) in matrix.axis_iter(Axis(0)).enumerate() {
+                            if let Some((item_id, &item_score)) = row
+                                .iter()
+                                .enumerate()
+                                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+                            {
+                                id = item_id;
+                                score = item_score;
+                                video_processor_utils::debug_println(format_args!("({}) <=> ({})", item_score * 100.0, &texts[item_id]));
+                            }
                         }
-                    }
-                    id > 3 && score > args.graphic_threshold
+                        Ok(id > 3 && score > args.graphic_threshold)
+                    })?
                 } else {
                     false
                 };
 
-                let latest_crop = crop::calculate_crop_area(
-                    args.use_stack_crop,
-                    is_graphic,
-                    image.width() as f32,
-                    image.height() as f32,
-                    &objects,
-                )?;
+                let crop_start = Instant::now();
+                let crop_result = calculate_frame_crop(args, image, is_graphic, &objects)?;
+                if let Some(stats) = renderer.perf_stats_mut() {
+                    stats.add_crop_calc(crop_start.elapsed());
+                }
+                let mut latest_crop = match crop_result {
+                    Some(crop) => crop,
+                    None if args.no_detection_strategy == crop::NoDetectionStrategy::PreviousCrop => {
+                        previous_output_crop.clone().unwrap_or_else(|| {
+                            crop::calculate_no_heads_crop(image.width() as f32, image.height() as f32, false)
+                        })
+                    }
+                    None => {
+                        video_processor_utils::debug_println(format_args!(
+                            "No detections in frame {}, skipping it",
+                            frame_index
+                        ));
+                        continue;
+                    }
+                };
+
+                // Frames past the end of a loaded crop sequence keep their calculated crop
+                if let Some(frame) = loaded_crops.as_mut().and_then(|crops| crops.pop_front()) {
+                    latest_crop = frame.crop;
+                }
+                if let Some(crop_sequence) = &mut crop_sequence {
+                    crop_sequence.add(latest_crop.clone(), crop_sequence_placeholder.clone(), objects.len());
+                    crop_sequence_frame_width = image.width() as f32;
+                }
+
+                if args.camera_motion_compensation {
+                    if let (Some(tracked), Some(previous)) = (&tracked_crop, &previous_frame) {
+                        if objects.is_empty() && !is_graphic {
+                            // The background moves opposite to the camera
+                            let (dx, dy) = image::estimate_background_motion(previous, image)?;
+                            latest_crop = compensate_camera_motion(
+                                tracked,
+                                -dx,
+                                -dy,
+                                image.width() as f32,
+                                image.height() as f32,
+                            )
+                            .unwrap_or(latest_crop);
+                        }
+                    }
+                    tracked_crop = Some(latest_crop.clone());
+                    previous_frame = Some(image.clone());
+                }
+
+                if let Some(min_crop_coverage) = args.min_crop_coverage {
+                    let coverage =
+                        latest_crop.area_ratio(image.width() as f32, image.height() as f32);
+                    let is_below = coverage < min_crop_coverage;
+                    if is_below && !is_low_coverage {
+                        println!(
+                            "Warning: crop covers {:.1}% of frame {}, below --min-crop-coverage {:.1}%",
+                            coverage * 100.0,
+                            frame_index,
+                            min_crop_coverage * 100.0
+                        );
+                    }
+                    is_low_coverage = is_below;
+                }
+
+                if args.safe_zone_check
+                    && !latest_crop.is_within_safe_zone(
+                        args.safe_zone_margin,
+                        image.width() as f32,
+                        image.height() as f32,
+                    )
+                {
+                    println!(
+                        "Warning: crop extends outside the {:.1}% safe zone in frame {}",
+                        args.safe_zone_margin, frame_index
+                    );
+                }
+
+                if let Some(preview_dir) = &args.debug_side_by_side {
+                    if frame_index % args.debug_side_by_side_interval.max(1) == 0 {
+                        let cropped = image::create_cropped_image(
+                            image,
+                            &latest_crop,
+                            image.height() as u32,
+                            args.pillarbox,
+                            image::RenderOptions {
+                                filter: args.resize_filter,
+                                sharpen: image::Sharpen::new(args.sharpen_amount, args.sharpen_radius),
+                                ..image::RenderOptions::default()
+                            },
+                            None,
+                        )?;
+                        let preview =
+                            image::create_side_by_side_preview(image, &cropped, image.width() as u32)?;
+                        let preview_path =
+                            Path::new(preview_dir).join(format!("frame_{:06}.jpg", frame_index));
+                        preview.save(&preview_path)?;
+                    }
+                }
+
+                if let Some(debug_frame_dir) = &args.debug_frame_output {
+                    if frame_index % args.debug_frame_interval.max(1) == 0 {
+                        let debug_frame = video_processor_utils::build_debug_frame(
+                            image,
+                            &objects,
+                            &latest_crop,
+                            frame_index,
+                        )?;
+                        let debug_frame_path =
+                            Path::new(debug_frame_dir).join(format!("frame_{:06}.jpg", frame_index));
+                        debug_frame.save(&debug_frame_path)?;
+                    }
+                }
+
+                renderer.set_overlay_confidences(
+                    objects.iter().map(|object| object.confidence().unwrap_or(1.0)).collect(),
+                );
 
                 // Print debug information
                 self.print_debug_info(&objects, &latest_crop, is_graphic);
+                previous_output_crop = Some(latest_crop.clone());
+                output_frames.push(frame_index);
 
                 // Create img only when needed (avoid unnecessary clone)
                 if smooth_duration_frames > 0 {
@@ -180,6 +484,7 @@ pub trait VideoProcessor {
                         &objects,
                         args,
                         &mut viewer,
+                        &mut renderer,
                         smooth_duration_frames,
                     )?;
                 } else {
@@ -193,22 +498,169 @@ pub trait VideoProcessor {
                         &latest_crop,
                         &mut viewer,
                         args.headless,
+                        args.pillarbox,
+                        &mut renderer,
+                    )?;
+                }
+
+                if let Some(exporter) = &mut crop_exporter {
+                    let applied_crop = self.last_applied_crop().unwrap_or(&latest_crop);
+                    let timestamp_s = (frame_index - 1) as f64 / frame_rate as f64;
+                    exporter.record(frame_index, timestamp_s, applied_crop, objects.len());
+                }
+
+                // Save the last frame in the output video, frames held back for smoothing are redone
+                let last_output_frame = (frame_index % PROGRESS_SAVE_INTERVAL == 0 && !args.dry_run)
+                    .then(|| output_frames.update(renderer.output_frame_count()))
+                    .flatten();
+                if let Some(last_output_frame) = last_output_frame {
+                    audio::create_progress_file(
+                        &output_dir,
+                        resume_frame + last_output_frame,
+                        self.last_applied_crop(),
                     )?;
                 }
             }
         }
-        self.finalize_processing(args, &mut viewer)?;
+        self.finalize_processing(args, &mut viewer, &mut renderer)?;
         viewer.finalize_video()?;
 
         // Finish progress tracking
-        progress_tracker.finish();
+        if !is_batch {
+            lock_progress(&progress_tracker).finish();
+        }
+
+        if let Some(stats) = renderer.perf_stats() {
+            println!("{}", stats.summary());
+            if let Some(crop_sequence) = &crop_sequence {
+                println!(
+                    "{}",
+                    video_processor_utils::crop_history_overview(
+                        crop_sequence,
+                        video_processor_utils::CROP_OVERVIEW_SAMPLES
+                    )
+                );
+            }
+        }
+
+        if let Some(summary) = renderer.dry_run_summary() {
+            println!("{}", summary.summary());
+        }
+
+        if let Some(exporter) = &crop_exporter {
+            exporter.flush()?;
+            println!("✓ Crop export saved to: {}", exporter.path().display());
+        }
+
+        if let (Some(path), Some(crop_sequence)) = (&args.save_crop_sequence, &crop_sequence) {
+            export::save_crop_sequence(Path::new(path), crop_sequence, crop_sequence_frame_width)?;
+            println!("✓ Crop sequence of {} frames saved to: {}", crop_sequence.len(), path);
+        }
+
+        if args.log_transitions {
+            self.write_transition_log(&Path::new(&output_dir).join("transitions.json"))?;
+        }
+
+        if args.thumbnail_interval > 0 && !renderer.thumbnails().is_empty() {
+            let grid_path = Path::new(&output_dir).join("thumbnail_grid.jpg");
+            image::generate_thumbnail_grid(
+                renderer.thumbnails(),
+                args.thumbnail_grid_cols,
+                args.thumbnail_grid_rows,
+                &grid_path,
+            )?;
+            println!("✓ Thumbnail grid saved to: {}", grid_path.display());
+        }
 
         perf(false);
 
         Ok(())
     }
 
+    /// Checks that the output directory exists and has enough free disk space for the output
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory the processed video is written to
+    /// * `min_free_gb` - The minimum free space required, in gigabytes (0 disables the check)
+    fn validate_output_path(&self, output_dir: &str, min_free_gb: f32) -> Result<()> {
+        let metadata = std::fs::metadata(output_dir)
+            .with_context(|| format!("Output directory not found: {}", output_dir))?;
+        if !metadata.is_dir() {
+            anyhow::bail!("Output path is not a directory: {}", output_dir);
+        }
+        video_processor_utils::ensure_free_space(
+            Path::new(output_dir),
+            min_free_gb,
+            video_processor_utils::available_disk_space,
+        )
+    }
+
+    /// Processes only the first frame of the video and saves it as `<output_dir>/first_frame.jpg`
+    ///
+    /// Useful as a quick check of detection accuracy and crop placement before a full run.
+    /// Graphic detection is skipped, so the crop is based on the detected objects alone.
+    ///
+    /// # Arguments
+    /// * `args` - The command line arguments
+    /// * `output_dir` - The directory to write the thumbnail to
+    fn save_first_frame(&mut self, args: &Args, output_dir: &str) -> Result<()> {
+        let area_thresholds = video_processor_utils::PerClassThresholds::parse(
+            args.object_area_threshold,
+            &args.class_area_threshold,
+        )?;
+        let config = config::build_model_config(args)?;
+        let mut model = YOLO::new(config.commit()?)?;
+
+        let data_loader = DataLoader::new(&args.source)?
+            .with_batch(model.batch() as _)
+            .build()?;
+        let Some(images) = data_loader.into_iter().next() else {
+            anyhow::bail!("No frames could be read from {}", args.source);
+        };
+        let detections = model.forward(&images)?;
+        let (Some(image), Some(detection)) = (images.first(), detections.first()) else {
+            anyhow::bail!("No frames could be read from {}", args.source);
+        };
+
+        let objects = extract_frame_objects(args, image, detection, &area_thresholds, self.zone_of_interest());
+        // A thumbnail always needs a frame, so fall back to the center crop without detections
+        let latest_crop = calculate_frame_crop(args, image, false, &objects)?.unwrap_or_else(|| {
+            crop::calculate_no_heads_crop(image.width() as f32, image.height() as f32, false)
+        });
+        self.print_debug_info(&objects, &latest_crop, false);
+
+        let thumbnail = image::create_cropped_image(
+            image,
+            &latest_crop,
+            image.height() as u32,
+            args.pillarbox,
+            image::RenderOptions {
+                filter: args.resize_filter,
+                sharpen: image::Sharpen::new(args.sharpen_amount, args.sharpen_radius),
+                ..image::RenderOptions::default()
+            },
+            None,
+        )?;
+        // A single frame is watermarked once, so the watermark is not kept loaded
+        let mut thumbnail = thumbnail.to_rgb8();
+        if let Some(watermark_path) = &args.watermark_path {
+            image::overlay_watermark(
+                &mut thumbnail,
+                Path::new(watermark_path),
+                args.watermark_position,
+                args.watermark_opacity,
+            )?;
+        }
+        let thumbnail_path = Path::new(output_dir).join("first_frame.jpg");
+        thumbnail
+            .save(&thumbnail_path)
+            .with_context(|| format!("Failed to save frame to {}", thumbnail_path.display()))?;
+        println!("✓ First frame saved to: {}", thumbnail_path.display());
+        Ok(())
+    }
+
     /// Processes a single frame with smoothing logic (to be implemented by concrete processors)
+    #[allow(clippy::too_many_arguments)]
     fn process_frame_with_smoothing(
         &mut self,
         img: &usls::Image,
@@ -216,15 +668,47 @@ pub trait VideoProcessor {
         objects: &[&usls::Hbb],
         args: &Args,
         viewer: &mut Viewer,
+        renderer: &mut video_processor_utils::CropRenderer,
         smooth_duration_frames: usize,
     ) -> Result<()>;
 
     /// Finalizes processing by handling any remaining frames in history (to be implemented by concrete processors)
-    fn finalize_processing(&mut self, _args: &Args, _viewer: &mut Viewer) -> Result<()> {
+    fn finalize_processing(
+        &mut self,
+        _args: &Args,
+        _viewer: &mut Viewer,
+        _renderer: &mut video_processor_utils::CropRenderer,
+    ) -> Result<()> {
         // Default implementation does nothing
         Ok(())
     }
 
+    /// Writes the crop transitions of the run as JSON (only history smoothing records them)
+    fn write_transition_log(&self, _path: &Path) -> Result<()> {
+        println!("Warning: --log-transitions is only supported with history smoothing");
+        Ok(())
+    }
+
+    /// Returns the region detections must be centered in to be used, if restricted
+    fn zone_of_interest(&self) -> Option<&crop::CropArea> {
+        None
+    }
+
+    /// Returns the crop most recently applied to an output frame (used for progress files)
+    fn last_applied_crop(&self) -> Option<&crop::CropResult> {
+        None
+    }
+
+    /// Restores the previously applied crop when resuming, to avoid a cold-start snap
+    fn restore_previous_crop(&mut self, _crop: crop::CropResult) {
+        // Default implementation does nothing
+    }
+
+    /// Passes the frame rate of the source video before the first frame is processed
+    fn set_frame_rate(&mut self, _frame_rate: f64) {
+        // Default implementation does nothing
+    }
+
     /// Prints debug information (can be overridden by concrete processors)
     fn print_debug_info(
         &self,
@@ -235,3 +719,235 @@ pub trait VideoProcessor {
         video_processor_utils::print_default_debug_info(objects, latest_crop, is_graphic);
     }
 }
+
+/// Extracts the detections used for cropping a frame
+///
+/// With `--object-names` detections matching any of the labels are used instead of `--object`.
+/// Face and head detections with implausible shapes are dropped before they reach the crop logic,
+/// and with `--dedup-distance` detections centered close to a more confident one are dropped too.
+fn extract_frame_objects<'a>(
+    args: &Args,
+    image: &usls::Image,
+    detection: &'a Y,
+    area_thresholds: &video_processor_utils::PerClassThresholds,
+    zone: Option<&crop::CropArea>,
+) -> Vec<&'a usls::Hbb> {
+    let object_names: Vec<&str> = args.object_names.iter().map(String::as_str).collect();
+    let mut objects = if object_names.is_empty() {
+        video_processor_utils::extract_objects_above_threshold(
+            detection,
+            &args.object,
+            args.object_prob_threshold,
+            area_thresholds,
+            image.width() as f32,
+            image.height() as f32,
+            zone,
+        )
+    } else {
+        video_processor_utils::extract_objects_matching_any(
+            detection,
+            &object_names,
+            args.object_prob_threshold,
+            area_thresholds,
+            image.width() as f32,
+            image.height() as f32,
+            zone,
+            args.nms_threshold,
+        )
+    };
+    let is_head_like = |name: &str| name == "face" || name == "head";
+    let has_head_like_names = if object_names.is_empty() {
+        is_head_like(&args.object)
+    } else {
+        object_names.iter().any(|name| is_head_like(name))
+    };
+    if has_head_like_names {
+        objects.retain(|hbb| {
+            video_processor_utils::is_valid_hbb(
+                hbb,
+                args.head_min_aspect,
+                args.head_max_aspect,
+                video_processor_utils::MIN_HEAD_SIZE,
+            )
+        });
+    }
+    if args.dedup_distance > 0.0 {
+        objects = video_processor_utils::deduplicate_hbbs_by_position(&objects, args.dedup_distance);
+    }
+    objects
+}
+
+/// Calculates the crop for a frame from its detections, honoring `--weighted-bbox`,
+/// `--allow-vertical-stack` and the `--crop-offset-x`, `--crop-offset-y` and `--crop-scale`
+/// adjustments
+///
+/// Returns `None` for frames without detections unless `--no-detection-strategy` is `center`.
+fn calculate_frame_crop(
+    args: &Args,
+    image: &usls::Image,
+    is_graphic: bool,
+    objects: &[&usls::Hbb],
+) -> Result<Option<crop::CropResult>> {
+    let crop_result = calculate_detection_crop(args, image, is_graphic, objects)?;
+    let transform = crop::CropTransform {
+        x_offset: args.crop_offset_x,
+        y_offset: args.crop_offset_y,
+        scale_factor: args.crop_scale,
+        ..crop::CropTransform::identity(image.width() as f32, image.height() as f32)
+    };
+    // The identity transform is skipped so unadjusted crops are not rounded by the round trip
+    // through the crop center
+    if transform == crop::CropTransform::identity(transform.frame_width, transform.frame_height) {
+        return Ok(crop_result);
+    }
+    Ok(crop_result.map(|crop_result| crop_result.apply_transform(&transform)))
+}
+
+/// Calculates the crop for a frame from its detections before any adjustments
+fn calculate_detection_crop(
+    args: &Args,
+    image: &usls::Image,
+    is_graphic: bool,
+    objects: &[&usls::Hbb],
+) -> Result<Option<crop::CropResult>> {
+    if let ([head1, head2], true, false) = (objects, args.allow_vertical_stack, is_graphic) {
+        if let Some(crop) = crop::calculate_vertical_two_heads_crop(
+            image.width() as f32,
+            image.height() as f32,
+            head1,
+            head2,
+        ) {
+            return Ok(Some(crop));
+        }
+    }
+    crop::calculate_crop_area(
+        args.use_stack_crop,
+        is_graphic,
+        image.width() as f32,
+        image.height() as f32,
+        objects,
+        &crop::CropOptions {
+            strategy: args.no_detection_strategy,
+            padding_percent: args.head_padding,
+            use_weighted_center: args.use_weighted_center,
+            weighted_bbox: args.weighted_bbox,
+        },
+    )
+}
+
+/// Moves a single or stacked crop against the camera motion
+///
+/// Returns `None` for resize and zoom-in crops, which are not tied to a subject position.
+fn compensate_camera_motion(
+    crop_result: &crop::CropResult,
+    camera_motion_dx: f32,
+    camera_motion_dy: f32,
+    frame_width: f32,
+    frame_height: f32,
+) -> Option<crop::CropResult> {
+    let guide = |area: &crop::CropArea| {
+        crop::calculate_optical_flow_guidance(
+            area,
+            camera_motion_dx,
+            camera_motion_dy,
+            frame_width,
+            frame_height,
+        )
+    };
+    match crop_result {
+        crop::CropResult::Single(_) | crop::CropResult::Stacked(_, _) => {
+            Some(crop_result.clone().map_crops(|area| guide(&area)))
+        }
+        crop::CropResult::Resize(_) | crop::CropResult::ZoomIn { .. } => None,
+    }
+}
+
+/// Loads the `--watermark-path` image, if any
+fn load_watermark(args: &Args) -> Result<Option<image::Watermark>> {
+    args.watermark_path
+        .as_ref()
+        .map(|path| {
+            image::Watermark::load(Path::new(path), args.watermark_position, args.watermark_opacity)
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::RgbImage;
+
+    #[test]
+    fn test_pre_processor_tints_frames() {
+        let config = VideoProcessorBuilder::new()
+            .with_pre_processor(|frame| {
+                let mut rgb = frame.to_rgb8();
+                for pixel in rgb.pixels_mut() {
+                    pixel[0] = 255;
+                }
+                Ok(usls::Image::from(rgb))
+            })
+            .build();
+
+        let frames = vec![usls::Image::from(RgbImage::from_pixel(4, 4, ::image::Rgb([10, 20, 30])))];
+        let tinted = config.pre_process(frames).unwrap();
+        assert_eq!(tinted.len(), 1);
+        assert_eq!(*tinted[0].to_rgb8().get_pixel(2, 2), ::image::Rgb([255, 20, 30]));
+
+        // Without a pre-processor frames pass through unchanged
+        let frames = vec![usls::Image::from(RgbImage::from_pixel(4, 4, ::image::Rgb([10, 20, 30])))];
+        let unchanged = VideoProcessorBuilder::new().build().pre_process(frames).unwrap();
+        assert_eq!(*unchanged[0].to_rgb8().get_pixel(2, 2), ::image::Rgb([10, 20, 30]));
+    }
+
+    #[test]
+    fn test_post_processor_resizes_output() {
+        let mut config = VideoProcessorBuilder::new()
+            .with_post_processor(|frame| {
+                let rgb = ::image::imageops::resize(
+                    &frame.to_rgb8(),
+                    frame.width() / 2,
+                    frame.height() / 2,
+                    ::image::imageops::FilterType::Triangle,
+                );
+                Ok(usls::Image::from(rgb))
+            })
+            .build();
+        let mut renderer =
+            video_processor_utils::CropRenderer::new(0).with_post_processor(config.post_processor.take());
+
+        let frame = usls::Image::from(RgbImage::new(1920, 1080));
+        let crop_result = crop::CropResult::Single(crop::CropArea::new(555.0, 0.0, 810.0, 1080.0));
+        let output = renderer
+            .render(&frame, &crop_result, image::PillarboxMode::Black)
+            .unwrap();
+        // The 1080x1920 portrait frame is halved by the post-processor
+        assert_eq!((output.width(), output.height()), (540, 960));
+    }
+
+    #[test]
+    fn test_calculate_frame_crop_applies_crop_adjustments() {
+        use argh::FromArgs;
+
+        let frame = usls::Image::from(RgbImage::new(1920, 1080));
+        let head = usls::Hbb::from_cxcywh(960.0, 400.0, 100.0, 120.0);
+        let objects = [&head];
+        let crop_area = |args: &[&str]| {
+            let args = Args::from_args(&["land2port"], args).unwrap();
+            match calculate_frame_crop(&args, &frame, false, &objects).unwrap() {
+                Some(crop::CropResult::Single(area)) => area,
+                other => panic!("Expected single crop, got {:?}", other),
+            }
+        };
+
+        let unadjusted = crop_area(&[]);
+        let moved = crop_area(&["--crop-offset-x", "100", "--crop-offset-y", "-50"]);
+        assert_eq!(moved.x, unadjusted.x + 100.0);
+        assert_eq!((moved.y, moved.width, moved.height), (0.0, unadjusted.width, unadjusted.height));
+
+        // Zooming in keeps the crop centered on the same point
+        let zoomed = crop_area(&["--crop-scale", "0.5"]);
+        assert_eq!(zoomed.width, unadjusted.width * 0.5);
+        assert_eq!(zoomed.x + zoomed.width / 2.0, unadjusted.x + unadjusted.width / 2.0);
+    }
+}
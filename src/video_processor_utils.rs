@@ -1,7 +1,12 @@
 use crate::crop;
+use crate::history;
 use crate::image;
-use anyhow::Result;
+use crate::video_processor::FrameTransformFn;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
 use std::env;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use usls::{Hbb, Viewer, Y};
 
 /// Helper function to check if debug logging is enabled
@@ -18,18 +23,435 @@ pub fn debug_println(args: std::fmt::Arguments) {
     }
 }
 
+/// State of the full-frame rendering used by `--debug-overlay`
+struct DebugOverlay {
+    smooth_percentage: f32,
+    previous_crop: Option<crop::CropResult>,
+    confidences: Vec<f32>,
+}
+
+/// Renders output frames from crop results
+///
+/// Keeps the resized crop cache across frames and applies the optional post-processor to every
+/// rendered frame.
+pub struct CropRenderer {
+    image_cache: image::ImageCache,
+    post_processor: Option<FrameTransformFn>,
+    resize_filter: image::ResizeFilter,
+    sharpen: image::Sharpen,
+    watermark: Option<image::Watermark>,
+    perf_stats: Option<PerformanceStats>,
+    thumbnail_interval: u64,
+    thumbnail_capacity: usize,
+    thumbnails: Vec<usls::Image>,
+    output_frame_count: u64,
+    debug_overlay: Option<DebugOverlay>,
+    dry_run: Option<DryRunSummary>,
+}
+
+impl CropRenderer {
+    /// Creates a renderer caching up to `image_cache_size` resized crops (0 disables caching)
+    pub fn new(image_cache_size: usize) -> Self {
+        Self {
+            image_cache: image::ImageCache::new(image_cache_size),
+            post_processor: None,
+            resize_filter: image::ResizeFilter::default(),
+            sharpen: image::Sharpen::default(),
+            watermark: None,
+            perf_stats: None,
+            thumbnail_interval: 0,
+            thumbnail_capacity: 0,
+            thumbnails: Vec::new(),
+            output_frame_count: 0,
+            debug_overlay: None,
+            dry_run: None,
+        }
+    }
+
+    /// Logs and counts crop decisions instead of rendering and writing frames when `enabled`
+    /// (see `--dry-run`)
+    pub fn with_dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled.then(DryRunSummary::default);
+        self
+    }
+
+    /// Returns the crop decisions counted in a dry run
+    pub fn dry_run_summary(&self) -> Option<&DryRunSummary> {
+        self.dry_run.as_ref()
+    }
+
+    /// Renders the full frame with the crop drawn on it instead of the cropped frame when
+    /// `enabled` (see `--debug-overlay`)
+    ///
+    /// The previous crop is also drawn when it differs from the crop by more than
+    /// `smooth_percentage` percent of the frame width.
+    pub fn with_debug_overlay(mut self, enabled: bool, smooth_percentage: f32) -> Self {
+        self.debug_overlay = enabled.then(|| DebugOverlay {
+            smooth_percentage,
+            previous_crop: None,
+            confidences: Vec::new(),
+        });
+        self
+    }
+
+    /// Sets the detection confidences labelled on the following debug overlay frames
+    pub fn set_overlay_confidences(&mut self, confidences: Vec<f32>) {
+        if let Some(overlay) = &mut self.debug_overlay {
+            overlay.confidences = confidences;
+        }
+    }
+
+    /// Keeps every `interval`-th output frame, up to `capacity` frames, for a thumbnail grid
+    /// (an interval of 0 keeps none)
+    pub fn with_thumbnails(mut self, interval: u64, capacity: usize) -> Self {
+        self.thumbnail_interval = interval;
+        self.thumbnail_capacity = capacity;
+        self
+    }
+
+    /// Returns the output frames kept for the thumbnail grid, oldest first
+    pub fn thumbnails(&self) -> &[usls::Image] {
+        &self.thumbnails
+    }
+
+    /// Returns the number of frames rendered so far
+    pub fn output_frame_count(&self) -> u64 {
+        self.output_frame_count
+    }
+
+    /// Counts an output frame and keeps it if it is due for the thumbnail grid
+    fn collect_thumbnail(&mut self, frame: &usls::Image) {
+        if self.thumbnail_interval > 0
+            && self.output_frame_count % self.thumbnail_interval == 0
+            && self.thumbnails.len() < self.thumbnail_capacity
+        {
+            self.thumbnails.push(frame.clone());
+        }
+        self.output_frame_count += 1;
+    }
+
+    /// Collects per-frame timings of the processing stages when `enabled` (see `--perf-stats`)
+    pub fn with_perf_stats(mut self, enabled: bool) -> Self {
+        self.perf_stats = enabled.then(PerformanceStats::default);
+        self
+    }
+
+    /// Returns the collected timings, if enabled
+    pub fn perf_stats(&self) -> Option<&PerformanceStats> {
+        self.perf_stats.as_ref()
+    }
+
+    /// Returns the collected timings for recording stages outside the renderer, if enabled
+    pub fn perf_stats_mut(&mut self) -> Option<&mut PerformanceStats> {
+        self.perf_stats.as_mut()
+    }
+
+    /// Scales crops with `resize_filter` instead of Lanczos3
+    pub fn with_resize_filter(mut self, resize_filter: image::ResizeFilter) -> Self {
+        self.resize_filter = resize_filter;
+        self
+    }
+
+    /// Sharpens crops with `sharpen` after they are scaled
+    pub fn with_sharpen(mut self, sharpen: image::Sharpen) -> Self {
+        self.sharpen = sharpen;
+        self
+    }
+
+    /// Blends `watermark` onto every rendered frame
+    pub fn with_watermark(mut self, watermark: Option<image::Watermark>) -> Self {
+        self.watermark = watermark;
+        self
+    }
+
+    /// Applies `post_processor` to every rendered frame, e.g. for a logo overlay
+    pub fn with_post_processor(mut self, post_processor: Option<FrameTransformFn>) -> Self {
+        self.post_processor = post_processor;
+        self
+    }
+
+    /// Crops a frame into a portrait output frame and applies the post-processor
+    ///
+    /// With a debug overlay the full frame is returned with the crop drawn on it, without
+    /// post-processing.
+    ///
+    /// # Arguments
+    /// * `img` - The full source frame
+    /// * `crop_result` - The crop to apply
+    /// * `pillarbox` - How to fill any padding around the crop
+    pub fn render(
+        &mut self,
+        img: &usls::Image,
+        crop_result: &crop::CropResult,
+        pillarbox: image::PillarboxMode,
+    ) -> Result<usls::Image> {
+        if let Some(overlay) = &mut self.debug_overlay {
+            let previous_crop = overlay.previous_crop.as_ref().filter(|previous| {
+                !crop::is_crop_similar(previous, crop_result, img.width() as f32, overlay.smooth_percentage)
+            });
+            let frame = image::draw_crop_overlay(img, crop_result, previous_crop, &overlay.confidences);
+            overlay.previous_crop = Some(crop_result.clone());
+            return Ok(frame);
+        }
+
+        let render_start = Instant::now();
+        let cropped_img = image::create_cropped_image(
+            img,
+            crop_result,
+            img.height() as u32,
+            pillarbox,
+            image::RenderOptions {
+                filter: self.resize_filter,
+                sharpen: self.sharpen,
+                watermark: self.watermark.as_ref(),
+            },
+            Some(&mut self.image_cache),
+        )?;
+        if let Some(stats) = &mut self.perf_stats {
+            stats.image_render_ms.push(duration_ms(render_start.elapsed()));
+        }
+        match &self.post_processor {
+            Some(post_processor) => post_processor(&cropped_img),
+            None => Ok(cropped_img),
+        }
+    }
+}
+
 /// Processes and displays a crop result
 pub fn process_and_display_crop(
     img: &usls::Image,
     crop_result: &crop::CropResult,
     viewer: &mut Viewer,
     headless: bool,
+    pillarbox: image::PillarboxMode,
+    renderer: &mut CropRenderer,
 ) -> Result<()> {
-    let cropped_img = image::create_cropped_image(img, crop_result, img.height() as u32)?;
+    if let Some(summary) = &mut renderer.dry_run {
+        summary.record(crop_result);
+        println!("{}", dry_run_log_line(renderer.output_frame_count, crop_result));
+        renderer.output_frame_count += 1;
+        return Ok(());
+    }
+
+    let cropped_img = renderer.render(img, crop_result, pillarbox)?;
     if !headless {
         viewer.imshow(&cropped_img)?;
     }
+    renderer.collect_thumbnail(&cropped_img);
+    let write_start = Instant::now();
     viewer.write_video_frame(&cropped_img)?;
+    if let Some(stats) = renderer.perf_stats_mut() {
+        stats.write_ms.push(duration_ms(write_start.elapsed()));
+    }
+    Ok(())
+}
+
+/// Formats a dry-run crop decision as a JSON log line with the output frame index and crop bounds
+fn dry_run_log_line(frame_index: u64, crop_result: &crop::CropResult) -> String {
+    let mut crops = Vec::new();
+    crop_result.for_each_crop(|area| crops.push([area.x, area.y, area.width, area.height]));
+    serde_json::json!({
+        "frame": frame_index,
+        "crop_type": crop_type_label(crop_result).to_lowercase(),
+        "crops": crops,
+    })
+    .to_string()
+}
+
+/// Numbers of output frames per crop type, counted with `--dry-run`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DryRunSummary {
+    pub single: u64,
+    pub stacked: u64,
+    pub resize: u64,
+    pub zoom: u64,
+}
+
+impl DryRunSummary {
+    /// Counts one output frame with this crop
+    fn record(&mut self, crop_result: &crop::CropResult) {
+        match crop_result {
+            crop::CropResult::Single(_) => self.single += 1,
+            crop::CropResult::Stacked(_, _) => self.stacked += 1,
+            crop::CropResult::Resize(_) => self.resize += 1,
+            crop::CropResult::ZoomIn { .. } => self.zoom += 1,
+        }
+    }
+
+    /// Formats the frame count of each crop type
+    pub fn summary(&self) -> String {
+        format!(
+            "Dry run crop decisions: {} single, {} stacked, {} resize, {} zoom ({} frames)",
+            self.single,
+            self.stacked,
+            self.resize,
+            self.zoom,
+            self.single + self.stacked + self.resize + self.zoom
+        )
+    }
+}
+
+/// Converts a duration to fractional milliseconds
+fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Per-frame timings of the processing stages, in milliseconds, collected with `--perf-stats`
+#[derive(Debug, Default)]
+pub struct PerformanceStats {
+    pub model_forward_ms: Vec<f64>,
+    pub crop_calc_ms: Vec<f64>,
+    pub image_render_ms: Vec<f64>,
+    pub write_ms: Vec<f64>,
+}
+
+impl PerformanceStats {
+    /// Records one model forward pass over a batch, split evenly across its frames
+    pub fn add_model_forward(&mut self, elapsed: Duration, frame_count: usize) {
+        let per_frame_ms = duration_ms(elapsed) / frame_count.max(1) as f64;
+        self.model_forward_ms
+            .extend(std::iter::repeat_n(per_frame_ms, frame_count));
+    }
+
+    /// Records the crop calculation of one frame
+    pub fn add_crop_calc(&mut self, elapsed: Duration) {
+        self.crop_calc_ms.push(duration_ms(elapsed));
+    }
+
+    /// Formats the mean and standard deviation of each stage, one stage per line
+    pub fn summary(&self) -> String {
+        let stages = [
+            ("model forward", &self.model_forward_ms),
+            ("crop calculation", &self.crop_calc_ms),
+            ("image render", &self.image_render_ms),
+            ("video write", &self.write_ms),
+        ];
+        let mut summary = String::from("Performance per frame:");
+        for (name, samples) in stages {
+            let count = samples.len().max(1) as f64;
+            let mean = samples.iter().sum::<f64>() / count;
+            let variance = samples.iter().map(|ms| (ms - mean).powi(2)).sum::<f64>() / count;
+            summary.push_str(&format!(
+                "\n  {:<16} {:>9.3} ± {:.3} ms ({} frames)",
+                name,
+                mean,
+                variance.sqrt(),
+                samples.len()
+            ));
+        }
+        summary
+    }
+}
+
+/// Number of evenly spaced crops listed in the `--perf-stats` crop history overview
+pub const CROP_OVERVIEW_SAMPLES: usize = 10;
+
+/// Formats an overview of the crops of a run for `--perf-stats`
+///
+/// Lists `sample_count` evenly spaced crops as their type and dominant center x, followed by
+/// every change of crop type with its position in the run.
+pub fn crop_history_overview(history: &history::CropHistory, sample_count: usize) -> String {
+    let mut overview = format!("Crop history overview ({} frames):", history.len());
+
+    let samples: Vec<String> = history
+        .sample_at(sample_count)
+        .into_iter()
+        .filter_map(|frame| {
+            crop_result_to_dominant_hbb(&frame.crop)
+                .map(|hbb| format!("{}@{:.0}", crop_type_label(&frame.crop), hbb.cx()))
+        })
+        .collect();
+    overview.push_str(&format!("\n  samples: {}", samples.join(" ")));
+
+    let mut changes = Vec::new();
+    let mut last_label = None;
+    for (crop_result, position) in history.time_ordered_crop_sequence() {
+        let label = crop_type_label(crop_result);
+        if last_label != Some(label) {
+            changes.push(format!("{} at {:.0}%", label, position * 100.0));
+            last_label = Some(label);
+        }
+    }
+    overview.push_str(&format!("\n  crop types: {}", changes.join(", ")));
+
+    overview
+}
+
+/// Bytes in a gigabyte, as used by `--min-free-gb`
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Gets the free space in bytes on the disk holding a path
+pub fn available_disk_space(path: &Path) -> Result<u64> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve path: {}", path.display()))?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    // The disk with the longest mount point containing the path is the one it lives on
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+        .with_context(|| format!("Could not find the disk holding {}", path.display()))
+}
+
+/// Checks that at least `min_free_gb` gigabytes are free where the output is written
+///
+/// If the free space cannot be determined a warning is printed and the check passes.
+///
+/// # Arguments
+/// * `output_dir` - The directory the output is written to
+/// * `min_free_gb` - The minimum free space required, in gigabytes (0 disables the check)
+/// * `available_space` - Looks up the free bytes for a path (normally `available_disk_space`)
+pub fn ensure_free_space(
+    output_dir: &Path,
+    min_free_gb: f32,
+    available_space: impl Fn(&Path) -> Result<u64>,
+) -> Result<()> {
+    if min_free_gb <= 0.0 {
+        return Ok(());
+    }
+
+    let available_bytes = match available_space(output_dir) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            println!("Warning: could not check free disk space: {:#}", e);
+            return Ok(());
+        }
+    };
+    let available_gb = available_bytes as f64 / BYTES_PER_GB;
+    if available_gb < min_free_gb as f64 {
+        anyhow::bail!(
+            "Only {:.1} GB free for {}, at least {:.1} GB required (see --min-free-gb)",
+            available_gb,
+            output_dir.display(),
+            min_free_gb
+        );
+    }
+    Ok(())
+}
+
+/// Crops a frame and saves it as an image file (format chosen by the file extension)
+///
+/// # Arguments
+/// * `img` - The full source frame
+/// * `crop_result` - The crop to apply
+/// * `pillarbox` - How to fill any padding around the crop
+/// * `options` - How the crop is scaled and finished
+/// * `path` - Where to write the image
+pub fn save_cropped_frame(
+    img: &usls::Image,
+    crop_result: &crop::CropResult,
+    pillarbox: image::PillarboxMode,
+    options: image::RenderOptions,
+    path: &Path,
+) -> Result<()> {
+    let cropped_img =
+        image::create_cropped_image(img, crop_result, img.height() as u32, pillarbox, options, None)?;
+    cropped_img
+        .save(path)
+        .with_context(|| format!("Failed to save frame to {}", path.display()))?;
     Ok(())
 }
 
@@ -70,6 +492,124 @@ pub fn predict_current_hbb(three_frames_ago: &Hbb, two_frames_ago: &Hbb, last_fr
     )
 }
 
+/// Computes the confidence in a predicted position after a number of consecutive missed frames
+///
+/// Confidence starts at `1.0` and drops by `1.0 / max_prediction_frames` per missed frame.
+///
+/// # Arguments
+/// * `missed_frames` - Number of consecutive frames without a detection
+/// * `max_prediction_frames` - Number of missed frames after which confidence reaches zero
+pub fn prediction_confidence(missed_frames: usize, max_prediction_frames: usize) -> f32 {
+    if max_prediction_frames == 0 {
+        return 0.0;
+    }
+    (1.0 - missed_frames as f32 / max_prediction_frames as f32).max(0.0)
+}
+
+/// Moves an HBB part of the way toward a predicted HBB
+///
+/// # Arguments
+/// * `last_frame` - The HBB from the last frame
+/// * `predicted` - The predicted HBB for the current frame
+/// * `alpha` - How far to move toward the prediction, from 0.0 (stay) to 1.0 (full prediction)
+pub fn blend_hbb_toward(last_frame: &Hbb, predicted: &Hbb, alpha: f32) -> Hbb {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let lerp = |a: f32, b: f32| a + (b - a) * alpha;
+    Hbb::from_cxcywh(
+        lerp(last_frame.cx(), predicted.cx()),
+        lerp(last_frame.cy(), predicted.cy()),
+        lerp(last_frame.width(), predicted.width()),
+        lerp(last_frame.height(), predicted.height()),
+    )
+}
+
+/// Smallest centroid movement in pixels per frame that counts toward a crop trend
+const MIN_TREND_STEP: f32 = 0.5;
+
+/// Checks whether the centers of consecutive crops keep moving in the same direction
+///
+/// # Arguments
+/// * `crops` - Crop areas from oldest to newest; each step between them must move by more than
+///   half a pixel and point the same way as the step before it
+pub fn is_consistent_crop_trend(crops: &[crop::CropArea]) -> bool {
+    if crops.len() < 2 {
+        return false;
+    }
+    let center = |area: &crop::CropArea| (area.x + area.width / 2.0, area.y + area.height / 2.0);
+    let steps: Vec<(f32, f32)> = crops
+        .windows(2)
+        .map(|pair| {
+            let (x1, y1) = center(&pair[0]);
+            let (x2, y2) = center(&pair[1]);
+            (x2 - x1, y2 - y1)
+        })
+        .collect();
+    let is_moving = steps
+        .iter()
+        .all(|(dx, dy)| (dx * dx + dy * dy).sqrt() > MIN_TREND_STEP);
+    let is_same_direction = steps
+        .windows(2)
+        .all(|pair| pair[0].0 * pair[1].0 + pair[0].1 * pair[1].1 > 0.0);
+    is_moving && is_same_direction
+}
+
+/// Predicts the current crop area from the previous three, like `predict_current_hbb`
+///
+/// # Arguments
+/// * `three_frames_ago` - The crop area from three frames ago
+/// * `two_frames_ago` - The crop area from two frames ago
+/// * `last_frame` - The crop area from the last frame
+/// * `frame_width` - The width of the frame
+/// * `frame_height` - The height of the frame
+///
+/// # Returns
+/// The last crop area moved by its velocity and half its acceleration, clamped to the frame
+pub fn predict_crop_area(
+    three_frames_ago: &crop::CropArea,
+    two_frames_ago: &crop::CropArea,
+    last_frame: &crop::CropArea,
+    frame_width: f32,
+    frame_height: f32,
+) -> crop::CropArea {
+    let v1_x = two_frames_ago.x - three_frames_ago.x;
+    let v1_y = two_frames_ago.y - three_frames_ago.y;
+    let v2_x = last_frame.x - two_frames_ago.x;
+    let v2_y = last_frame.y - two_frames_ago.y;
+
+    let predicted_x = last_frame.x + v2_x + 0.5 * (v2_x - v1_x);
+    let predicted_y = last_frame.y + v2_y + 0.5 * (v2_y - v1_y);
+    crop::CropArea::new(
+        predicted_x.clamp(0.0, (frame_width - last_frame.width).max(0.0)),
+        predicted_y.clamp(0.0, (frame_height - last_frame.height).max(0.0)),
+        last_frame.width,
+        last_frame.height,
+    )
+}
+
+/// Builds an HBB covering the given crop area (the inverse of deriving a crop from an HBB)
+pub fn build_hbb_from_crop_area(crop: &crop::CropArea) -> Hbb {
+    Hbb::from_xywh(crop.x, crop.y, crop.width, crop.height)
+}
+
+/// Extracts the dominant crop area of a crop result as an HBB
+///
+/// For stacked crops this is the first (top) crop, for zooms the current zoom position.
+pub fn crop_result_to_dominant_hbb(crop: &crop::CropResult) -> Option<Hbb> {
+    match crop {
+        crop::CropResult::Single(area) | crop::CropResult::Resize(area) => {
+            Some(build_hbb_from_crop_area(area))
+        }
+        crop::CropResult::Stacked(area, _) => Some(build_hbb_from_crop_area(area)),
+        crop::CropResult::ZoomIn {
+            start,
+            end,
+            progress,
+        } => Some(build_hbb_from_crop_area(&crop::interpolate_crop_area(
+            start, end, *progress,
+        ))),
+    }
+}
+
 /// Prints the default debug information for video processors
 pub fn print_default_debug_info(objects: &[&usls::Hbb], latest_crop: &crop::CropResult, is_graphic: bool) {
     debug_println(format_args!("--------------------------------"));
@@ -78,15 +618,168 @@ pub fn print_default_debug_info(objects: &[&usls::Hbb], latest_crop: &crop::Crop
     debug_println(format_args!("is_graphic: {:?}", is_graphic));
 }
 
+/// Returns the crop type shown in debug overlays
+fn crop_type_label(crop: &crop::CropResult) -> &'static str {
+    match crop {
+        crop::CropResult::Single(_) => "SINGLE",
+        crop::CropResult::Stacked(_, _) => "STACKED",
+        crop::CropResult::Resize(_) => "RESIZE",
+        crop::CropResult::ZoomIn { .. } => "ZOOM",
+    }
+}
+
+/// Builds a 9:16 debug frame showing what the crop decision was based on
+///
+/// The source frame is centered on a black 9:16 canvas (padded above and below for landscape
+/// frames) with the detected heads outlined in green and the crop areas in red. The frame
+/// number is written in the top-left corner, the object count at the top center and the crop
+/// type in the top-right corner.
+///
+/// # Arguments
+/// * `image` - The source frame
+/// * `objects` - The detections used for the crop
+/// * `crop` - The crop calculated for the frame
+/// * `frame_n` - The frame number to show
+pub fn build_debug_frame(
+    image: &usls::Image,
+    objects: &[&Hbb],
+    crop: &crop::CropResult,
+    frame_n: u64,
+) -> Result<usls::Image> {
+    let frame = image.to_rgb8();
+    let (width, height) = frame.dimensions();
+    if width == 0 || height == 0 {
+        anyhow::bail!("Cannot build a debug frame from an empty {}x{} image", width, height);
+    }
+
+    let canvas_height = height.max(width * 16 / 9);
+    let canvas_width = width.max(canvas_height * 9 / 16);
+    let x_offset = (canvas_width - width) / 2;
+    let y_offset = (canvas_height - height) / 2;
+    let mut canvas = ::image::RgbImage::new(canvas_width, canvas_height);
+    ::image::imageops::overlay(&mut canvas, &frame, x_offset as i64, y_offset as i64);
+
+    // Lines and text grow with the canvas so they stay readable on full-resolution frames
+    let scale = (canvas_width / 270).max(1);
+    let to_canvas = |area: crop::CropArea| {
+        crop::CropArea::new(area.x + x_offset as f32, area.y + y_offset as f32, area.width, area.height)
+    };
+
+    let green = ::image::Rgb([0, 255, 0]);
+    for object in objects {
+        let area = crop::CropArea::new(object.xmin(), object.ymin(), object.width(), object.height());
+        image::draw_rect_outline(&mut canvas, &to_canvas(area), scale, green);
+    }
+    let red = ::image::Rgb([255, 0, 0]);
+    crop.for_each_crop(|area| image::draw_rect_outline(&mut canvas, &to_canvas(area.clone()), scale, red));
+
+    let white = ::image::Rgb([255, 255, 255]);
+    let margin = 2 * scale;
+    image::draw_text(&mut canvas, &frame_n.to_string(), margin, margin, scale, white);
+    let count = objects.len().to_string();
+    let count_x = canvas_width.saturating_sub(image::text_width(&count, scale)) / 2;
+    image::draw_text(&mut canvas, &count, count_x, margin, scale, white);
+    let label = crop_type_label(crop);
+    let label_x = canvas_width.saturating_sub(image::text_width(label, scale) + margin);
+    image::draw_text(&mut canvas, label, label_x, margin, scale, white);
+
+    Ok(usls::Image::from(canvas))
+}
+
+/// Smallest width or height in pixels for a head detection to be trusted
+pub const MIN_HEAD_SIZE: f32 = 4.0;
+
+/// Checks whether a detection has a plausible shape
+///
+/// # Arguments
+/// * `hbb` - The detection to check
+/// * `min_aspect` - The smallest allowed width / height ratio
+/// * `max_aspect` - The largest allowed width / height ratio
+/// * `min_size` - The smallest allowed width and height in pixels
+pub fn is_valid_hbb(hbb: &Hbb, min_aspect: f32, max_aspect: f32, min_size: f32) -> bool {
+    if hbb.height() <= 0.0 {
+        return false;
+    }
+    let aspect = hbb.width() / hbb.height();
+    aspect >= min_aspect && aspect <= max_aspect && hbb.width().min(hbb.height()) >= min_size
+}
+
+/// Checks whether the center of an HBB lies inside a zone
+pub fn is_center_in_zone(hbb: &Hbb, zone: &crop::CropArea) -> bool {
+    hbb.cx() >= zone.x
+        && hbb.cx() <= zone.x + zone.width
+        && hbb.cy() >= zone.y
+        && hbb.cy() <= zone.y + zone.height
+}
+
+/// Minimum detection areas, as fractions of the frame, per detection class
+///
+/// Classes without their own threshold use the global `--object-area-threshold`, except `ball`,
+/// which defaults to 0.0 because balls are always small.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerClassThresholds {
+    default: f32,
+    thresholds: HashMap<String, f32>,
+}
+
+impl PerClassThresholds {
+    /// Creates thresholds that use `default` for every class but `ball`
+    pub fn new(default: f32) -> Self {
+        Self {
+            default,
+            thresholds: HashMap::from([("ball".to_string(), 0.0)]),
+        }
+    }
+
+    /// Sets the threshold of one class
+    pub fn with_threshold(mut self, class_name: &str, threshold: f32) -> Self {
+        self.thresholds.insert(class_name.to_string(), threshold);
+        self
+    }
+
+    /// Parses `name:value` entries given with `--class-area-threshold`, e.g. `heads:0.005`
+    ///
+    /// # Arguments
+    /// * `default` - The threshold for classes that are not listed
+    /// * `specs` - The per-class entries; later entries for the same class win
+    pub fn parse(default: f32, specs: &[String]) -> Result<Self> {
+        let mut thresholds = Self::new(default);
+        for spec in specs {
+            let (class_name, value) = spec
+                .rsplit_once(':')
+                .with_context(|| format!("Invalid --class-area-threshold '{}', expected name:value", spec))?;
+            let threshold: f32 = value
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid area threshold in --class-area-threshold '{}'", spec))?;
+            if class_name.trim().is_empty() || !(0.0..=1.0).contains(&threshold) {
+                anyhow::bail!(
+                    "Invalid --class-area-threshold '{}', expected a class name and a fraction of the frame between 0 and 1",
+                    spec
+                );
+            }
+            thresholds = thresholds.with_threshold(class_name.trim(), threshold);
+        }
+        Ok(thresholds)
+    }
+
+    /// Returns the minimum area of detections of `class_name`
+    pub fn threshold_for(&self, class_name: &str) -> f32 {
+        self.thresholds.get(class_name).copied().unwrap_or(self.default)
+    }
+}
+
 /// Extracts head detections above the probability threshold from YOLO detection results
 pub fn extract_objects_above_threshold<'a>(
     detection: &'a Y,
     object_name: &str,
     object_prob_threshold: f32,
-    object_area_threshold: f32,
+    area_thresholds: &PerClassThresholds,
     frame_width: f32,
     frame_height: f32,
+    zone: Option<&crop::CropArea>,
 ) -> Vec<&'a Hbb> {
+    let object_area_threshold = area_thresholds.threshold_for(object_name);
     if let Some(hbbs) = detection.hbbs() {
         let frame_area = frame_width * frame_height;
         hbbs.iter()
@@ -105,17 +798,14 @@ pub fn extract_objects_above_threshold<'a>(
                     false
                 };
 
-                // Check area threshold (skip for ball objects)
-                let meets_area_threshold = if object_name == "ball" {
-                    true // Skip area threshold for ball objects
-                } else {
-                    // Calculate area as percentage of frame
-                    let object_area = hbb.width() * hbb.height();
-                    let area_percentage = object_area / frame_area;
-                    area_percentage >= object_area_threshold
-                };
+                // Check area threshold as percentage of frame
+                let object_area = hbb.width() * hbb.height();
+                let meets_area_threshold = object_area / frame_area >= object_area_threshold;
+
+                // Check that the object's center lies inside the zone of interest, if any
+                let is_in_zone = zone.is_none_or(|zone| is_center_in_zone(hbb, zone));
 
-                meets_threshold && matches_name && meets_area_threshold
+                meets_threshold && matches_name && meets_area_threshold && is_in_zone
             })
             .collect()
     } else {
@@ -123,8 +813,740 @@ pub fn extract_objects_above_threshold<'a>(
     }
 }
 
+/// Computes the intersection over union of two HBBs
+pub fn hbb_iou(a: &Hbb, b: &Hbb) -> f32 {
+    let overlap_width = (a.xmin() + a.width()).min(b.xmin() + b.width()) - a.xmin().max(b.xmin());
+    let overlap_height =
+        (a.ymin() + a.height()).min(b.ymin() + b.height()) - a.ymin().max(b.ymin());
+    if overlap_width <= 0.0 || overlap_height <= 0.0 {
+        return 0.0;
+    }
+    let intersection = overlap_width * overlap_height;
+    let union = a.width() * a.height() + b.width() * b.height() - intersection;
+    if union <= 0.0 { 0.0 } else { intersection / union }
+}
+
+/// Extracts detections matching any of several labels above the probability threshold
+///
+/// Useful for models trained with overlapping labels such as `head` and `face`. When two matched
+/// boxes overlap by more than `nms_threshold` IoU only the more confident one is kept.
+///
+/// # Arguments
+/// * `detection` - The YOLO detection results
+/// * `object_names` - The labels to keep
+/// * `object_prob_threshold` - The minimum confidence
+/// * `area_thresholds` - The minimum area of each label as a fraction of the frame
+/// * `frame_width` - The width of the frame
+/// * `frame_height` - The height of the frame
+/// * `zone` - The region detections must be centered in, if restricted
+/// * `nms_threshold` - The IoU above which two matched boxes are treated as duplicates
+///
+/// # Returns
+/// The matched detections, most confident first
+#[allow(clippy::too_many_arguments)]
+pub fn extract_objects_matching_any<'a>(
+    detection: &'a Y,
+    object_names: &[&str],
+    object_prob_threshold: f32,
+    area_thresholds: &PerClassThresholds,
+    frame_width: f32,
+    frame_height: f32,
+    zone: Option<&crop::CropArea>,
+    nms_threshold: f32,
+) -> Vec<&'a Hbb> {
+    let mut candidates: Vec<&Hbb> = object_names
+        .iter()
+        .flat_map(|object_name| {
+            extract_objects_above_threshold(
+                detection,
+                object_name,
+                object_prob_threshold,
+                area_thresholds,
+                frame_width,
+                frame_height,
+                zone,
+            )
+        })
+        .collect();
+    sort_by_confidence_desc(&mut candidates);
+
+    let mut kept: Vec<&Hbb> = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        if kept.iter().all(|hbb| hbb_iou(hbb, candidate) <= nms_threshold) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// Sorts detections by confidence, most confident first
+fn sort_by_confidence_desc(hbbs: &mut [&Hbb]) {
+    hbbs.sort_by(|a, b| {
+        b.confidence()
+            .unwrap_or(0.0)
+            .partial_cmp(&a.confidence().unwrap_or(0.0))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Removes detections whose centers are closer than `min_center_distance` to a more confident one
+///
+/// Catches duplicates from different anchor scales that overlap too little for IoU-based NMS.
+///
+/// # Arguments
+/// * `heads` - The detections to deduplicate
+/// * `min_center_distance` - The minimum distance in pixels between kept centers
+///
+/// # Returns
+/// The kept detections, most confident first
+pub fn deduplicate_hbbs_by_position<'a>(heads: &[&'a Hbb], min_center_distance: f32) -> Vec<&'a Hbb> {
+    let mut candidates = heads.to_vec();
+    sort_by_confidence_desc(&mut candidates);
+
+    let mut kept: Vec<&Hbb> = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let is_far_from_kept = kept.iter().all(|hbb| {
+            (hbb.cx() - candidate.cx()).hypot(hbb.cy() - candidate.cy()) >= min_center_distance
+        });
+        if is_far_from_kept {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// Chooses the crop for the oldest frame of a lookahead window
+///
+/// The previous crop is kept while the oldest crop is similar to it. A different crop is only
+/// taken when the newest crop in the window is similar to it, i.e. the change persists through
+/// the lookahead; otherwise the change is treated as a transient glitch.
+///
+/// # Arguments
+/// * `previous` - The crop applied to the previous output frame
+/// * `window` - The crops of the oldest buffered frame followed by the lookahead frames
+/// * `frame_width` - The width of the frame
+/// * `threshold` - The similarity threshold percentage
+pub fn choose_lookahead_crop(
+    previous: Option<&crop::CropResult>,
+    window: &[crop::CropResult],
+    frame_width: f32,
+    threshold: f32,
+) -> Option<crop::CropResult> {
+    let (Some(oldest), Some(newest)) = (window.first(), window.last()) else {
+        return previous.cloned();
+    };
+    let Some(previous) = previous else {
+        return Some(oldest.clone());
+    };
+
+    if crop::is_crop_similar(oldest, previous, frame_width, threshold) {
+        Some(previous.clone())
+    } else if crop::is_crop_similar(newest, oldest, frame_width, threshold) {
+        Some(oldest.clone())
+    } else {
+        Some(previous.clone())
+    }
+}
+
+/// Updates an exponential moving average of motion speed
+///
+/// # Arguments
+/// * `ema` - The current average, or `None` before the first sample
+/// * `speed` - The latest motion speed in pixels per frame
+/// * `window_frames` - The number of frames the average approximately spans
+pub fn update_motion_ema(ema: Option<f32>, speed: f32, window_frames: usize) -> f32 {
+    let alpha = 2.0 / (window_frames as f32 + 1.0);
+    match ema {
+        Some(ema) => alpha * speed + (1.0 - alpha) * ema,
+        None => speed,
+    }
+}
+
+/// Reuses the CLIP graphic classification of a frame for the following frames
+///
+/// Classification only runs for frames without detections, at most once every `interval`
+/// frames. Frames with detections are never graphic.
+pub struct GraphicCheckCache {
+    interval: usize,
+    last_graphic_check_frame: Option<u64>,
+    last_is_graphic: bool,
+}
+
+impl GraphicCheckCache {
+    /// Creates a cache that classifies at most once every `interval` frames (0 behaves like 1)
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            last_graphic_check_frame: None,
+            last_is_graphic: false,
+        }
+    }
+
+    /// Decides whether a frame is a graphic, calling `classify` only when the cached result is stale
+    ///
+    /// # Arguments
+    /// * `frame_index` - The index of the frame
+    /// * `has_objects` - Whether any objects were detected in the frame
+    /// * `classify` - Runs the CLIP classification of the frame
+    pub fn is_graphic(
+        &mut self,
+        frame_index: u64,
+        has_objects: bool,
+        classify: impl FnOnce() -> Result<bool>,
+    ) -> Result<bool> {
+        if has_objects {
+            self.last_is_graphic = false;
+            return Ok(false);
+        }
+
+        let is_stale = self
+            .last_graphic_check_frame
+            .is_none_or(|last| frame_index.saturating_sub(last) >= self.interval as u64);
+        if is_stale {
+            self.last_is_graphic = classify()?;
+            self.last_graphic_check_frame = Some(frame_index);
+        }
+        Ok(self.last_is_graphic)
+    }
+}
+
+/// Maps output frames back to the source frames they were rendered from
+///
+/// Smoothing processors hold frames back before rendering them, so the last decoded frame is
+/// ahead of the last frame in the output video. The progress file must record the latter, or a
+/// resumed run would leave out the frames that were still held back when the run was stopped.
+#[derive(Default)]
+pub struct OutputFrameTracker {
+    pending: VecDeque<u64>,
+    output_frames: u64,
+    last_output_frame: Option<u64>,
+}
+
+impl OutputFrameTracker {
+    /// Records that the source frame `frame_index` was handed to the processor
+    pub fn push(&mut self, frame_index: u64) {
+        self.pending.push_back(frame_index);
+    }
+
+    /// Catches up with the renderer and returns the last source frame that reached the output
+    ///
+    /// # Arguments
+    /// * `output_frame_count` - The number of frames rendered so far (see `CropRenderer`)
+    pub fn update(&mut self, output_frame_count: u64) -> Option<u64> {
+        while self.output_frames < output_frame_count {
+            if let Some(frame_index) = self.pending.pop_front() {
+                self.last_output_frame = Some(frame_index);
+            }
+            self.output_frames += 1;
+        }
+        self.last_output_frame
+    }
+}
+
+/// Scales the smoothing duration down as motion speed increases
+///
+/// Returns `max(1, base_frames * (1 - speed_factor))` where `speed_factor` is the motion speed
+/// relative to `max_expected_speed`, clamped to `[0, 1]`.
+///
+/// # Arguments
+/// * `base_frames` - The smoothing duration at rest, in frames
+/// * `ema_speed` - The average motion speed in pixels per frame
+/// * `max_expected_speed` - The speed at which smoothing drops to a single frame
+pub fn adaptive_smooth_duration_frames(
+    base_frames: usize,
+    ema_speed: f32,
+    max_expected_speed: f32,
+) -> usize {
+    if max_expected_speed <= 0.0 {
+        return base_frames.max(1);
+    }
+    let speed_factor = (ema_speed / max_expected_speed).clamp(0.0, 1.0);
+    ((base_frames as f32 * (1.0 - speed_factor)).round() as usize).max(1)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::crop::{CropArea, CropResult};
+
+    #[test]
+    fn test_build_hbb_from_crop_area_round_trip() {
+        let area = CropArea::new(555.0, 12.5, 810.0, 1067.5);
+        let hbb = build_hbb_from_crop_area(&area);
+
+        assert_eq!(hbb.xmin(), area.x);
+        assert_eq!(hbb.ymin(), area.y);
+        assert_eq!(hbb.width(), area.width);
+        assert_eq!(hbb.height(), area.height);
+
+        let round_trip = CropArea::new(hbb.xmin(), hbb.ymin(), hbb.width(), hbb.height());
+        assert_eq!(round_trip, area);
+    }
+
+    /// Runs a sequence of crops through a lookahead window like the simple smoothing processor
+    fn run_lookahead(crops: &[CropResult], lookahead: usize) -> Vec<CropResult> {
+        let mut previous: Option<CropResult> = None;
+        let mut output = Vec::new();
+        for i in 0..crops.len() {
+            let end = (i + lookahead + 1).min(crops.len());
+            let chosen =
+                choose_lookahead_crop(previous.as_ref(), &crops[i..end], 1920.0, 10.0).unwrap();
+            previous = Some(chosen.clone());
+            output.push(chosen);
+        }
+        output
+    }
+
+    #[test]
+    fn test_choose_lookahead_crop_class_change() {
+        let single = CropResult::Single(CropArea::new(555.0, 0.0, 810.0, 1080.0));
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 60.0, 960.0, 853.0),
+            CropArea::new(960.0, 60.0, 960.0, 853.0),
+        );
+
+        // A lasting change to stacked crops is applied on the frame where it starts
+        let crops = vec![
+            single.clone(),
+            single.clone(),
+            stacked.clone(),
+            stacked.clone(),
+            stacked.clone(),
+            stacked.clone(),
+        ];
+        let output = run_lookahead(&crops, 3);
+        assert!(matches!(output[1], CropResult::Single(_)));
+        assert!(output[2..].iter().all(|crop| matches!(crop, CropResult::Stacked(_, _))));
+
+        // A one-frame glitch seen through the lookahead is ignored
+        let crops = vec![single.clone(), stacked.clone(), single.clone(), single.clone(), single.clone()];
+        let output = run_lookahead(&crops, 3);
+        assert!(output.iter().all(|crop| matches!(crop, CropResult::Single(_))));
+    }
+
+    #[test]
+    fn test_choose_lookahead_crop_without_previous() {
+        let single = CropResult::Single(CropArea::new(555.0, 0.0, 810.0, 1080.0));
+        let chosen = choose_lookahead_crop(None, std::slice::from_ref(&single), 1920.0, 10.0);
+        assert!(matches!(chosen, Some(CropResult::Single(area)) if area.x == 555.0));
+        assert!(choose_lookahead_crop(None, &[], 1920.0, 10.0).is_none());
+    }
+
+    #[test]
+    fn test_crop_trend_and_prediction_for_pan() {
+        // A presenter walking right moves the crop 20px per frame
+        let pan: Vec<crop::CropArea> = (0..5)
+            .map(|i| crop::CropArea::new(100.0 + 20.0 * i as f32, 0.0, 607.5, 1080.0))
+            .collect();
+
+        assert!(is_consistent_crop_trend(&pan[..4]));
+        let predicted = predict_crop_area(&pan[1], &pan[2], &pan[3], 1920.0, 1080.0);
+        assert_eq!(predicted, pan[4]);
+
+        // Too few crops, a stationary crop and a reversal are not trends
+        assert!(!is_consistent_crop_trend(&pan[..1]));
+        let still = vec![pan[0].clone(); 4];
+        assert!(!is_consistent_crop_trend(&still));
+        let reversal = [pan[0].clone(), pan[1].clone(), pan[2].clone(), pan[1].clone()];
+        assert!(!is_consistent_crop_trend(&reversal));
+
+        // Predictions stay inside the frame
+        let edge = predict_crop_area(&pan[2], &pan[3], &pan[4], 800.0, 1080.0);
+        assert_eq!(edge.x, 800.0 - 607.5);
+    }
+
+    #[test]
+    fn test_prediction_confidence_decay_schedule() {
+        let max_prediction_frames = 10;
+        let schedule: Vec<f32> = (1..=15)
+            .map(|missed| prediction_confidence(missed, max_prediction_frames))
+            .collect();
+
+        // Confidence drops by 0.1 per missed frame and bottoms out at zero
+        for (i, confidence) in schedule.iter().enumerate() {
+            let expected = (1.0 - (i + 1) as f32 * 0.1).max(0.0);
+            assert!((confidence - expected).abs() < 1e-5);
+        }
+        assert!(schedule.windows(2).all(|w| w[1] <= w[0]));
+
+        // Prediction continues for 9 missed frames and stops from the 10th on
+        let predicting: Vec<bool> = schedule.iter().map(|c| *c >= 0.1).collect();
+        assert_eq!(predicting.iter().filter(|p| **p).count(), 9);
+        assert!(predicting[..9].iter().all(|p| *p));
+        assert!(predicting[9..].iter().all(|p| !*p));
+
+        assert_eq!(prediction_confidence(0, max_prediction_frames), 1.0);
+        assert_eq!(prediction_confidence(3, 0), 0.0);
+    }
+
+    #[test]
+    fn test_blend_hbb_toward() {
+        let last = Hbb::from_cxcywh(100.0, 100.0, 20.0, 20.0);
+        let predicted = Hbb::from_cxcywh(200.0, 150.0, 20.0, 20.0);
+
+        let full = blend_hbb_toward(&last, &predicted, 1.0);
+        assert_eq!((full.cx(), full.cy()), (200.0, 150.0));
+
+        let half = blend_hbb_toward(&last, &predicted, 0.5);
+        assert_eq!((half.cx(), half.cy(), half.width()), (150.0, 125.0, 20.0));
+
+        let none = blend_hbb_toward(&last, &predicted, 0.0);
+        assert_eq!((none.cx(), none.cy()), (100.0, 100.0));
+    }
+
+    #[test]
+    fn test_update_motion_ema() {
+        assert_eq!(update_motion_ema(None, 12.0, 30), 12.0);
+
+        // A constant speed keeps the average steady
+        assert_eq!(update_motion_ema(Some(5.0), 5.0, 30), 5.0);
+
+        // The average converges toward a new speed over the window
+        let mut ema = Some(0.0);
+        for _ in 0..30 {
+            ema = Some(update_motion_ema(ema, 10.0, 30));
+        }
+        let ema = ema.unwrap();
+        assert!(ema > 8.0 && ema < 10.0);
+    }
+
+    #[test]
+    fn test_graphic_check_cache_interval() {
+        let mut cache = GraphicCheckCache::new(5);
+        let mut calls = 0;
+        let total_frames: u64 = 23;
+        for frame_index in 1..=total_frames {
+            let is_graphic = cache
+                .is_graphic(frame_index, false, || {
+                    calls += 1;
+                    Ok(true)
+                })
+                .unwrap();
+            assert!(is_graphic);
+        }
+        assert_eq!(calls, total_frames.div_ceil(5));
+    }
+
+    #[test]
+    fn test_output_frame_tracker_resume_matches_full_run() {
+        // A processor that holds `delay` frames back and skips frames without detections
+        let run = |frames: std::ops::RangeInclusive<u64>, stop_after: Option<u64>| {
+            let delay = 7;
+            let mut tracker = OutputFrameTracker::default();
+            let mut held = VecDeque::new();
+            let mut written = Vec::new();
+            let mut saved = None;
+            for frame_index in frames {
+                if frame_index % 13 != 0 {
+                    tracker.push(frame_index);
+                    held.push_back(frame_index);
+                    if held.len() > delay {
+                        written.push(held.pop_front().unwrap());
+                    }
+                }
+                saved = tracker.update(written.len() as u64).or(saved);
+                if stop_after == Some(frame_index) {
+                    // Interrupted: the held frames never reach the output
+                    return (written, saved);
+                }
+            }
+            written.extend(held);
+            (written, saved)
+        };
+
+        let (full, _) = run(1..=200, None);
+        let (mut interrupted, saved) = run(1..=200, Some(120));
+        let saved = saved.unwrap();
+        assert_eq!(interrupted.last(), Some(&saved));
+        assert!(saved < 120);
+
+        // The resumed run starts after the last written frame, so no frame is lost or repeated
+        let (resumed, _) = run(saved + 1..=200, None);
+        interrupted.extend(resumed);
+        assert_eq!(interrupted, full);
+    }
+
+    #[test]
+    fn test_graphic_check_cache_detections() {
+        let mut cache = GraphicCheckCache::new(5);
+        assert!(cache.is_graphic(1, false, || Ok(true)).unwrap());
+
+        // A detection is never graphic and clears the cached result without classifying
+        assert!(!cache.is_graphic(2, true, || panic!("classified a frame with detections")).unwrap());
+        assert!(!cache.is_graphic(3, false, || panic!("classified within the interval")).unwrap());
+        assert!(cache.is_graphic(6, false, || Ok(true)).unwrap());
+    }
+
+    #[test]
+    fn test_adaptive_smooth_duration_frames() {
+        // At rest the full duration is used
+        assert_eq!(adaptive_smooth_duration_frames(45, 0.0, 20.0), 45);
+        // Half the expected maximum speed halves the duration
+        assert_eq!(adaptive_smooth_duration_frames(45, 10.0, 20.0), 23);
+        // At or above the maximum speed the duration bottoms out at one frame
+        assert_eq!(adaptive_smooth_duration_frames(45, 20.0, 20.0), 1);
+        assert_eq!(adaptive_smooth_duration_frames(45, 80.0, 20.0), 1);
+        // An invalid maximum speed disables adaptation
+        assert_eq!(adaptive_smooth_duration_frames(45, 10.0, 0.0), 45);
+
+        // A speed sequence that slows down lengthens the duration again
+        let mut ema = None;
+        let mut durations = Vec::new();
+        for speed in [20.0, 20.0, 20.0, 0.0, 0.0, 0.0] {
+            ema = Some(update_motion_ema(ema, speed, 2));
+            durations.push(adaptive_smooth_duration_frames(30, ema.unwrap(), 20.0));
+        }
+        assert_eq!(durations[..3], [1, 1, 1]);
+        assert!(durations.windows(2).skip(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_crop_result_to_dominant_hbb() {
+        let top = CropArea::new(0.0, 60.0, 960.0, 853.0);
+        let bottom = CropArea::new(960.0, 120.0, 960.0, 853.0);
+
+        let hbb = crop_result_to_dominant_hbb(&CropResult::Single(top.clone())).unwrap();
+        assert_eq!((hbb.xmin(), hbb.ymin(), hbb.width(), hbb.height()), (0.0, 60.0, 960.0, 853.0));
+
+        let hbb = crop_result_to_dominant_hbb(&CropResult::Stacked(top.clone(), bottom)).unwrap();
+        assert_eq!((hbb.xmin(), hbb.ymin(), hbb.width(), hbb.height()), (0.0, 60.0, 960.0, 853.0));
+
+        let full_frame = CropArea::new(0.0, 0.0, 1920.0, 1080.0);
+        let hbb = crop_result_to_dominant_hbb(&CropResult::Resize(full_frame)).unwrap();
+        assert_eq!((hbb.width(), hbb.height()), (1920.0, 1080.0));
+
+        let zoom = CropResult::ZoomIn {
+            start: CropArea::new(0.0, 0.0, 800.0, 1000.0),
+            end: CropArea::new(200.0, 100.0, 400.0, 500.0),
+            progress: 0.5,
+        };
+        let hbb = crop_result_to_dominant_hbb(&zoom).unwrap();
+        assert_eq!((hbb.xmin(), hbb.ymin(), hbb.width(), hbb.height()), (100.0, 50.0, 600.0, 750.0));
+    }
+
+    #[test]
+    fn test_crop_result_hbb_round_trip() {
+        let to_area = |hbb: &Hbb| CropArea::new(hbb.xmin(), hbb.ymin(), hbb.width(), hbb.height());
+
+        let single = CropResult::Single(CropArea::new(656.25, 0.0, 607.5, 1080.0));
+        let hbb = crop_result_to_dominant_hbb(&single).unwrap();
+        assert_eq!(CropResult::Single(to_area(&hbb)), single);
+
+        let resize = CropResult::Resize(CropArea::new(0.0, 0.0, 1920.0, 1080.0));
+        let hbb = crop_result_to_dominant_hbb(&resize).unwrap();
+        assert_eq!(CropResult::Resize(to_area(&hbb)), resize);
+
+        // Only the top crop of a stacked result survives the round trip
+        let top = CropArea::new(0.0, 60.0, 960.0, 853.0);
+        let bottom = CropArea::new(960.0, 120.0, 960.0, 853.0);
+        let stacked = CropResult::Stacked(top.clone(), bottom.clone());
+        let hbb = crop_result_to_dominant_hbb(&stacked).unwrap();
+        assert_eq!(CropResult::Stacked(to_area(&hbb), bottom), stacked);
+    }
+
+    #[test]
+    fn test_ensure_free_space() {
+        let dir = Path::new("/output");
+        let gb = |value: f64| (value * BYTES_PER_GB) as u64;
+
+        assert!(ensure_free_space(dir, 2.0, |_| Ok(gb(10.0))).is_ok());
+
+        let result = ensure_free_space(dir, 2.0, |_| Ok(gb(1.5)));
+        let message = format!("{:#}", result.unwrap_err());
+        assert!(message.contains("1.5 GB free"), "{}", message);
+
+        // Disabled check and unknown free space both pass
+        assert!(ensure_free_space(dir, 0.0, |_| Ok(0)).is_ok());
+        assert!(ensure_free_space(dir, 2.0, |_| anyhow::bail!("no disk")).is_ok());
+    }
+
+    #[test]
+    fn test_save_cropped_frame_writes_thumbnail() {
+        let dir = std::env::temp_dir().join(format!("land2port_first_frame_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("first_frame.jpg");
+
+        let frame = usls::Image::from(::image::RgbImage::from_pixel(320, 180, ::image::Rgb([200, 40, 40])));
+        let crop = CropResult::Single(CropArea::new(100.0, 0.0, 101.25, 180.0));
+        save_cropped_frame(
+            &frame,
+            &crop,
+            image::PillarboxMode::Black,
+            image::RenderOptions::default(),
+            &path,
+        )
+        .unwrap();
+
+        let saved = ::image::open(&path).unwrap();
+        assert!(saved.width() > 0 && saved.height() > 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_valid_hbb() {
+        // A 1x200 sliver is an artifact, not a head
+        let sliver = Hbb::from_xywh(100.0, 100.0, 1.0, 200.0);
+        assert!(!is_valid_hbb(&sliver, 0.5, 3.0, MIN_HEAD_SIZE));
+
+        let head = Hbb::from_xywh(100.0, 100.0, 80.0, 100.0);
+        assert!(is_valid_hbb(&head, 0.5, 3.0, MIN_HEAD_SIZE));
+
+        let wide = Hbb::from_xywh(100.0, 100.0, 400.0, 100.0);
+        assert!(!is_valid_hbb(&wide, 0.3, 3.0, MIN_HEAD_SIZE));
+
+        let tiny = Hbb::from_xywh(100.0, 100.0, 3.0, 3.0);
+        assert!(!is_valid_hbb(&tiny, 0.3, 3.0, MIN_HEAD_SIZE));
+
+        let flat = Hbb::from_xywh(100.0, 100.0, 10.0, 0.0);
+        assert!(!is_valid_hbb(&flat, 0.3, 3.0, 0.0));
+    }
+
+    #[test]
+    fn test_extract_objects_in_zone() {
+        let ball = |cx: f32, cy: f32| {
+            Hbb::from_cxcywh(cx, cy, 20.0, 20.0)
+                .with_confidence(0.9)
+                .with_name("ball")
+        };
+        // One ball on the pitch and two on the LED boards along the top edge
+        let detection = Y::default().with_hbbs(&[ball(960.0, 700.0), ball(200.0, 60.0), ball(1700.0, 50.0)]);
+        let zone = CropArea::new(0.0, 200.0, 1920.0, 880.0);
+
+        let all = extract_objects_above_threshold(&detection, "ball", 0.5, &PerClassThresholds::new(0.0), 1920.0, 1080.0, None);
+        assert_eq!(all.len(), 3);
+
+        let in_zone =
+            extract_objects_above_threshold(&detection, "ball", 0.5, &PerClassThresholds::new(0.0), 1920.0, 1080.0, Some(&zone));
+        assert_eq!(in_zone.len(), 1);
+        assert_eq!((in_zone[0].cx(), in_zone[0].cy()), (960.0, 700.0));
+
+        // A zone without balls leaves nothing, so the no-ball branch takes over
+        let empty_zone = CropArea::new(0.0, 900.0, 100.0, 100.0);
+        assert!(
+            extract_objects_above_threshold(&detection, "ball", 0.5, &PerClassThresholds::new(0.0), 1920.0, 1080.0, Some(&empty_zone))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_per_class_area_thresholds() {
+        let specs = ["heads:0.005".to_string(), "person:0.03".to_string()];
+        let thresholds = PerClassThresholds::parse(0.02, &specs).unwrap();
+        assert_eq!(thresholds.threshold_for("heads"), 0.005);
+        assert_eq!(thresholds.threshold_for("person"), 0.03);
+        assert_eq!(thresholds.threshold_for("face"), 0.02);
+        assert_eq!(thresholds.threshold_for("ball"), 0.0);
+        assert_eq!(
+            PerClassThresholds::parse(0.02, &["ball:0.001".to_string()]).unwrap().threshold_for("ball"),
+            0.001
+        );
+        for invalid in ["heads", "heads:small", ":0.01", "heads:2"] {
+            assert!(PerClassThresholds::parse(0.02, &[invalid.to_string()]).is_err(), "{}", invalid);
+        }
+
+        // A 100x100 head is 0.48% of a 1080p frame
+        let detection = Y::default().with_hbbs(&[
+            Hbb::from_xywh(100.0, 100.0, 100.0, 100.0).with_confidence(0.9).with_name("heads"),
+            Hbb::from_xywh(800.0, 100.0, 20.0, 20.0).with_confidence(0.9).with_name("ball"),
+        ]);
+        let global = PerClassThresholds::new(0.02);
+        assert!(extract_objects_above_threshold(&detection, "heads", 0.5, &global, 1920.0, 1080.0, None).is_empty());
+        assert_eq!(extract_objects_above_threshold(&detection, "ball", 0.5, &global, 1920.0, 1080.0, None).len(), 1);
+        let wide_shot = global.with_threshold("heads", 0.004);
+        assert_eq!(extract_objects_above_threshold(&detection, "heads", 0.5, &wide_shot, 1920.0, 1080.0, None).len(), 1);
+    }
+
+    #[test]
+    fn test_extract_objects_matching_any() {
+        let detection = Y::default().with_hbbs(&[
+            Hbb::from_xywh(100.0, 100.0, 100.0, 120.0).with_confidence(0.8).with_name("head"),
+            Hbb::from_xywh(800.0, 100.0, 100.0, 120.0).with_confidence(0.9).with_name("face"),
+            Hbb::from_xywh(1500.0, 100.0, 100.0, 120.0).with_confidence(0.9).with_name("person"),
+        ]);
+
+        // Both labels are matched, most confident first; other labels are ignored
+        let objects = extract_objects_matching_any(
+            &detection,
+            &["head", "face"],
+            0.5,
+            &PerClassThresholds::new(0.0),
+            1920.0,
+            1080.0,
+            None,
+            0.5,
+        );
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].name(), Some("face"));
+        assert_eq!(objects[1].name(), Some("head"));
+
+        // A single name behaves like extract_objects_above_threshold
+        let heads =
+            extract_objects_matching_any(&detection, &["head"], 0.5, &PerClassThresholds::new(0.0), 1920.0, 1080.0, None, 0.5);
+        assert_eq!(heads.len(), 1);
+    }
+
+    #[test]
+    fn test_deduplicate_hbbs_by_position() {
+        let weak = Hbb::from_cxcywh(500.0, 300.0, 80.0, 100.0).with_confidence(0.7);
+        let strong = Hbb::from_cxcywh(506.0, 308.0, 120.0, 140.0).with_confidence(0.9);
+        let other = Hbb::from_cxcywh(1200.0, 300.0, 80.0, 100.0).with_confidence(0.8);
+
+        // The two heads 10px apart are duplicates; the more confident one is kept
+        let heads = deduplicate_hbbs_by_position(&[&weak, &strong, &other], 15.0);
+        assert_eq!(heads.len(), 2);
+        assert_eq!(heads[0].confidence(), Some(0.9));
+        assert_eq!(heads[1].cx(), 1200.0);
+
+        // A smaller distance keeps both
+        let heads = deduplicate_hbbs_by_position(&[&weak, &strong, &other], 5.0);
+        assert_eq!(heads.len(), 3);
+    }
+
+    #[test]
+    fn test_extract_objects_matching_any_deduplicates() {
+        // A head box and a face box on the same person, plus a second person's face
+        let detection = Y::default().with_hbbs(&[
+            Hbb::from_xywh(100.0, 100.0, 100.0, 120.0).with_confidence(0.7).with_name("head"),
+            Hbb::from_xywh(110.0, 110.0, 90.0, 100.0).with_confidence(0.9).with_name("face"),
+            Hbb::from_xywh(800.0, 100.0, 100.0, 120.0).with_confidence(0.8).with_name("face"),
+        ]);
+
+        let objects = extract_objects_matching_any(
+            &detection,
+            &["head", "face"],
+            0.5,
+            &PerClassThresholds::new(0.0),
+            1920.0,
+            1080.0,
+            None,
+            0.5,
+        );
+        assert_eq!(objects.len(), 2);
+        assert_eq!((objects[0].xmin(), objects[0].confidence()), (110.0, Some(0.9)));
+        assert_eq!(objects[1].xmin(), 800.0);
+
+        // A higher threshold keeps both overlapping boxes
+        let objects = extract_objects_matching_any(
+            &detection,
+            &["head", "face"],
+            0.5,
+            &PerClassThresholds::new(0.0),
+            1920.0,
+            1080.0,
+            None,
+            0.9,
+        );
+        assert_eq!(objects.len(), 3);
+
+        assert_eq!(
+            hbb_iou(
+                &Hbb::from_xywh(0.0, 0.0, 10.0, 10.0),
+                &Hbb::from_xywh(5.0, 0.0, 10.0, 10.0)
+            ),
+            50.0 / 150.0
+        );
+    }
+
     #[test]
     fn test_area_threshold_calculation() {
         // Test area threshold calculation logic
@@ -150,4 +1572,97 @@ mod tests {
         let should_check_area = non_ball_object_name != "ball";
         assert!(should_check_area);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_crop_history_overview() {
+        let placeholder = usls::Image::from(::image::RgbImage::new(1, 1));
+        let mut history = history::CropHistory::new();
+        for i in 0..5 {
+            let area = CropArea::new(100.0 * i as f32, 0.0, 600.0, 1080.0);
+            history.add(CropResult::Single(area), placeholder.clone(), 1);
+        }
+        for _ in 0..5 {
+            let top = CropArea::new(0.0, 0.0, 960.0, 540.0);
+            let bottom = CropArea::new(960.0, 0.0, 960.0, 540.0);
+            history.add(CropResult::Stacked(top, bottom), placeholder.clone(), 2);
+        }
+
+        let overview = crop_history_overview(&history, 5);
+        assert_eq!(
+            overview,
+            "Crop history overview (10 frames):\n  \
+             samples: SINGLE@300 SINGLE@500 SINGLE@700 STACKED@480 STACKED@480\n  \
+             crop types: SINGLE at 0%, STACKED at 56%"
+        );
+    }
+
+    #[test]
+    fn test_performance_stats_summary() {
+        let mut stats = PerformanceStats::default();
+        stats.add_model_forward(Duration::from_millis(40), 4);
+        stats.add_crop_calc(Duration::from_micros(500));
+        stats.image_render_ms.extend([2.0, 4.0]);
+        assert_eq!(stats.model_forward_ms, vec![10.0; 4]);
+
+        let summary = stats.summary();
+        for stage in ["model forward", "crop calculation", "image render", "video write"] {
+            assert!(summary.contains(stage), "{} missing from {}", stage, summary);
+        }
+        assert!(summary.contains("3.000 ± 1.000 ms (2 frames)"));
+        assert!(summary.contains("(0 frames)"));
+    }
+
+    #[test]
+    fn test_dry_run_summary_and_log_line() {
+        let single = CropResult::Single(CropArea::new(656.25, 0.0, 607.5, 1080.0));
+        let stacked = CropResult::Stacked(
+            CropArea::new(0.0, 0.0, 960.0, 540.0),
+            CropArea::new(960.0, 540.0, 960.0, 540.0),
+        );
+
+        let mut summary = DryRunSummary::default();
+        for crop in [&single, &stacked, &single] {
+            summary.record(crop);
+        }
+        assert_eq!((summary.single, summary.stacked, summary.resize, summary.zoom), (2, 1, 0, 0));
+        assert_eq!(
+            summary.summary(),
+            "Dry run crop decisions: 2 single, 1 stacked, 0 resize, 0 zoom (3 frames)"
+        );
+
+        let line: serde_json::Value = serde_json::from_str(&dry_run_log_line(7, &stacked)).unwrap();
+        assert_eq!(line["frame"], 7);
+        assert_eq!(line["crop_type"], "stacked");
+        assert_eq!(line["crops"][1], serde_json::json!([960.0, 540.0, 960.0, 540.0]));
+    }
+
+    #[test]
+    fn test_build_debug_frame() {
+        let frame = usls::Image::from(::image::RgbImage::from_pixel(180, 100, ::image::Rgb([128, 128, 128])));
+        let head = Hbb::from_xywh(20.0, 20.0, 30.0, 30.0);
+        let crop = CropResult::Single(CropArea::new(60.0, 0.0, 56.0, 100.0));
+
+        let debug_frame = build_debug_frame(&frame, &[&head], &crop, 1).unwrap().to_rgb8();
+        // 9:16 canvas with the frame centered vertically
+        assert_eq!(debug_frame.dimensions(), (180, 320));
+        assert_eq!(*debug_frame.get_pixel(150, 200), ::image::Rgb([128, 128, 128]));
+        assert_eq!(*debug_frame.get_pixel(90, 50), ::image::Rgb([0, 0, 0]));
+
+        // Head outline in green, crop outline in red, both offset by the padding
+        let green = ::image::Rgb([0, 255, 0]);
+        let red = ::image::Rgb([255, 0, 0]);
+        assert_eq!(*debug_frame.get_pixel(20, 130), green);
+        assert_eq!(*debug_frame.get_pixel(35, 145), ::image::Rgb([128, 128, 128]));
+        assert_eq!(*debug_frame.get_pixel(60, 150), red);
+        assert_eq!(*debug_frame.get_pixel(80, 110), red);
+
+        // Text rows start 2 pixels down: "1" top-left, "1" top-center, "SINGLE" top-right
+        let white = ::image::Rgb([255, 255, 255]);
+        let black = ::image::Rgb([0, 0, 0]);
+        assert_eq!(*debug_frame.get_pixel(4, 2), white);
+        assert_eq!(*debug_frame.get_pixel(2, 2), black);
+        assert_eq!(*debug_frame.get_pixel(89, 2), white);
+        assert_eq!(*debug_frame.get_pixel(144, 2), white);
+        assert_eq!(*debug_frame.get_pixel(143, 2), black);
+    }
+}